@@ -0,0 +1,387 @@
+//! Analysis of lambda terms and BLC programs
+
+use lambda_calculus::term::*;
+use lambda_calculus::{IntoChurchNum, IntoScottNum, IntoChurchList, IntoScottList, IntoPairList};
+use lambda_calculus::NOR;
+use lambda_calculus::data::num::{church, scott};
+use lambda_calculus::data::list::{self, pair};
+use encoding::bcl::to_bcl;
+use encoding::binary::{self, to_bits};
+use execution::{prepare, Error, Input, Limits, Strategy};
+use std::collections::HashMap;
+
+/// Returns the number of nodes (variables, abstractions and applications) of a `Term`.
+///
+/// # Example
+/// ```
+/// use blc::analysis::size;
+/// use blc::encoding::binary::from_bits;
+///
+/// assert_eq!(size(&from_bits(b"0000110").unwrap()), 3); // λλ2
+/// ```
+pub fn size(term: &Term) -> usize {
+    let mut stack = vec![term];
+    let mut count = 0;
+
+    while let Some(term) = stack.pop() {
+        count += 1;
+        match *term {
+            Var(_) => (),
+            Abs(ref t) => stack.push(t),
+            App(ref t1, ref t2) => {
+                stack.push(t1);
+                stack.push(t2)
+            }
+        }
+    }
+
+    count
+}
+
+/// The outcome of evaluating a program with a single `Strategy`.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct StrategyReport {
+    /// the evaluated strategy
+    pub strategy: Strategy,
+    /// the number of performed β-reductions
+    pub steps: usize,
+    /// the size of the largest intermediate term, in nodes
+    pub peak_size: usize,
+    /// `true` if the evaluation finished within the limits
+    pub terminated: bool
+}
+
+/// Evaluates a program applied to the given input with each of the given strategies and reports
+/// how each of them fared. Since some strategies may never terminate for some programs, the
+/// `limits` should not be left unbounded.
+///
+/// # Example
+/// ```
+/// use blc::analysis::compare_strategies;
+/// use blc::execution::{Input, Limits, Strategy};
+///
+/// let k_i_omega = b"010100001100010010001101000011010"; // (λλ2)(λ1)((λ11)(λ11))
+/// let limits = Limits { steps: 100, size: 0 };
+/// let strategies = [Strategy::Normal, Strategy::Applicative];
+///
+/// let reports = compare_strategies(k_i_omega, Input::Nothing, &strategies, limits).unwrap();
+///
+/// assert!(reports[0].terminated);
+/// assert_eq!(reports[0].steps, 2);
+/// assert!(!reports[1].terminated);
+/// ```
+/// # Errors
+///
+/// Returns an `Error` if the program or the input are not valid BLC.
+pub fn compare_strategies(
+    program: &[u8],
+    input: Input,
+    strategies: &[Strategy],
+    limits: Limits
+) -> Result<Vec<StrategyReport>, Error> {
+    let term = prepare(program, input)?;
+
+    Ok(strategies.iter().map(|&strategy| evaluate(term.clone(), strategy, limits)).collect())
+}
+
+fn evaluate(mut term: Term, strategy: Strategy, limits: Limits) -> StrategyReport {
+    let mut report = StrategyReport {
+        strategy,
+        steps: 0,
+        peak_size: size(&term),
+        terminated: false
+    };
+
+    loop {
+        let count = strategy.step(&mut term);
+        if count == 0 {
+            report.terminated = true;
+            return report
+        }
+        if limits.steps != 0 && report.steps >= limits.steps { return report }
+        report.steps += count;
+
+        let current_size = size(&term);
+        if current_size > report.peak_size { report.peak_size = current_size }
+        if limits.size != 0 && current_size > limits.size { return report }
+    }
+}
+
+/// The aligned reduction traces of a program evaluated with two strategies.
+#[derive(Debug, PartialEq, Clone)]
+pub struct TraceDiff {
+    /// the compared strategies
+    pub strategies: (Strategy, Strategy),
+    /// the terms after every step of each strategy, starting with the initial term
+    pub traces: (Vec<Term>, Vec<Term>),
+    /// `true` for each strategy that reached a normal form within the limits
+    pub terminated: (bool, bool),
+    /// the number of leading steps after which both strategies produced the same terms
+    pub common_steps: usize,
+    /// the first pair of steps after the divergence at which both strategies produced the same
+    /// term again, if there is one
+    pub rejoined: Option<(usize, usize)>
+}
+
+impl TraceDiff {
+    /// Returns the number of the step at which the strategies first produced different terms, or
+    /// `None` if their traces are the same.
+    pub fn diverged_at(&self) -> Option<usize> {
+        if self.traces.0 == self.traces.1 { None } else { Some(self.common_steps + 1) }
+    }
+
+    /// Renders the traces side by side, marking the steps that differ with `|` and the ones
+    /// that don't with `=`.
+    pub fn render(&self) -> String {
+        let (ref left, ref right) = self.traces;
+        let width = left.iter().map(|t| format!("{:?}", t).chars().count()).max().unwrap_or(0);
+        let mut output = format!(
+            "{:>5}  {:<width$}    {:?}\n",
+            "step", format!("{:?}", self.strategies.0), self.strategies.1, width = width
+        );
+
+        for step in 0..left.len().max(right.len()) {
+            let (l, r) = (left.get(step), right.get(step));
+            let marker = if l == r { '=' } else { '|' };
+            let show = |t: Option<&Term>| t.map_or(String::new(), |t| format!("{:?}", t));
+
+            output.push_str(&format!(
+                "{:>5}  {:<width$} {}  {}\n",
+                step, show(l), marker, show(r), width = width
+            ));
+        }
+
+        output
+    }
+}
+
+/// Evaluates a program applied to the given input with two strategies and aligns their traces,
+/// showing where they diverge and whether they meet again; both evaluations are bounded by the
+/// `limits`.
+///
+/// # Example
+/// ```
+/// use blc::analysis::diff_traces;
+/// use blc::execution::{Input, Limits, Strategy};
+///
+/// let k_i_omega = b"010100001100010010001101000011010"; // (λλ2)(λ1)((λ11)(λ11))
+/// let limits = Limits { steps: 5, size: 0 };
+/// let strategies = (Strategy::Normal, Strategy::Applicative);
+///
+/// let diff = diff_traces(k_i_omega, Input::Nothing, strategies, limits).unwrap();
+///
+/// assert_eq!(diff.terminated, (true, false));
+/// assert_eq!(diff.diverged_at(), Some(2)); // both contract (λλ2)(λ1) first
+/// ```
+/// # Errors
+///
+/// Returns an `Error` if the program or the input are not valid BLC.
+pub fn diff_traces(
+    program: &[u8],
+    input: Input,
+    strategies: (Strategy, Strategy),
+    limits: Limits
+) -> Result<TraceDiff, Error> {
+    let term = prepare(program, input)?;
+    let (left, left_terminated) = trace(term.clone(), strategies.0, limits);
+    let (right, right_terminated) = trace(term, strategies.1, limits);
+
+    let common_steps = left.iter().zip(&right).take_while(|(l, r)| l == r).count() - 1;
+    let positions = left.iter()
+        .enumerate()
+        .skip(common_steps + 1)
+        .map(|(i, term)| (term, i))
+        .collect::<HashMap<_, _>>();
+    let rejoined = right.iter()
+        .enumerate()
+        .skip(common_steps + 1)
+        .find_map(|(j, term)| positions.get(term).map(|&i| (i, j)));
+
+    Ok(TraceDiff {
+        strategies,
+        traces: (left, right),
+        terminated: (left_terminated, right_terminated),
+        common_steps,
+        rejoined
+    })
+}
+
+fn trace(mut term: Term, strategy: Strategy, limits: Limits) -> (Vec<Term>, bool) {
+    let mut trace = vec![term.clone()];
+
+    loop {
+        if strategy.step(&mut term) == 0 { return (trace, true) }
+        trace.push(term.clone());
+
+        if limits.steps != 0 && trace.len() > limits.steps { return (trace, false) }
+        if limits.size != 0 && size(&term) > limits.size { return (trace, false) }
+    }
+}
+
+/// The cost of representing data with a single encoding.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct EncodingReport {
+    /// the name of the encoding
+    pub encoding: &'static str,
+    /// the size of the encoded data in BLC, in bits
+    pub bits: usize,
+    /// the number of β-reductions needed to normalize the result of the reference operation on
+    /// the encoded data: the predecessor of numerals and the head of lists
+    pub steps: usize
+}
+
+/// Encodes the number `n` as a Church and as a Scott numeral and reports their sizes and the
+/// costs of computing their predecessors.
+///
+/// # Example
+/// ```
+/// use blc::analysis::compare_numeral_encodings;
+///
+/// let reports = compare_numeral_encodings(10);
+///
+/// assert_eq!(reports[0].encoding, "Church");
+/// assert!(reports[0].steps > reports[1].steps); // Scott numerals are cheap to take apart
+/// ```
+pub fn compare_numeral_encodings(n: usize) -> Vec<EncodingReport> {
+    vec![
+        report("Church", n.into_church(), church::pred()),
+        report("Scott", n.into_scott(), scott::pred())
+    ]
+}
+
+/// Encodes a list as a Church list, a Scott list and a pair list (the encoding of BLC input) and
+/// reports their sizes and the costs of taking their heads.
+///
+/// # Example
+/// ```
+/// use blc::analysis::compare_list_encodings;
+/// use blc::stdlib::gen::church;
+///
+/// let reports = compare_list_encodings(&[church(1), church(2), church(3)]);
+///
+/// assert_eq!(reports.len(), 3);
+/// assert_eq!(reports[2].encoding, "pair");
+/// ```
+pub fn compare_list_encodings(elements: &[Term]) -> Vec<EncodingReport> {
+    vec![
+        report("Church", elements.to_vec().into_church(), list::church::head()),
+        report("Scott", elements.to_vec().into_scott(), list::scott::head()),
+        report("pair", elements.to_vec().into_pair_list(), pair::head())
+    ]
+}
+
+fn report(encoding: &'static str, data: Term, operation: Term) -> EncodingReport {
+    let bits = to_bits(&data).len();
+    let steps = app(operation, data).reduce(NOR, 0);
+
+    EncodingReport { encoding, bits, steps }
+}
+
+/// The sizes of a program in binary lambda calculus and in binary combinatory logic.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct ProgramSizes {
+    /// the size in BLC, in bits
+    pub blc: usize,
+    /// the size in BCL, in bits
+    pub bcl: usize
+}
+
+/// Reports the sizes of a closed program in BLC and BCL.
+///
+/// # Example
+/// ```
+/// use blc::analysis::{compare_program_encodings, ProgramSizes};
+/// use blc::encoding::binary::from_bits;
+///
+/// let k = from_bits(b"0000110").unwrap();
+///
+/// assert_eq!(compare_program_encodings(&k), Ok(ProgramSizes { blc: 7, bcl: 2 }));
+/// ```
+/// # Errors
+///
+/// Returns `binary::Error::NotATerm` if the program is not closed.
+pub fn compare_program_encodings(program: &Term) -> Result<ProgramSizes, binary::Error> {
+    Ok(ProgramSizes { blc: to_bits(program).len(), bcl: to_bcl(program)?.len() })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use encoding::binary::from_bits;
+
+    #[test]
+    fn sizes() {
+        assert_eq!(size(&Var(1)), 1);
+        assert_eq!(size(&from_bits(b"00000001011110100111010").unwrap()), 10);
+    }
+
+    #[test]
+    fn step_limits() {
+        let k_i_omega = b"010100001100010010001101000011010"; // normalizes in 2 steps
+        let strategies = [Strategy::Normal];
+        let (two, one) = (Limits { steps: 2, size: 0 }, Limits { steps: 1, size: 0 });
+
+        let exact = compare_strategies(k_i_omega, Input::Nothing, &strategies, two);
+        let short = compare_strategies(k_i_omega, Input::Nothing, &strategies, one);
+
+        assert!(exact.unwrap()[0].terminated);
+        assert!(!short.unwrap()[0].terminated);
+    }
+
+    #[test]
+    fn size_limits() {
+        let omega3 = b"01000101101010000101101010"; // (λ111)(λ111)
+        let strategies = [Strategy::Normal];
+        let limits = Limits { steps: 0, size: 50 };
+        let report = compare_strategies(omega3, Input::Nothing, &strategies, limits).unwrap()[0];
+
+        assert!(!report.terminated);
+        assert!(report.peak_size > 50);
+    }
+
+    #[test]
+    fn trace_diffs() {
+        let k_i_omega = b"010100001100010010001101000011010";
+        let limits = Limits { steps: 4, size: 0 };
+        let same = (Strategy::Normal, Strategy::CallByName);
+        let different = (Strategy::Normal, Strategy::Applicative);
+
+        let diff = diff_traces(k_i_omega, Input::Nothing, same, limits).unwrap();
+        assert_eq!(diff.diverged_at(), None);
+        assert_eq!(diff.rejoined, None);
+
+        let diff = diff_traces(k_i_omega, Input::Nothing, different, limits).unwrap();
+        assert_eq!(diff.common_steps, 1);
+        assert_eq!(diff.traces.1.len(), 5);
+        assert_eq!(diff.render().lines().count(), 6);
+        assert!(diff.render().lines().nth(1).unwrap().contains(" = "));
+
+        // (λ11)((λ1)(λ1)) meets again at (λ1)(λ1)
+        let omega_ii = b"01000110100100100010";
+        let diff = diff_traces(omega_ii, Input::Nothing, different, limits).unwrap();
+        assert_eq!(diff.rejoined, Some((3, 2)));
+    }
+
+    #[test]
+    fn encodings() {
+        let numerals = compare_numeral_encodings(0);
+        assert_eq!((numerals[0].bits, numerals[1].bits), (6, 7)); // λλ1 and λλ2
+
+        let lists = compare_list_encodings(&[]);
+        assert!(lists.iter().all(|report| report.bits < 8));
+
+        let reverse = from_bits(b"00010110010001101000000000010111001111101111000010110111101100\
+                                  00010");
+        let sizes = compare_program_encodings(&reverse.unwrap()).unwrap();
+        assert_eq!(sizes.blc, 67);
+        assert!(sizes.bcl > sizes.blc);
+    }
+
+    #[test]
+    fn invalid_programs() {
+        assert_eq!(
+            compare_strategies(b"22", Input::Nothing, &[Strategy::Normal], Limits::default()),
+            Err(Error::InvalidProgram)
+        );
+    }
+}
@@ -0,0 +1,214 @@
+//! Benchmarks of the evaluation backends
+//!
+//! `run_benchmarks` executes every program of the bundled `CORPUS` with every backend and measures
+//! how many β-reductions it took, how fast they were performed and how large the term grew, so
+//! that performance regressions can be spotted with `report`. Step-by-step backends are measured
+//! after every step, which makes them report the peak size of the term; the others only report
+//! their times.
+//!
+//! The backends are the ones that reach the normal forms of all the programs: `cek::Cek` diverges
+//! on the ones that rely on lazy evaluation and `optimal::Optimal` can't read most of them back.
+//! John Tromp's Brainfuck interpreter is not included yet, as it doesn't run "Hello World!" here.
+
+use lambda_calculus::Term;
+use analysis::size;
+use encoding::binary::{decompress, from_bits};
+use execution::{apply_input, Input, Strategy};
+use execution::closures::Closures;
+use execution::graph::Graph;
+use execution::interned::Interned;
+use execution::iterative::Iterative;
+use execution::lazy::Lazy;
+use execution::machine::{Machine, SmallStep, BigStep};
+use execution::memo::Memo;
+use execution::strictness::Hybrid;
+use execution::vm::Vm;
+use repr::levels;
+use std::time::{Duration, Instant};
+
+/// A program from the benchmark corpus.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Benchmark {
+    /// the name of the program
+    pub name: &'static str,
+    /// the packed program
+    pub program: &'static [u8],
+    /// the unencoded input of the program
+    pub input: &'static [u8]
+}
+
+/// The bundled benchmark programs: John Tromp's string reversal, inflate and deflate (from the
+/// IOCCC 2012), a sort and the first 8 elements of Tromp's characteristic sequence of primes.
+pub const CORPUS: &[Benchmark] = &[
+    Benchmark {
+        name: "reverse",
+        program: &[0x16, 0x46, 0x80, 0x17, 0x3e, 0xf0, 0xb7, 0xb0, 0x40],
+        input: b"herp derp"
+    },
+    Benchmark {
+        name: "inflate",
+        program: &[
+            0x44, 0x44, 0x68, 0x16, 0x01, 0x79, 0x1a, 0x00, 0x16, 0x7f, 0xfb, 0xcb, 0xcf, 0xdf,
+            0x65, 0xfb, 0xed, 0x0f, 0x3c, 0xe7, 0x3c, 0xf3, 0xc2, 0xd8, 0x20, 0x58, 0x2c, 0x0b,
+            0x06, 0xc0
+        ],
+        input: &[0x1, 0x7a, 0x74]
+    },
+    Benchmark {
+        name: "deflate",
+        program: &[
+            0x44, 0x68, 0x16, 0x05, 0x7e, 0x01, 0x17, 0x00, 0xbe, 0x55, 0xff, 0xf0, 0x0d, 0xc1,
+            0x8b, 0xb2, 0xc1, 0xb0, 0xf8, 0x7c, 0x2d, 0xd8, 0x05, 0x9e, 0x09, 0x7f, 0xbf, 0xb1,
+            0x48, 0x39, 0xce, 0x81, 0xce, 0x80
+        ],
+        input: b"00000001011110100111010"
+    },
+    Benchmark {
+        name: "sort",
+        program: &[
+            0x15, 0x46, 0x84, 0x06, 0x05, 0x46, 0x81, 0x60, 0x15, 0xfb, 0xec, 0x2f, 0x80, 0x01,
+            0x5b, 0xf9, 0x7f, 0x0b, 0x7e, 0xf7, 0x2f, 0xec, 0x2d, 0xfb, 0x80, 0x56, 0x05, 0xfd,
+            0x85, 0xbb, 0x76, 0x11, 0x5d, 0x50, 0x5c, 0x00, 0xbe, 0x7f, 0xc1, 0x2b, 0xff, 0x0f,
+            0xfc, 0x2c, 0x1b, 0x72, 0xbf, 0xf0, 0xff, 0xc2, 0xc1, 0x6d, 0x34, 0x50, 0x40
+        ],
+        input: b"3241"
+    },
+    Benchmark {
+        name: "primes",
+        program: &[
+            0x15, 0x11, 0xc2, 0xed, 0x0e, 0x17, 0x68, 0x05, 0x51, 0x60, 0x02, 0x0d, 0x01, 0x0a,
+            0x8b, 0x01, 0x06, 0xe0, 0x94, 0x05, 0xbb, 0x23, 0x06, 0xcb, 0xe4, 0x05, 0x78, 0x19,
+            0xde, 0x31, 0x72, 0x30, 0x58, 0x81, 0xce, 0x73, 0x9c, 0xe7, 0x3a, 0x44, 0x66, 0x51,
+            0xa0, 0x16, 0x09, 0x15, 0xf7, 0xa4, 0x68, 0x73, 0x40, 0x0b, 0x73, 0x9f, 0xde, 0x01,
+            0xf3, 0x70, 0x2c, 0x1b, 0x40
+        ],
+        input: b""
+    }
+];
+
+/// The names of the measured backends, in the order they are measured in.
+pub const BACKENDS: &[&str] = &[
+    "small-step", "small-step strict data", "big-step", "lazy", "graph", "interned", "iterative",
+    "closures", "memo", "hybrid", "vm", "levels"
+];
+
+/// The results of running a single benchmark with a single backend.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Measurement {
+    /// the name of the benchmark
+    pub benchmark: &'static str,
+    /// the name of the backend
+    pub backend: &'static str,
+    /// the number of performed β-reductions
+    pub steps: usize,
+    /// the time it took to reach the normal form
+    pub elapsed: Duration,
+    /// the size of the largest intermediate term, in nodes, for step-by-step backends
+    pub peak_size: Option<usize>
+}
+
+impl Measurement {
+    /// Returns the number of β-reductions performed per second.
+    pub fn steps_per_sec(&self) -> f64 {
+        self.steps as f64 / self.elapsed.as_secs_f64().max(f64::MIN_POSITIVE)
+    }
+}
+
+/// Runs a benchmark with every backend.
+///
+/// # Example
+/// ```
+/// use blc::bench::{run_benchmark, BACKENDS, CORPUS};
+///
+/// let measurements = run_benchmark(&CORPUS[0]);
+///
+/// assert_eq!(measurements.len(), BACKENDS.len());
+/// assert!(measurements.iter().all(|m| m.steps > 0));
+/// ```
+pub fn run_benchmark(benchmark: &Benchmark) -> Vec<Measurement> {
+    let program = from_bits(&decompress(benchmark.program)).unwrap(); // safe - a valid program
+    let term = apply_input(program, Input::Bytes(benchmark.input)).unwrap(); // safe - bytes
+    let measurement = |backend, steps, elapsed, peak_size| Measurement {
+        benchmark: benchmark.name, backend, steps, elapsed, peak_size
+    };
+
+    let mut measurements = Vec::with_capacity(BACKENDS.len());
+
+    for (&backend, strategy) in BACKENDS.iter().zip(&[Strategy::Normal, Strategy::StrictData]) {
+        let (steps, elapsed, peak_size) = measure_steps(term.clone(), *strategy);
+        measurements.push(measurement(backend, steps, elapsed, Some(peak_size)));
+    }
+
+    for (&backend, mut machine) in BACKENDS[2..].iter().zip(machines()) {
+        let mut reduced = term.clone();
+        let start = Instant::now();
+        let steps = machine.reduce(&mut reduced, 0);
+        measurements.push(measurement(backend, steps, start.elapsed(), None));
+    }
+
+    let start = Instant::now();
+    let levels_term = levels::from_indices(&term).unwrap(); // safe - a closed term
+    let (_, stats) = levels_term.normalize_with_stats().unwrap(); // safe - a closed term
+    measurements.push(measurement(BACKENDS[11], stats.reductions, start.elapsed(), None));
+
+    measurements
+}
+
+/// The backends implementing `Machine` that are not measured step by step, in the order of
+/// `BACKENDS`.
+fn machines() -> Vec<Box<dyn Machine>> {
+    vec![
+        Box::new(BigStep), Box::new(Lazy), Box::new(Graph), Box::new(Interned),
+        Box::new(Iterative), Box::new(Closures), Box::new(Memo::new()), Box::new(Hybrid),
+        Box::new(Vm)
+    ]
+}
+
+fn measure_steps(mut term: Term, strategy: Strategy) -> (usize, Duration, usize) {
+    let mut machine = SmallStep { strategy };
+    let mut steps = 0;
+    let mut peak_size = size(&term);
+    let start = Instant::now();
+
+    loop {
+        let count = machine.step(&mut term);
+        if count == 0 { break }
+        steps += count;
+        peak_size = peak_size.max(size(&term));
+    }
+
+    (steps, start.elapsed(), peak_size)
+}
+
+/// Runs every benchmark of the `CORPUS` with every backend.
+pub fn run_benchmarks() -> Vec<Measurement> {
+    CORPUS.iter().flat_map(run_benchmark).collect()
+}
+
+/// Renders measurements as a table.
+///
+/// # Example
+/// ```
+/// use blc::bench::{report, run_benchmark, CORPUS};
+///
+/// let table = report(&run_benchmark(&CORPUS[0]));
+///
+/// assert!(table.starts_with("benchmark"));
+/// assert!(table.contains("reverse"));
+/// ```
+pub fn report(measurements: &[Measurement]) -> String {
+    let mut table = format!(
+        "{:<12} {:<24} {:>10} {:>14} {:>12}\n",
+        "benchmark", "backend", "steps", "steps/s", "peak size"
+    );
+
+    for m in measurements {
+        let peak_size = m.peak_size.map_or("-".into(), |size| size.to_string());
+        table.push_str(&format!(
+            "{:<12} {:<24} {:>10} {:>14.0} {:>12}\n",
+            m.benchmark, m.backend, m.steps, m.steps_per_sec(), peak_size
+        ));
+    }
+
+    table
+}
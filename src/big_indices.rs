@@ -0,0 +1,458 @@
+//! Lambda `Term`s with De Bruijn indices of arbitrary size
+//!
+//! The `Term`s of `lambda_calculus` and of the `native` module store their indices in `usize`s,
+//! so how large an index can get depends on the platform. The `Term` of this module stores its
+//! indices as arbitrary-precision `Index`es instead, so that machine-generated programs with huge
+//! unary indices are parsed, printed and reduced the same way everywhere. It is available with
+//! `features = ["big_indices"]`.
+
+pub use self::Term::*;
+pub use encoding::binary::Error;
+use std::cmp::Ordering;
+use std::fmt;
+use std::mem;
+
+/// A non-negative integer of arbitrary size, used as a De Bruijn index.
+#[derive(PartialEq, Eq, Clone, Hash, Default)]
+pub struct Index(Vec<u32>); // little-endian limbs without trailing zeros
+
+impl Index {
+    /// Returns `true` if the index is `0`, which is never bound.
+    pub fn is_zero(&self) -> bool { self.0.is_empty() }
+
+    /// Returns the index as a `usize`, if it fits in one.
+    pub fn to_usize(&self) -> Option<usize> {
+        if self.0.len() > 4 { return None }
+
+        let value = self.0.iter().rev().fold(0u128, |acc, &limb| acc << 32 | u128::from(limb));
+        if value <= usize::MAX as u128 { Some(value as usize) } else { None }
+    }
+
+    /// Increases the index by 1.
+    pub fn increment(&mut self) {
+        self.add(1);
+    }
+
+    /// Decreases the index by 1.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is `0`.
+    pub fn decrement(&mut self) {
+        assert!(!self.is_zero(), "can't decrement a zero index");
+
+        for limb in &mut self.0 {
+            let (value, borrow) = limb.overflowing_sub(1);
+            *limb = value;
+            if !borrow { break }
+        }
+
+        while self.0.last() == Some(&0) { self.0.pop(); }
+    }
+
+    /// Increases the index by `n`.
+    pub fn add(&mut self, n: usize) {
+        let mut carry = n as u128;
+        let mut i = 0;
+
+        while carry != 0 {
+            if i == self.0.len() { self.0.push(0) }
+
+            let sum = u128::from(self.0[i]) + (carry & 0xffff_ffff);
+            self.0[i] = sum as u32;
+            carry = (carry >> 32) + (sum >> 32);
+            i += 1;
+        }
+    }
+
+    fn cmp_usize(&self, n: usize) -> Ordering {
+        self.to_usize().map_or(Ordering::Greater, |i| i.cmp(&n))
+    }
+
+    /// Divides the index by `divisor` in place and returns the remainder.
+    fn div_rem(&mut self, divisor: u32) -> u32 {
+        let mut remainder = 0u64;
+
+        for limb in self.0.iter_mut().rev() {
+            let value = remainder << 32 | u64::from(*limb);
+            *limb = (value / u64::from(divisor)) as u32;
+            remainder = value % u64::from(divisor);
+        }
+
+        while self.0.last() == Some(&0) { self.0.pop(); }
+        remainder as u32
+    }
+}
+
+impl From<usize> for Index {
+    fn from(n: usize) -> Self {
+        let mut index = Index::default();
+        index.add(n);
+        index
+    }
+}
+
+impl Ord for Index {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let (limbs, other_limbs) = (self.0.iter().rev(), other.0.iter().rev());
+        self.0.len().cmp(&other.0.len()).then_with(|| limbs.cmp(other_limbs))
+    }
+}
+
+impl PartialOrd for Index {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> { Some(self.cmp(other)) }
+}
+
+impl fmt::Display for Index {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_zero() { return write!(f, "0") }
+
+        let mut rest = self.clone();
+        let mut chunks = Vec::new();
+        while !rest.is_zero() { chunks.push(rest.div_rem(1_000_000_000)) }
+
+        write!(f, "{}", chunks.pop().unwrap())?; // safe - the index is not zero
+        chunks.iter().rev().try_for_each(|chunk| write!(f, "{:09}", chunk))
+    }
+}
+
+impl fmt::UpperHex for Index {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.0.split_last() {
+            None => write!(f, "0"),
+            Some((last, rest)) => {
+                write!(f, "{:X}", last)?;
+                rest.iter().rev().try_for_each(|limb| write!(f, "{:08X}", limb))
+            }
+        }
+    }
+}
+
+impl fmt::Debug for Index {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result { fmt::Display::fmt(self, f) }
+}
+
+/// A lambda term that is either a variable with a De Bruijn `Index`, an abstraction over a term
+/// or an application of one term to another.
+#[derive(PartialEq, Eq, Clone, Hash)]
+pub enum Term {
+    /// a variable
+    Var(Index),
+    /// an abstraction
+    Abs(Box<Term>),
+    /// an application
+    App(Box<Term>, Box<Term>)
+}
+
+/// Produces a variable with the given De Bruijn index.
+pub fn var(index: usize) -> Term { Var(Index::from(index)) }
+
+/// Wraps a `Term` in an `Abs`traction.
+pub fn abs(term: Term) -> Term { Abs(Box::new(term)) }
+
+/// Produces an `App`lication of two given `Term`s without any reduction.
+pub fn app(lhs: Term, rhs: Term) -> Term { App(Box::new(lhs), Box::new(rhs)) }
+
+/// Parse a blc-encoded `Term`; the indices of its variables can be arbitrarily large.
+///
+/// # Example
+/// ```
+/// use blc::big_indices::{abs, from_bits, var};
+///
+/// assert_eq!(from_bits(b"0000110"), Ok(abs(abs(var(2)))));
+/// ```
+/// # Errors
+///
+/// Returns `Error::NotATerm` if the input is not a valid term.
+pub fn from_bits(input: &[u8]) -> Result<Term, Error> {
+    parse(input, &mut 0).ok_or(Error::NotATerm)
+}
+
+fn parse(input: &[u8], pos: &mut usize) -> Option<Term> {
+    while input.get(*pos).is_some_and(|b| b" \t\r\n".contains(b)) { *pos += 1 }
+
+    match (input.get(*pos), input.get(*pos + 1)) {
+        (Some(b'0'), Some(b'0')) => {
+            *pos += 2;
+            Some(abs(parse(input, pos)?))
+        },
+        (Some(b'0'), Some(b'1')) => {
+            *pos += 2;
+            let lhs = parse(input, pos)?;
+            Some(app(lhs, parse(input, pos)?))
+        },
+        (Some(b'1'), _) => {
+            let mut index = Index::default();
+            while input.get(*pos) == Some(&b'1') {
+                index.increment();
+                *pos += 1;
+            }
+            // the terminating `0` may be missing at the end of the input
+            if input.get(*pos) == Some(&b'0') { *pos += 1 }
+            Some(Var(index))
+        },
+        _ => None
+    }
+}
+
+/// Encode a `Term` as blc "bits".
+///
+/// # Example
+/// ```
+/// use blc::big_indices::{abs, to_bits, var};
+///
+/// assert_eq!(to_bits(&abs(abs(var(2)))), b"0000110");
+/// ```
+pub fn to_bits(term: &Term) -> Vec<u8> {
+    let mut output = Vec::new();
+    write_bits(term, &mut output);
+    output
+}
+
+fn write_bits(term: &Term, output: &mut Vec<u8>) {
+    match *term {
+        Var(ref index) => {
+            let mut ones = index.clone();
+            while !ones.is_zero() {
+                output.push(b'1');
+                ones.decrement();
+            }
+            output.push(b'0');
+        },
+        Abs(ref body) => {
+            output.extend_from_slice(b"00");
+            write_bits(body, output);
+        },
+        App(ref lhs, ref rhs) => {
+            output.extend_from_slice(b"01");
+            write_bits(lhs, output);
+            write_bits(rhs, output);
+        }
+    }
+}
+
+/// Reduces a `Term` to its normal form using the normal evaluation order.
+///
+/// # Example
+/// ```
+/// use blc::big_indices::{beta, from_bits, to_bits};
+///
+/// let k_i = from_bits(b"0100001100010").unwrap(); // (λλ2)(λ1)
+///
+/// assert_eq!(to_bits(&beta(k_i)), b"000010"); // λλ1
+/// ```
+pub fn beta(mut term: Term) -> Term {
+    term.reduce(0);
+    term
+}
+
+impl Term {
+    /// Performs normal-order β-reduction with an optional limit on the number of reductions (`0`
+    /// means no limit) and returns the number of performed reductions.
+    pub fn reduce(&mut self, limit: usize) -> usize {
+        let mut count = 0;
+        self.beta_nor(limit, &mut count);
+
+        count
+    }
+
+    fn beta_cbn(&mut self, limit: usize, count: &mut usize) {
+        if limit != 0 && *count == limit { return }
+
+        if let App(ref mut lhs, _) = *self {
+            lhs.beta_cbn(limit, count);
+        } else {
+            return
+        }
+
+        if self.is_reducible(limit, *count) {
+            self.eval(count);
+            self.beta_cbn(limit, count);
+        }
+    }
+
+    fn beta_nor(&mut self, limit: usize, count: &mut usize) {
+        if limit != 0 && *count == limit { return }
+
+        match *self {
+            Abs(ref mut body) => return body.beta_nor(limit, count),
+            App(ref mut lhs, _) => lhs.beta_cbn(limit, count),
+            Var(_) => return
+        }
+
+        if self.is_reducible(limit, *count) {
+            self.eval(count);
+            self.beta_nor(limit, count);
+        } else if let App(ref mut lhs, ref mut rhs) = *self {
+            lhs.beta_nor(limit, count);
+            rhs.beta_nor(limit, count);
+        }
+    }
+
+    fn is_reducible(&self, limit: usize, count: usize) -> bool {
+        if let App(ref lhs, _) = *self {
+            if let Abs(_) = **lhs { return limit == 0 || count < limit }
+        }
+        false
+    }
+
+    fn eval(&mut self, count: &mut usize) {
+        if let App(lhs, rhs) = mem::replace(self, Var(Index::default())) {
+            if let Abs(mut body) = *lhs {
+                body.substitute(&rhs, 1);
+                *self = *body;
+                *count += 1;
+            }
+        }
+    }
+
+    fn substitute(&mut self, rhs: &Term, depth: usize) {
+        match *self {
+            Var(ref mut i) => match i.cmp_usize(depth) {
+                Ordering::Equal => {
+                    let mut substituted = rhs.clone();
+                    substituted.shift(depth - 1, 0);
+                    *self = substituted;
+                },
+                Ordering::Greater => i.decrement(),
+                Ordering::Less => ()
+            },
+            Abs(ref mut body) => body.substitute(rhs, depth + 1),
+            App(ref mut lhs, ref mut rhs2) => {
+                lhs.substitute(rhs, depth);
+                rhs2.substitute(rhs, depth)
+            }
+        }
+    }
+
+    fn shift(&mut self, by: usize, depth: usize) {
+        match *self {
+            Var(ref mut i) => if i.cmp_usize(depth) == Ordering::Greater { i.add(by) },
+            Abs(ref mut body) => body.shift(by, depth + 1),
+            App(ref mut lhs, ref mut rhs) => {
+                lhs.shift(by, depth);
+                rhs.shift(by, depth)
+            }
+        }
+    }
+}
+
+impl fmt::Debug for Term {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        show(self, 0, f)
+    }
+}
+
+fn show(term: &Term, context_precedence: usize, f: &mut fmt::Formatter) -> fmt::Result {
+    match *term {
+        Var(ref i) if i.is_zero() => write!(f, "undefined"),
+        Var(ref i) => write!(f, "{:X}", i),
+        Abs(ref t) => {
+            if context_precedence > 1 { write!(f, "(")? }
+            write!(f, "λ")?;
+            show(t, 0, f)?;
+            if context_precedence > 1 { write!(f, ")")? }
+            Ok(())
+        },
+        App(ref t1, ref t2) => {
+            if context_precedence == 3 { write!(f, "(")? }
+            show(t1, 2, f)?;
+            show(t2, 3, f)?;
+            if context_precedence == 3 { write!(f, ")")? }
+            Ok(())
+        }
+    }
+}
+
+#[cfg(feature = "lambda_calculus")]
+impl From<::lambda_calculus::Term> for Term {
+    fn from(term: ::lambda_calculus::Term) -> Self {
+        match term {
+            ::lambda_calculus::Var(i) => var(i),
+            ::lambda_calculus::Abs(t) => abs(Term::from(*t)),
+            ::lambda_calculus::App(t1, t2) => app(Term::from(*t1), Term::from(*t2))
+        }
+    }
+}
+
+#[cfg(feature = "lambda_calculus")]
+impl ::std::convert::TryFrom<Term> for ::lambda_calculus::Term {
+    type Error = Error;
+
+    /// Fails with `Error::NotATerm` if any of the indices doesn't fit in a `usize`.
+    fn try_from(term: Term) -> Result<Self, Error> {
+        use std::convert::TryInto;
+
+        match term {
+            Var(i) => i.to_usize().map(::lambda_calculus::Var).ok_or(Error::NotATerm),
+            Abs(t) => Ok(::lambda_calculus::abs((*t).try_into()?)),
+            App(t1, t2) => Ok(::lambda_calculus::app((*t1).try_into()?, (*t2).try_into()?))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Returns the smallest index that doesn't fit in a `usize`.
+    fn huge() -> Index {
+        let mut index = Index::from(usize::MAX);
+        index.increment();
+        index
+    }
+
+    #[test]
+    fn indices() {
+        let mut index = huge();
+        let hex_digits = 2 * mem::size_of::<usize>();
+
+        assert_eq!(index.to_usize(), None);
+        assert!(index > Index::from(usize::MAX));
+        assert_eq!(format!("{:X}", index), format!("1{}", "0".repeat(hex_digits)));
+        if mem::size_of::<usize>() == 8 {
+            assert_eq!(index.to_string(), "18446744073709551616");
+        }
+
+        index.decrement();
+        assert_eq!(index.to_usize(), Some(usize::MAX));
+        assert_eq!(Index::from(1_000_000_007).to_string(), "1000000007");
+    }
+
+    #[test]
+    fn parsing_and_printing() {
+        assert_eq!(from_bits(b"10"), Ok(var(1)));
+        assert_eq!(from_bits(b"00 00\t10"), Ok(abs(abs(var(1)))));
+        assert_eq!(from_bits(b"01"), Err(Error::NotATerm));
+
+        let succ = b"000000011100101111011010";
+        assert_eq!(to_bits(&from_bits(succ).unwrap()), succ);
+        assert_eq!(format!("{:?}", from_bits(succ).unwrap()), "λλλ2(321)");
+    }
+
+    #[test]
+    fn reduction() {
+        // (λ1 H) y, with H free, reduces to y (H - 1)
+        let mut term = app(abs(app(var(1), Var(huge()))), var(7));
+        term.reduce(0);
+
+        let mut lowered = huge();
+        lowered.decrement();
+        assert_eq!(term, app(var(7), Var(lowered)));
+
+        // (λλ2) (λH) shifts H by one binder
+        let mut shifted = huge();
+        shifted.increment();
+        assert_eq!(beta(app(abs(abs(var(2))), abs(Var(huge())))), abs(abs(Var(shifted))));
+    }
+
+    #[cfg(feature = "lambda_calculus")]
+    #[test]
+    fn conversions() {
+        use lambda_calculus::combinators::S;
+        use std::convert::TryFrom;
+
+        assert_eq!(::lambda_calculus::Term::try_from(Term::from(S())), Ok(S()));
+        assert_eq!(::lambda_calculus::Term::try_from(Var(huge())), Err(Error::NotATerm));
+    }
+}
@@ -0,0 +1,244 @@
+//! The `blc` command-line interface
+
+extern crate blc;
+
+use blc::digest::Digest;
+use blc::encoding::lambda::{escape, Escaping};
+use blc::execution::{run_term, run_term_bytes, ClosedTerm, Input, IoMode, Options};
+use blc::execution::demand::run_interactive;
+use blc::program::Program;
+use blc::program::bundle::Bundle;
+use blc::program::lint::lint_path;
+use std::env;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::Path;
+use std::process;
+
+const USAGE: &str = "\
+usage: blc <command> [<args>]
+
+commands:
+    run [--escape <mode>] [--digest] [--bits] [--eta] <program> [<input>]
+        executes a program with the contents of the input file (or the standard input) as its
+        argument; non-printable output characters are rendered according to the mode: `raw`
+        (the default), `hex`, `strip` or `replace`; with `--digest`, the SHA-256 digest of the
+        output bytes, as they are written, is printed to the standard error; with `--bits`, the
+        program runs in bit mode, like with `uni -b`: the input and the output are lists of bits,
+        written as the characters `0` and `1`; with `--eta`, the output is η-reduced before it is
+        decoded
+    exec <script> [<input>]
+        executes a script, a program file that can start with a `#!` line and comment lines,
+        with the contents of the input file (or the standard input) as its argument, reading them
+        only as far as the script needs them and writing the output as it is produced; meant to
+        be the interpreter of executable `.blc` files
+    bundle pack <bundle> <program>...
+        writes the programs to a bundle, each named after its metadata or its file name
+    bundle unpack <bundle> [<directory>]
+        writes every program of a bundle to a `<name>.Blc` container in the directory (or the
+        current one)
+    bundle list <bundle>
+        prints the name, the size and the author of every program of a bundle
+    bundle run <bundle> <name> [<input>]
+        executes the program of a bundle with the given name with the contents of the input
+        file (or the standard input) as its argument
+    lint <program>
+        analyzes a program without executing it and prints the problems found in it and its size;
+        fails if the program is invalid";
+
+fn main() {
+    let args = env::args().skip(1).collect::<Vec<String>>();
+
+    let result = match args.first().map(|arg| arg.as_str()) {
+        Some("run") => run(&args[1..]),
+        Some("exec") => exec(&args[1..]),
+        Some("bundle") => bundle(&args[1..]),
+        Some("lint") => lint(&args[1..]),
+        Some("help") | Some("--help") | Some("-h") => {
+            println!("{}", USAGE);
+            Ok(())
+        },
+        _ => Err(USAGE.into())
+    };
+
+    if let Err(message) = result {
+        eprintln!("{}", message);
+        process::exit(1);
+    }
+}
+
+fn run(args: &[String]) -> Result<(), String> {
+    let mut escaping = Escaping::Raw;
+    let mut digest = false;
+    let mut bits = false;
+    let mut eta = false;
+    let mut paths = Vec::new();
+    let mut args = args.iter();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--escape" => escaping = parse_escaping(args.next())?,
+            "--digest" => digest = true,
+            "--bits" => bits = true,
+            "--eta" => eta = true,
+            _ => paths.push(arg)
+        }
+    }
+
+    let (program_path, input_path) = match paths[..] {
+        [program] => (program, None),
+        [program, input] => (program, Some(input)),
+        _ => return Err(USAGE.into())
+    };
+
+    let program = Program::from_path(program_path)
+        .map_err(|e| format!("can't load {}: {:?}", program_path, e))?;
+    let input = read_input(input_path).map_err(|e| format!("can't read the input: {}", e))?;
+    let io_mode = if bits { IoMode::Bits } else { IoMode::Bytes };
+    let options = Options { io_mode, eta, ..Options::default() };
+    let program = ClosedTerm::new(program.into_term())
+        .map_err(|e| format!("execution failed: {:?}", e))?;
+    // raw output is written byte for byte; escaped output is text, written as UTF-8
+    let output = match escaping {
+        Escaping::Raw => run_term_bytes(program, Input::Bytes(&input), options),
+        _ => run_term(program, Input::Bytes(&input), options)
+            .map(|output| escape(&output, escaping).into_bytes())
+    };
+    let output = output.map_err(|e| format!("execution failed: {:?}", e))?;
+
+    if digest { eprintln!("sha256: {}", Digest::of(&output)) }
+    io::stdout().write_all(&output).map_err(|e| e.to_string())
+}
+
+fn exec(args: &[String]) -> Result<(), String> {
+    let (script_path, input_path) = match args {
+        [script] => (script, None),
+        [script, input] => (script, Some(input)),
+        _ => return Err(USAGE.into())
+    };
+
+    let script = Program::from_path(script_path)
+        .map_err(|e| format!("can't load {}: {:?}", script_path, e))?;
+    let script = ClosedTerm::new(script.into_term())
+        .map_err(|e| format!("execution failed: {:?}", e))?;
+
+    // the input is read as it is needed and the output written as it is produced, so scripts can
+    // be interactive
+    let result = match input_path {
+        Some(path) => {
+            let input = fs::File::open(path).map_err(|e| format!("can't read the input: {}", e))?;
+            run_interactive(script, input, io::stdout().lock())
+        },
+        None => run_interactive(script, io::stdin().lock(), io::stdout().lock())
+    };
+    result.map_err(|e| format!("execution failed: {:?}", e))
+}
+
+fn bundle(args: &[String]) -> Result<(), String> {
+    let (command, bundle_path, args) = match args {
+        [command, bundle_path, args @ ..] => (command.as_str(), bundle_path, args),
+        _ => return Err(USAGE.into())
+    };
+
+    if command == "pack" {
+        if args.is_empty() { return Err(USAGE.into()) }
+
+        let mut bundle = Bundle::new();
+        for path in args {
+            let program = Program::from_path(path)
+                .map_err(|e| format!("can't load {}: {:?}", path, e))?;
+            let name = match program.metadata().name {
+                Some(ref name) => name.clone(),
+                None => program_name(path)
+            };
+            bundle.insert(name, program);
+        }
+
+        return fs::write(bundle_path, bundle.to_bytes())
+            .map_err(|e| format!("can't write {}: {}", bundle_path, e))
+    }
+
+    let bundle = Bundle::from_path(bundle_path)
+        .map_err(|e| format!("can't load {}: {:?}", bundle_path, e))?;
+
+    match (command, args) {
+        ("unpack", []) | ("unpack", [_]) => {
+            let directory = Path::new(args.first().map_or(".", |dir| dir.as_str()));
+
+            for (name, program) in bundle.iter() {
+                if name.is_empty() || name.contains(['/', '\\']) {
+                    return Err(format!("can't unpack a program named `{}`", name))
+                }
+                let path = directory.join(format!("{}.Blc", name));
+                fs::write(&path, program.to_container())
+                    .map_err(|e| format!("can't write {}: {}", path.display(), e))?;
+            }
+            Ok(())
+        },
+        ("list", []) => {
+            for (name, program) in bundle.iter() {
+                let bits = program.bit_count();
+                match program.metadata().author {
+                    Some(ref author) => println!("{}\t{} bits\tby {}", name, bits, author),
+                    None => println!("{}\t{} bits", name, bits)
+                }
+            }
+            Ok(())
+        },
+        ("run", [name]) | ("run", [name, _]) => {
+            let program = bundle.get(name)
+                .ok_or_else(|| format!("there is no program named `{}` in {}", name, bundle_path))?;
+            let input = read_input(args.get(1))
+                .map_err(|e| format!("can't read the input: {}", e))?;
+            let output = ClosedTerm::new(program.term().clone())
+                .and_then(|program| {
+                    run_term_bytes(program, Input::Bytes(&input), Options::default())
+                })
+                .map_err(|e| format!("execution failed: {:?}", e))?;
+
+            io::stdout().write_all(&output).map_err(|e| e.to_string())
+        },
+        _ => Err(USAGE.into())
+    }
+}
+
+/// Prints the findings of linting a program file and fails if it is not valid.
+fn lint(args: &[String]) -> Result<(), String> {
+    let path = match args {
+        [path] => path,
+        _ => return Err(USAGE.into())
+    };
+
+    let report = lint_path(path).map_err(|e| format!("can't load {}: {:?}", path, e))?;
+    print!("{}", report);
+
+    if report.is_valid() { Ok(()) } else { Err(format!("{} is not a valid program", path)) }
+}
+
+/// Returns the name of a program file without its directories and extensions.
+fn program_name(path: &str) -> String {
+    let file_name = Path::new(path).file_name().map_or(path.into(), |name| name.to_string_lossy());
+
+    file_name.split('.').next().unwrap_or_default().to_string()
+}
+
+fn parse_escaping(mode: Option<&String>) -> Result<Escaping, String> {
+    match mode.map(|mode| mode.as_str()) {
+        Some("raw")     => Ok(Escaping::Raw),
+        Some("hex")     => Ok(Escaping::Hex),
+        Some("strip")   => Ok(Escaping::Strip),
+        Some("replace") => Ok(Escaping::Replace('\u{fffd}')),
+        _ => Err("the escape mode must be one of `raw`, `hex`, `strip` or `replace`".into())
+    }
+}
+
+fn read_input(path: Option<&String>) -> io::Result<Vec<u8>> {
+    match path {
+        Some(path) => fs::read(path),
+        None => {
+            let mut input = Vec::new();
+            io::stdin().read_to_end(&mut input)?;
+            Ok(input)
+        }
+    }
+}
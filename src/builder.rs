@@ -0,0 +1,179 @@
+//! A checked term-construction DSL
+//!
+//! Terms are described with `lam`, `app` and `var` and turned into lambda `Term`s with
+//! `Expr::build`, which validates every variable against the number of binders enclosing it; this
+//! way open terms are rejected while they are being constructed instead of failing at run time.
+
+use lambda_calculus::term::{Term, Var, Abs, App};
+use lambda_calculus::term;
+use self::Error::*;
+
+/// An error that can occur when an `Expr` is built into a `Term`.
+#[derive(Debug, PartialEq)]
+pub enum Error {
+    /// a variable with the De Bruijn index 0, which is never bound
+    ZeroIndex,
+    /// a variable with the given index is not bound by any of the `depth` enclosing abstractions
+    Unbound {
+        /// the index of the variable
+        index: usize,
+        /// the number of abstractions enclosing the variable
+        depth: usize
+    }
+}
+
+/// A lambda expression that has not been validated yet.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expr(Node);
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Var(usize),
+    Lam(Box<Expr>),
+    App(Box<Expr>, Box<Expr>),
+    Term(Term)
+}
+
+/// The context an `Expr` is being built in.
+struct Context {
+    depth: usize
+}
+
+/// Produces a variable with the given De Bruijn index.
+///
+/// # Example
+/// ```
+/// use blc::builder::{lam, var};
+///
+/// assert!(lam(var(1)).build().is_ok());
+/// assert!(var(1).build().is_err());
+/// ```
+pub fn var(index: usize) -> Expr { Expr(Node::Var(index)) }
+
+/// Produces an abstraction over the given `Expr`.
+///
+/// # Example
+/// ```
+/// use blc::builder::{lam, var};
+/// use blc::encoding::binary::to_bits;
+///
+/// assert_eq!(to_bits(&lam(lam(var(2))).build().unwrap()), b"0000110");
+/// ```
+pub fn lam(body: Expr) -> Expr { Expr(Node::Lam(Box::new(body))) }
+
+/// Produces an application of one `Expr` to another.
+///
+/// # Example
+/// ```
+/// use blc::builder::{lam, app, var};
+/// use blc::encoding::binary::to_bits;
+///
+/// assert_eq!(to_bits(&lam(app(var(1), var(1))).build().unwrap()), b"00011010");
+/// ```
+pub fn app(lhs: Expr, rhs: Expr) -> Expr { Expr(Node::App(Box::new(lhs), Box::new(rhs))) }
+
+impl Expr {
+    /// Builds a closed `Term` out of `self`.
+    ///
+    /// # Example
+    /// ```
+    /// use blc::builder::{lam, app, var, Error};
+    ///
+    /// assert!(lam(lam(app(var(2), var(1)))).build().is_ok());
+    /// assert_eq!(lam(app(var(1), var(2))).build(), Err(Error::Unbound { index: 2, depth: 1 }));
+    /// ```
+    /// # Errors
+    ///
+    /// Returns an `Error` if `self` contains a variable that is not bound by any abstraction.
+    pub fn build(self) -> Result<Term, Error> {
+        self.build_in(&mut Context { depth: 0 })
+    }
+
+    fn build_in(self, ctx: &mut Context) -> Result<Term, Error> {
+        match self.0 {
+            Node::Var(i) => {
+                check_var(i, ctx.depth)?;
+                Ok(Var(i))
+            },
+            Node::Lam(body) => {
+                ctx.depth += 1;
+                let body = body.build_in(ctx);
+                ctx.depth -= 1;
+                Ok(term::abs(body?))
+            },
+            Node::App(lhs, rhs) => {
+                let lhs = lhs.build_in(ctx)?;
+                let rhs = rhs.build_in(ctx)?;
+                Ok(term::app(lhs, rhs))
+            },
+            Node::Term(t) => {
+                check_term(&t, ctx.depth)?;
+                Ok(t)
+            }
+        }
+    }
+}
+
+/// Embeds an existing `Term`; its free variables are validated against the binders around it.
+///
+/// # Example
+/// ```
+/// use blc::builder::{lam, app, var, Expr};
+/// use blc::encoding::lambda::encode;
+///
+/// assert!(app(lam(var(1)), Expr::from(encode(b"hello"))).build().is_ok());
+/// ```
+impl From<Term> for Expr {
+    fn from(term: Term) -> Self { Expr(Node::Term(term)) }
+}
+
+fn check_var(index: usize, depth: usize) -> Result<(), Error> {
+    if index == 0 {
+        Err(ZeroIndex)
+    } else if index > depth {
+        Err(Unbound { index, depth })
+    } else {
+        Ok(())
+    }
+}
+
+fn check_term(term: &Term, depth: usize) -> Result<(), Error> {
+    match *term {
+        Var(i) => check_var(i, depth),
+        Abs(ref t) => check_term(t, depth + 1),
+        App(ref lhs, ref rhs) => {
+            check_term(lhs, depth)?;
+            check_term(rhs, depth)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use lambda_calculus::combinators::{S, K};
+
+    #[test]
+    fn closed_terms() {
+        assert_eq!(lam(lam(var(2))).build(), Ok(K()));
+        assert_eq!(
+            lam(lam(lam(app(app(var(3), var(1)), app(var(2), var(1)))))).build(),
+            Ok(S())
+        );
+    }
+
+    #[test]
+    fn open_terms() {
+        assert_eq!(var(1).build(),                   Err(Unbound { index: 1, depth: 0 }));
+        assert_eq!(lam(lam(var(3))).build(),         Err(Unbound { index: 3, depth: 2 }));
+        assert_eq!(app(lam(var(1)), var(1)).build(), Err(Unbound { index: 1, depth: 0 }));
+        assert_eq!(lam(var(0)).build(),              Err(ZeroIndex));
+    }
+
+    #[test]
+    fn embedded_terms() {
+        assert_eq!(lam(Expr::from(Var(1))).build(),            Ok(term::abs(Var(1))));
+        assert_eq!(Expr::from(term::abs(Var(2))).build(),      Err(Unbound { index: 2, depth: 1 }));
+        assert_eq!(lam(Expr::from(term::abs(Var(2)))).build(), Ok(K()));
+    }
+}
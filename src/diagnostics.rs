@@ -0,0 +1,221 @@
+//! Diagnostics for textual BLC programs
+//!
+//! `diagnose` checks a textual BLC program for parse errors and unbound variables and describes
+//! each problem with a `Diagnostic` pointing at the offending region of the source, which can be
+//! rendered in a human-readable form with `Diagnostic::render`.
+
+use std::ops::Range;
+
+/// A problem found in a textual BLC program.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Diagnostic {
+    /// the description of the problem
+    pub message: String,
+    /// the explanation attached to the offending region
+    pub label: String,
+    /// the byte offsets of the offending region in the source
+    pub span: Range<usize>
+}
+
+/// Checks a textual BLC program for problems; an empty result means it is a valid, closed term.
+///
+/// # Example
+/// ```
+/// use blc::diagnostics::diagnose;
+///
+/// assert!(diagnose(b"0000110").is_empty());
+/// assert_eq!(diagnose(b"001110")[0].message, "variable 3 is not bound");
+/// ```
+pub fn diagnose(source: &[u8]) -> Vec<Diagnostic> {
+    let mut parser = Parser { source, pos: 0, diagnostics: Vec::new() };
+
+    if let Err(diagnostic) = parser.term(0) {
+        parser.diagnostics.push(diagnostic);
+    } else {
+        parser.skip_whitespace();
+        if parser.pos < source.len() {
+            parser.diagnostics.push(Diagnostic {
+                message: "unexpected input after the end of the term".into(),
+                label: "this is not part of the program".into(),
+                span: parser.pos..source.len()
+            });
+        }
+    }
+
+    parser.diagnostics
+}
+
+struct Parser<'a> {
+    source: &'a [u8],
+    pos: usize,
+    diagnostics: Vec<Diagnostic>
+}
+
+impl<'a> Parser<'a> {
+    fn skip_whitespace(&mut self) {
+        while self.source.get(self.pos).is_some_and(|b| [9, 10, 13, 32].contains(b)) {
+            self.pos += 1
+        }
+    }
+
+    fn term(&mut self, depth: usize) -> Result<(), Diagnostic> {
+        self.skip_whitespace();
+        let start = self.pos;
+
+        match self.source.get(self.pos) {
+            Some(&b'0') => {
+                self.pos += 1;
+                match self.source.get(self.pos) {
+                    Some(&b'0') => {
+                        self.pos += 1;
+                        self.term(depth + 1)
+                    },
+                    Some(&b'1') => {
+                        self.pos += 1;
+                        self.term(depth)?;
+                        self.term(depth)
+                    },
+                    _ => Err(self.unexpected(start, "expected `00` or `01`"))
+                }
+            },
+            Some(&b'1') => {
+                while self.source.get(self.pos) == Some(&b'1') { self.pos += 1 }
+                let index = self.pos - start;
+
+                if self.source.get(self.pos) != Some(&b'0') {
+                    return Err(self.unexpected(start, "a variable must end with a `0`"))
+                }
+                self.pos += 1;
+
+                if index > depth {
+                    self.diagnostics.push(Diagnostic {
+                        message: format!("variable {} is not bound", index),
+                        label: match depth {
+                            0 => "there are no enclosing abstractions".into(),
+                            1 => "there is only 1 enclosing abstraction".into(),
+                            d => format!("there are only {} enclosing abstractions", d)
+                        },
+                        span: start..self.pos
+                    });
+                }
+                Ok(())
+            },
+            _ => Err(self.unexpected(start, "expected a term"))
+        }
+    }
+
+    fn unexpected(&self, start: usize, label: &str) -> Diagnostic {
+        match self.source.get(self.pos) {
+            None => Diagnostic {
+                message: "unexpected end of input".into(),
+                label: label.into(),
+                span: start..self.pos
+            },
+            Some(&b) => Diagnostic {
+                message: format!("unexpected {}", describe(b)),
+                label: label.into(),
+                span: start..self.pos + 1
+            }
+        }
+    }
+}
+
+fn describe(byte: u8) -> String {
+    match byte {
+        b'0' | b'1' => format!("bit `{}`", byte as char),
+        b if b.is_ascii_graphic() => format!("character `{}`", b as char),
+        b => format!("byte 0x{:02x}", b)
+    }
+}
+
+impl Diagnostic {
+    /// Renders `self` against the source it was produced for, underlining the offending region.
+    ///
+    /// # Example
+    /// ```
+    /// use blc::diagnostics::diagnose;
+    ///
+    /// let source = b"0000\n1110";
+    ///
+    /// assert_eq!(
+    ///     diagnose(source)[0].render(source),
+    ///     concat!(
+    ///         "error: variable 3 is not bound\n",
+    ///         "  --> 2:1\n",
+    ///         "  |\n",
+    ///         "2 | 1110\n",
+    ///         "  | ^^^^ there are only 2 enclosing abstractions\n"
+    ///     )
+    /// );
+    /// ```
+    pub fn render(&self, source: &[u8]) -> String {
+        let start = self.span.start.min(source.len());
+        let line_start = source[..start].iter().rposition(|&b| b == b'\n').map_or(0, |p| p + 1);
+        let line_end = source[start..].iter()
+            .position(|&b| b == b'\n')
+            .map_or(source.len(), |p| start + p);
+        let line_no = source[..start].iter().filter(|&&b| b == b'\n').count() + 1;
+        let line = String::from_utf8_lossy(&source[line_start..line_end]);
+        let line = line.trim_end_matches('\r');
+
+        let column = start - line_start;
+        let width = (self.span.end.min(line_end).max(start + 1) - start).max(1);
+        let gutter = " ".repeat(line_no.to_string().len());
+
+        format!(
+            "error: {}\n{} --> {}:{}\n{} |\n{} | {}\n{} | {}{} {}\n",
+            self.message,
+            gutter, line_no, column + 1,
+            gutter,
+            line_no, line,
+            gutter, " ".repeat(column), "^".repeat(width), self.label
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn valid_programs() {
+        assert!(diagnose(b"0010").is_empty());
+        assert!(diagnose(b" 00 00\t110\n").is_empty());
+        assert!(diagnose(b"00010110010001101000000000010111001111101111000010110111101100000\
+                           10").is_empty());
+    }
+
+    #[test]
+    fn unbound_variables() {
+        let diagnostics = diagnose(b"00011101110");
+
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(diagnostics[0].span, 4..7);
+        assert_eq!(diagnostics[1].span, 7..11);
+        assert_eq!(diagnostics[1].message, "variable 3 is not bound");
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(diagnose(b"01").pop().unwrap().message, "unexpected end of input");
+        assert_eq!(diagnose(b"0x10").pop().unwrap().message, "unexpected character `x`");
+        assert_eq!(diagnose(b"0011").pop().unwrap().label, "a variable must end with a `0`");
+        assert_eq!(diagnose(b"0010 10").pop().unwrap().span, 5..7);
+    }
+
+    #[test]
+    fn rendering() {
+        let source = b"0010 10";
+
+        assert_eq!(
+            diagnose(source)[0].render(source),
+            concat!(
+                "error: unexpected input after the end of the term\n",
+                "  --> 1:6\n",
+                "  |\n",
+                "1 | 0010 10\n",
+                "  |      ^^ this is not part of the program\n"
+            )
+        );
+    }
+}
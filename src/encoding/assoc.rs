@@ -0,0 +1,190 @@
+//! Lambda encoding for association lists
+//!
+//! An association list maps byte-string keys to byte-string values; it is encoded as a list of
+//! pairs `λ1 K V`, where `K` and `V` are encoded like the strings of `lambda::encode`. `lookup`
+//! and `key_matcher` build the terms that programs need to query such lists, so table-driven
+//! programs can be given their tables straight from Rust.
+
+use lambda_calculus::*;
+use lambda_calculus::combinators::Y;
+use lambda_calculus::data::boolean::{tru, fls};
+use encoding::binary::Error;
+use encoding::lambda::encode;
+use pair_list::listify_terms;
+
+/// A decoded entry of an association list: a key and its value.
+pub type Entry = (Vec<u8>, Vec<u8>);
+
+/// Encodes an association list of byte strings as a lambda `Term`.
+///
+/// # Example
+/// ```
+/// use blc::encoding::assoc::{encode_map, decode_map};
+///
+/// let map = encode_map(&[(b"one", b"1"), (b"two", b"2")]);
+///
+/// assert_eq!(
+///     decode_map(&map),
+///     Ok(vec![(b"one".to_vec(), b"1".to_vec()), (b"two".to_vec(), b"2".to_vec())])
+/// );
+/// ```
+pub fn encode_map<K: AsRef<[u8]>, V: AsRef<[u8]>>(entries: &[(K, V)]) -> Term {
+    listify_terms(entries.iter()
+        .map(|(key, value)| abs(app!(Var(1), encode(key.as_ref()), encode(value.as_ref()))))
+        .collect()
+    )
+}
+
+/// Decodes a lambda-encoded association list.
+///
+/// # Errors
+///
+/// Returns `Error::NotATerm` if `term` is not a list of pairs of encoded byte strings.
+pub fn decode_map(term: &Term) -> Result<Vec<Entry>, Error> {
+    list(term)?.into_iter()
+        .map(|entry| {
+            let (key, value) = cell(entry).ok_or(Error::NotATerm)?;
+            Ok((decode_bytes(key)?, decode_bytes(value)?))
+        })
+        .collect()
+}
+
+/// Returns a closed term that, applied to an encoded byte string, reduces to `true` (`λλ2`) if
+/// it is `key` and to `false` (`λλ1`) otherwise.
+///
+/// # Example
+/// ```
+/// extern crate blc;
+/// extern crate lambda_calculus;
+///
+/// use blc::encoding::assoc::key_matcher;
+/// use blc::encoding::lambda::encode;
+/// use lambda_calculus::*;
+/// use lambda_calculus::data::boolean::{tru, fls};
+///
+/// let is_ab = key_matcher(b"ab");
+///
+/// assert_eq!(beta(app(is_ab.clone(), encode(b"ab")), NOR, 0), tru());
+/// assert_eq!(beta(app(is_ab.clone(), encode(b"a")), NOR, 0), fls());
+/// assert_eq!(beta(app(is_ab, encode(b"abc")), NOR, 0), fls());
+/// ```
+pub fn key_matcher(key: &[u8]) -> Term {
+    match_list(key.iter().map(|&byte| {
+        let bits = (0..8).rev().map(|i| {
+            // a `0` bit is encoded as `true`, so it is matched by the identity
+            if byte & (1 << i) == 0 { abs(Var(1)) } else { abs(app!(Var(1), fls(), tru())) }
+        });
+        match_list(bits.collect())
+    }).collect())
+}
+
+/// Matches a list whose elements satisfy the given closed predicates, respectively.
+fn match_list(predicates: Vec<Term>) -> Term {
+    let mut matcher = abs(app!(Var(1), abs(abs(abs(fls()))), tru()));
+
+    for predicate in predicates.into_iter().rev() {
+        // λs. s (λh λt λ_. predicate h (matcher t) false) false
+        let cons_case = abs(abs(abs(app!(app(predicate, Var(3)), app(matcher, Var(2)), fls()))));
+        matcher = abs(app!(Var(1), cons_case, fls()));
+    }
+
+    matcher
+}
+
+/// Returns a closed term that, applied to an encoded association list, reduces to the value of
+/// the first entry with the given `key`, or to `default` if there is no such entry. `default`
+/// needs to be closed.
+///
+/// # Example
+/// ```
+/// extern crate blc;
+/// extern crate lambda_calculus;
+///
+/// use blc::encoding::assoc::{encode_map, lookup};
+/// use blc::encoding::lambda::{encode, decode};
+/// use lambda_calculus::*;
+///
+/// let map = encode_map(&[(b"one", b"1"), (b"two", b"2")]);
+/// let find = |key: &[u8]| decode(beta(app(lookup(key, encode(b"?")), map.clone()), NOR, 0));
+///
+/// assert_eq!(find(b"two"), Ok("2".into()));
+/// assert_eq!(find(b"six"), Ok("?".into()));
+/// ```
+pub fn lookup(key: &[u8], default: Term) -> Term {
+    // λk λv. matcher k v (rec t), with `rec` and `t` bound further out
+    let found = abs(abs(app!(app(key_matcher(key), Var(2)), Var(1), app(Var(7), Var(4)))));
+    // λe λt λ_. e found
+    let cons_case = abs(abs(abs(app(Var(3), found))));
+
+    // Y (λrec λm. m cons_case default)
+    app(Y(), abs(abs(app!(Var(1), cons_case, default))))
+}
+
+fn list(mut term: &Term) -> Result<Vec<&Term>, Error> {
+    let mut elements = Vec::new();
+
+    while *term != fls() {
+        let (head, tail) = cell(term).ok_or(Error::NotATerm)?;
+        elements.push(head);
+        term = tail;
+    }
+
+    Ok(elements)
+}
+
+fn cell(term: &Term) -> Option<(&Term, &Term)> {
+    let (pair, tail) = term.unabs_ref().and_then(|t| t.unapp_ref()).ok()?;
+    let (var, head) = pair.unapp_ref().ok()?;
+
+    if *var == Var(1) { Some((head, tail)) } else { None }
+}
+
+fn decode_bytes(term: &Term) -> Result<Vec<u8>, Error> {
+    list(term)?.into_iter()
+        .map(|byte| {
+            let bits = list(byte)?;
+            if bits.len() != 8 { return Err(Error::NotATerm) }
+
+            bits.into_iter().try_fold(0u8, |acc, bit| {
+                if *bit == tru() {
+                    Ok(acc << 1)
+                } else if *bit == fls() {
+                    Ok(acc << 1 | 1)
+                } else {
+                    Err(Error::NotATerm)
+                }
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use encoding::lambda::decode;
+
+    #[test]
+    fn round_trips() {
+        let entries: &[(&[u8], &[u8])] = &[(b"", b"empty"), (b"\x00\xff", b""), (b"k", b"v")];
+        let decoded = decode_map(&encode_map(entries)).unwrap();
+
+        assert_eq!(decoded.len(), 3);
+        for (&(key, value), (decoded_key, decoded_value)) in entries.iter().zip(&decoded) {
+            assert_eq!(key, &decoded_key[..]);
+            assert_eq!(value, &decoded_value[..]);
+        }
+        assert_eq!(decode_map(&encode(b"ab")), Err(Error::NotATerm));
+        assert_eq!(decode_map(&fls()), Ok(vec![]));
+    }
+
+    #[test]
+    fn lookups() {
+        let map = encode_map(&[(&b"a"[..], &b"first"[..]), (b"b", b"second"), (b"a", b"shadowed")]);
+        let find = |key: &[u8]| decode(beta(app(lookup(key, fls()), map.clone()), NOR, 0));
+
+        assert_eq!(find(b"a"), Ok("first".into()));
+        assert_eq!(find(b"b"), Ok("second".into()));
+        assert_eq!(find(b"c"), Ok("".into()));
+        assert_eq!(find(b""), Ok("".into()));
+    }
+}
@@ -0,0 +1,110 @@
+//! Binary combinatory logic
+//!
+//! Binary combinatory logic (BCL) encodes terms built from the `S` and `K` combinators: `00` is
+//! `K`, `01` is `S` and `1` precedes an application. Lambda terms are translated to it with
+//! bracket abstraction, which makes BCL programs comparable with their BLC counterparts.
+
+use lambda_calculus::term::*;
+use encoding::binary::Error;
+
+#[derive(Debug, PartialEq, Clone)]
+enum Combinator {
+    S,
+    K,
+    Var(usize),
+    App(Box<Combinator>, Box<Combinator>)
+}
+
+use self::Combinator as C;
+
+fn capp(lhs: Combinator, rhs: Combinator) -> Combinator {
+    C::App(Box::new(lhs), Box::new(rhs))
+}
+
+/// Translates a closed lambda `Term` to binary combinatory logic.
+///
+/// # Example
+/// ```
+/// use blc::encoding::bcl::to_bcl;
+/// use blc::encoding::binary::from_bits;
+///
+/// let k = from_bits(b"0000110").unwrap();
+///
+/// assert_eq!(to_bcl(&k), Ok(b"00".to_vec()));
+/// ```
+/// # Errors
+///
+/// Returns `Error::NotATerm` if the term is not closed.
+pub fn to_bcl(term: &Term) -> Result<Vec<u8>, Error> {
+    let combinator = translate(term);
+    let mut output = Vec::new();
+    write(&combinator, &mut output)?;
+
+    Ok(output)
+}
+
+fn translate(term: &Term) -> Combinator {
+    match *term {
+        Var(i) => C::Var(i),
+        Abs(ref body) => abstract_var(translate(body)),
+        App(ref lhs, ref rhs) => capp(translate(lhs), translate(rhs))
+    }
+}
+
+/// Removes the variable with index 1 from `combinator`, decrementing the indices of the others.
+fn abstract_var(combinator: Combinator) -> Combinator {
+    if !occurs(&combinator) { return capp(C::K, lower(combinator)) }
+
+    match combinator {
+        C::Var(_) => capp(capp(C::S, C::K), C::K), // I = SKK
+        C::App(lhs, rhs) => {
+            if *rhs == C::Var(1) && !occurs(&lhs) { return lower(*lhs) } // η-reduction
+
+            capp(capp(C::S, abstract_var(*lhs)), abstract_var(*rhs))
+        },
+        _ => unreachable!() // safe - `S` and `K` don't contain the variable
+    }
+}
+
+fn occurs(combinator: &Combinator) -> bool {
+    match *combinator {
+        C::Var(i) => i == 1,
+        C::App(ref lhs, ref rhs) => occurs(lhs) || occurs(rhs),
+        _ => false
+    }
+}
+
+fn lower(combinator: Combinator) -> Combinator {
+    match combinator {
+        C::Var(i) => C::Var(i - 1),
+        C::App(lhs, rhs) => capp(lower(*lhs), lower(*rhs)),
+        c => c
+    }
+}
+
+fn write(combinator: &Combinator, output: &mut Vec<u8>) -> Result<(), Error> {
+    match *combinator {
+        C::S => output.extend_from_slice(b"01"),
+        C::K => output.extend_from_slice(b"00"),
+        C::Var(_) => return Err(Error::NotATerm),
+        C::App(ref lhs, ref rhs) => {
+            output.push(b'1');
+            write(lhs, output)?;
+            write(rhs, output)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use lambda_calculus::combinators::{I, S};
+
+    #[test]
+    fn translations() {
+        assert_eq!(to_bcl(&I()), Ok(b"11010000".to_vec()));
+        assert_eq!(to_bcl(&S()), Ok(b"01".to_vec()));
+        assert_eq!(to_bcl(&Var(1)), Err(Error::NotATerm));
+    }
+}
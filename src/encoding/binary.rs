@@ -1,13 +1,25 @@
 //! Binary encoding for lambda `Term`s
+//!
+//! Programs are parsed from their bits, ASCII `0`s and `1`s, with `from_bits` and written back out
+//! with its inverse, `to_bits`, which works for terms in any `TermRep`resentation; `compress` and
+//! `decompress` convert between the bits and the packed bytes of `.Blc` files, and a `BitReader`
+//! unpacks the bits from a reader as they are needed.
 
+#[cfg(feature = "lambda_calculus")]
 use lambda_calculus::term::*;
+use repr::{TermRep, View};
 use self::Error::*;
+use std::io::{self, Read};
 
 /// An error that can occur if the input stream of "bits" is not valid binary lambda calculus.
 #[derive(Debug, PartialEq)]
 pub enum Error {
     /// not a valid term
-    NotATerm
+    NotATerm,
+    /// packed bytes continue with whole bytes after the term
+    TrailingData,
+    /// the bits padding the last packed byte after the term are not all `0`
+    InvalidPadding
 }
 
 /// Parse a blc-encoded lambda `Term`.
@@ -21,7 +33,25 @@ pub enum Error {
 /// assert!(k.is_ok());
 /// assert_eq!(to_bits(&k.unwrap()), Vec::from(&b"0000110"[..]));
 /// ```
+#[cfg(feature = "lambda_calculus")]
 pub fn from_bits(input: &[u8]) -> Result<Term, Error> {
+    from_bits_as(input)
+}
+
+/// Parse a blc-encoded term in any `TermRep`resentation.
+///
+/// # Example
+#[cfg_attr(feature = "lambda_calculus", doc = "```")]
+#[cfg_attr(not(feature = "lambda_calculus"), doc = "```ignore")]
+/// extern crate blc;
+/// extern crate lambda_calculus;
+///
+/// use blc::encoding::binary::from_bits_as;
+/// use lambda_calculus::*;
+///
+/// assert_eq!(from_bits_as::<Term>(b"0000110"), Ok(abs(abs(Var(2)))));
+/// ```
+pub fn from_bits_as<T: TermRep>(input: &[u8]) -> Result<T, Error> {
     if let Some((result, _)) = _from_bits(input) {
         Ok(result)
     } else {
@@ -29,24 +59,24 @@ pub fn from_bits(input: &[u8]) -> Result<Term, Error> {
     }
 }
 
-fn _from_bits(input: &[u8]) -> Option<(Term, &[u8])> {
+fn _from_bits<T: TermRep>(input: &[u8]) -> Option<(T, &[u8])> {
     if input.is_empty() { return None }
 
     if [9, 10, 13, 32].contains(&input[0]) {
         _from_bits(&input[1..]) // skip whitespaces
     } else {
-        match &input[0..2] {
-            b"00" => {
+        match input.get(0..2) {
+            Some(b"00") => {
                 if let Some((term, rest)) = _from_bits(&input[2..]) {
-                    Some((abs(term), rest))
+                    Some((T::abs(term), rest))
                 } else {
                     None
                 }
             },
-            b"01" => {
+            Some(b"01") => {
                 if let Some((term1, rest1)) = _from_bits(&input[2..]) {
                     if let Some((term2, rest2)) = _from_bits(rest1) {
-                        Some((app(term1, term2), rest2))
+                        Some((T::app(term1, term2), rest2))
                     } else {
                         None
                     }
@@ -54,12 +84,12 @@ fn _from_bits(input: &[u8]) -> Option<(Term, &[u8])> {
                     None
                 }
             },
-            b"10" | b"11" => {
+            Some(b"10") | Some(b"11") => {
                 let i = input.iter().take_while(|&b| *b == b'1').count();
-                if input[2..].is_empty() {
-                    Some((Var(i), &*b""))
+                if input.get(i) == Some(&b'0') {
+                    Some((T::var(i), &input[i+1..]))
                 } else {
-                    Some((Var(i), &input[i+1..]))
+                    None // the index is not terminated
                 }
             },
             _ => None
@@ -67,10 +97,109 @@ fn _from_bits(input: &[u8]) -> Option<(Term, &[u8])> {
     }
 }
 
-/// Represent a lambda `Term` in blc.
+/// Parse a blc-encoded term in any `TermRep`resentation from a stream of bits, `true` standing for
+/// `1`; no more bits than the term consists of are consumed.
+///
+/// Any bit-level reader can be plugged in without an intermediate buffer, e.g. a
+/// `bitstream_io::BitReader` with `std::iter::from_fn(|| reader.read_bit().ok())`.
 ///
 /// # Example
+#[cfg_attr(feature = "lambda_calculus", doc = "```")]
+#[cfg_attr(not(feature = "lambda_calculus"), doc = "```ignore")]
+/// extern crate blc;
+/// extern crate lambda_calculus;
+///
+/// use blc::encoding::binary::from_bit_iter;
+/// use lambda_calculus::*;
+///
+/// let mut bits = [false, false, true, false, true].iter().cloned();
+///
+/// assert_eq!(from_bit_iter::<Term, _>(&mut bits), Ok(abs(Var(1))));
+/// assert_eq!(bits.next(), Some(true));
 /// ```
+/// # Errors
+///
+/// Returns `Error::NotATerm` if the stream ends before a whole term is read.
+pub fn from_bit_iter<T: TermRep, I: Iterator<Item = bool>>(bits: &mut I) -> Result<T, Error> {
+    match bits.next() {
+        Some(false) => match bits.next() {
+            Some(false) => Ok(T::abs(from_bit_iter(bits)?)),
+            Some(true) => {
+                let lhs = from_bit_iter(bits)?;
+                Ok(T::app(lhs, from_bit_iter(bits)?))
+            },
+            None => Err(NotATerm)
+        },
+        Some(true) => {
+            let mut index = 1;
+            loop {
+                match bits.next() {
+                    Some(true) => index += 1,
+                    Some(false) => return Ok(T::var(index)),
+                    None => return Err(NotATerm)
+                }
+            }
+        },
+        None => Err(NotATerm)
+    }
+}
+
+/// Write a lambda term in any `TermRep`resentation as a stream of bits to the given sink, `true`
+/// standing for `1`; the first error returned by the sink stops the writing.
+///
+/// Any bit-level writer can be plugged in without an intermediate buffer, e.g. a
+/// `bitstream_io::BitWriter` with `|bit| writer.write_bit(bit)`.
+///
+/// # Example
+#[cfg_attr(feature = "lambda_calculus", doc = "```")]
+#[cfg_attr(not(feature = "lambda_calculus"), doc = "```ignore")]
+/// use blc::encoding::binary::{from_bits, to_bit_sink};
+///
+/// let k = from_bits(b"0000110").unwrap();
+/// let mut bits = Vec::new();
+/// to_bit_sink(&k, |bit| -> Result<(), ()> {
+///     bits.push(bit);
+///     Ok(())
+/// }).unwrap();
+///
+/// assert_eq!(bits, [false, false, false, false, true, true, false]);
+/// ```
+/// # Errors
+///
+/// Returns the first error returned by the sink.
+pub fn to_bit_sink<T: TermRep, E, F: FnMut(bool) -> Result<(), E>>(term: &T, mut sink: F)
+    -> Result<(), E>
+{
+    _to_bit_sink(term, &mut sink)
+}
+
+fn _to_bit_sink<T: TermRep, E, F: FnMut(bool) -> Result<(), E>>(term: &T, sink: &mut F)
+    -> Result<(), E>
+{
+    match term.view() {
+        View::Var(i) => {
+            for _ in 0..i { sink(true)? }
+            sink(false)
+        },
+        View::Abs(t) => {
+            sink(false)?;
+            sink(false)?;
+            _to_bit_sink(t, sink)
+        },
+        View::App(t1, t2) => {
+            sink(false)?;
+            sink(true)?;
+            _to_bit_sink(t1, sink)?;
+            _to_bit_sink(t2, sink)
+        }
+    }
+}
+
+/// Represent a lambda term in blc; it can be in any `TermRep`resentation.
+///
+/// # Example
+#[cfg_attr(feature = "lambda_calculus", doc = "```")]
+#[cfg_attr(not(feature = "lambda_calculus"), doc = "```ignore")]
 /// use blc::encoding::binary::{from_bits, to_bits};
 ///
 /// let k = from_bits(b"0000110");
@@ -78,33 +207,34 @@ fn _from_bits(input: &[u8]) -> Option<(Term, &[u8])> {
 /// assert!(k.is_ok());
 /// assert_eq!(to_bits(&k.unwrap()), Vec::from(&b"0000110"[..]));
 /// ```
-pub fn to_bits(term: &Term) -> Vec<u8> {
+pub fn to_bits<T: TermRep>(term: &T) -> Vec<u8> {
     let mut output = Vec::new();
     _to_bits(term, &mut output);
 
     output
 }
 
-fn _to_bits(term: &Term, output: &mut Vec<u8>) {
-    match *term {
-        Var(i) => {
+fn _to_bits<T: TermRep>(term: &T, output: &mut Vec<u8>) {
+    match term.view() {
+        View::Var(i) => {
             for _ in 0..i { output.push(b'1') }
             output.push(b'0');
         }
-        Abs(ref t) => {
+        View::Abs(t) => {
             output.extend_from_slice(b"00");
-            output.append(&mut to_bits(t));
+            _to_bits(t, output);
         }
-        App(ref t1, ref t2) => {
+        View::App(t1, t2) => {
             output.extend_from_slice(b"01");
-            output.append(&mut to_bits(t1));
-            output.append(&mut to_bits(t2));
+            _to_bits(t1, output);
+            _to_bits(t2, output);
         }
     }
 }
 
-/// Convert a stream of "bits" into bytes. It is not always reversible with `decompress`, because
-/// it produces full bytes, while the length of its input can be indivisible by 8.
+/// Convert a stream of "bits" into bytes, most significant bit first, padding the last byte with
+/// `0`s. It is not always reversible with `decompress`, because it produces full bytes, while the
+/// length of its input can be indivisible by 8.
 ///
 /// # Example
 /// ```
@@ -112,25 +242,10 @@ fn _to_bits(term: &Term, output: &mut Vec<u8>) {
 ///
 /// let succ_compressed = compress(&*b"000000011100101111011010");
 /// assert_eq!(succ_compressed, vec![0x1, 0xCB, 0xDA]);
+/// assert_eq!(compress(b"0010"), vec![0x20]);
 /// ```
 pub fn compress(bits: &[u8]) -> Vec<u8> {
-    let length = bits.len();
-    let mut output = Vec::with_capacity(length / 8 + 1);
-    let mut pos = 0;
-
-    while pos <= length - 8 {
-        output.push(bits_to_byte(&bits[pos..(pos + 8)]));
-        pos += 8;
-    }
-
-    if pos != length {
-        let mut last_byte = Vec::with_capacity(8);
-        last_byte.extend_from_slice(&bits[pos..]);
-        for _ in 0..(8 - (length - pos)) { last_byte.push(b'0') }
-        output.push(bits_to_byte(&last_byte));
-    }
-
-    output
+    bits.chunks(8).map(|byte| bits_to_byte(byte) << (8 - byte.len())).collect()
 }
 
 fn bits_to_byte(bits: &[u8]) -> u8 {
@@ -157,23 +272,174 @@ pub fn decompress(bytes: &[u8]) -> Vec<u8> {
     output
 }
 
-#[cfg(test)]
+/// Convert bytes into "bits" like `decompress` does, but only the bits of the term they pack,
+/// without the `0`s padding the last byte, checking that nothing else follows the term.
+///
+/// # Example
+/// ```
+/// use blc::encoding::binary::{compress, try_decompress, Error};
+///
+/// assert_eq!(try_decompress(&compress(b"0010")), Ok(b"0010".to_vec()));
+/// assert_eq!(try_decompress(&[0x21]), Err(Error::InvalidPadding));
+/// assert_eq!(try_decompress(&[0x20, 0x00]), Err(Error::TrailingData));
+/// ```
+/// # Errors
+///
+/// Returns `Error::NotATerm` if the bytes end before a whole term, `Error::TrailingData` if whole
+/// bytes follow it and `Error::InvalidPadding` if the rest of its last byte is not all `0`s.
+pub fn try_decompress(bytes: &[u8]) -> Result<Vec<u8>, Error> {
+    let mut bits = decompress(bytes);
+    let length = term_length(&bits).ok_or(NotATerm)?;
+
+    if bits.len() - length >= 8 { return Err(TrailingData) }
+    if bits[length..].iter().any(|&bit| bit != b'0') { return Err(InvalidPadding) }
+
+    bits.truncate(length);
+    Ok(bits)
+}
+
+/// Returns the number of bits the term at the start of `bits` consists of, or `None` if they end
+/// before a whole term.
+fn term_length(bits: &[u8]) -> Option<usize> {
+    let mut pos = 0;
+    let mut missing = 1; // the number of terms still to be read
+
+    while missing != 0 {
+        match (bits.get(pos)?, bits.get(pos + 1)?) {
+            (b'0', b'0') => pos += 2,
+            (b'0', _) => {
+                pos += 2;
+                missing += 1;
+            },
+            _ => {
+                pos += bits[pos..].iter().position(|&bit| bit == b'0')? + 1;
+                missing -= 1;
+            }
+        }
+    }
+
+    Some(pos)
+}
+
+/// A reader of the bits packed in the bytes of an `io::Read`, most significant first, which are
+/// only read when they are needed; unlike with `decompress`, the bits are never expanded into an
+/// 8 times larger buffer. Bytes are read one at a time, so an unbuffered reader should be wrapped
+/// in an `io::BufReader`.
+///
+/// As an `Iterator`, it can be passed to `from_bit_iter`; the iteration ends at the end of the
+/// reader or at the first error, which is then kept.
+///
+/// # Example
+#[cfg_attr(feature = "lambda_calculus", doc = "```")]
+#[cfg_attr(not(feature = "lambda_calculus"), doc = "```ignore")]
+/// extern crate blc;
+/// extern crate lambda_calculus;
+///
+/// use blc::encoding::binary::{from_bit_iter, BitReader};
+/// use lambda_calculus::*;
+/// use std::io::Cursor;
+///
+/// let mut bits = BitReader::new(Cursor::new([0x0c])); // λλ2 and a padding bit
+///
+/// assert_eq!(from_bit_iter::<Term, _>(&mut bits), Ok(abs(abs(Var(2)))));
+/// assert_eq!(bits.collect::<Vec<bool>>(), [false]);
+/// ```
+#[derive(Debug)]
+pub struct BitReader<R> {
+    reader: R,
+    byte: u8,
+    left: u32,
+    error: Option<io::Error>
+}
+
+impl<R: Read> BitReader<R> {
+    /// Creates a reader of the bits packed in the bytes of `reader`.
+    pub fn new(reader: R) -> Self {
+        BitReader { reader, byte: 0, left: 0, error: None }
+    }
+
+    /// Reads the next bit, `true` standing for `1`; returns `None` at the end of the reader.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of the underlying reader if reading a byte fails.
+    pub fn read_bit(&mut self) -> io::Result<Option<bool>> {
+        if self.left == 0 {
+            let mut byte = [0];
+            loop {
+                match self.reader.read(&mut byte) {
+                    Ok(0) => return Ok(None),
+                    Ok(_) => break,
+                    Err(ref e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                    Err(e) => return Err(e)
+                }
+            }
+            self.byte = byte[0];
+            self.left = 8;
+        }
+        self.left -= 1;
+
+        Ok(Some(self.byte >> self.left & 1 == 1))
+    }
+
+    /// Returns the error that ended the iteration, if any.
+    pub fn error(&self) -> Option<&io::Error> { self.error.as_ref() }
+
+    /// Returns the underlying reader.
+    pub fn into_inner(self) -> R { self.reader }
+}
+
+impl<R: Read> Iterator for BitReader<R> {
+    type Item = bool;
+
+    fn next(&mut self) -> Option<bool> {
+        if self.error.is_some() { return None }
+
+        self.read_bit().unwrap_or_else(|error| {
+            self.error = Some(error);
+            None
+        })
+    }
+}
+
+#[cfg(all(test, feature = "lambda_calculus"))]
 mod test {
     use super::*;
+    use lambda_calculus::abs;
 
-    const QUINE: &'static [u8; 66] =
+    const QUINE: &[u8; 66] =
         b"000101100100011010000000000001011011110010111100111111011111011010";
 
-    const PRIMES: &'static [u8; 167] =
+    const PRIMES: &[u8; 167] =
         b"00010001100110010100011010000000010110000010010001010111110111101001000110100001\
           11001101000000000010110111001110011111110111100000000111110011011100000010110000\
           0110110";
 
-    const BLC: &'static [u8; 232] =
+    const BLC: &[u8; 232] =
         b"01010001101000000001010110000000000111100001011111100111100001011100111100000011\
           11000010110110111001111100001111100001011110100111010010110011100001101100001011\
           111000011111000011100110111101111100111101110110000110010001101000011010";
 
+    #[test]
+    fn bit_streams() {
+        for &code in &[&QUINE[..], &PRIMES[..], &BLC[..]] {
+            let term = from_bits(code).unwrap();
+            let mut bits = Vec::new();
+            to_bit_sink(&term, |bit| -> Result<(), ()> {
+                bits.push(bit);
+                Ok(())
+            }).unwrap();
+
+            assert_eq!(bits, code.iter().map(|&b| b == b'1').collect::<Vec<_>>());
+            assert_eq!(from_bit_iter(&mut bits.into_iter()), Ok(term));
+        }
+
+        let mut truncated = [false, true, true].iter().cloned();
+
+        assert_eq!(from_bit_iter::<Term, _>(&mut truncated), Err(NotATerm));
+        assert_eq!(to_bit_sink(&Var(3), |bit| if bit { Ok(()) } else { Err("0") }), Err("0"));
+    }
+
     #[test]
     fn variables() {
         assert_eq!(from_bits(b"10"),   Ok(Var(1)));
@@ -200,6 +466,16 @@ mod test {
         assert_eq!(from_bits(b"00 00\t00\n10\r\n"), Ok(abs!(3, Var(1))));
     }
 
+    #[test]
+    fn truncated_terms() {
+        assert_eq!(from_bits(b"0"),     Err(NotATerm));
+        assert_eq!(from_bits(b"1"),     Err(NotATerm));
+        assert_eq!(from_bits(b"11"),    Err(NotATerm));
+        assert_eq!(from_bits(b"111"),   Err(NotATerm));
+        assert_eq!(from_bits(b"00111"), Err(NotATerm));
+        assert_eq!(from_bits(b"01100"), Err(NotATerm));
+    }
+
     #[test]
     fn from_bits_and_back() {
         let k =    b"0000110";
@@ -207,13 +483,13 @@ mod test {
         let s =    b"00000001011110100111010";
         let succ = b"000000011100101111011010";
 
-        assert_eq!(to_bits(&from_bits(&*k).unwrap()),      k);
-        assert_eq!(to_bits(&from_bits(&*v15).unwrap()),    v15);
-        assert_eq!(to_bits(&from_bits(&*s).unwrap()),      s);
-        assert_eq!(to_bits(&from_bits(&*succ).unwrap()),   succ);
-        assert_eq!(to_bits(&from_bits(&*QUINE).unwrap()),  &QUINE[..]);
-        assert_eq!(to_bits(&from_bits(&*PRIMES).unwrap()), Vec::from(&PRIMES[..]));
-        assert_eq!(to_bits(&from_bits(&*BLC).unwrap()),    Vec::from(&BLC[..]));
+        assert_eq!(to_bits(&from_bits(k).unwrap()),      k);
+        assert_eq!(to_bits(&from_bits(v15).unwrap()),    v15);
+        assert_eq!(to_bits(&from_bits(s).unwrap()),      s);
+        assert_eq!(to_bits(&from_bits(succ).unwrap()),   succ);
+        assert_eq!(to_bits(&from_bits(QUINE).unwrap()),  &QUINE[..]);
+        assert_eq!(to_bits(&from_bits(PRIMES).unwrap()), Vec::from(&PRIMES[..]));
+        assert_eq!(to_bits(&from_bits(BLC).unwrap()),    Vec::from(&BLC[..]));
     }
 
     #[test]
@@ -225,6 +501,9 @@ mod test {
         let blc_c = compress(&BLC[..]);
         assert_eq!(blc_c.first().unwrap(), &0x51);
         assert_eq!(blc_c.last().unwrap(),  &0x1a);
+
+        assert_eq!(compress(b"0000110"), vec![0x0c]);
+        assert_eq!(compress(b""), vec![]);
     }
 
     #[test]
@@ -237,4 +516,36 @@ mod test {
     fn compress_decompress() {
         assert_eq!(decompress(&compress(&BLC[..])), Vec::from(&BLC[..]));
     }
+
+    #[test]
+    fn validating_decompression() {
+        for &code in &[&QUINE[..], &PRIMES[..], &BLC[..]] {
+            assert_eq!(try_decompress(&compress(code)), Ok(code.to_vec()));
+        }
+
+        assert_eq!(try_decompress(&[0x80]), Ok(b"10".to_vec())); // a free variable
+        assert_eq!(try_decompress(&[0xff]), Err(NotATerm));
+        assert_eq!(try_decompress(&[]), Err(NotATerm));
+    }
+
+    struct Failing;
+
+    impl Read for Failing {
+        fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+            Err(io::ErrorKind::BrokenPipe.into())
+        }
+    }
+
+    #[test]
+    fn bit_reader() {
+        let packed = compress(&PRIMES[..]);
+        let mut bits = BitReader::new(io::Cursor::new(&packed));
+
+        assert_eq!(from_bit_iter::<Term, _>(&mut bits), from_bits(&PRIMES[..]));
+        assert_eq!(bits.count(), packed.len() * 8 - PRIMES.len());
+
+        let mut failing = BitReader::new(Failing);
+        assert_eq!(from_bit_iter::<Term, _>(&mut failing), Err(NotATerm));
+        assert_eq!(failing.error().map(io::Error::kind), Some(io::ErrorKind::BrokenPipe));
+    }
 }
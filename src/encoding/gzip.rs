@@ -0,0 +1,382 @@
+//! Decompression of gzip-wrapped data
+//!
+//! It is available with `features = ["gzip"]` and only supports decompression; the DEFLATE
+//! decoder follows RFC 1951 and the gzip wrapper RFC 1952.
+
+use self::Error::*;
+
+/// An error that can occur during decompression.
+#[derive(Debug, PartialEq)]
+pub enum Error {
+    /// the data doesn't start with a valid gzip header
+    InvalidHeader,
+    /// the compressed data is malformed or truncated
+    InvalidData,
+    /// the checksum or the length of the decompressed data doesn't match
+    ChecksumMismatch
+}
+
+const MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// Returns `true` if `data` starts with the gzip magic number.
+///
+/// # Example
+/// ```
+/// use blc::encoding::gzip::is_gzip;
+///
+/// assert!(is_gzip(&[0x1f, 0x8b, 0x08]));
+/// assert!(!is_gzip(b"0010"));
+/// ```
+pub fn is_gzip(data: &[u8]) -> bool {
+    data.starts_with(&MAGIC)
+}
+
+/// Decompresses gzip-wrapped data; concatenated gzip members are decompressed one after another.
+///
+/// # Example
+/// ```
+/// use blc::encoding::gzip::decompress;
+///
+/// let gzipped = [
+///     0x1f, 0x8b, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02, 0x03, 0x33, 0x30, 0x30, 0x34, 0x00,
+///     0x00, 0x33, 0xf5, 0x80, 0x15, 0x04, 0x00, 0x00, 0x00
+/// ];
+///
+/// assert_eq!(decompress(&gzipped), Ok(b"0010".to_vec()));
+/// ```
+/// # Errors
+///
+/// Returns an `Error` if `data` is not valid gzip-wrapped DEFLATE data.
+pub fn decompress(data: &[u8]) -> Result<Vec<u8>, Error> {
+    let mut output = Vec::new();
+    let mut pos = 0;
+
+    loop {
+        let start = output.len();
+        pos += header_len(&data[pos..])?;
+
+        let mut input = BitReader { data, pos, bit: 0 };
+        inflate_into(&mut input, &mut output)?;
+        pos = input.pos + (input.bit != 0) as usize;
+
+        let trailer = data.get(pos..pos + 8).ok_or(InvalidData)?;
+        let crc = u32::from_le_bytes([trailer[0], trailer[1], trailer[2], trailer[3]]);
+        let len = u32::from_le_bytes([trailer[4], trailer[5], trailer[6], trailer[7]]);
+        if crc32(&output[start..]) != crc || (output.len() - start) as u32 != len {
+            return Err(ChecksumMismatch)
+        }
+        pos += 8;
+
+        if !is_gzip(&data[pos..]) { return Ok(output) }
+    }
+}
+
+fn header_len(data: &[u8]) -> Result<usize, Error> {
+    const FHCRC: u8 = 2;
+    const FEXTRA: u8 = 4;
+    const FNAME: u8 = 8;
+    const FCOMMENT: u8 = 16;
+
+    if data.len() < 10 || !is_gzip(data) || data[2] != 8 { return Err(InvalidHeader) }
+    let flags = data[3];
+    let mut pos = 10;
+
+    if flags & FEXTRA != 0 {
+        let len = data.get(pos..pos + 2).ok_or(InvalidHeader)?;
+        pos += 2 + u16::from_le_bytes([len[0], len[1]]) as usize;
+    }
+    for &flag in &[FNAME, FCOMMENT] {
+        if flags & flag != 0 {
+            pos += 1 + data.get(pos..).and_then(|d| d.iter().position(|&b| b == 0))
+                .ok_or(InvalidHeader)?;
+        }
+    }
+    if flags & FHCRC != 0 { pos += 2 }
+
+    if pos > data.len() { Err(InvalidHeader) } else { Ok(pos) }
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    !data.iter().fold(!0u32, |crc, &byte| {
+        (0..8).fold(crc ^ byte as u32, |crc, _| {
+            if crc & 1 != 0 { (crc >> 1) ^ 0xedb8_8320 } else { crc >> 1 }
+        })
+    })
+}
+
+/// Decompresses raw DEFLATE data.
+///
+/// # Errors
+///
+/// Returns `Error::InvalidData` if `data` is not valid DEFLATE data.
+pub fn inflate(data: &[u8]) -> Result<Vec<u8>, Error> {
+    let mut output = Vec::new();
+    inflate_into(&mut BitReader { data, pos: 0, bit: 0 }, &mut output)?;
+
+    Ok(output)
+}
+
+struct BitReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+    bit: u8
+}
+
+impl<'a> BitReader<'a> {
+    fn bits(&mut self, count: u8) -> Result<u32, Error> {
+        let mut value = 0;
+
+        for i in 0..count {
+            let byte = *self.data.get(self.pos).ok_or(InvalidData)?;
+            value |= (((byte >> self.bit) & 1) as u32) << i;
+            self.bit += 1;
+            if self.bit == 8 {
+                self.bit = 0;
+                self.pos += 1;
+            }
+        }
+
+        Ok(value)
+    }
+
+    fn align(&mut self) {
+        if self.bit != 0 {
+            self.bit = 0;
+            self.pos += 1;
+        }
+    }
+}
+
+/// A canonical Huffman code: the number of codes of every length and the symbols ordered by code.
+struct Huffman {
+    counts: [u16; 16],
+    symbols: Vec<u16>
+}
+
+impl Huffman {
+    fn new(lengths: &[u8]) -> Result<Huffman, Error> {
+        let mut counts = [0u16; 16];
+        for &len in lengths { counts[len as usize] += 1 }
+        counts[0] = 0;
+
+        let mut left: i32 = 1;
+        for &count in &counts[1..] {
+            left = left * 2 - count as i32;
+            if left < 0 { return Err(InvalidData) }
+        }
+
+        let mut offsets = [0u16; 16];
+        for len in 1..15 { offsets[len + 1] = offsets[len] + counts[len] }
+
+        let mut symbols = vec![0; lengths.len()];
+        for (symbol, &len) in lengths.iter().enumerate() {
+            if len != 0 {
+                symbols[offsets[len as usize] as usize] = symbol as u16;
+                offsets[len as usize] += 1;
+            }
+        }
+
+        Ok(Huffman { counts, symbols })
+    }
+
+    fn decode(&self, input: &mut BitReader) -> Result<u16, Error> {
+        let (mut code, mut first, mut index) = (0i32, 0i32, 0i32);
+
+        for len in 1..16 {
+            code |= input.bits(1)? as i32;
+            let count = self.counts[len] as i32;
+            if code - count < first {
+                return Ok(self.symbols[(index + code - first) as usize])
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+
+        Err(InvalidData)
+    }
+}
+
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258
+];
+const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0
+];
+const DIST_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577
+];
+const DIST_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13, 13
+];
+
+fn inflate_into(input: &mut BitReader, output: &mut Vec<u8>) -> Result<(), Error> {
+    let start = output.len();
+
+    loop {
+        let last = input.bits(1)? == 1;
+
+        match input.bits(2)? {
+            0 => stored(input, output)?,
+            1 => {
+                let (lengths, distances) = fixed_codes()?;
+                codes(input, output, start, &lengths, &distances)?
+            },
+            2 => {
+                let (lengths, distances) = dynamic_codes(input)?;
+                codes(input, output, start, &lengths, &distances)?
+            },
+            _ => return Err(InvalidData)
+        }
+
+        if last { return Ok(()) }
+    }
+}
+
+fn stored(input: &mut BitReader, output: &mut Vec<u8>) -> Result<(), Error> {
+    input.align();
+    let header = input.data.get(input.pos..input.pos + 4).ok_or(InvalidData)?;
+    let len = u16::from_le_bytes([header[0], header[1]]);
+    if len != !u16::from_le_bytes([header[2], header[3]]) { return Err(InvalidData) }
+    input.pos += 4;
+
+    let block = input.data.get(input.pos..input.pos + len as usize).ok_or(InvalidData)?;
+    output.extend_from_slice(block);
+    input.pos += len as usize;
+
+    Ok(())
+}
+
+fn fixed_codes() -> Result<(Huffman, Huffman), Error> {
+    let mut lengths = [0u8; 288];
+    for (symbol, len) in lengths.iter_mut().enumerate() {
+        *len = match symbol {
+            0..=143   => 8,
+            144..=255 => 9,
+            256..=279 => 7,
+            _         => 8
+        };
+    }
+
+    Ok((Huffman::new(&lengths)?, Huffman::new(&[5; 30])?))
+}
+
+fn dynamic_codes(input: &mut BitReader) -> Result<(Huffman, Huffman), Error> {
+    const ORDER: [usize; 19] = [16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15];
+
+    let literals = input.bits(5)? as usize + 257;
+    let distances = input.bits(5)? as usize + 1;
+    let code_lengths = input.bits(4)? as usize + 4;
+    if literals > 286 || distances > 30 { return Err(InvalidData) }
+
+    let mut lengths = [0u8; 19];
+    for &symbol in &ORDER[..code_lengths] { lengths[symbol] = input.bits(3)? as u8 }
+    let code_length_code = Huffman::new(&lengths)?;
+
+    let mut lengths = vec![0u8; literals + distances];
+    let mut index = 0;
+    while index < lengths.len() {
+        let (value, repeat) = match code_length_code.decode(input)? {
+            symbol @ 0..=15 => (symbol as u8, 1),
+            16 => (*lengths[..index].last().ok_or(InvalidData)?, 3 + input.bits(2)?),
+            17 => (0, 3 + input.bits(3)?),
+            _  => (0, 11 + input.bits(7)?)
+        };
+        if index + repeat as usize > lengths.len() { return Err(InvalidData) }
+        for len in &mut lengths[index..index + repeat as usize] { *len = value }
+        index += repeat as usize;
+    }
+    if lengths[256] == 0 { return Err(InvalidData) }
+
+    Ok((Huffman::new(&lengths[..literals])?, Huffman::new(&lengths[literals..])?))
+}
+
+fn codes(
+    input: &mut BitReader,
+    output: &mut Vec<u8>,
+    start: usize,
+    lengths: &Huffman,
+    distances: &Huffman
+) -> Result<(), Error> {
+    loop {
+        let symbol = lengths.decode(input)? as usize;
+
+        if symbol < 256 {
+            output.push(symbol as u8);
+        } else if symbol == 256 {
+            return Ok(())
+        } else {
+            let symbol = symbol - 257;
+            if symbol >= 29 { return Err(InvalidData) }
+            let len = LENGTH_BASE[symbol] as usize + input.bits(LENGTH_EXTRA[symbol])? as usize;
+
+            let symbol = distances.decode(input)? as usize;
+            if symbol >= 30 { return Err(InvalidData) }
+            let dist = DIST_BASE[symbol] as usize + input.bits(DIST_EXTRA[symbol])? as usize;
+            if dist > output.len() - start { return Err(InvalidData) }
+
+            for _ in 0..len {
+                let byte = output[output.len() - dist];
+                output.push(byte);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn stored_blocks() {
+        let stored = [0x01, 0x04, 0x00, 0xfb, 0xff, 0x30, 0x30, 0x31, 0x30];
+
+        assert_eq!(inflate(&stored), Ok(b"0010".to_vec()));
+    }
+
+    #[test]
+    fn fixed_huffman_blocks() {
+        let quick = [
+            0x1f, 0x8b, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02, 0x03, 0x2b, 0xc9, 0x48, 0x55,
+            0x28, 0x2c, 0xcd, 0x4c, 0xce, 0x56, 0x48, 0x2a, 0xca, 0x2f, 0xcf, 0x53, 0x48, 0xcb,
+            0xaf, 0x50, 0xc8, 0x2a, 0xcd, 0x2d, 0x28, 0x56, 0xc8, 0x2f, 0x4b, 0x2d, 0x52, 0x28,
+            0x01, 0x4a, 0xe7, 0x24, 0x56, 0x55, 0x2a, 0xa4, 0xe4, 0xa7, 0x5b, 0x83, 0x79, 0x83,
+            0x40, 0x31, 0x00, 0x4a, 0x11, 0x75, 0x28, 0xb4, 0x00, 0x00, 0x00
+        ];
+
+        assert_eq!(
+            decompress(&quick).unwrap(),
+            b"the quick brown fox jumps over the lazy dog; ".repeat(4)
+        );
+    }
+
+    #[test]
+    fn dynamic_huffman_blocks() {
+        let bits = [
+            0x3d, 0x8c, 0xc9, 0x0d, 0x00, 0x00, 0x08, 0xc2, 0x56, 0xa2, 0xfb, 0x2f, 0xa7, 0x41,
+            0xd0, 0x87, 0x34, 0xe1, 0x40, 0x12, 0xf2, 0x61, 0xf0, 0x0f, 0x9a, 0x2c, 0x05, 0x38,
+            0x23, 0xce, 0x85, 0xd2, 0xef, 0x52, 0xfa, 0xfc, 0x4e, 0xa3, 0xaf, 0x2b, 0x03
+        ];
+
+        assert_eq!(
+            inflate(&bits).unwrap(),
+            &b"100010000001010001010010100011001001100001001101110001110010010001101000000000\
+               100001001010110010100001001000010010000001"[..]
+        );
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(decompress(b"0010"), Err(InvalidHeader));
+        assert_eq!(decompress(&[0x1f, 0x8b, 0x08, 0, 0, 0, 0, 0, 0, 3, 0x07]), Err(InvalidData));
+
+        let mut corrupted = [
+            0x1f, 0x8b, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02, 0x03, 0x33, 0x30, 0x30, 0x34,
+            0x00, 0x00, 0x33, 0xf5, 0x80, 0x15, 0x04, 0x00, 0x00, 0x00
+        ];
+        corrupted[17] ^= 1;
+        assert_eq!(decompress(&corrupted), Err(ChecksumMismatch));
+    }
+}
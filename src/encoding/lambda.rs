@@ -17,40 +17,163 @@ use pair_list::*;
 ///
 /// assert_eq!(decode(k).unwrap(), "(λλ2)");
 /// ```
-pub fn decode(term: Term) -> Result<String, Error> {
-    if term == fls() {
-        Ok("".into())
-    } else if is_list(&term) && is_list(head_ref(&term).unwrap()) { // safe
-        let (head, tail) = uncons(term).unwrap(); // safe
-        let byte = decode_byte(head)?;
-        let chr = char::from(byte);
-        Ok(chr.to_string() + &decode(tail)?)
-    } else if head_ref(&term) == Ok(&fls()) {
-        Ok("1".to_string() + &decode(tail(term).unwrap())?) // safe
-    } else if head_ref(&term) == Ok(&tru()) {
-        Ok("0".to_string() + &decode(tail(term).unwrap())?) // safe
-    } else {
-        Ok(format!("({:?})", term))
+pub fn decode(mut term: Term) -> Result<String, Error> {
+    let mut output = String::new();
+
+    loop {
+        if term == fls() {
+            return Ok(output)
+        } else if is_list(&term) && is_list(head_ref(&term).unwrap()) { // safe
+            let (head, tail) = uncons(term).unwrap(); // safe
+            output.push(char::from(decode_byte(head)?));
+            term = tail;
+        } else if head_ref(&term) == Ok(&fls()) {
+            output.push('1');
+            term = tail(term).unwrap(); // safe
+        } else if head_ref(&term) == Ok(&tru()) {
+            output.push('0');
+            term = tail(term).unwrap(); // safe
+        } else {
+            output.push_str(&format!("({:?})", term));
+            return Ok(output)
+        }
+    }
+}
+
+/// Decode lambda-encoded data as bytes, like `decode` does, but without mapping them to `char`s;
+/// text that `decode` falls back to for terms that are not strings is encoded as UTF-8.
+///
+/// # Example
+/// ```
+/// use blc::encoding::lambda::{decode_bytes, encode};
+///
+/// assert_eq!(decode_bytes(encode(b"\x80\xff")).unwrap(), b"\x80\xff");
+/// ```
+pub fn decode_bytes(mut term: Term) -> Result<Vec<u8>, Error> {
+    let mut output = Vec::new();
+
+    loop {
+        if term == fls() {
+            return Ok(output)
+        } else if is_list(&term) && is_list(head_ref(&term).unwrap()) { // safe
+            let (head, tail) = uncons(term).unwrap(); // safe
+            output.push(decode_byte(head)?);
+            term = tail;
+        } else if head_ref(&term) == Ok(&fls()) {
+            output.push(b'1');
+            term = tail(term).unwrap(); // safe
+        } else if head_ref(&term) == Ok(&tru()) {
+            output.push(b'0');
+            term = tail(term).unwrap(); // safe
+        } else {
+            output.extend_from_slice(format!("({:?})", term).as_bytes());
+            return Ok(output)
+        }
     }
 }
 
+/// Decode a lambda-encoded list of bits as the characters `0` and `1`, like the output of a
+/// program in the bit mode of John Tromp's universal machine (`uni -b`); text that `decode` falls
+/// back to for terms that are not lists of bits is encoded as UTF-8.
+///
+/// # Example
+/// ```
+/// use blc::encoding::lambda::{decode_bits, encode_bits};
+///
+/// assert_eq!(decode_bits(encode_bits(b"0110")).unwrap(), b"0110");
+/// ```
+pub fn decode_bits(mut term: Term) -> Result<Vec<u8>, Error> {
+    let mut output = Vec::new();
+
+    loop {
+        if term == fls() {
+            return Ok(output)
+        } else if head_ref(&term) == Ok(&fls()) {
+            output.push(b'1');
+            term = tail(term).unwrap(); // safe
+        } else if head_ref(&term) == Ok(&tru()) {
+            output.push(b'0');
+            term = tail(term).unwrap(); // safe
+        } else {
+            output.extend_from_slice(format!("({:?})", term).as_bytes());
+            return Ok(output)
+        }
+    }
+}
+
+/// The way non-printable characters are rendered by `escape`.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Escaping {
+    /// keep them intact
+    Raw,
+    /// replace them with `\xNN` escape sequences
+    Hex,
+    /// remove them
+    Strip,
+    /// replace each of them with the given character
+    Replace(char)
+}
+
+/// Makes decoded output safe to display by rendering its non-printable characters (control
+/// characters other than newlines and tabs) in the given way.
+///
+/// # Example
+/// ```
+/// use blc::encoding::lambda::{escape, Escaping};
+///
+/// assert_eq!(escape("a\x07b\n", Escaping::Hex), "a\\x07b\n");
+/// assert_eq!(escape("a\x07b\n", Escaping::Strip), "ab\n");
+/// assert_eq!(escape("a\x07b\n", Escaping::Replace('.')), "a.b\n");
+/// ```
+pub fn escape(output: &str, escaping: Escaping) -> String {
+    let mut escaped = String::with_capacity(output.len());
+
+    for c in output.chars() {
+        if !c.is_control() || c == '\n' || c == '\t' || escaping == Escaping::Raw {
+            escaped.push(c);
+        } else {
+            match escaping {
+                Escaping::Hex => escaped.push_str(&format!("\\x{:02x}", c as u32)),
+                Escaping::Replace(replacement) => escaped.push(replacement),
+                _ => ()
+            }
+        }
+    }
+
+    escaped
+}
+
+/// Decode lambda-encoded data as a `String` like `decode` does, rendering non-printable
+/// characters with the given `Escaping`.
+///
+/// # Example
+/// ```
+/// use blc::encoding::lambda::{encode, decode_escaped, Escaping};
+///
+/// assert_eq!(decode_escaped(encode(b"\x1b[1m"), Escaping::Hex).unwrap(), "\\x1b[1m");
+/// ```
+pub fn decode_escaped(term: Term, escaping: Escaping) -> Result<String, Error> {
+    Ok(escape(&decode(term)?, escaping))
+}
+
 fn decode_byte(encoded_byte: Term) -> Result<u8, Error> {
     let indices = vectorize_list(encoded_byte)
         .into_iter()
         .map(|t| t.unabs().and_then(|t| t.unabs()).and_then(|t| t.unvar()))
         .collect::<Result<Vec<usize>, TermError>>();
 
-    if let Ok(indices) = indices {
-        Ok(!indices.into_iter().map(|b| (b - 1) as u8).fold(0, |acc, b| acc * 2 + b))
-    } else {
-        Err(Error::NotATerm)
+    match indices {
+        Ok(ref indices) if indices.len() == 8 && indices.iter().all(|&b| b == 1 || b == 2) => {
+            Ok(!indices.iter().map(|&b| (b - 1) as u8).fold(0, |acc, b| acc * 2 + b))
+        },
+        _ => Err(Error::NotATerm)
     }
 }
 
 fn encode_byte(byte: u8) -> Term {
     let bitstr = format!("{:08b}", byte);
     let bits = bitstr.as_bytes();
-    listify_terms(bits.into_iter().map(|&bit| encode_bit(bit)).collect::<Vec<Term>>())
+    listify_terms(bits.iter().map(|&bit| encode_bit(bit)).collect::<Vec<Term>>())
 }
 
 fn encode_bit(bit: u8) -> Term {
@@ -73,7 +196,23 @@ fn encode_bit(bit: u8) -> Term {
 /// );
 /// ```
 pub fn encode(input: &[u8]) -> Term {
-    listify_terms(input.into_iter().map(|&b| encode_byte(b)).collect::<Vec<Term>>())
+    listify_terms(input.iter().map(|&b| encode_byte(b)).collect::<Vec<Term>>())
+}
+
+/// Encode bytes as a lambda-encoded list of bits, one bit per byte, like the input of a program
+/// in the bit mode of John Tromp's universal machine (`uni -b`): only the least significant bit
+/// of every byte is used, so the characters `0` and `1` stand for the corresponding bits.
+///
+/// # Example
+/// ```
+/// use blc::encoding::lambda::encode_bits;
+///
+/// assert_eq!(&*format!("{:?}", encode_bits(b"01")), "λ1(λλ2)(λ1(λλ1)(λλ1))");
+/// ```
+pub fn encode_bits(input: &[u8]) -> Term {
+    let bits = input.iter().map(|&byte| if byte & 1 == 0 { tru() } else { fls() });
+
+    listify_terms(bits.collect::<Vec<Term>>())
 }
 
 #[cfg(test)]
@@ -118,4 +257,40 @@ mod test {
         assert_eq!(decode(encode(b"01zeros110and1ones101")).unwrap(), "01zeros110and1ones101");
         assert_eq!(decode(encode(b"\0(1)")).unwrap(),                 "\0(1)");
     }
+
+    #[test]
+    fn decode_bytes_encode() {
+        let bytes = (0..=255).collect::<Vec<u8>>();
+
+        assert_eq!(decode_bytes(encode(&bytes)).unwrap(), bytes);
+        assert_eq!(decode_bytes(encode(b"0110")).unwrap(), b"0110");
+        assert_eq!(decode_bytes(from_bits(b"0000110").unwrap()).unwrap(), "(λλ2)".as_bytes());
+    }
+
+    #[test]
+    fn decode_bits_encode_bits() {
+        assert_eq!(decode_bits(encode_bits(b"")).unwrap(), b"");
+        assert_eq!(decode_bits(encode_bits(b"0111010101011")).unwrap(), b"0111010101011");
+        assert_eq!(decode_bits(encode_bits(b"\x02\x03pq")).unwrap(), b"0101");
+        assert_eq!(decode_bits(encode(b"a")).unwrap(), format!("({:?})", encode(b"a")).as_bytes());
+    }
+
+    #[test]
+    fn malformed_bytes() {
+        let byte = |bits: Vec<Term>| listify_terms(vec![listify_terms(bits)]);
+
+        assert_eq!(decode(byte(vec![fls(); 9])), Err(Error::NotATerm));
+        assert_eq!(decode_bytes(byte(vec![tru(); 7])), Err(Error::NotATerm));
+        assert_eq!(decode(byte(vec![abs(abs(Var(3))); 8])), Err(Error::NotATerm));
+    }
+
+    #[test]
+    fn escaping() {
+        let output = decode(encode(b"\0ok\x7f\xe9\r\n")).unwrap();
+
+        assert_eq!(escape(&output, Escaping::Raw),          output);
+        assert_eq!(escape(&output, Escaping::Hex),          "\\x00ok\\x7f\u{e9}\\x0d\n");
+        assert_eq!(escape(&output, Escaping::Strip),        "ok\u{e9}\n");
+        assert_eq!(escape(&output, Escaping::Replace('?')), "?ok?\u{e9}?\n");
+    }
 }
@@ -1,4 +1,13 @@
 //! BLC-relevant encodings
 
+#[cfg(feature = "lambda_calculus")]
+pub mod assoc;
+#[cfg(feature = "lambda_calculus")]
+pub mod bcl;
 pub mod binary;
+#[cfg(feature = "gzip")]
+pub mod gzip;
+#[cfg(feature = "lambda_calculus")]
 pub mod lambda;
+#[cfg(feature = "zstd")]
+pub mod zstd;
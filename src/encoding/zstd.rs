@@ -0,0 +1,769 @@
+//! Decompression of zstd-wrapped data
+//!
+//! It is available with `features = ["zstd"]` and only supports decompression of frames that don't
+//! need a dictionary; the decoder follows RFC 8878.
+
+use self::Error::*;
+
+/// An error that can occur during decompression.
+#[derive(Debug, PartialEq)]
+pub enum Error {
+    /// the data doesn't start with a valid zstd frame header
+    InvalidHeader,
+    /// the compressed data is malformed or truncated
+    InvalidData,
+    /// the checksum or the size of the decompressed data doesn't match
+    ChecksumMismatch,
+    /// the frame can only be decompressed with the dictionary of the given ID
+    UnsupportedDictionary(u32)
+}
+
+const MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+
+/// Returns `true` if `data` starts with the zstd magic number.
+///
+/// # Example
+/// ```
+/// use blc::encoding::zstd::is_zstd;
+///
+/// assert!(is_zstd(&[0x28, 0xb5, 0x2f, 0xfd, 0x04]));
+/// assert!(!is_zstd(b"0010"));
+/// ```
+pub fn is_zstd(data: &[u8]) -> bool {
+    data.starts_with(&MAGIC)
+}
+
+/// Decompresses zstd-wrapped data; concatenated frames are decompressed one after another and
+/// skippable frames are skipped.
+///
+/// # Example
+/// ```
+/// use blc::encoding::zstd::decompress;
+///
+/// let zstd = [
+///     0x28, 0xb5, 0x2f, 0xfd, 0x04, 0x58, 0x21, 0x00, 0x00, 0x30, 0x30, 0x31, 0x30, 0x01, 0x21,
+///     0xc5, 0xbc
+/// ];
+///
+/// assert_eq!(decompress(&zstd), Ok(b"0010".to_vec()));
+/// ```
+/// # Errors
+///
+/// Returns an `Error` if `data` is not valid zstd-compressed data.
+pub fn decompress(data: &[u8]) -> Result<Vec<u8>, Error> {
+    let mut output = Vec::new();
+    let mut pos = 0;
+
+    loop {
+        let magic = little_endian(data.get(pos..pos + 4).ok_or(InvalidHeader)?) as u32;
+
+        if magic & 0xffff_fff0 == 0x184d_2a50 { // a skippable frame
+            let len = little_endian(data.get(pos + 4..pos + 8).ok_or(InvalidData)?) as usize;
+            pos += 8 + len;
+            if pos > data.len() { return Err(InvalidData) }
+        } else if is_zstd(&data[pos..]) {
+            pos = frame(data, pos + 4, &mut output)?;
+        } else {
+            return Err(InvalidHeader)
+        }
+
+        if pos == data.len() { return Ok(output) }
+    }
+}
+
+fn little_endian(bytes: &[u8]) -> u64 {
+    bytes.iter().rev().fold(0, |value, &byte| value << 8 | byte as u64)
+}
+
+/// Decompresses the frame whose header starts at `pos`; returns the position after the frame.
+fn frame(data: &[u8], mut pos: usize, output: &mut Vec<u8>) -> Result<usize, Error> {
+    let start = output.len();
+    let descriptor = *data.get(pos).ok_or(InvalidHeader)?;
+    let single_segment = descriptor & 0x20 != 0;
+    let has_checksum = descriptor & 0x04 != 0;
+    if descriptor & 0x08 != 0 { return Err(InvalidHeader) }
+
+    let dictionary_len = [0, 1, 2, 4][(descriptor & 3) as usize];
+    let size_len = match descriptor >> 6 {
+        0 => single_segment as usize,
+        1 => 2,
+        2 => 4,
+        _ => 8
+    };
+    pos += 1 + !single_segment as usize; // the window size only bounds the memory of a decoder
+
+    let dictionary = little_endian(data.get(pos..pos + dictionary_len).ok_or(InvalidHeader)?);
+    if dictionary != 0 { return Err(UnsupportedDictionary(dictionary as u32)) }
+    pos += dictionary_len;
+
+    let size = data.get(pos..pos + size_len).ok_or(InvalidHeader)?;
+    let size = match size_len {
+        0 => None,
+        2 => Some(little_endian(size) + 256),
+        _ => Some(little_endian(size))
+    };
+    pos += size_len;
+
+    let mut context = Context::new(start);
+    loop {
+        let header = little_endian(data.get(pos..pos + 3).ok_or(InvalidData)?) as usize;
+        let (last, len) = (header & 1 != 0, header >> 3);
+        pos += 3;
+
+        match (header >> 1) & 3 {
+            0 => {
+                output.extend_from_slice(data.get(pos..pos + len).ok_or(InvalidData)?);
+                pos += len;
+            },
+            1 => {
+                let byte = *data.get(pos).ok_or(InvalidData)?;
+                output.resize(output.len() + len, byte);
+                pos += 1;
+            },
+            2 => {
+                context.block(data.get(pos..pos + len).ok_or(InvalidData)?, output)?;
+                pos += len;
+            },
+            _ => return Err(InvalidData)
+        }
+
+        if last { break }
+    }
+
+    if size.is_some_and(|size| size != (output.len() - start) as u64) {
+        return Err(ChecksumMismatch)
+    }
+    if has_checksum {
+        let checksum = little_endian(data.get(pos..pos + 4).ok_or(InvalidData)?);
+        if xxh64(&output[start..]) & 0xffff_ffff != checksum { return Err(ChecksumMismatch) }
+        pos += 4;
+    }
+
+    Ok(pos)
+}
+
+/// The state carried over between the compressed blocks of a frame.
+struct Context {
+    start: usize,
+    offsets: [usize; 3],
+    huffman: Option<Huffman>,
+    tables: [Option<Fse>; 3]
+}
+
+const LITERAL_LENGTHS: usize = 0;
+const OFFSETS: usize = 1;
+const MATCH_LENGTHS: usize = 2;
+
+impl Context {
+    fn new(start: usize) -> Context {
+        Context { start, offsets: [1, 4, 8], huffman: None, tables: [None, None, None] }
+    }
+
+    fn block(&mut self, block: &[u8], output: &mut Vec<u8>) -> Result<(), Error> {
+        let (literals, len) = self.literals(block)?;
+
+        self.sequences(&block[len..], &literals, output)
+    }
+
+    /// Decodes the literals section; returns the literals along with the length of the section.
+    fn literals(&mut self, block: &[u8]) -> Result<(Vec<u8>, usize), Error> {
+        let first = *block.first().ok_or(InvalidData)?;
+        let (kind, format) = (first & 3, (first >> 2) & 3);
+
+        if kind < 2 {
+            let (header_len, len) = match format {
+                0 | 2 => (1, first as usize >> 3),
+                1     => (2, little_endian(block.get(0..2).ok_or(InvalidData)?) as usize >> 4),
+                _     => (3, little_endian(block.get(0..3).ok_or(InvalidData)?) as usize >> 4)
+            };
+
+            return if kind == 0 {
+                let literals = block.get(header_len..header_len + len).ok_or(InvalidData)?;
+                Ok((literals.to_vec(), header_len + len))
+            } else {
+                let byte = *block.get(header_len).ok_or(InvalidData)?;
+                Ok((vec![byte; len], header_len + 1))
+            }
+        }
+
+        let (header_len, width, streams) = match format {
+            0 => (3, 10, 1),
+            1 => (3, 10, 4),
+            2 => (4, 14, 4),
+            _ => (5, 18, 4)
+        };
+        let header = little_endian(block.get(0..header_len).ok_or(InvalidData)?) as usize >> 4;
+        let mask = (1 << width) - 1;
+        let (len, compressed_len) = (header & mask, (header >> width) & mask);
+
+        let mut data = block.get(header_len..header_len + compressed_len).ok_or(InvalidData)?;
+        if kind == 2 {
+            let (huffman, table_len) = Huffman::read(data)?;
+            self.huffman = Some(huffman);
+            data = &data[table_len..];
+        }
+        let huffman = self.huffman.as_ref().ok_or(InvalidData)?; // a treeless block reuses it
+
+        let literals = if streams == 1 {
+            huffman.decode(data, len)?
+        } else {
+            let jumps = data.get(0..6).ok_or(InvalidData)?;
+            let mut ends = [0; 4];
+            for i in 0..3 {
+                ends[i + 1] = ends[i] + little_endian(&jumps[2 * i..2 * i + 2]) as usize;
+            }
+            let data = &data[6..];
+            if ends[3] > data.len() { return Err(InvalidData) }
+
+            let stream_len = len.div_ceil(4);
+            let last_len = len.checked_sub(3 * stream_len).ok_or(InvalidData)?;
+            let mut literals = Vec::with_capacity(len);
+            for i in 0..4 {
+                let stream = if i < 3 { &data[ends[i]..ends[i + 1]] } else { &data[ends[3]..] };
+                let count = if i < 3 { stream_len } else { last_len };
+                literals.extend(huffman.decode(stream, count)?);
+            }
+            literals
+        };
+
+        Ok((literals, header_len + compressed_len))
+    }
+
+    /// Decodes the sequences section and executes the sequences.
+    fn sequences(&mut self, data: &[u8], literals: &[u8], output: &mut Vec<u8>)
+        -> Result<(), Error>
+    {
+        let first = *data.first().ok_or(InvalidData)? as usize;
+        let byte = |i: usize| data.get(i).map(|&b| b as usize).ok_or(InvalidData);
+        let (count, mut pos) = match first {
+            0        => (0, 1),
+            1..=127  => (first, 1),
+            128..=254 => (((first - 128) << 8) + byte(1)?, 2),
+            _        => (byte(1)? + (byte(2)? << 8) + 0x7f00, 3)
+        };
+        if count == 0 {
+            output.extend_from_slice(literals);
+            return Ok(())
+        }
+
+        let modes = byte(pos)?;
+        if modes & 3 != 0 { return Err(InvalidData) }
+        pos += 1;
+        for &(table, shift) in &[(LITERAL_LENGTHS, 6), (OFFSETS, 4), (MATCH_LENGTHS, 2)] {
+            match (modes >> shift) & 3 {
+                0 => self.tables[table] = Some(Fse::predefined(table)?),
+                1 => {
+                    self.tables[table] = Some(Fse::rle(byte(pos)? as u16));
+                    pos += 1;
+                },
+                2 => {
+                    let (fse, len) = Fse::read(&data[pos..], MAX_LOGS[table], MAX_SYMBOLS[table])?;
+                    self.tables[table] = Some(fse);
+                    pos += len;
+                },
+                _ => if self.tables[table].is_none() { return Err(InvalidData) }
+            }
+        }
+
+        let (lls, ofs, mls) = match self.tables {
+            [Some(ref lls), Some(ref ofs), Some(ref mls)] => (lls, ofs, mls),
+            _ => unreachable!() // every table is either set above or rejected as missing
+        };
+        let mut bits = BackwardBits::new(&data[pos..])?;
+        let mut ll_state = bits.bits(lls.log) as usize;
+        let mut of_state = bits.bits(ofs.log) as usize;
+        let mut ml_state = bits.bits(mls.log) as usize;
+        let mut literal = 0;
+
+        for i in 0..count {
+            let of_code = ofs.symbol(of_state) as usize;
+            let ml_code = mls.symbol(ml_state) as usize;
+            let ll_code = lls.symbol(ll_state) as usize;
+            if of_code > 31 || ml_code > 52 || ll_code > 35 { return Err(InvalidData) }
+
+            let offset = (1 << of_code) + bits.bits(of_code as u8);
+            let match_len = ML_BASE[ml_code] as usize + bits.bits(ML_BITS[ml_code]) as usize;
+            let literal_len = LL_BASE[ll_code] as usize + bits.bits(LL_BITS[ll_code]) as usize;
+
+            if i + 1 < count {
+                ll_state = lls.next(ll_state, &mut bits);
+                ml_state = mls.next(ml_state, &mut bits);
+                of_state = ofs.next(of_state, &mut bits);
+            }
+
+            let offset = resolve_offset(&mut self.offsets, offset as usize, literal_len)?;
+            let copied = literals.get(literal..literal + literal_len).ok_or(InvalidData)?;
+            output.extend_from_slice(copied);
+            literal += literal_len;
+
+            if offset > output.len() - self.start { return Err(InvalidData) }
+            for _ in 0..match_len {
+                let byte = output[output.len() - offset];
+                output.push(byte);
+            }
+        }
+        if !bits.is_empty() { return Err(InvalidData) }
+
+        output.extend_from_slice(&literals[literal..]);
+
+        Ok(())
+    }
+}
+
+/// Resolves an offset value, which can refer to one of the `recent`ly used offsets.
+fn resolve_offset(recent: &mut [usize; 3], value: usize, literal_len: usize) -> Result<usize, Error> {
+    if value > 3 {
+        *recent = [value - 3, recent[0], recent[1]];
+        return Ok(value - 3)
+    }
+
+    let offset = match value - 1 + (literal_len == 0) as usize {
+        0 => return Ok(recent[0]),
+        1 => {
+            let offset = recent[1];
+            recent[1] = recent[0];
+            offset
+        },
+        index => {
+            let offset = if index == 2 { recent[2] } else { recent[0] - 1 };
+            if offset == 0 { return Err(InvalidData) }
+            recent[2] = recent[1];
+            recent[1] = recent[0];
+            offset
+        }
+    };
+    recent[0] = offset;
+
+    Ok(offset)
+}
+
+/// A bitstream read backwards from its last bit, as the entropy-coded streams are.
+struct BackwardBits<'a> {
+    data: &'a [u8],
+    pos: isize
+}
+
+impl<'a> BackwardBits<'a> {
+    fn new(data: &'a [u8]) -> Result<BackwardBits<'a>, Error> {
+        let last = *data.last().ok_or(InvalidData)?;
+        if last == 0 { return Err(InvalidData) }
+
+        // the highest set bit of the last byte marks the beginning of the stream
+        Ok(BackwardBits { data, pos: (data.len() * 8) as isize - last.leading_zeros() as isize - 1 })
+    }
+
+    /// Returns the next `count` bits without consuming them; the bits before the beginning of the
+    /// stream are read as `0`s.
+    fn peek(&self, count: u8) -> u64 {
+        let mut value = 0;
+
+        for i in 0..count as isize {
+            let pos = self.pos - count as isize + i;
+            if pos >= 0 {
+                value |= (((self.data[pos as usize / 8] >> (pos % 8)) & 1) as u64) << i;
+            }
+        }
+
+        value
+    }
+
+    fn skip(&mut self, count: u8) {
+        self.pos -= count as isize;
+    }
+
+    fn bits(&mut self, count: u8) -> u64 {
+        let value = self.peek(count);
+        self.skip(count);
+
+        value
+    }
+
+    fn is_empty(&self) -> bool {
+        self.pos == 0
+    }
+
+    fn is_overflowed(&self) -> bool {
+        self.pos < 0
+    }
+}
+
+/// A Huffman code for literals: the symbol and code length for every value of the next
+/// `max_bits` bits.
+struct Huffman {
+    max_bits: u8,
+    table: Vec<(u8, u8)>
+}
+
+impl Huffman {
+    /// Reads a Huffman tree description; returns the code along with the length of the
+    /// description.
+    fn read(data: &[u8]) -> Result<(Huffman, usize), Error> {
+        let header = *data.first().ok_or(InvalidData)? as usize;
+
+        if header < 128 {
+            let weights = fse_weights(data.get(1..1 + header).ok_or(InvalidData)?)?;
+            Ok((Huffman::new(weights)?, 1 + header))
+        } else {
+            let count = header - 127;
+            let bytes = data.get(1..1 + count.div_ceil(2)).ok_or(InvalidData)?;
+            let weights = (0..count)
+                .map(|i| if i % 2 == 0 { bytes[i / 2] >> 4 } else { bytes[i / 2] & 0xf })
+                .collect();
+            Ok((Huffman::new(weights)?, 1 + bytes.len()))
+        }
+    }
+
+    /// Builds the code from the weights of all the symbols but the last one, whose weight is
+    /// implied.
+    fn new(mut weights: Vec<u8>) -> Result<Huffman, Error> {
+        if weights.len() > 255 || weights.iter().any(|&weight| weight > 11) {
+            return Err(InvalidData)
+        }
+
+        let sum: u32 = weights.iter().filter(|&&weight| weight > 0).map(|&w| 1 << (w - 1)).sum();
+        let max_bits = 32 - sum.leading_zeros();
+        let remainder = (1 << max_bits) - sum;
+        if sum == 0 || max_bits > 11 || !remainder.is_power_of_two() { return Err(InvalidData) }
+        weights.push(remainder.trailing_zeros() as u8 + 1);
+
+        // the codes are ordered by weight and then by symbol, the longest ones first
+        let mut table = Vec::with_capacity(1 << max_bits);
+        for weight in 1..max_bits as u8 + 1 {
+            for (symbol, _) in weights.iter().enumerate().filter(|&(_, &w)| w == weight) {
+                for _ in 0..1 << (weight - 1) {
+                    table.push((symbol as u8, max_bits as u8 + 1 - weight));
+                }
+            }
+        }
+
+        Ok(Huffman { max_bits: max_bits as u8, table })
+    }
+
+    fn decode(&self, stream: &[u8], count: usize) -> Result<Vec<u8>, Error> {
+        let mut bits = BackwardBits::new(stream)?;
+        let mut symbols = Vec::with_capacity(count);
+
+        for _ in 0..count {
+            let (symbol, len) = self.table[bits.peek(self.max_bits) as usize];
+            bits.skip(len);
+            symbols.push(symbol);
+        }
+
+        if bits.is_empty() { Ok(symbols) } else { Err(InvalidData) }
+    }
+}
+
+/// Decodes the FSE-compressed Huffman weights, which are interleaved between two states.
+fn fse_weights(data: &[u8]) -> Result<Vec<u8>, Error> {
+    let (fse, len) = Fse::read(data, 6, 255)?;
+    let mut bits = BackwardBits::new(&data[len..])?;
+    let mut states = [bits.bits(fse.log) as usize, bits.bits(fse.log) as usize];
+    let mut weights = Vec::new();
+
+    for i in 0.. {
+        let (current, other) = (i % 2, 1 - i % 2);
+        weights.push(fse.symbol(states[current]) as u8);
+        states[current] = fse.next(states[current], &mut bits);
+
+        if bits.is_overflowed() {
+            weights.push(fse.symbol(states[other]) as u8);
+            break
+        }
+        if weights.len() > 255 { return Err(InvalidData) }
+    }
+
+    Ok(weights)
+}
+
+/// A finite state entropy decoding table: the symbol, the number of bits to read for the next
+/// state and the baseline the bits are added to, for every state.
+struct Fse {
+    log: u8,
+    table: Vec<(u16, u8, usize)>
+}
+
+const MAX_LOGS: [u8; 3] = [9, 8, 9];
+const MAX_SYMBOLS: [usize; 3] = [35, 31, 52];
+
+const LL_DEFAULT: [i16; 36] = [
+    4, 3, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 1, 1, 1, 2, 2, 2, 2, 2, 2, 2, 2, 2, 3, 2, 1, 1, 1, 1, 1,
+    -1, -1, -1, -1
+];
+const OF_DEFAULT: [i16; 29] = [
+    1, 1, 1, 1, 1, 1, 2, 2, 2, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, -1, -1, -1, -1, -1
+];
+const ML_DEFAULT: [i16; 53] = [
+    1, 4, 3, 2, 2, 2, 2, 2, 2, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1,
+    1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, -1, -1, -1, -1, -1, -1, -1
+];
+
+const LL_BASE: [u32; 36] = [
+    0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 18, 20, 22, 24, 28, 32, 40, 48, 64,
+    128, 256, 512, 1024, 2048, 4096, 8192, 16384, 32768, 65536
+];
+const LL_BITS: [u8; 36] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 3, 3, 4, 6, 7, 8, 9, 10, 11,
+    12, 13, 14, 15, 16
+];
+const ML_BASE: [u32; 53] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27, 28,
+    29, 30, 31, 32, 33, 34, 35, 37, 39, 41, 43, 47, 51, 59, 67, 83, 99, 131, 259, 515, 1027, 2051,
+    4099, 8195, 16387, 32771, 65539
+];
+const ML_BITS: [u8; 53] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    1, 1, 1, 1, 2, 2, 3, 3, 4, 4, 5, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16
+];
+
+impl Fse {
+    fn predefined(table: usize) -> Result<Fse, Error> {
+        match table {
+            LITERAL_LENGTHS => Fse::new(&LL_DEFAULT, 6),
+            OFFSETS         => Fse::new(&OF_DEFAULT, 5),
+            _               => Fse::new(&ML_DEFAULT, 6)
+        }
+    }
+
+    fn rle(symbol: u16) -> Fse {
+        Fse { log: 0, table: vec![(symbol, 0, 0)] }
+    }
+
+    /// Reads a table description of at most `max_log` accuracy with symbols up to `max_symbol`;
+    /// returns the table along with the length of the description.
+    fn read(data: &[u8], max_log: u8, max_symbol: usize) -> Result<(Fse, usize), Error> {
+        let mut bits = ForwardBits { data, pos: 0 };
+        let log = bits.bits(4) as u8 + 5;
+        if log > max_log { return Err(InvalidData) }
+
+        let mut remaining: i32 = (1 << log) + 1;
+        let mut threshold: i32 = 1 << log;
+        let mut width = log + 1;
+        let mut counts = Vec::new();
+
+        while remaining > 1 {
+            let max = 2 * threshold - 1 - remaining;
+            let mut value = bits.peek(width) as i32;
+            if value & (threshold - 1) < max {
+                bits.pos += width as usize - 1;
+                value &= threshold - 1;
+            } else {
+                bits.pos += width as usize;
+                value &= 2 * threshold - 1;
+                if value >= threshold { value -= max }
+            }
+            let count = value - 1; // -1 stands for a "less than 1" probability
+
+            remaining -= count.abs();
+            if remaining < 1 { return Err(InvalidData) }
+            counts.push(count as i16);
+
+            if count == 0 {
+                loop {
+                    let repeat = bits.bits(2);
+                    counts.extend((0..repeat).map(|_| 0));
+                    if repeat < 3 { break }
+                }
+            }
+            while remaining < threshold {
+                width -= 1;
+                threshold >>= 1;
+            }
+            if counts.len() > max_symbol + 1 { return Err(InvalidData) }
+        }
+
+        let len = bits.pos.div_ceil(8);
+        if remaining != 1 || len > data.len() { return Err(InvalidData) }
+
+        Ok((Fse::new(&counts, log)?, len))
+    }
+
+    fn new(counts: &[i16], log: u8) -> Result<Fse, Error> {
+        let size = 1 << log;
+        let mut symbols = vec![0; size];
+        let mut states = vec![0; counts.len()];
+
+        // the symbols of "less than 1" probability take the last states
+        let mut high = size;
+        for (symbol, &count) in counts.iter().enumerate() {
+            if count == -1 {
+                high = high.checked_sub(1).ok_or(InvalidData)?;
+                symbols[high] = symbol as u16;
+                states[symbol] = 1;
+            } else {
+                states[symbol] = count.max(0) as usize;
+            }
+        }
+
+        let step = (size >> 1) + (size >> 3) + 3;
+        let mut pos = 0;
+        for (symbol, &count) in counts.iter().enumerate() {
+            for _ in 0..count.max(0) {
+                symbols[pos] = symbol as u16;
+                pos = (pos + step) & (size - 1);
+                while pos >= high { pos = (pos + step) & (size - 1) }
+            }
+        }
+        if pos != 0 { return Err(InvalidData) }
+
+        let table = symbols.iter().map(|&symbol| {
+            let state = states[symbol as usize];
+            states[symbol as usize] += 1;
+            let bits = log - (31 - (state as u32).leading_zeros()) as u8;
+            (symbol, bits, (state << bits) - size)
+        }).collect();
+
+        Ok(Fse { log, table })
+    }
+
+    fn symbol(&self, state: usize) -> u16 {
+        self.table[state].0
+    }
+
+    fn next(&self, state: usize, bits: &mut BackwardBits) -> usize {
+        let (_, count, base) = self.table[state];
+
+        base + bits.bits(count) as usize
+    }
+}
+
+/// A bitstream read from its first bit, as the FSE table descriptions are.
+struct ForwardBits<'a> {
+    data: &'a [u8],
+    pos: usize
+}
+
+impl<'a> ForwardBits<'a> {
+    /// Returns the next `count` bits without consuming them; the bits after the end of the data
+    /// are read as `0`s.
+    fn peek(&self, count: u8) -> u64 {
+        let mut value = 0;
+
+        for i in 0..count as usize {
+            let pos = self.pos + i;
+            if let Some(byte) = self.data.get(pos / 8) {
+                value |= (((byte >> (pos % 8)) & 1) as u64) << i;
+            }
+        }
+
+        value
+    }
+
+    fn bits(&mut self, count: u8) -> u64 {
+        let value = self.peek(count);
+        self.pos += count as usize;
+
+        value
+    }
+}
+
+/// The 64-bit xxHash of `data` with a seed of `0`, the low 32 bits of which are the checksum of a
+/// frame.
+fn xxh64(data: &[u8]) -> u64 {
+    const PRIME1: u64 = 0x9e37_79b1_85eb_ca87;
+    const PRIME2: u64 = 0xc2b2_ae3d_27d4_eb4f;
+    const PRIME3: u64 = 0x1656_67b1_9e37_79f9;
+    const PRIME4: u64 = 0x85eb_ca77_c2b2_ae63;
+    const PRIME5: u64 = 0x27d4_eb2f_1656_67c5;
+
+    fn round(acc: u64, lane: u64) -> u64 {
+        acc.wrapping_add(lane.wrapping_mul(PRIME2)).rotate_left(31).wrapping_mul(PRIME1)
+    }
+
+    fn merge(acc: u64, lane: u64) -> u64 {
+        (acc ^ round(0, lane)).wrapping_mul(PRIME1).wrapping_add(PRIME4)
+    }
+
+    let stripes = data.chunks_exact(32);
+    let tail = stripes.remainder();
+    let mut hash = if data.len() >= 32 {
+        let mut lanes = [PRIME1.wrapping_add(PRIME2), PRIME2, 0, 0u64.wrapping_sub(PRIME1)];
+        for stripe in stripes {
+            for (lane, bytes) in lanes.iter_mut().zip(stripe.chunks_exact(8)) {
+                *lane = round(*lane, little_endian(bytes));
+            }
+        }
+
+        let hash = lanes[0].rotate_left(1).wrapping_add(lanes[1].rotate_left(7))
+            .wrapping_add(lanes[2].rotate_left(12)).wrapping_add(lanes[3].rotate_left(18));
+        lanes.iter().fold(hash, |hash, &lane| merge(hash, lane))
+    } else {
+        PRIME5
+    }.wrapping_add(data.len() as u64);
+
+    let words = tail.chunks_exact(8);
+    let mut rest = words.remainder();
+    for word in words {
+        hash ^= round(0, little_endian(word));
+        hash = hash.rotate_left(27).wrapping_mul(PRIME1).wrapping_add(PRIME4);
+    }
+    if rest.len() >= 4 {
+        hash ^= little_endian(&rest[..4]).wrapping_mul(PRIME1);
+        hash = hash.rotate_left(23).wrapping_mul(PRIME2).wrapping_add(PRIME3);
+        rest = &rest[4..];
+    }
+    for &byte in rest {
+        hash ^= (byte as u64).wrapping_mul(PRIME5);
+        hash = hash.rotate_left(11).wrapping_mul(PRIME1);
+    }
+
+    hash ^= hash >> 33;
+    hash = hash.wrapping_mul(PRIME2);
+    hash ^= hash >> 29;
+    hash = hash.wrapping_mul(PRIME3);
+    hash ^ (hash >> 32)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const ID: [u8; 17] = [
+        0x28, 0xb5, 0x2f, 0xfd, 0x04, 0x58, 0x21, 0x00, 0x00, 0x30, 0x30, 0x31, 0x30, 0x01, 0x21,
+        0xc5, 0xbc
+    ];
+
+    #[test]
+    fn raw_and_rle_blocks() {
+        let ones = [0x28, 0xb5, 0x2f, 0xfd, 0x20, 0x40, 0x03, 0x02, 0x00, 0x31];
+
+        assert_eq!(decompress(&ID), Ok(b"0010".to_vec()));
+        assert_eq!(decompress(&ones), Ok(vec![b'1'; 64]));
+    }
+
+    #[test]
+    fn compressed_blocks() {
+        let primes = [
+            0x28, 0xb5, 0x2f, 0xfd, 0x04, 0x68, 0xf5, 0x00, 0x00, 0x72, 0x8a, 0x06, 0x04, 0xe0,
+            0x0f, 0x0d, 0x18, 0x36, 0x58, 0xe0, 0xe6, 0x03, 0xbc, 0x3f, 0xe7, 0x16, 0x80, 0xe6,
+            0xd0, 0x48, 0xef, 0x2b, 0x12, 0x2c, 0x40, 0xa3, 0xcc, 0x88, 0x00, 0x2b, 0x38, 0x05,
+            0x22
+        ];
+
+        assert_eq!(
+            decompress(&primes).unwrap(),
+            &b"00010001100110010100011010000000010110000010010001010111110111101001000110100001\
+               11001101000000000010110111001110011111110111100000000111110011011100000010110000\
+               0110110"[..]
+        );
+    }
+
+    #[test]
+    fn multiple_frames() {
+        let mut frames = vec![0x50, 0x2a, 0x4d, 0x18, 0x02, 0x00, 0x00, 0x00, 0x68, 0x69];
+        frames.extend_from_slice(&ID);
+        frames.extend_from_slice(&ID);
+
+        assert_eq!(decompress(&frames), Ok(b"00100010".to_vec()));
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(decompress(b"0010"), Err(InvalidHeader));
+        assert_eq!(decompress(&ID[..12]), Err(InvalidData));
+        assert_eq!(decompress(&[0x28, 0xb5, 0x2f, 0xfd, 0x21, 0x07]), Err(UnsupportedDictionary(7)));
+
+        let mut corrupted = ID;
+        corrupted[15] ^= 1;
+        assert_eq!(decompress(&corrupted), Err(ChecksumMismatch));
+    }
+}
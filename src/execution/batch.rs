@@ -0,0 +1,210 @@
+//! Execution of a program with many inputs
+
+use lambda_calculus::*;
+use execution::{apply_input, ClosedTerm, Error, Input};
+use execution::output::decoded;
+use std::iter;
+use std::panic;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+/// The number of reductions drawn from a `Fuel` pool at a time.
+const CHUNK: usize = 1024;
+
+/// A pool of β-reductions that can be shared by many executions, bounding all of them at once.
+///
+/// # Example
+/// ```
+/// use blc::execution::Fuel;
+///
+/// let fuel = Fuel::new(1_000_000_000);
+///
+/// assert_eq!(fuel.remaining(), 1_000_000_000);
+/// ```
+#[derive(Debug)]
+pub struct Fuel(AtomicUsize);
+
+impl Fuel {
+    /// Creates a pool of the given number of β-reductions.
+    pub fn new(reductions: usize) -> Self {
+        Fuel(AtomicUsize::new(reductions))
+    }
+
+    /// Returns the number of β-reductions left in the pool.
+    pub fn remaining(&self) -> usize {
+        self.0.load(Ordering::SeqCst)
+    }
+
+    fn take(&self, wanted: usize) -> usize {
+        let previous = self.0.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |left| {
+            Some(left - left.min(wanted))
+        }).unwrap(); // safe - the update always succeeds
+
+        previous.min(wanted)
+    }
+
+    fn refund(&self, reductions: usize) {
+        self.0.fetch_add(reductions, Ordering::SeqCst);
+    }
+}
+
+/// Reduces `term` to its normal form in normal order, drawing the β-reductions from `fuel`.
+///
+/// # Errors
+///
+/// Returns `Error::StepLimitExceeded` if the pool runs out before the normal form is reached.
+pub fn reduce_with_fuel(term: &mut Term, fuel: &Fuel) -> Result<(), Error> {
+    let mut steps = 0;
+
+    loop {
+        let drawn = fuel.take(CHUNK);
+        if drawn == 0 { return Err(Error::StepLimitExceeded { steps }) }
+
+        let performed = term.reduce(NOR, drawn);
+        steps += performed;
+        if performed < drawn {
+            fuel.refund(drawn - performed);
+            return Ok(())
+        }
+    }
+}
+
+/// Executes a binary lambda calculus program with each of the given inputs, parsing it only once;
+/// all the executions draw their β-reductions from the same `fuel` pool, so the whole batch is
+/// bounded no matter how the individual executions behave.
+///
+/// # Example
+/// ```
+/// use blc::execution::{run_batch, Fuel};
+/// use blc::execution::Input::Bytes;
+///
+/// let reverse_blc = b"0001011001000110100000000001011100111110111100001011011110110000010";
+/// let fuel = Fuel::new(10_000);
+/// let outputs = run_batch(&*reverse_blc, &[Bytes(b"abc"), Bytes(b"herp")], &fuel);
+///
+/// assert_eq!(outputs, vec![Ok("cba".into()), Ok("preh".into())]);
+/// ```
+pub fn run_batch(blc_program: &[u8], inputs: &[Input], fuel: &Fuel) -> Vec<Result<String, Error>> {
+    let program = match ClosedTerm::from_bits(blc_program) {
+        Ok(program) => program.into_term(),
+        Err(_) => return inputs.iter().map(|_| Err(Error::InvalidProgram)).collect()
+    };
+
+    inputs.iter().map(|&input| {
+        let mut calculation = apply_input(program.clone(), input)?;
+        reduce_with_fuel(&mut calculation, fuel)?;
+
+        decoded(calculation)
+    }).collect()
+}
+
+/// Executes a binary lambda calculus program with each of the given inputs like `run` does,
+/// parsing it only once and spreading the executions over as many threads as the machine can run
+/// in parallel; the outputs are in the order of the inputs. An execution that panics results in
+/// `Error::Panicked` for its input, without affecting the others.
+///
+/// # Example
+/// ```
+/// use blc::execution::run_parallel;
+///
+/// let reverse_blc = b"0001011001000110100000000001011100111110111100001011011110110000010";
+/// let outputs = run_parallel(&*reverse_blc, &[b"abc", b"herp"]);
+///
+/// assert_eq!(outputs, vec![Ok("cba".into()), Ok("preh".into())]);
+/// ```
+pub fn run_parallel(blc_program: &[u8], inputs: &[&[u8]]) -> Vec<Result<String, Error>> {
+    let program = match ClosedTerm::from_bits(blc_program) {
+        Ok(program) => program.into_term(),
+        Err(_) => return inputs.iter().map(|_| Err(Error::InvalidProgram)).collect()
+    };
+    let threads = thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+    let chunk = inputs.len().div_ceil(threads).max(1);
+
+    thread::scope(|scope| {
+        let workers = inputs.chunks(chunk).map(|inputs| {
+            let program = &program;
+            let worker = scope.spawn(move || inputs.iter().map(|&input| {
+                isolated(|| {
+                    let mut calculation = apply_input(program.clone(), Input::Bytes(input))?;
+                    calculation.reduce(NOR, 0);
+
+                    decoded(calculation)
+                })
+            }).collect::<Vec<_>>());
+            (worker, inputs.len())
+        }).collect::<Vec<_>>();
+
+        workers.into_iter().flat_map(|(worker, inputs)| {
+            worker.join().unwrap_or_else(|_| {
+                iter::repeat_with(|| Err(Error::Panicked)).take(inputs).collect()
+            })
+        }).collect()
+    })
+}
+
+/// Runs an execution, turning a panic into `Error::Panicked`.
+fn isolated<F: FnOnce() -> Result<String, Error>>(execution: F) -> Result<String, Error> {
+    panic::catch_unwind(panic::AssertUnwindSafe(execution)).unwrap_or(Err(Error::Panicked))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use lambda_calculus::combinators::O;
+
+    const REVERSE: &[u8] = b"0001011001000110100000000001011100111110111100001011011110110000010";
+
+    #[test]
+    fn fuel_is_shared() {
+        let fuel = Fuel::new(100_000);
+        assert_eq!(run_batch(REVERSE, &[Input::Bytes(b"abc")], &fuel), vec![Ok("cba".into())]);
+        let cost = 100_000 - fuel.remaining();
+
+        let fuel = Fuel::new(cost * 3 / 2);
+        let outputs = run_batch(REVERSE, &[Input::Bytes(b"abc"), Input::Bytes(b"abc")], &fuel);
+
+        let steps = cost * 3 / 2 - cost;
+        assert_eq!(outputs, vec![Ok("cba".into()), Err(Error::StepLimitExceeded { steps })]);
+        assert_eq!(fuel.remaining(), 0);
+    }
+
+    #[test]
+    fn exhaustion() {
+        let fuel = Fuel::new(2 * CHUNK + 1);
+        let mut omega = O();
+
+        let steps = 2 * CHUNK + 1;
+        assert_eq!(reduce_with_fuel(&mut omega, &fuel), Err(Error::StepLimitExceeded { steps }));
+        assert_eq!(fuel.remaining(), 0);
+        assert_eq!(
+            run_batch(REVERSE, &[Input::Bytes(b"abc")], &fuel),
+            vec![Err(Error::StepLimitExceeded { steps: 0 })]
+        );
+    }
+
+    #[test]
+    fn invalid_programs() {
+        let outputs = run_batch(b"22", &[Input::Nothing, Input::Nothing], &Fuel::new(10));
+
+        assert_eq!(outputs, vec![Err(Error::InvalidProgram), Err(Error::InvalidProgram)]);
+        assert_eq!(run_parallel(b"22", &[b"a"]), vec![Err(Error::InvalidProgram)]);
+    }
+
+    #[test]
+    fn parallel_outputs_are_ordered() {
+        let inputs = (0..50u8).map(|i| vec![b'a' + i % 26; i as usize]).collect::<Vec<_>>();
+        let inputs = inputs.iter().map(|input| &input[..]).collect::<Vec<&[u8]>>();
+        let expected = inputs.iter()
+            .map(|input| Ok(input.iter().rev().map(|&b| b as char).collect()))
+            .collect::<Vec<Result<String, Error>>>();
+
+        assert_eq!(run_parallel(REVERSE, &inputs), expected);
+        assert_eq!(run_parallel(REVERSE, &[]), vec![]);
+    }
+
+    #[test]
+    fn panics() {
+        assert_eq!(isolated(|| panic!("a bug")), Err(Error::Panicked));
+        assert_eq!(isolated(|| Ok("ok".into())), Ok("ok".into()));
+    }
+}
@@ -0,0 +1,197 @@
+//! Call-by-value evaluation by a CEK machine
+//!
+//! The reducers of `lambda_calculus` rewrite the term itself, substituting arguments into the
+//! bodies of abstractions. The CEK machine instead never changes the term: its state consists of
+//! the subterm under evaluation (the control), the values of the variables bound around it (the
+//! environment) and an explicit stack of what remains to be done with the result (the
+//! continuation). Arguments are evaluated before they are passed, and the normal form is read back
+//! by evaluating the bodies of abstractions with fresh variables, so `Cek` reaches the normal
+//! forms of `Strategy::Applicative` and, like it, diverges on terms that only have a normal form
+//! thanks to discarding a divergent argument.
+
+use lambda_calculus::*;
+use execution::machine::Machine;
+use std::rc::Rc;
+
+/// A CEK machine evaluating terms in call-by-value order.
+///
+/// If it runs out of reductions before reaching the normal form, the term is left as it was.
+///
+/// # Example
+/// ```
+/// use blc::execution::machine::{Machine, BigStep};
+/// use blc::execution::cek::Cek;
+/// use blc::encoding::binary::from_bits;
+///
+/// // (λ1 1 1) applied to (λλ1)(λ1)(λ1), which is evaluated before it is passed
+/// let term = from_bits(b"01000101101010010100001000100010").unwrap();
+///
+/// assert_eq!(Cek.normalize(term.clone()), BigStep.normalize(term.clone()));
+/// assert!(Cek.reduce(&mut term.clone(), 0) < BigStep.reduce(&mut term.clone(), 0));
+/// ```
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub struct Cek;
+
+impl Machine for Cek {
+    fn reduce(&mut self, term: &mut Term, limit: usize) -> usize {
+        let mut evaluator = Evaluator { limit, count: 0 };
+        let normal = evaluator.eval(term, &None).and_then(|value| evaluator.quote(value, 0));
+
+        if let Ok(normal) = normal { *term = normal }
+        evaluator.count
+    }
+}
+
+/// The values of the variables bound around a subterm, innermost first.
+type Env<'a> = Option<Rc<Frame<'a>>>;
+
+struct Frame<'a> {
+    value: Value<'a>,
+    next: Env<'a>
+}
+
+#[derive(Clone)]
+enum Value<'a> {
+    /// the body of an abstraction along with the values of its free variables
+    Closure(&'a Term, Env<'a>),
+    /// a variable, applied to values
+    Neutral(Head, Vec<Value<'a>>)
+}
+
+#[derive(Clone, Copy)]
+enum Head {
+    /// a variable bound by an abstraction being read back, by its depth counted from the root
+    Level(usize),
+    /// a free variable of the evaluated term, by its De Bruijn index counted from the root
+    Free(usize)
+}
+
+enum Control<'a> {
+    /// a subterm to evaluate in an environment
+    Eval(&'a Term, Env<'a>),
+    /// a value to pass to the topmost continuation
+    Return(Value<'a>)
+}
+
+enum Continuation<'a> {
+    /// evaluate the argument of an application whose function was evaluated
+    Argument(&'a Term, Env<'a>),
+    /// apply an evaluated function to the evaluated argument
+    Call(Value<'a>)
+}
+
+/// Signals that the evaluator ran out of reductions.
+struct Exhausted;
+
+struct Evaluator {
+    limit: usize,
+    count: usize
+}
+
+impl Evaluator {
+    fn eval<'a>(&mut self, term: &'a Term, env: &Env<'a>) -> Result<Value<'a>, Exhausted> {
+        let mut control = Control::Eval(term, env.clone());
+        let mut stack = Vec::new();
+
+        loop {
+            control = match control {
+                Control::Eval(term, env) => match *term {
+                    Var(i) => Control::Return(lookup(i, &env)),
+                    Abs(ref body) => Control::Return(Value::Closure(body, env)),
+                    App(ref lhs, ref rhs) => {
+                        stack.push(Continuation::Argument(rhs, env.clone()));
+                        Control::Eval(lhs, env)
+                    }
+                },
+                Control::Return(value) => match stack.pop() {
+                    None => return Ok(value),
+                    Some(Continuation::Argument(rhs, env)) => {
+                        stack.push(Continuation::Call(value));
+                        Control::Eval(rhs, env)
+                    },
+                    Some(Continuation::Call(Value::Closure(body, env))) => {
+                        if self.limit != 0 && self.count == self.limit { return Err(Exhausted) }
+                        self.count += 1;
+
+                        Control::Eval(body, Some(Rc::new(Frame { value, next: env })))
+                    },
+                    Some(Continuation::Call(Value::Neutral(head, mut args))) => {
+                        args.push(value);
+                        Control::Return(Value::Neutral(head, args))
+                    }
+                }
+            };
+        }
+    }
+
+    /// Reads back the normal form of `value` under `depth` abstractions.
+    fn quote(&mut self, value: Value, depth: usize) -> Result<Term, Exhausted> {
+        match value {
+            Value::Closure(body, env) => {
+                let value = Value::Neutral(Head::Level(depth), Vec::new());
+                let body = self.eval(body, &Some(Rc::new(Frame { value, next: env })))?;
+
+                Ok(abs(self.quote(body, depth + 1)?))
+            },
+            Value::Neutral(head, args) => {
+                let mut term = match head {
+                    Head::Level(level) => Var(depth - level),
+                    Head::Free(index) => Var(index + depth)
+                };
+                for arg in args {
+                    term = app(term, self.quote(arg, depth)?);
+                }
+                Ok(term)
+            }
+        }
+    }
+}
+
+fn lookup<'a>(index: usize, env: &Env<'a>) -> Value<'a> {
+    let mut frame = env;
+
+    for passed in 1..index {
+        frame = match *frame {
+            Some(ref f) => &f.next,
+            None => return Value::Neutral(Head::Free(index - passed + 1), Vec::new())
+        };
+    }
+    match *frame {
+        Some(ref f) => f.value.clone(),
+        None => Value::Neutral(Head::Free(1), Vec::new())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use lambda_calculus::combinators::{I, K, O, S};
+    use lambda_calculus::data::num::church::{add, mul};
+    use execution::machine::BigStep;
+
+    #[test]
+    fn normal_forms() {
+        let terms = vec![
+            app!(S(), K(), K()),
+            app!(mul(), 3.into_church(), 4.into_church()),
+            app!(add(), 2.into_church(), app(I(), 5.into_church())),
+            abs(app(abs(app(Var(3), Var(1))), Var(2)))
+        ];
+
+        for term in terms {
+            assert_eq!(Cek.normalize(term.clone()), BigStep.normalize(term));
+        }
+    }
+
+    #[test]
+    fn limits() {
+        let mut omega = O();
+        assert_eq!(Cek.reduce(&mut omega, 10), 10);
+        assert_eq!(omega, O());
+
+        // a divergent argument is evaluated even though it is discarded
+        let mut discarding = app!(K(), I(), O());
+        assert_eq!(Cek.reduce(&mut discarding, 100), 100);
+        assert_eq!(discarding, app!(K(), I(), O()));
+    }
+}
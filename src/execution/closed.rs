@@ -0,0 +1,80 @@
+//! Terms that are known to be closed
+
+use lambda_calculus::Term;
+use encoding::binary::from_bits;
+use execution::Error;
+use std::convert::TryFrom;
+
+/// A lambda `Term` without free variables.
+///
+/// It can only be obtained by validating a term, so every `ClosedTerm` can be safely evaluated and
+/// its result decoded.
+///
+/// # Example
+/// ```
+/// extern crate blc;
+/// extern crate lambda_calculus;
+///
+/// use blc::execution::ClosedTerm;
+/// use blc::execution::Error::InvalidProgram;
+/// use lambda_calculus::*;
+///
+/// assert!(ClosedTerm::new(abs(Var(1))).is_ok());
+/// assert_eq!(ClosedTerm::new(abs(Var(2))), Err(InvalidProgram));
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub struct ClosedTerm(Term);
+
+impl ClosedTerm {
+    /// Checks that `term` is closed.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::InvalidProgram` if `term` has free variables.
+    pub fn new(term: Term) -> Result<Self, Error> {
+        if term.is_supercombinator() { Ok(ClosedTerm(term)) } else { Err(Error::InvalidProgram) }
+    }
+
+    /// Parses a binary lambda calculus program and checks that it is closed.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::InvalidProgram` if the program is not valid BLC or has free variables.
+    pub fn from_bits(blc_program: &[u8]) -> Result<Self, Error> {
+        ClosedTerm::new(from_bits(blc_program).or(Err(Error::InvalidProgram))?)
+    }
+
+    /// Returns the closed `Term`.
+    pub fn as_term(&self) -> &Term { &self.0 }
+
+    /// Converts `self` into the closed `Term`.
+    pub fn into_term(self) -> Term { self.0 }
+}
+
+impl TryFrom<Term> for ClosedTerm {
+    type Error = Error;
+
+    fn try_from(term: Term) -> Result<Self, Error> { ClosedTerm::new(term) }
+}
+
+impl AsRef<Term> for ClosedTerm {
+    fn as_ref(&self) -> &Term { &self.0 }
+}
+
+impl From<ClosedTerm> for Term {
+    fn from(term: ClosedTerm) -> Term { term.0 }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use execution::{run, Input, Error::*};
+
+    #[test]
+    fn open_programs_are_rejected() {
+        assert_eq!(ClosedTerm::from_bits(b"110"), Err(InvalidProgram));
+        assert_eq!(run(b"110", Input::Nothing), Err(InvalidProgram));
+        assert_eq!(run(b"0010", Input::Bits(b"10")), Err(InvalidArgument));
+        assert_eq!(run(b"0010", Input::Bytes(b"ok")), Ok("ok".into()));
+    }
+}
@@ -0,0 +1,223 @@
+//! Evaluation by compilation to closures
+//!
+//! `lazy::Lazy` interprets a copy of the term, deciding what to do with every node each time it
+//! is evaluated. `Closures` instead compiles every node into a Rust closure once, with the number
+//! of environment frames a variable is found behind worked out in advance, and abstractions
+//! evaluate to Rust functions (higher-order abstract syntax), so applying one is just a call. The
+//! evaluation is call-by-need like in `Lazy` and reaches the same normal forms in the same number
+//! of β-reductions; compiling costs a pass over the term, which pays off for programs that spend
+//! long in interpreters and other tight loops.
+
+use lambda_calculus::*;
+use execution::machine::Machine;
+use execution::{apply_input, ClosedTerm, Error, Input};
+use execution::output::decoded;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// An evaluator compiling terms to closures and evaluating them in call-by-need order.
+///
+/// If it runs out of reductions before reaching the normal form, the term is left as it was.
+///
+/// # Example
+/// ```
+/// use blc::execution::machine::{Machine, BigStep};
+/// use blc::execution::closures::Closures;
+/// use blc::encoding::binary::from_bits;
+///
+/// // (λ1 1 1) applied to (λλ1)(λ1)(λ1), whose duplicated result is only computed once
+/// let term = from_bits(b"01000101101010010100001000100010").unwrap();
+///
+/// assert_eq!(Closures.normalize(term.clone()), BigStep.normalize(term.clone()));
+/// assert!(Closures.reduce(&mut term.clone(), 0) < BigStep.reduce(&mut term.clone(), 0));
+/// ```
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub struct Closures;
+
+impl Machine for Closures {
+    fn reduce(&mut self, term: &mut Term, limit: usize) -> usize {
+        let mut evaluator = Evaluator { limit, count: 0 };
+        let normal = compile(term)(&mut evaluator, &None)
+            .and_then(|value| evaluator.quote(value, 0));
+
+        if let Ok(normal) = normal { *term = normal }
+        evaluator.count
+    }
+}
+
+/// Executes a binary lambda calculus program like `run` does, but compiled to closures.
+///
+/// # Example
+/// ```
+/// use blc::execution::closures::run_closures;
+/// use blc::execution::Input::Bytes;
+///
+/// let reverse_blc = b"0001011001000110100000000001011100111110111100001011011110110000010";
+///
+/// assert_eq!(run_closures(&*reverse_blc, Bytes(b"herp derp")), Ok("pred preh".into()));
+/// ```
+/// # Errors
+///
+/// Returns the same `Error`s as `run`.
+pub fn run_closures(blc_program: &[u8], input: Input) -> Result<String, Error> {
+    let program = ClosedTerm::from_bits(blc_program)?.into_term();
+    let mut calculation = apply_input(program, input)?;
+    Closures.reduce(&mut calculation, 0);
+
+    decoded(calculation)
+}
+
+/// A compiled term, evaluating to its value in the given environment.
+type Code = Rc<dyn Fn(&mut Evaluator, &Env) -> Result<Value, Exhausted>>;
+
+/// A Rust function an abstraction evaluates to, taking its argument.
+type Function = Rc<dyn Fn(&mut Evaluator, Thunk) -> Result<Value, Exhausted>>;
+
+/// The values of the variables bound around a piece of `Code`, innermost first.
+type Env = Option<Rc<Frame>>;
+
+struct Frame {
+    thunk: Thunk,
+    next: Env
+}
+
+/// A possibly not yet evaluated argument, shared by all its uses.
+type Thunk = Rc<RefCell<State>>;
+
+enum State {
+    Delayed(Code, Env),
+    Forced(Value)
+}
+
+#[derive(Clone)]
+enum Value {
+    /// an abstraction, compiled along with the values of its free variables
+    Function(Function),
+    /// a variable, applied to arguments
+    Neutral(Head, Vec<Thunk>)
+}
+
+#[derive(Clone, Copy)]
+enum Head {
+    /// a variable bound by an abstraction being read back, by its depth counted from the root
+    Level(usize),
+    /// a free variable of the evaluated term, by its De Bruijn index counted from the root
+    Free(usize)
+}
+
+/// Signals that the evaluator ran out of reductions.
+struct Exhausted;
+
+fn compile(term: &Term) -> Code {
+    match *term {
+        Var(i) => Rc::new(move |evaluator, env| {
+            let mut frame = env;
+            for passed in 1..i {
+                frame = match *frame {
+                    Some(ref f) => &f.next,
+                    None => return Ok(Value::Neutral(Head::Free(i - passed + 1), Vec::new()))
+                };
+            }
+            match *frame {
+                Some(ref f) => evaluator.force(&f.thunk),
+                None => Ok(Value::Neutral(Head::Free(1), Vec::new()))
+            }
+        }),
+        Abs(ref body) => {
+            let body = compile(body);
+            Rc::new(move |_, env| {
+                let (body, env) = (body.clone(), env.clone());
+                Ok(Value::Function(Rc::new(move |evaluator, thunk| {
+                    body(evaluator, &Some(Rc::new(Frame { thunk, next: env.clone() })))
+                })))
+            })
+        },
+        App(ref lhs, ref rhs) => {
+            let (lhs, rhs) = (compile(lhs), compile(rhs));
+            Rc::new(move |evaluator, env| {
+                let function = lhs(evaluator, env)?;
+                let argument = Rc::new(RefCell::new(State::Delayed(rhs.clone(), env.clone())));
+                evaluator.apply(function, argument)
+            })
+        }
+    }
+}
+
+struct Evaluator {
+    limit: usize,
+    count: usize
+}
+
+impl Evaluator {
+    fn apply(&mut self, function: Value, argument: Thunk) -> Result<Value, Exhausted> {
+        match function {
+            Value::Function(function) => {
+                if self.limit != 0 && self.count == self.limit { return Err(Exhausted) }
+                self.count += 1;
+
+                function(self, argument)
+            },
+            Value::Neutral(head, mut args) => {
+                args.push(argument);
+                Ok(Value::Neutral(head, args))
+            }
+        }
+    }
+
+    fn force(&mut self, thunk: &Thunk) -> Result<Value, Exhausted> {
+        let (code, env) = match *thunk.borrow() {
+            State::Forced(ref value) => return Ok(value.clone()),
+            State::Delayed(ref code, ref env) => (code.clone(), env.clone())
+        };
+
+        let value = code(self, &env)?;
+        *thunk.borrow_mut() = State::Forced(value.clone());
+        Ok(value)
+    }
+
+    /// Reads back the normal form of `value` under `depth` abstractions.
+    fn quote(&mut self, value: Value, depth: usize) -> Result<Term, Exhausted> {
+        match value {
+            Value::Function(function) => {
+                let fresh = Value::Neutral(Head::Level(depth), Vec::new());
+                let body = function(self, Rc::new(RefCell::new(State::Forced(fresh))))?;
+
+                Ok(abs(self.quote(body, depth + 1)?))
+            },
+            Value::Neutral(head, args) => {
+                let mut term = match head {
+                    Head::Level(level) => Var(depth - level),
+                    Head::Free(index) => Var(index + depth)
+                };
+                for arg in args {
+                    let arg = self.force(&arg)?;
+                    term = app(term, self.quote(arg, depth)?);
+                }
+                Ok(term)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use execution::lazy::Lazy;
+    use lambda_calculus::data::num::church::{fac, mul};
+
+    #[test]
+    fn same_as_lazy() {
+        let terms = vec![
+            app!(mul(), 3.into_church(), 3.into_church()),
+            app(fac(), 4.into_church()),
+            abs(app(abs(app(Var(3), Var(1))), Var(2)))
+        ];
+
+        for term in terms {
+            let (mut compiled, mut interpreted) = (term.clone(), term);
+
+            assert_eq!(Closures.reduce(&mut compiled, 0), Lazy.reduce(&mut interpreted, 0));
+            assert_eq!(compiled, interpreted);
+        }
+    }
+}
@@ -0,0 +1,102 @@
+//! Evaluation of output data down to its constructors
+//!
+//! Decoding the output of a program only needs its list cells, bits and bytes exposed, so instead
+//! of normalizing it, every cell of the list is only reduced to weak head normal form, as is the
+//! body of its abstraction; the same is done recursively with the head and tail of every cell.
+//! Anything that doesn't turn out to be a list cell is left in weak head normal form.
+//!
+//! Evaluating the output list one cell at a time also makes it possible to stop as soon as it
+//! gets too long.
+
+use lambda_calculus::*;
+use execution::Error;
+
+/// Reduces the list cells of `term` until its structure is exposed down to the constructors;
+/// returns the number of performed reductions.
+pub fn force(term: &mut Term) -> usize {
+    force_list(term, 0, false).unwrap_or_default() // safe - no limit
+}
+
+/// Reduces the cells of the list `term` one by one, like `force` does; with `normalize`, their
+/// heads and the end of the list are normalized instead. Returns the number of performed
+/// reductions.
+///
+/// # Errors
+///
+/// Returns `Error::OutputLimitExceeded` if the list has more than `limit` cells (`0` means no
+/// limit).
+pub fn force_list(mut term: &mut Term, limit: usize, normalize: bool) -> Result<usize, Error> {
+    let mut cells = 0;
+    let mut count = 0;
+
+    loop {
+        count += term.reduce(CBN, 0);
+        if let Abs(ref mut body) = *term { count += body.reduce(CBN, 0); }
+
+        if !is_cell(term) {
+            if normalize { count += term.reduce(NOR, 0); }
+            return Ok(count)
+        }
+        if limit != 0 && cells == limit { return Err(Error::OutputLimitExceeded) }
+        cells += 1;
+
+        let (head, tail) = cell_mut(term).unwrap(); // safe - checked above
+        count += if normalize { head.reduce(NOR, 0) } else { force(head) };
+        term = tail;
+    }
+}
+
+pub fn is_cell(term: &Term) -> bool {
+    if let Abs(ref body) = *term {
+        if let App(ref pair, _) = **body {
+            if let App(ref head_var, _) = **pair { return **head_var == Var(1) }
+        }
+    }
+    false
+}
+
+pub fn cell_mut(term: &mut Term) -> Option<(&mut Term, &mut Term)> {
+    if let Abs(ref mut body) = *term {
+        if let App(ref mut pair, ref mut tail) = **body {
+            if let App(_, ref mut head) = **pair { return Some((head, tail)) }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use lambda_calculus::combinators::{I, O, Y};
+    use lambda_calculus::data::boolean::{tru, fls, not};
+
+    #[test]
+    fn lists_are_forced() {
+        let cell = |head, tail| abs(app!(Var(1), head, tail));
+        let mut list = app(I(), cell(app(not(), fls()), app(I(), cell(tru(), fls()))));
+        force(&mut list);
+
+        assert_eq!(list, cell(tru(), cell(tru(), fls())));
+    }
+
+    #[test]
+    fn abstractions_are_not_entered() {
+        let cell = |head, tail| abs(app!(Var(1), head, tail));
+        let mut list = cell(abs(abs(O())), fls());
+        force(&mut list);
+
+        assert_eq!(list, cell(abs(abs(O())), fls()));
+    }
+
+    #[test]
+    fn length_limits() {
+        let cell = |head, tail| abs(app!(Var(1), head, tail));
+        let list = cell(app(not(), tru()), app(I(), cell(tru(), fls())));
+
+        assert_eq!(force_list(&mut list.clone(), 1, true), Err(Error::OutputLimitExceeded));
+        assert_eq!(force_list(&mut list.clone(), 2, true), Ok(4));
+
+        let mut endless = app(Y(), abs(cell(tru(), Var(2))));
+        assert_eq!(force_list(&mut endless, 3, true), Err(Error::OutputLimitExceeded));
+    }
+}
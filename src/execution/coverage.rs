@@ -0,0 +1,313 @@
+//! Coverage of program subterms
+//!
+//! `coverage` executes a program in normal order while tracking where every subterm of the
+//! intermediate terms came from in the original program. A subterm of the program is covered if
+//! it (or a copy of it) took part in a contracted redex, as its application, its abstraction or a
+//! substituted variable, or if it ended up in the normal form; the regions of the program that
+//! were never covered are dead code that can be removed without changing the result of the run.
+
+use lambda_calculus::*;
+use execution::{apply_input, ClosedTerm, Error, Input};
+use std::fmt;
+use std::mem;
+use std::ops::Range;
+
+/// The coverage of a program by a single run.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Coverage {
+    /// the length of the program, in bits of its canonical (whitespace-free) encoding
+    pub bits: usize,
+    /// the number of reduction steps performed
+    pub steps: usize,
+    /// `true` if the run reached a normal form; otherwise the coverage only reflects the steps
+    /// performed before reaching the step limit
+    pub complete: bool,
+    /// the bit ranges of the never-covered regions of the program, in ascending order
+    pub dead: Vec<Range<usize>>
+}
+
+impl Coverage {
+    /// Returns the number of bits of the program that were never covered.
+    pub fn dead_bits(&self) -> usize {
+        self.dead.iter().map(|range| range.len()).sum()
+    }
+}
+
+impl fmt::Display for Coverage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for range in &self.dead {
+            writeln!(f, "dead: bits {}..{}", range.start, range.end)?;
+        }
+        write!(f, "{} of {} bits covered in {} steps", self.bits - self.dead_bits(), self.bits,
+            self.steps)?;
+        if !self.complete { write!(f, " (no normal form reached)")? }
+        Ok(())
+    }
+}
+
+/// Executes a binary lambda calculus program with the given input in normal order, performing at
+/// most `limit` reduction steps (`0` means no limit), and reports which parts of the program were
+/// covered by the run.
+///
+/// # Example
+/// ```
+/// use blc::execution::Input;
+/// use blc::execution::coverage::coverage;
+///
+/// let program = b"01 01 000010 0010 0010"; // (λλ1)(λ1)(λ1): the first `λ1` is discarded
+/// let coverage = coverage(program, Input::Nothing, 0).unwrap();
+///
+/// assert_eq!(coverage.dead, [10..14]);
+/// assert_eq!(coverage.steps, 2);
+/// ```
+/// # Errors
+///
+/// Returns an `Error` if the program or the argument are not valid BLC.
+pub fn coverage(blc_program: &[u8], input: Input, limit: usize) -> Result<Coverage, Error> {
+    let program = ClosedTerm::from_bits(blc_program)?.into_term();
+    let mut regions = Vec::new();
+    let tagged = tag(&program, 0, &mut regions);
+
+    let mut term = match input {
+        Input::Nothing => tagged,
+        _ => {
+            // safe - with an input, the program is applied to it
+            let (_, arg) = apply_input(program.clone(), input)?.unapp().unwrap();
+            Node { kind: Kind::App(Box::new(tagged), Box::new(untagged(&arg))), origin: None }
+        }
+    };
+    let mut covered = vec![false; regions.len()];
+
+    let mut steps = 0;
+    while limit == 0 || steps < limit {
+        if !step(&mut term, &mut covered) { break }
+        steps += 1;
+    }
+    let complete = !has_redex(&term);
+    if complete { cover_all(&term, &mut covered) }
+
+    let mut dead: Vec<Range<usize>> = Vec::new();
+    collect_dead(&program, &mut 0, &regions, &covered, &mut dead);
+    dead.dedup_by(|next, last| {
+        let adjacent = last.end == next.start;
+        if adjacent { last.end = next.end }
+        adjacent
+    });
+
+    Ok(Coverage { bits: regions[0].end, steps, complete, dead })
+}
+
+/// A term whose nodes know which subterm of the program, if any, they are copies of.
+struct Node {
+    kind: Kind,
+    origin: Option<usize>
+}
+
+enum Kind {
+    Var(usize),
+    Abs(Box<Node>),
+    App(Box<Node>, Box<Node>)
+}
+
+impl Clone for Node {
+    fn clone(&self) -> Self {
+        let kind = match self.kind {
+            Kind::Var(i) => Kind::Var(i),
+            Kind::Abs(ref body) => Kind::Abs(body.clone()),
+            Kind::App(ref lhs, ref rhs) => Kind::App(lhs.clone(), rhs.clone())
+        };
+        Node { kind, origin: self.origin }
+    }
+}
+
+/// Tags the subterms of `term`, which starts at bit `start` of the program, with their indices
+/// in `regions`, in preorder, and records their bit ranges there.
+fn tag(term: &Term, start: usize, regions: &mut Vec<Range<usize>>) -> Node {
+    let index = regions.len();
+    regions.push(start..start);
+
+    let (kind, end) = match *term {
+        Var(i) => (Kind::Var(i), start + i + 1),
+        Abs(ref body) => {
+            let body = tag(body, start + 2, regions);
+            let end = regions[index + 1].end;
+            (Kind::Abs(Box::new(body)), end)
+        },
+        App(ref lhs, ref rhs) => {
+            let lhs = tag(lhs, start + 2, regions);
+            let middle = regions[index + 1].end;
+            let rhs_index = regions.len();
+            let rhs = tag(rhs, middle, regions);
+            (Kind::App(Box::new(lhs), Box::new(rhs)), regions[rhs_index].end)
+        }
+    };
+    regions[index].end = end;
+
+    Node { kind, origin: Some(index) }
+}
+
+fn untagged(term: &Term) -> Node {
+    let kind = match *term {
+        Var(i) => Kind::Var(i),
+        Abs(ref body) => Kind::Abs(Box::new(untagged(body))),
+        App(ref lhs, ref rhs) => Kind::App(Box::new(untagged(lhs)), Box::new(untagged(rhs)))
+    };
+    Node { kind, origin: None }
+}
+
+/// Collects the maximal never-covered subterms of `term`, the one at `index` in preorder, and
+/// returns `true` if any of its subterms was covered.
+fn collect_dead(
+    term: &Term,
+    index: &mut usize,
+    regions: &[Range<usize>],
+    covered: &[bool],
+    dead: &mut Vec<Range<usize>>
+) -> bool {
+    let own = *index;
+    *index += 1;
+
+    let mut dead_subterms = Vec::new();
+    let live = match *term {
+        Var(_) => covered[own],
+        Abs(ref body) => {
+            collect_dead(body, index, regions, covered, &mut dead_subterms) | covered[own]
+        },
+        App(ref lhs, ref rhs) => {
+            collect_dead(lhs, index, regions, covered, &mut dead_subterms)
+                | collect_dead(rhs, index, regions, covered, &mut dead_subterms)
+                | covered[own]
+        }
+    };
+
+    if live { dead.extend(dead_subterms) } else { dead.push(regions[own].clone()) }
+    live
+}
+
+fn cover(node: &Node, covered: &mut [bool]) {
+    if let Some(origin) = node.origin { covered[origin] = true }
+}
+
+fn cover_all(node: &Node, covered: &mut [bool]) {
+    cover(node, covered);
+    match node.kind {
+        Kind::Var(_) => (),
+        Kind::Abs(ref body) => cover_all(body, covered),
+        Kind::App(ref lhs, ref rhs) => {
+            cover_all(lhs, covered);
+            cover_all(rhs, covered);
+        }
+    }
+}
+
+fn has_redex(node: &Node) -> bool {
+    match node.kind {
+        Kind::Var(_) => false,
+        Kind::Abs(ref body) => has_redex(body),
+        Kind::App(ref lhs, ref rhs) => {
+            matches!(lhs.kind, Kind::Abs(_)) || has_redex(lhs) || has_redex(rhs)
+        }
+    }
+}
+
+/// Contracts the leftmost outermost redex in `node`, if there is one.
+fn step(node: &mut Node, covered: &mut [bool]) -> bool {
+    let is_redex = match node.kind {
+        Kind::App(ref lhs, _) => matches!(lhs.kind, Kind::Abs(_)),
+        _ => false
+    };
+
+    if is_redex {
+        let placeholder = Node { kind: Kind::Var(0), origin: None };
+        let redex = mem::replace(node, placeholder);
+        cover(&redex, covered);
+        if let Kind::App(lhs, rhs) = redex.kind {
+            cover(&lhs, covered);
+            if let Kind::Abs(body) = lhs.kind {
+                *node = substitute(*body, 1, &rhs, covered);
+            }
+        }
+        return true
+    }
+
+    match node.kind {
+        Kind::Var(_) => false,
+        Kind::Abs(ref mut body) => step(body, covered),
+        Kind::App(ref mut lhs, ref mut rhs) => step(lhs, covered) || step(rhs, covered)
+    }
+}
+
+/// Substitutes `arg` for the variable bound `depth` abstractions above `node`.
+fn substitute(node: Node, depth: usize, arg: &Node, covered: &mut [bool]) -> Node {
+    let Node { kind, origin } = node;
+
+    let kind = match kind {
+        Kind::Var(i) if i == depth => {
+            if let Some(origin) = origin { covered[origin] = true }
+            return shifted(arg, depth - 1, 0)
+        },
+        Kind::Var(i) if i > depth => Kind::Var(i - 1),
+        Kind::Var(i) => Kind::Var(i),
+        Kind::Abs(body) => Kind::Abs(Box::new(substitute(*body, depth + 1, arg, covered))),
+        Kind::App(lhs, rhs) => Kind::App(
+            Box::new(substitute(*lhs, depth, arg, covered)),
+            Box::new(substitute(*rhs, depth, arg, covered))
+        )
+    };
+
+    Node { kind, origin }
+}
+
+/// Returns a copy of `node` with its variables that are free above `cutoff` abstractions
+/// increased by `by`.
+fn shifted(node: &Node, by: usize, cutoff: usize) -> Node {
+    if by == 0 { return node.clone() }
+
+    let kind = match node.kind {
+        Kind::Var(i) if i > cutoff => Kind::Var(i + by),
+        Kind::Var(i) => Kind::Var(i),
+        Kind::Abs(ref body) => Kind::Abs(Box::new(shifted(body, by, cutoff + 1))),
+        Kind::App(ref lhs, ref rhs) => {
+            Kind::App(Box::new(shifted(lhs, by, cutoff)), Box::new(shifted(rhs, by, cutoff)))
+        }
+    };
+
+    Node { kind, origin: node.origin }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const REVERSE: &[u8] = b"0001011001000110100000000001011100111110111100001011011110110000010";
+
+    #[test]
+    fn fully_covered_programs() {
+        let id = coverage(b"0010", Input::Nothing, 0).unwrap();
+        assert_eq!(id, Coverage { bits: 4, steps: 0, complete: true, dead: vec![] });
+
+        let reverse = coverage(REVERSE, Input::Bytes(b"ab"), 0).unwrap();
+        assert_eq!(reverse.bits, REVERSE.len());
+        assert!(reverse.complete);
+        assert_eq!(reverse.dead_bits(), 0);
+    }
+
+    #[test]
+    fn dead_code() {
+        // (λλ1) X ((λλ1) Ω I), where `X` = λ1 and `I` = λ1; `X` and `Ω` are discarded
+        let program = b"01 01 000010 0010 01 01 000010 010001101000011010 0010";
+        let report = coverage(program, Input::Nothing, 0).unwrap();
+
+        assert!(report.complete);
+        assert_eq!(report.dead, [10..14, 24..42]);
+        assert_eq!(
+            report.to_string(),
+            "dead: bits 10..14\ndead: bits 24..42\n24 of 46 bits covered in 4 steps"
+        );
+
+        let limited = coverage(program, Input::Nothing, 1).unwrap();
+        assert!(!limited.complete);
+        assert_eq!(limited.steps, 1);
+        assert_eq!(limited.dead_bits(), 40); // only `(λλ1) X` and its `λλ1` were contracted
+    }
+}
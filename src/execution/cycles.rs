@@ -0,0 +1,60 @@
+//! Detection of cycling reductions
+//!
+//! A reduction step is a function of the term it is applied to, so once a term recurs during a
+//! reduction, the steps between its occurrences repeat forever. `Cycles` remembers a window of
+//! the latest terms of a reduction and reports such a recurrence, which catches redexes that
+//! reduce to themselves, like `Ω`, and short cycles of terms; reductions that diverge by growing
+//! the term, like unfolding a fixed point, are not detected.
+
+use lambda_calculus::*;
+use std::collections::VecDeque;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+/// The latest terms of a reduction.
+pub struct Cycles {
+    window: usize,
+    /// the remembered terms with their hashes, the latest last
+    recent: VecDeque<(u64, Term)>
+}
+
+impl Cycles {
+    /// Creates a window of the given number of latest terms.
+    pub fn new(window: usize) -> Self {
+        Cycles { window, recent: VecDeque::with_capacity(window) }
+    }
+
+    /// Records the next term of the reduction; returns the number of steps since it last
+    /// occurred, if it is one of the remembered terms.
+    pub fn record(&mut self, term: &Term) -> Option<usize> {
+        let mut hasher = DefaultHasher::new();
+        term.hash(&mut hasher);
+        let hash = hasher.finish();
+
+        let period = self.recent.iter().rev()
+            .position(|&(h, ref t)| h == hash && t == term)
+            .map(|distance| distance + 1);
+
+        if self.recent.len() == self.window { self.recent.pop_front(); }
+        self.recent.push_back((hash, term.clone()));
+        period
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use lambda_calculus::combinators::{I, O};
+
+    #[test]
+    fn periods() {
+        let mut cycles = Cycles::new(2);
+
+        assert_eq!(cycles.record(&O()), None);
+        assert_eq!(cycles.record(&O()), Some(1));
+        assert_eq!(cycles.record(&I()), None);
+        assert_eq!(cycles.record(&O()), Some(2));
+        assert_eq!(cycles.record(&Var(1)), None);
+        assert_eq!(cycles.record(&I()), None); // outside of the window
+    }
+}
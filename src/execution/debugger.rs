@@ -0,0 +1,185 @@
+//! Breakpoints
+//!
+//! A `Debugger` reduces a term in normal order like `run` does, but pauses before contracting a
+//! redex that matches one of its breakpoints, e.g. an application of a particular combinator of
+//! the debugged program; while it is paused, the term and the location of the redex can be
+//! inspected before the reduction is resumed.
+
+use lambda_calculus::*;
+use execution::explain::{next_redex, Branch};
+use execution::{prepare, Error, Input};
+
+/// The reason a `Debugger` stopped reducing.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Stop {
+    /// the next redex matches the breakpoint with the given name, at the given location
+    Breakpoint(String, Vec<Branch>),
+    /// the term is in normal form
+    NormalForm,
+    /// the allowed number of reduction steps was performed
+    StepLimit
+}
+
+/// A condition on the abstraction and the argument of a redex.
+type Condition = Box<dyn Fn(&Term, &Term) -> bool>;
+
+/// A normal-order reduction that pauses at breakpoints.
+///
+/// # Example
+/// ```
+/// use blc::execution::debugger::{Debugger, Stop};
+/// use blc::execution::explain::Branch;
+/// use blc::execution::Input::Bytes;
+/// use blc::encoding::binary::from_bits;
+///
+/// let twice = b"01 0010 0010"; // (λ1)(λ1), applied to the input
+/// let mut debugger = Debugger::from_program(twice, Bytes(b"")).unwrap();
+/// debugger.break_on("identity", from_bits(b"0010").unwrap());
+///
+/// assert_eq!(debugger.resume(0), Stop::Breakpoint("identity".into(), vec![Branch::Function]));
+/// assert_eq!(debugger.resume(0), Stop::Breakpoint("identity".into(), vec![]));
+/// assert_eq!(debugger.resume(0), Stop::NormalForm);
+/// assert_eq!(debugger.steps(), 2);
+/// ```
+pub struct Debugger {
+    term: Term,
+    breakpoints: Vec<(String, Condition)>,
+    steps: usize,
+    /// whether the reduction is paused at a breakpoint, which resuming it steps past
+    paused: bool
+}
+
+impl Debugger {
+    /// Starts debugging the reduction of `term`.
+    pub fn new(term: Term) -> Self {
+        Debugger { term, breakpoints: Vec::new(), steps: 0, paused: false }
+    }
+
+    /// Starts debugging the execution of a binary lambda calculus program applied to `input`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Error` if the program or the argument are not valid BLC.
+    pub fn from_program(blc_program: &[u8], input: Input) -> Result<Self, Error> {
+        Ok(Debugger::new(prepare(blc_program, input)?))
+    }
+
+    /// Adds a breakpoint pausing the reduction before `abstraction` is applied to an argument.
+    pub fn break_on(&mut self, name: &str, abstraction: Term) -> &mut Self {
+        self.break_when(name, move |candidate, _| *candidate == abstraction)
+    }
+
+    /// Adds a breakpoint pausing the reduction before a redex whose abstraction and argument
+    /// satisfy `condition`.
+    pub fn break_when<C>(&mut self, name: &str, condition: C) -> &mut Self
+        where C: Fn(&Term, &Term) -> bool + 'static
+    {
+        self.breakpoints.push((name.into(), Box::new(condition)));
+        self
+    }
+
+    /// Removes all the breakpoints.
+    pub fn clear_breakpoints(&mut self) {
+        self.breakpoints.clear();
+    }
+
+    /// Reduces the term until the next redex matches a breakpoint, the normal form is reached or
+    /// `limit` steps are performed (`0` means no limit). If the reduction is paused at a
+    /// breakpoint, the redex it paused before is contracted first.
+    pub fn resume(&mut self, limit: usize) -> Stop {
+        let mut performed = 0;
+
+        loop {
+            let location = match next_redex(&self.term) {
+                Some(location) => location,
+                None => return Stop::NormalForm
+            };
+
+            if !self.paused {
+                if let Some(name) = self.matching(&location) {
+                    self.paused = true;
+                    return Stop::Breakpoint(name, location)
+                }
+            }
+            if limit != 0 && performed == limit { return Stop::StepLimit }
+
+            self.step();
+            performed += 1;
+        }
+    }
+
+    /// Performs a single reduction step regardless of the breakpoints; returns `false` if there
+    /// was nothing left to reduce.
+    pub fn step(&mut self) -> bool {
+        self.paused = false;
+        let reduced = self.term.reduce(NOR, 1) == 1;
+        if reduced { self.steps += 1 }
+        reduced
+    }
+
+    /// Returns the current term.
+    pub fn term(&self) -> &Term { &self.term }
+
+    /// Consumes the `Debugger`, returning the current term.
+    pub fn into_term(self) -> Term { self.term }
+
+    /// Returns the number of steps performed so far.
+    pub fn steps(&self) -> usize { self.steps }
+
+    /// Returns the name of the first breakpoint matching the redex at `location`.
+    fn matching(&self, location: &[Branch]) -> Option<String> {
+        let (abstraction, argument) = match *subterm(&self.term, location) {
+            App(ref lhs, ref rhs) => (lhs, rhs),
+            _ => return None
+        };
+
+        self.breakpoints.iter()
+            .find(|&(_, condition)| condition(abstraction, argument))
+            .map(|(name, _)| name.clone())
+    }
+}
+
+fn subterm<'a>(mut term: &'a Term, location: &[Branch]) -> &'a Term {
+    for branch in location {
+        term = match (term, *branch) {
+            (Abs(body), Branch::Body) => body,
+            (App(lhs, _), Branch::Function) => lhs,
+            (App(_, rhs), Branch::Argument) => rhs,
+            _ => unreachable!() // safe - the location was found in this term
+        }
+    }
+    term
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use lambda_calculus::combinators::{I, K, O};
+
+    #[test]
+    fn named_combinators() {
+        let mut debugger = Debugger::new(abs(app!(K(), Var(1), app(I(), O()))));
+        debugger.break_on("K", K()).break_on("I", I());
+        let location = vec![Branch::Body, Branch::Function];
+
+        assert_eq!(debugger.resume(0), Stop::Breakpoint("K".into(), location));
+        assert_eq!(debugger.term(), &abs(app!(K(), Var(1), app(I(), O()))));
+        assert_eq!(debugger.resume(0), Stop::NormalForm); // `I Ω` is discarded
+        assert_eq!(debugger.into_term(), abs(Var(1)));
+    }
+
+    #[test]
+    fn conditions_and_limits() {
+        let mut debugger = Debugger::new(O());
+        debugger.break_when("discarding", |abstraction, _| *abstraction == K());
+
+        assert_eq!(debugger.resume(5), Stop::StepLimit);
+        assert_eq!(debugger.steps(), 5);
+
+        debugger.clear_breakpoints();
+        debugger.break_when("any", |_, _| true);
+        assert_eq!(debugger.resume(5), Stop::Breakpoint("any".into(), vec![]));
+        assert!(debugger.step());
+        assert_eq!(debugger.steps(), 6);
+    }
+}
@@ -0,0 +1,619 @@
+//! User-defined decoders of program outputs
+//!
+//! `run` decodes the normal form of a program as a string of bytes, which is not what every
+//! program produces. A `DecoderRegistry` holds decoders that turn normal forms into values of a
+//! domain-specific type, each installed for a `Shape` of output, as told by `classify`, or for
+//! the outputs accepted by a predicate; `DecoderRegistry::run` executes a program and decodes its
+//! output with the first decoder that applies to it.
+//!
+//! More generally, `run_decoded` executes a program and decodes its output with any
+//! `OutputDecoder`: an `IoMode`, which decodes it like `run_with` does, a `DecoderRegistry`, a
+//! function of the output term or one of the decoders of common data, like `Numeral`, `Boolean`
+//! or `BytesOrTerm`.
+
+use lambda_calculus::*;
+use lambda_calculus::data::boolean::{tru, fls};
+use execution::{apply_input_in, decode_strict, evaluate, ClosedTerm, Error, Input, IoMode, Options};
+use pretty::pretty;
+
+/// A conversion of the output of a program to a value.
+///
+/// # Example
+/// ```
+/// use blc::execution::decoders::OutputDecoder;
+/// use blc::execution::{Error, IoMode};
+/// use blc::encoding::lambda::encode;
+/// use blc::encoding::binary::to_bits;
+///
+/// let size = |output| Ok::<_, Error>(to_bits(&output).len());
+///
+/// assert_eq!(IoMode::Bytes.decode_output(encode(b"a")), Ok("a".into()));
+/// assert_eq!(size.decode_output(encode(b"")), Ok(6));
+/// ```
+pub trait OutputDecoder {
+    /// The type of the decoded values.
+    type Output;
+
+    /// Decodes the output of a program, evaluated as far as the `Options` of its execution
+    /// require.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Error` if the output can't be decoded.
+    fn decode_output(&self, output: Term) -> Result<Self::Output, Error>;
+}
+
+impl OutputDecoder for IoMode {
+    type Output = String;
+
+    fn decode_output(&self, output: Term) -> Result<String, Error> {
+        self.decode(output)
+    }
+}
+
+impl<T> OutputDecoder for DecoderRegistry<T> {
+    type Output = T;
+
+    fn decode_output(&self, output: Term) -> Result<T, Error> {
+        self.decode(&output).ok_or(Error::NoDecoder)
+    }
+}
+
+impl<T, F> OutputDecoder for F where F: Fn(Term) -> Result<T, Error> {
+    type Output = T;
+
+    fn decode_output(&self, output: Term) -> Result<T, Error> {
+        self(output)
+    }
+}
+
+/// A decoder of Church numerals, `λλ2(2(...(2 1)))`, as `u64`s.
+///
+/// # Example
+/// ```
+/// use blc::execution::decoders::{run_decoded, Numeral};
+/// use blc::execution::{Input, Options};
+///
+/// let succ = b"000000011100101111011010"; // λλλ2(3 2 1)
+/// let two = Input::Bits(b"0000011100111010"); // λλ2(2 1)
+///
+/// assert_eq!(run_decoded(succ, two, Options::default(), &Numeral), Ok(3));
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct Numeral;
+
+impl OutputDecoder for Numeral {
+    type Output = u64;
+
+    fn decode_output(&self, output: Term) -> Result<u64, Error> {
+        numeral(&output).ok_or_else(|| unexpected(Shape::Numeral, &output))
+    }
+}
+
+/// A decoder of Church booleans, `λλ2` and `λλ1`, as `bool`s.
+///
+/// # Example
+/// ```
+/// use blc::execution::decoders::{run_decoded, Boolean};
+/// use blc::execution::{Input, Options};
+///
+/// let not = b"000101100000100000110"; // λ1(λλ1)(λλ2)
+/// let tru = Input::Bits(b"0000110");
+///
+/// assert_eq!(run_decoded(not, tru, Options::default(), &Boolean), Ok(false));
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct Boolean;
+
+impl OutputDecoder for Boolean {
+    type Output = bool;
+
+    fn decode_output(&self, output: Term) -> Result<bool, Error> {
+        match output {
+            ref output if *output == tru() => Ok(true),
+            ref output if *output == fls() => Ok(false),
+            output => Err(unexpected(Shape::Boolean, &output))
+        }
+    }
+}
+
+/// A decoder of pairs, `λ1 a b`, decoding their elements with the given decoders.
+///
+/// # Example
+/// ```
+/// use blc::execution::decoders::{OutputDecoder, Boolean, Numeral, Pair};
+/// use blc::encoding::binary::from_bits;
+///
+/// let pair = from_bits(b"00 01 01 10 0000110 00000111010").unwrap(); // λ1(λλ2)(λλ2 1)
+///
+/// assert_eq!(Pair(Boolean, Numeral).decode_output(pair), Ok((true, 1)));
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct Pair<A, B>(pub A, pub B);
+
+impl<A: OutputDecoder, B: OutputDecoder> OutputDecoder for Pair<A, B> {
+    type Output = (A::Output, B::Output);
+
+    fn decode_output(&self, output: Term) -> Result<Self::Output, Error> {
+        let (first, second) = uncell(output).map_err(|output| unexpected(Shape::Pair, &output))?;
+
+        Ok((self.0.decode_output(first)?, self.1.decode_output(second)?))
+    }
+}
+
+/// A decoder of lists like the ones `run` decodes as strings, `λ1 h t` ending with `λλ1`,
+/// decoding their elements with the given decoder.
+///
+/// # Example
+/// ```
+/// use blc::execution::decoders::{run_decoded, List, Numeral};
+/// use blc::execution::{Input, Options};
+///
+/// let identity = b"0010";
+/// let two_three = Input::Bits(b"00 01 01 10 0000 01 110 01 110 10 00 01 01 10 0000 01 110 01 110 \
+///     01 110 10 000010"); // the list of the numerals 2 and 3
+/// let output = run_decoded(identity, two_three, Options::default(), &List(Numeral));
+///
+/// assert_eq!(output, Ok(vec![2, 3]));
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct List<D>(pub D);
+
+impl<D: OutputDecoder> OutputDecoder for List<D> {
+    type Output = Vec<D::Output>;
+
+    fn decode_output(&self, mut output: Term) -> Result<Self::Output, Error> {
+        let mut elements = Vec::new();
+
+        while output != fls() {
+            let (head, tail) = uncell(output).map_err(|output| unexpected(Shape::List, &output))?;
+            elements.push(self.0.decode_output(head)?);
+            output = tail;
+        }
+
+        Ok(elements)
+    }
+}
+
+/// A decoder of Church lists, their own right folds, `λλ1 h (t 1 2)` ending with `λλ2`, in normal
+/// form, decoding their elements with the given decoder; the elements have to be closed.
+///
+/// # Example
+/// ```
+/// extern crate blc;
+/// extern crate lambda_calculus;
+///
+/// use blc::execution::decoders::{ChurchList, Numeral, OutputDecoder};
+/// use lambda_calculus::*;
+///
+/// let list = vec![2.into_church(), 3.into_church()].into_church();
+///
+/// assert_eq!(ChurchList(Numeral).decode_output(list), Ok(vec![2, 3]));
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct ChurchList<D>(pub D);
+
+impl<D: OutputDecoder> OutputDecoder for ChurchList<D> {
+    type Output = Vec<D::Output>;
+
+    fn decode_output(&self, output: Term) -> Result<Self::Output, Error> {
+        let not_a_list = |output: &Term| unexpected(Shape::List, output);
+        let mut body = match output {
+            Abs(body) => match *body {
+                Abs(body) => *body,
+                body => return Err(not_a_list(&abs(body)))
+            },
+            output => return Err(not_a_list(&output))
+        };
+        let mut elements = Vec::new();
+
+        while body != Var(2) {
+            let (head, tail) = match body {
+                App(lhs, tail) => match *lhs {
+                    App(var, head) if *var == Var(1) && head.is_supercombinator() => (*head, *tail),
+                    lhs => return Err(not_a_list(&abs(abs(app(lhs, *tail)))))
+                },
+                body => return Err(not_a_list(&abs(abs(body))))
+            };
+            elements.push(self.0.decode_output(head)?);
+            body = tail;
+        }
+
+        Ok(elements)
+    }
+}
+
+/// The output of a program, decoded by `BytesOrTerm`.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum RunOutput {
+    /// a list of bytes
+    Bytes(Vec<u8>),
+    /// anything else, printed by `pretty::pretty`
+    Term(String)
+}
+
+/// A decoder of lists of bytes that falls back to printing any other output term; unlike the
+/// decoding of `run`, it never mixes decoded bytes with printed terms.
+///
+/// # Example
+/// ```
+/// use blc::execution::decoders::{run_decoded, BytesOrTerm, RunOutput};
+/// use blc::execution::{Input, Options};
+///
+/// let reverse_blc = b"0001011001000110100000000001011100111110111100001011011110110000010";
+/// let s = b"00 000000010111101001110100"; // λ_.λλλ31(21)
+/// let run = |program| run_decoded(program, Input::Bytes(b"ab"), Options::default(), &BytesOrTerm);
+///
+/// assert_eq!(run(reverse_blc), Ok(RunOutput::Bytes(b"ba".to_vec())));
+/// assert_eq!(run(s), Ok(RunOutput::Term("S".into())));
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct BytesOrTerm;
+
+impl OutputDecoder for BytesOrTerm {
+    type Output = RunOutput;
+
+    fn decode_output(&self, output: Term) -> Result<RunOutput, Error> {
+        Ok(match decode_strict(&output) {
+            Ok(bytes) => RunOutput::Bytes(bytes),
+            Err(_) => RunOutput::Term(pretty(&output, false))
+        })
+    }
+}
+
+/// Splits `λ1 a b` into `a` and `b`; returns the term itself if it is not a pair.
+fn uncell(term: Term) -> Result<(Term, Term), Term> {
+    if cell(&term).is_none() { return Err(term) }
+
+    match term {
+        Abs(body) => match *body {
+            App(lhs, second) => match *lhs {
+                App(_, first) => Ok((*first, *second)),
+                _ => unreachable!() // safe - checked above
+            },
+            _ => unreachable!() // safe - checked above
+        },
+        _ => unreachable!() // safe - checked above
+    }
+}
+
+/// Returns the `Error` of an output of another `Shape` than the `expected` one.
+fn unexpected(expected: Shape, output: &Term) -> Error {
+    Error::UnexpectedShape { expected, found: classify(output) }
+}
+
+/// Executes a binary lambda calculus program like `run_with` does, but decodes its output with
+/// the given `OutputDecoder`; the `IoMode` of the `options` only applies to the input.
+///
+/// # Example
+/// ```
+/// use blc::execution::decoders::run_decoded;
+/// use blc::execution::{Error, Input, Options};
+/// use blc::encoding::binary::to_bits;
+///
+/// let bits = |output| Ok::<_, Error>(to_bits(&output));
+/// let output = run_decoded(b"0010", Input::Bits(b"0000110"), Options::default(), &bits);
+///
+/// assert_eq!(output, Ok(b"0000110".to_vec()));
+/// ```
+/// # Errors
+///
+/// Returns the same `Error`s as `run_with`, except for decoding the output, which fails with the
+/// `Error`s of the decoder.
+pub fn run_decoded<D: OutputDecoder>(
+    blc_program: &[u8],
+    input: Input,
+    options: Options,
+    decoder: &D
+) -> Result<D::Output, Error> {
+    let program = ClosedTerm::from_bits(blc_program)?.into_term();
+    let mut calculation = apply_input_in(program, input, options.io_mode)?;
+    evaluate(&mut calculation, options)?;
+
+    decoder.decode_output(calculation)
+}
+
+/// The general shape of an output term.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum Shape {
+    /// a Church boolean; `λλ1` is classified as `false` rather than as `0` or the empty list
+    Boolean,
+    /// a Church numeral greater than 0
+    Numeral,
+    /// a non-empty list of bytes, i.e. of lists of 8 booleans
+    Bytes,
+    /// a non-empty list of booleans
+    Bits,
+    /// a non-empty list of anything else
+    List,
+    /// a pair, `λ1 a b`, that is not a list cell
+    Pair,
+    /// any other term
+    Term
+}
+
+/// Tells the `Shape` of a term in normal form.
+///
+/// # Example
+/// ```
+/// use blc::encoding::binary::from_bits;
+/// use blc::encoding::lambda::encode;
+/// use blc::execution::decoders::{classify, Shape};
+///
+/// assert_eq!(classify(&encode(b"a")), Shape::Bytes);
+/// assert_eq!(classify(&from_bits(b"0000110").unwrap()), Shape::Boolean); // λλ2
+/// assert_eq!(classify(&from_bits(b"00000111010").unwrap()), Shape::Numeral); // λλ21
+/// ```
+pub fn classify(term: &Term) -> Shape {
+    if *term == tru() || *term == fls() { return Shape::Boolean }
+    if is_numeral(term) { return Shape::Numeral }
+
+    let elements = match list(term) {
+        Some(ref elements) if !elements.is_empty() => elements.clone(),
+        _ if cell(term).is_some() => return Shape::Pair,
+        _ => return Shape::Term
+    };
+    let is_bits = |term: &Term| {
+        list(term).is_some_and(|bits| bits.len() == 8 && bits.iter().all(|bit| is_boolean(bit)))
+    };
+
+    if elements.iter().all(|element| is_bits(element)) {
+        Shape::Bytes
+    } else if elements.iter().all(|element| is_boolean(element)) {
+        Shape::Bits
+    } else {
+        Shape::List
+    }
+}
+
+type Decoder<T> = Box<dyn Fn(&Term) -> T>;
+
+enum Key {
+    Shape(Shape),
+    Predicate(Box<dyn Fn(&Term) -> bool>)
+}
+
+/// A collection of output decoders producing values of type `T`.
+///
+/// # Example
+/// ```
+/// use blc::execution::decoders::{DecoderRegistry, Shape};
+/// use blc::execution::{Input, Options};
+///
+/// #[derive(Debug, PartialEq)]
+/// enum Value { Flag(bool), Other }
+///
+/// let mut registry = DecoderRegistry::new();
+/// registry
+///     .register_shape(Shape::Boolean, |term| Value::Flag(format!("{:?}", term) == "λλ2"))
+///     .register(|_| true, |_| Value::Other);
+///
+/// let not = b"000101100000100000110"; // λ1(λλ1)(λλ2), applied to true
+/// let input = Input::Bits(b"0000110");
+///
+/// assert_eq!(registry.run(not, input, Options::default()), Ok(Value::Flag(false)));
+/// assert_eq!(registry.run(b"0010", Input::Nothing, Options::default()), Ok(Value::Other));
+/// ```
+pub struct DecoderRegistry<T> {
+    decoders: Vec<(Key, Decoder<T>)>
+}
+
+impl<T> DecoderRegistry<T> {
+    /// Creates a registry without any decoders.
+    pub fn new() -> Self {
+        DecoderRegistry { decoders: Vec::new() }
+    }
+
+    /// Installs a decoder for the outputs of the given `Shape`.
+    pub fn register_shape<D>(&mut self, shape: Shape, decoder: D) -> &mut Self
+        where D: Fn(&Term) -> T + 'static
+    {
+        self.decoders.push((Key::Shape(shape), Box::new(decoder)));
+        self
+    }
+
+    /// Installs a decoder for the outputs accepted by `predicate`.
+    pub fn register<P, D>(&mut self, predicate: P, decoder: D) -> &mut Self
+        where P: Fn(&Term) -> bool + 'static, D: Fn(&Term) -> T + 'static
+    {
+        self.decoders.push((Key::Predicate(Box::new(predicate)), Box::new(decoder)));
+        self
+    }
+
+    /// Returns the number of installed decoders.
+    pub fn len(&self) -> usize { self.decoders.len() }
+
+    /// Returns `true` if no decoders are installed.
+    pub fn is_empty(&self) -> bool { self.decoders.is_empty() }
+
+    /// Decodes `term` with the first installed decoder that applies to it; decoders are tried in
+    /// the order they were installed in.
+    pub fn decode(&self, term: &Term) -> Option<T> {
+        let shape = classify(term);
+
+        self.decoders.iter()
+            .find(|(key, _)| match *key {
+                Key::Shape(s) => s == shape,
+                Key::Predicate(ref predicate) => predicate(term)
+            })
+            .map(|(_, decoder)| decoder(term))
+    }
+
+    /// Executes a binary lambda calculus program like `run_with` does, but decodes its output with
+    /// the installed decoders.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same `Error`s as `run_with` and `Error::NoDecoder` if none of the decoders
+    /// applies to the output.
+    pub fn run(&self, blc_program: &[u8], input: Input, options: Options) -> Result<T, Error> {
+        run_decoded(blc_program, input, options, self)
+    }
+}
+
+impl<T> Default for DecoderRegistry<T> {
+    fn default() -> Self { DecoderRegistry::new() }
+}
+
+fn is_boolean(term: &Term) -> bool {
+    *term == tru() || *term == fls()
+}
+
+fn is_numeral(term: &Term) -> bool {
+    numeral(term).is_some()
+}
+
+/// Returns the value of a Church numeral in normal form, or `None` if `term` is not one.
+///
+/// # Example
+/// ```
+/// extern crate blc;
+/// extern crate lambda_calculus;
+///
+/// use blc::execution::decoders::numeral;
+/// use lambda_calculus::*;
+///
+/// assert_eq!(numeral(&3.into_church()), Some(3));
+/// assert_eq!(numeral(&abs(Var(1))), None);
+/// ```
+pub fn numeral(term: &Term) -> Option<u64> {
+    let mut body = term.unabs_ref().and_then(|t| t.unabs_ref()).ok()?;
+    let mut n = 0;
+
+    while let App(ref lhs, ref rhs) = *body {
+        if **lhs != Var(2) { return None }
+        n += 1;
+        body = rhs;
+    }
+
+    if *body == Var(1) { Some(n) } else { None }
+}
+
+fn list(mut term: &Term) -> Option<Vec<&Term>> {
+    let mut elements = Vec::new();
+
+    while *term != fls() {
+        let (head, tail) = cell(term)?;
+        elements.push(head);
+        term = tail;
+    }
+
+    Some(elements)
+}
+
+/// Returns the elements of the pair `λ1 a b`.
+fn cell(term: &Term) -> Option<(&Term, &Term)> {
+    let (pair, second) = term.unabs_ref().and_then(|t| t.unapp_ref()).ok()?;
+    let (var, first) = pair.unapp_ref().ok()?;
+
+    if *var == Var(1) { Some((first, second)) } else { None }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use encoding::lambda::{decode, encode};
+    use pair_list::{head_ref, listify_terms};
+    use lambda_calculus::combinators::{I, K};
+
+    #[test]
+    fn shapes() {
+        assert_eq!(classify(&fls()), Shape::Boolean);
+        assert_eq!(classify(&3.into_church()), Shape::Numeral);
+        assert_eq!(classify(&encode(b"\x00")), Shape::Bytes);
+        assert_eq!(classify(&listify_terms(vec![tru(), fls()])), Shape::Bits);
+        assert_eq!(classify(&listify_terms(vec![I(), K()])), Shape::List);
+        assert_eq!(classify(&abs(app!(Var(1), I(), K()))), Shape::Pair);
+        assert_eq!(classify(&I()), Shape::Term);
+    }
+
+    #[test]
+    fn decoders_are_tried_in_order() {
+        let mut registry = DecoderRegistry::new();
+        registry
+            .register(|term| *term == I(), |_| "identity".to_string())
+            .register_shape(Shape::Bytes, |term| decode(term.clone()).unwrap())
+            .register_shape(Shape::Term, |term| format!("{:?}", term));
+
+        assert_eq!(registry.len(), 3);
+        assert_eq!(registry.decode(&I()), Some("identity".into()));
+        assert_eq!(registry.decode(&K()), None); // a boolean
+        assert_eq!(registry.decode(&encode(b"ok")), Some("ok".into()));
+        assert_eq!(registry.decode(&app(K(), I())), Some("(λλ2)(λ1)".into()));
+    }
+
+    #[test]
+    fn undecodable_outputs() {
+        let registry = DecoderRegistry::<()>::default();
+
+        assert!(registry.is_empty());
+        let output = registry.run(b"0010", Input::Nothing, Options::default());
+
+        assert_eq!(output, Err(Error::NoDecoder));
+    }
+
+    #[test]
+    fn output_decoders() {
+        let reverse = b"0001011001000110100000000001011100111110111100001011011110110000010";
+        let mut registry = DecoderRegistry::new();
+        registry.register_shape(Shape::Bytes, |term| decode(term.clone()).unwrap().len());
+        let (input, options) = (Input::Bytes(b"abc"), Options::default());
+        let failing = |_| Err::<(), _>(Error::NoDecoder);
+
+        assert_eq!(run_decoded(reverse, input, options, &registry), Ok(3));
+        assert_eq!(run_decoded(reverse, input, options, &failing), Err(Error::NoDecoder));
+
+        let bits = Options { io_mode: IoMode::Bits, ..Options::default() };
+        let output = run_decoded(reverse, Input::Bytes(b"01"), bits, &IoMode::Bits);
+        assert_eq!(output, Ok("10".into()));
+    }
+
+    #[test]
+    fn numerals() {
+        assert_eq!(Numeral.decode_output(fls()), Ok(0));
+        assert_eq!(Numeral.decode_output(10.into_church()), Ok(10));
+
+        let bytes = Error::UnexpectedShape { expected: Shape::Numeral, found: Shape::Bytes };
+        assert_eq!(Numeral.decode_output(encode(b"a")), Err(bytes));
+    }
+
+    #[test]
+    fn booleans() {
+        assert_eq!(Boolean.decode_output(tru()), Ok(true));
+        assert_eq!(Boolean.decode_output(fls()), Ok(false));
+
+        let numeral = Error::UnexpectedShape { expected: Shape::Boolean, found: Shape::Numeral };
+        assert_eq!(Boolean.decode_output(1.into_church()), Err(numeral));
+    }
+
+    #[test]
+    fn structures() {
+        let numerals = listify_terms(vec![2.into_church(), 3.into_church(), 5.into_church()]);
+        let pair = abs(app!(Var(1), tru(), numerals.clone()));
+        let church = vec![1.into_church(), tru()].into_church();
+        let unexpected = |expected, found| Error::UnexpectedShape { expected, found };
+
+        assert_eq!(List(Numeral).decode_output(numerals), Ok(vec![2, 3, 5]));
+        assert_eq!(Pair(Boolean, List(Numeral)).decode_output(pair), Ok((true, vec![2, 3, 5])));
+        assert_eq!(List(Numeral).decode_output(fls()), Ok(vec![]));
+        let error = List(Numeral).decode_output(I()).unwrap_err();
+        assert_eq!(error, unexpected(Shape::List, Shape::Term));
+        let error = Pair(Numeral, Numeral).decode_output(K()).unwrap_err();
+        assert_eq!(error, unexpected(Shape::Pair, Shape::Boolean));
+
+        assert_eq!(ChurchList(Numeral).decode_output(tru()), Ok(vec![]));
+        let error = ChurchList(Numeral).decode_output(church).unwrap_err();
+        assert_eq!(error, unexpected(Shape::Numeral, Shape::Boolean));
+    }
+
+    #[test]
+    fn fallback_to_terms() {
+        let a = head_ref(&encode(b"a")).unwrap().clone();
+        let mixed = listify_terms(vec![a, I()]);
+        let printed = "[[K, nil, nil, K, K, K, K, nil], I]".to_string();
+
+        assert!(decode(mixed.clone()).unwrap().starts_with("a("));
+        assert_eq!(BytesOrTerm.decode_output(mixed), Ok(RunOutput::Term(printed)));
+        assert_eq!(BytesOrTerm.decode_output(fls()), Ok(RunOutput::Bytes(vec![])));
+    }
+}
@@ -0,0 +1,169 @@
+//! Reduction with delta rules
+//!
+//! Arithmetic on Church numerals takes many β-reductions, even though the result could be computed
+//! natively right away. A `DeltaRule` recognizes such subterms and replaces them with their
+//! results; `Delta` reduces in normal order, but gives its rules a chance to rewrite every
+//! application it passes before it looks for a redex in it. Rules are expected to only replace
+//! terms with β-equivalent ones, so the normal forms don't change, only how fast they are reached.
+
+use lambda_calculus::*;
+use lambda_calculus::data::num::church;
+use execution::decoders::numeral;
+use execution::machine::Machine;
+use std::convert::TryFrom;
+
+/// The maximum number of reductions `NumeralOperator` spends on evaluating each operand.
+const OPERAND_FUEL: usize = 1_000;
+
+/// A recognizer of subterms along with a shortcut computing their normal forms.
+pub trait DeltaRule {
+    /// Returns a replacement of `term`, β-equivalent to it, if the rule applies to it.
+    fn rewrite(&self, term: &Term) -> Option<Term>;
+}
+
+impl<F: Fn(&Term) -> Option<Term>> DeltaRule for F {
+    fn rewrite(&self, term: &Term) -> Option<Term> { self(term) }
+}
+
+/// A rule replacing an operator applied to two Church numerals with the numeral it computes.
+///
+/// The operands don't have to be numerals already: each of them is given a bounded number of
+/// reductions to become one.
+pub struct NumeralOperator {
+    operator: Term,
+    operation: fn(u64, u64) -> Option<u64>
+}
+
+impl NumeralOperator {
+    /// Creates a rule for `operator`, which `operation` computes natively; `operation` returns
+    /// `None` if the result can't be computed, e.g. because it would overflow.
+    pub fn new(operator: Term, operation: fn(u64, u64) -> Option<u64>) -> Self {
+        NumeralOperator { operator, operation }
+    }
+
+    /// Creates a rule for `lambda_calculus::data::num::church::add`.
+    pub fn addition() -> Self { NumeralOperator::new(church::add(), u64::checked_add) }
+
+    /// Creates a rule for `lambda_calculus::data::num::church::mul`.
+    pub fn multiplication() -> Self { NumeralOperator::new(church::mul(), u64::checked_mul) }
+}
+
+impl DeltaRule for NumeralOperator {
+    fn rewrite(&self, term: &Term) -> Option<Term> {
+        let (lhs, rhs) = term.unapp_ref().ok()?;
+        let (operator, first) = lhs.unapp_ref().ok()?;
+        if *operator != self.operator { return None }
+
+        let (first, second) = (operand(first)?, operand(rhs)?);
+        let result = (self.operation)(first, second)?;
+
+        Some(usize::try_from(result).ok()?.into_church())
+    }
+}
+
+/// Evaluates a closed term with the operand fuel; returns its value if it is a Church numeral.
+fn operand(term: &Term) -> Option<u64> {
+    if !term.is_supercombinator() { return None }
+
+    let mut evaluated = term.clone();
+    if evaluated.reduce(NOR, OPERAND_FUEL) == OPERAND_FUEL { return None }
+    numeral(&evaluated)
+}
+
+/// A normal-order evaluator applying delta rules.
+///
+/// Every rewrite by a rule counts as a single reduction.
+///
+/// # Example
+/// ```
+/// extern crate blc;
+/// extern crate lambda_calculus;
+///
+/// use blc::execution::machine::{Machine, BigStep};
+/// use blc::execution::delta::{Delta, NumeralOperator};
+/// use lambda_calculus::*;
+/// use lambda_calculus::data::num::church::{add, mul};
+///
+/// let mut delta = Delta::new();
+/// delta.register(NumeralOperator::addition());
+/// delta.register(NumeralOperator::multiplication());
+///
+/// let term = app!(mul(), app!(add(), 20.into_church(), 1.into_church()), 2.into_church());
+///
+/// assert_eq!(delta.normalize(term.clone()), 42.into_church());
+/// assert!(delta.reduce(&mut term.clone(), 0) < BigStep.reduce(&mut term.clone(), 0));
+/// ```
+#[derive(Default)]
+pub struct Delta {
+    rules: Vec<Box<dyn DeltaRule>>
+}
+
+impl Delta {
+    /// Creates an evaluator without any rules.
+    pub fn new() -> Self { Delta::default() }
+
+    /// Adds a rule; rules are tried in the order they are registered in.
+    pub fn register<R: DeltaRule + 'static>(&mut self, rule: R) {
+        self.rules.push(Box::new(rule))
+    }
+
+    /// Rewrites the leftmost outermost application a rule applies to or contracts the leftmost
+    /// outermost redex, whichever comes first; returns `false` if there is neither.
+    fn step(&self, term: &mut Term) -> bool {
+        if let App(..) = *term {
+            if let Some(rewritten) = self.rules.iter().find_map(|rule| rule.rewrite(term)) {
+                *term = rewritten;
+                return true
+            }
+            if let App(ref lhs, _) = *term {
+                if let Abs(_) = **lhs { return term.reduce(NOR, 1) == 1 }
+            }
+        }
+
+        match *term {
+            Var(_) => false,
+            Abs(ref mut body) => self.step(body),
+            App(ref mut lhs, ref mut rhs) => self.step(lhs) || self.step(rhs)
+        }
+    }
+}
+
+impl Machine for Delta {
+    fn reduce(&mut self, term: &mut Term, limit: usize) -> usize {
+        let mut count = 0;
+
+        while (limit == 0 || count < limit) && self.step(term) { count += 1 }
+
+        count
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use lambda_calculus::combinators::I;
+    use lambda_calculus::data::num::church::{add, succ};
+
+    #[test]
+    fn rules() {
+        let mut delta = Delta::new();
+        delta.register(NumeralOperator::addition());
+        delta.register(|term: &Term| if *term == app(succ(), 0.into_church()) {
+            Some(1.into_church())
+        } else {
+            None
+        });
+
+        let mut sum = app!(add(), 2.into_church(), 3.into_church());
+        assert_eq!(delta.reduce(&mut sum, 0), 1);
+        assert_eq!(sum, 5.into_church());
+
+        let mut successor = app(I(), app(succ(), 0.into_church()));
+        assert_eq!(delta.reduce(&mut successor, 0), 2);
+        assert_eq!(successor, 1.into_church());
+
+        // an operand that is not a numeral is left to β-reduction
+        let mut open = abs(app!(add(), Var(1), 1.into_church()));
+        assert!(delta.reduce(&mut open, 0) > 1);
+    }
+}
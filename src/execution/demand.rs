@@ -0,0 +1,342 @@
+//! Demand-driven input
+//!
+//! `run_reader` doesn't encode the whole input of a program up front. The unread part of the input
+//! is stood in for by a free variable; whenever the evaluation of the output gets stuck while the
+//! variable is still around, the next chunk of the input, followed by a new such variable, is read
+//! and substituted for it, and the end of the input is substituted as the empty list. Input that
+//! the program never needs is never read, so large files or interactive input can be fed to
+//! programs without encoding all of it first.
+//!
+//! An `Interaction` runs a program the same way, but the input is pushed into it by the caller
+//! and the output is pulled from it as far as it is known, so programs that alternate between
+//! reading some input and writing some output can be talked to; `run_interactive` does that with
+//! a reader and a writer.
+
+use lambda_calculus::*;
+use lambda_calculus::data::boolean::{tru, fls};
+use encoding::lambda::encode;
+use execution::{constructors, evaluate, write_flushed, ClosedTerm, Error, Options};
+use execution::output::decoded_bytes;
+use pair_list::{is_list, uncons};
+use std::io::{self, Read, Write};
+use std::mem;
+
+/// The largest number of bytes read at a time.
+const CHUNK: usize = 4096;
+
+/// Executes a binary lambda calculus program like `run_bytes` does, but reads its input from
+/// `reader` only as far as the program needs it.
+///
+/// # Example
+/// ```
+/// use blc::execution::demand::run_reader;
+/// use std::io::Cursor;
+///
+/// let reverse_blc = b"0001011001000110100000000001011100111110111100001011011110110000010";
+///
+/// assert_eq!(run_reader(&*reverse_blc, Cursor::new(b"herp")), Ok(b"preh".to_vec()));
+/// ```
+/// # Errors
+///
+/// Returns `Error::Io` if the input can't be read, or the same `Error`s as `run_bytes`.
+pub fn run_reader<R: Read>(blc_program: &[u8], mut reader: R) -> Result<Vec<u8>, Error> {
+    let program = ClosedTerm::from_bits(blc_program)?.into_term();
+    let mut calculation = app(program, Var(1));
+    let mut buffer = [0; CHUNK];
+
+    loop {
+        constructors::force(&mut calculation);
+        if calculation.is_supercombinator() { break } // the rest of the input is not needed
+
+        let read = read_chunk(&mut reader, &mut buffer)?;
+        let chunk = if read == 0 { fls() } else { open_list(&buffer[..read]) };
+        substitute(&mut calculation, 0, &chunk);
+    }
+    evaluate(&mut calculation, Options::default())?;
+
+    decoded_bytes(calculation)
+}
+
+/// Executes a closed program with its input read from `reader` only as far as the program needs
+/// it, like `run_reader` does, and writes every byte of its output to `writer` as soon as it is
+/// known, like `run_streaming` does; the output that doesn't depend on the unread input is written
+/// before more of it is read.
+///
+/// # Example
+/// ```
+/// use blc::execution::ClosedTerm;
+/// use blc::execution::demand::run_interactive;
+/// use std::io::Cursor;
+///
+/// let reverse_blc = b"0001011001000110100000000001011100111110111100001011011110110000010";
+/// let reverse = ClosedTerm::from_bits(reverse_blc).unwrap();
+/// let mut output = Vec::new();
+///
+/// assert_eq!(run_interactive(reverse, Cursor::new(b"herp"), &mut output), Ok(()));
+/// assert_eq!(output, b"preh");
+/// ```
+/// # Errors
+///
+/// Returns `Error::Io` if the input can't be read or the output can't be written, or the same
+/// `Error`s as `run_bytes`.
+pub fn run_interactive<R: Read, W: Write>(program: ClosedTerm, mut reader: R, mut writer: W)
+    -> Result<(), Error>
+{
+    let mut interaction = Interaction::from_term(program);
+    let mut buffer = [0; CHUNK];
+
+    loop {
+        write_flushed(&mut writer, &interaction.pull()?)?;
+        if interaction.is_finished() { return Ok(()) }
+
+        match read_chunk(&mut reader, &mut buffer)? {
+            0 => interaction.close(),
+            read => interaction.push(&buffer[..read])
+        }
+    }
+}
+
+/// Reads the next chunk of the input into `buffer`; returns its length, which is `0` at the end
+/// of the input.
+fn read_chunk<R: Read>(reader: &mut R, buffer: &mut [u8]) -> Result<usize, Error> {
+    loop {
+        match reader.read(buffer) {
+            Ok(read) => return Ok(read),
+            Err(ref e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(Error::Io(e.kind()))
+        }
+    }
+}
+
+/// An execution of a program that input is pushed into and output is pulled from in turns.
+///
+/// # Example
+/// ```
+/// use blc::execution::demand::Interaction;
+///
+/// let mut echo = Interaction::new(b"0010").unwrap();
+///
+/// echo.push(b"hi");
+/// assert_eq!(echo.pull(), Ok(b"hi".to_vec()));
+/// echo.push(b"!");
+/// assert_eq!(echo.pull(), Ok(b"!".to_vec()));
+/// echo.close();
+/// assert_eq!(echo.pull(), Ok(vec![]));
+/// assert!(echo.is_finished());
+/// ```
+#[derive(Debug, Clone)]
+pub struct Interaction {
+    rest: Term,
+    written: usize,
+    finished: bool
+}
+
+impl Interaction {
+    /// Starts an execution of a binary lambda calculus program without any input yet.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::InvalidProgram` if the program is not a valid, closed BLC term.
+    pub fn new(blc_program: &[u8]) -> Result<Self, Error> {
+        Ok(Interaction::from_term(ClosedTerm::from_bits(blc_program)?))
+    }
+
+    /// Starts an execution of a closed program without any input yet.
+    pub fn from_term(program: ClosedTerm) -> Self {
+        Interaction { rest: app(program.into_term(), Var(1)), written: 0, finished: false }
+    }
+
+    /// Appends `bytes` to the input of the program; input pushed after `close` is ignored.
+    pub fn push(&mut self, bytes: &[u8]) {
+        if !bytes.is_empty() { substitute(&mut self.rest, 0, &open_list(bytes)) }
+    }
+
+    /// Ends the input of the program.
+    pub fn close(&mut self) {
+        substitute(&mut self.rest, 0, &fls())
+    }
+
+    /// Evaluates the program as far as the input pushed so far allows and returns the bytes of
+    /// the output that became known since the previous call; the rest of the output, if it is
+    /// not a list of bytes, is decoded as a whole once the input is no longer needed, like
+    /// `run_bytes` does.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same `Error`s as `run_bytes`.
+    pub fn pull(&mut self) -> Result<Vec<u8>, Error> {
+        let mut output = Vec::new();
+
+        while !self.finished {
+            self.rest.reduce(CBN, 0);
+            if let Abs(ref mut body) = self.rest { body.reduce(CBN, 0); }
+
+            if let Some((element, tail)) = self.next_element()? {
+                output.extend(element);
+                self.written += 1;
+                self.rest = tail;
+            } else if self.rest.is_supercombinator() {
+                self.finished = true;
+                self.rest.reduce(NOR, 0);
+                let written = self.written;
+                let rest = decoded_bytes(mem::replace(&mut self.rest, fls())).map_err(|e| match e {
+                    Error::OutputNotBytes { position } => {
+                        Error::OutputNotBytes { position: written + position }
+                    },
+                    error => error
+                })?;
+                output.extend(rest);
+            } else {
+                break // waiting for more input
+            }
+        }
+
+        Ok(output)
+    }
+
+    /// Returns `true` if the whole output has been pulled.
+    pub fn is_finished(&self) -> bool { self.finished }
+
+    /// Splits a cell whose head is a byte or a bit that doesn't depend on the input any more off
+    /// the output; returns its decoding and the tail.
+    fn next_element(&mut self) -> Result<Option<(Vec<u8>, Term)>, Error> {
+        if !constructors::is_cell(&self.rest) { return Ok(None) }
+
+        let (head, tail) = constructors::cell_mut(&mut self.rest).unwrap(); // safe - checked above
+        head.reduce(NOR, 0);
+        let (head, tail) = match (lowered(head, 0), lowered(tail, 0)) {
+            (Some(head), Some(tail)) => (head, tail),
+            _ => return Ok(None)
+        };
+        if !head.is_supercombinator() || !(is_list(&head) || head == tru() || head == fls()) {
+            return Ok(None)
+        }
+
+        let element = decoded_bytes(abs(app!(Var(1), head, fls())))
+            .or(Err(Error::OutputNotBytes { position: self.written }))?;
+        Ok(Some((element, tail)))
+    }
+}
+
+/// Encodes `bytes` as a list whose tail is the free variable standing for the rest of the input.
+fn open_list(bytes: &[u8]) -> Term {
+    let mut list = Var(bytes.len() + 1); // under the abstractions of all the list cells
+
+    for &byte in bytes.iter().rev() {
+        let encoded = uncons(encode(&[byte])).unwrap().0; // safe - a list of one byte
+        list = abs(app!(Var(1), encoded, list));
+    }
+
+    list
+}
+
+/// Substitutes `chunk` for the free variable standing for the unread input in `term`, which is
+/// under `depth` abstractions.
+fn substitute(term: &mut Term, depth: usize, chunk: &Term) {
+    match *term {
+        Var(i) if i == depth + 1 => *term = shifted(chunk, depth, 0),
+        Var(_) => (),
+        Abs(ref mut body) => substitute(body, depth + 1, chunk),
+        App(ref mut lhs, ref mut rhs) => {
+            substitute(lhs, depth, chunk);
+            substitute(rhs, depth, chunk);
+        }
+    }
+}
+
+/// Returns a copy of `term` with its variables that are free above `cutoff` abstractions
+/// increased by `by`.
+fn shifted(term: &Term, by: usize, cutoff: usize) -> Term {
+    match *term {
+        Var(i) if i > cutoff => Var(i + by),
+        Var(i) => Var(i),
+        Abs(ref body) => abs(shifted(body, by, cutoff + 1)),
+        App(ref lhs, ref rhs) => app(shifted(lhs, by, cutoff), shifted(rhs, by, cutoff))
+    }
+}
+
+/// Returns a copy of `term` with its variables that are free above `cutoff` abstractions
+/// decreased by one, if none of them is the one bound right above them.
+fn lowered(term: &Term, cutoff: usize) -> Option<Term> {
+    Some(match *term {
+        Var(i) if i == cutoff + 1 => return None,
+        Var(i) if i > cutoff => Var(i - 1),
+        Var(i) => Var(i),
+        Abs(ref body) => abs(lowered(body, cutoff + 1)?),
+        App(ref lhs, ref rhs) => app(lowered(lhs, cutoff)?, lowered(rhs, cutoff)?)
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::io::Cursor;
+
+    /// Yields its bytes one at a time and fails afterwards instead of reporting the end.
+    struct Unending<'a>(&'a [u8]);
+
+    impl Read for Unending<'_> {
+        fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+            let (&first, rest) = self.0.split_first().ok_or(io::ErrorKind::BrokenPipe)?;
+            buffer[0] = first;
+            self.0 = rest;
+            Ok(1)
+        }
+    }
+
+    #[test]
+    fn whole_input() {
+        let reverse = b"0001011001000110100000000001011100111110111100001011011110110000010";
+        let long = (0..1_000).map(|i| i as u8).collect::<Vec<u8>>();
+
+        assert_eq!(run_reader(b"0010", Cursor::new(&long)), Ok(long));
+        assert_eq!(run_reader(reverse, Unending(b"ab")), Err(Error::Io(io::ErrorKind::BrokenPipe)));
+        assert_eq!(run_reader(b"0010", Cursor::new(b"")), Ok(vec![]));
+    }
+
+    #[test]
+    fn partial_input() {
+        // λ1 (λλλλ1 4 (λλ1)) (λλ1): the first byte of the input
+        let first_byte = b"00 01 01 10 00000000 01 01 10 11110 000010 000010";
+
+        assert_eq!(run_reader(first_byte, Unending(b"ab")), Ok(b"a".to_vec()));
+        assert_eq!(run_reader(b"00000010", Unending(b"")), Ok(vec![])); // λλλ1 ignores it
+    }
+
+    #[test]
+    fn interaction() {
+        let reverse = b"0001011001000110100000000001011100111110111100001011011110110000010";
+        let mut interaction = Interaction::new(reverse).unwrap();
+
+        interaction.push(b"ab");
+        assert_eq!(interaction.pull(), Ok(vec![]));
+        assert!(!interaction.is_finished());
+        interaction.push(b"c");
+        interaction.close();
+        assert_eq!(interaction.pull(), Ok(b"cba".to_vec()));
+        assert!(interaction.is_finished());
+
+        // λ1 (λλλλ1 4 (λλ1)) (λλ1): the first byte of the input
+        let first_byte = b"00 01 01 10 00000000 01 01 10 11110 000010 000010";
+        let mut interaction = Interaction::new(first_byte).unwrap();
+
+        interaction.push(b"xy");
+        assert_eq!(interaction.pull(), Ok(b"x".to_vec()));
+        assert!(interaction.is_finished());
+        assert!(Interaction::new(b"110").is_err());
+    }
+
+    #[test]
+    fn interactive_runs() {
+        let reverse = b"0001011001000110100000000001011100111110111100001011011110110000010";
+        let first_byte = b"00 01 01 10 00000000 01 01 10 11110 000010 000010";
+        let reverse = ClosedTerm::from_bits(reverse).unwrap();
+        let first_byte = ClosedTerm::from_bits(first_byte).unwrap();
+        let (mut reversed, mut first) = (Vec::new(), Vec::new());
+
+        assert_eq!(run_interactive(reverse, Cursor::new(b"herp derp"), &mut reversed), Ok(()));
+        assert_eq!(reversed, b"pred preh");
+        assert_eq!(run_interactive(first_byte, Unending(b"ab"), &mut first), Ok(()));
+        assert_eq!(first, b"a");
+    }
+}
@@ -0,0 +1,90 @@
+//! η-reduction
+
+use lambda_calculus::*;
+use std::mem;
+
+/// Performs all the η-contractions (`λx.M x` to `M` when `x` doesn't occur in `M`) in `term` and
+/// returns their number. Contracting a β-normal term can't create new β-redexes, so applying it
+/// to a normal form results in a βη-normal form.
+///
+/// # Example
+/// ```
+/// use blc::execution::eta_reduce;
+/// use blc::encoding::binary::{from_bits, to_bits};
+///
+/// let mut one = from_bits(b"00000111010").unwrap(); // λλ21
+///
+/// assert_eq!(eta_reduce(&mut one), 1);
+/// assert_eq!(to_bits(&one), b"0010"); // λ1
+/// ```
+pub fn eta_reduce(term: &mut Term) -> usize {
+    let mut count = match *term {
+        Var(_) => 0,
+        Abs(ref mut body) => eta_reduce(body),
+        App(ref mut lhs, ref mut rhs) => eta_reduce(lhs) + eta_reduce(rhs)
+    };
+
+    if is_eta_redex(term) {
+        let body = mem::replace(term, Var(0)).unabs().unwrap(); // safe - an η-redex
+        let mut function = body.lhs().unwrap(); // safe - an η-redex
+        lower(&mut function, 0);
+        *term = function;
+        count += 1;
+    }
+
+    count
+}
+
+fn is_eta_redex(term: &Term) -> bool {
+    if let Abs(ref body) = *term {
+        if let App(ref function, ref arg) = **body {
+            return **arg == Var(1) && !occurs(function, 1)
+        }
+    }
+    false
+}
+
+fn occurs(term: &Term, index: usize) -> bool {
+    match *term {
+        Var(i) => i == index,
+        Abs(ref body) => occurs(body, index + 1),
+        App(ref lhs, ref rhs) => occurs(lhs, index) || occurs(rhs, index)
+    }
+}
+
+fn lower(term: &mut Term, depth: usize) {
+    match *term {
+        Var(ref mut i) => if *i > depth { *i -= 1 },
+        Abs(ref mut body) => lower(body, depth + 1),
+        App(ref mut lhs, ref mut rhs) => {
+            lower(lhs, depth);
+            lower(rhs, depth)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use lambda_calculus::{parse, DeBruijn};
+
+    fn eta_normal(code: &str) -> Term {
+        let mut term = parse(code, DeBruijn).unwrap();
+        eta_reduce(&mut term);
+        term
+    }
+
+    #[test]
+    fn contractions() {
+        assert_eq!(eta_normal("λλ321"), parse("1", DeBruijn).unwrap());
+        assert_eq!(eta_normal("λ(λ1)1"), parse("λ1", DeBruijn).unwrap());
+        assert_eq!(eta_normal("λ2(λ21)"), parse("1", DeBruijn).unwrap());
+    }
+
+    #[test]
+    fn non_contractions() {
+        for code in &["λ11", "λλ12", "λ(λ2)1", "λλ2"] {
+            assert_eq!(eta_normal(code), parse(code, DeBruijn).unwrap());
+        }
+    }
+}
@@ -0,0 +1,190 @@
+//! Annotated normal-order reduction
+//!
+//! `explain` reduces a term in normal order and describes every contracted redex: where it was
+//! found, what was substituted for what and what the term turned into, for front-ends that teach
+//! or debug BLC programs one step at a time.
+
+use lambda_calculus::*;
+use std::fmt;
+
+/// A branch taken on the way from the root of a term to one of its subterms.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Branch {
+    /// the body of an abstraction
+    Body,
+    /// the left-hand side of an application
+    Function,
+    /// the right-hand side of an application
+    Argument
+}
+
+/// A description of a single reduction step.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Explanation {
+    /// the path from the root of the term to the contracted redex
+    pub location: Vec<Branch>,
+    /// the abstraction of the redex
+    pub abstraction: Term,
+    /// the argument of the redex, substituted for the variable bound by the abstraction
+    pub argument: Term,
+    /// the number of occurrences of the substituted variable; `0` if the argument was discarded
+    pub occurrences: usize,
+    /// the term after the step
+    pub result: Term
+}
+
+impl fmt::Display for Explanation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.location.is_empty() {
+            write!(f, "at the root, ")?;
+        } else {
+            let path = self.location.iter().map(|branch| match *branch {
+                Branch::Body => "body",
+                Branch::Function => "function",
+                Branch::Argument => "argument"
+            }).collect::<Vec<_>>();
+            write!(f, "at {}, ", path.join("/"))?;
+        }
+
+        write!(f, "({:?}) is applied to ({:?}): ", self.abstraction, self.argument)?;
+        match self.occurrences {
+            0 => write!(f, "the argument is discarded"),
+            1 => write!(f, "the argument replaces 1 occurrence of the variable"),
+            n => write!(f, "the argument replaces {} occurrences of the variable", n)
+        }
+    }
+}
+
+/// Reduces `term` in normal order and explains each of the at most `limit` steps (`0` means no
+/// limit, which is only safe for terms with a normal form).
+///
+/// # Example
+/// ```
+/// use blc::encoding::binary::from_bits;
+/// use blc::execution::explain::explain;
+///
+/// let k_i = from_bits(b"0100001100010").unwrap(); // (λλ2)(λ1)
+/// let steps = explain(k_i, 0);
+///
+/// assert_eq!(steps.len(), 1);
+/// assert_eq!(
+///     steps[0].to_string(),
+///     "at the root, (λλ2) is applied to (λ1): the argument replaces 1 occurrence of the variable"
+/// );
+/// ```
+pub fn explain(mut term: Term, limit: usize) -> Vec<Explanation> {
+    let mut explanations = Vec::new();
+
+    while limit == 0 || explanations.len() < limit {
+        let mut location = Vec::new();
+        if !find_redex(&term, &mut location) { break }
+
+        let redex = subterm_mut(&mut term, &location);
+        let (abstraction, argument) = match *redex {
+            App(ref lhs, ref rhs) => ((**lhs).clone(), (**rhs).clone()),
+            _ => unreachable!() // safe - `find_redex` only finds applications
+        };
+        let occurrences = count_bound(abstraction.unabs_ref().unwrap(), 1); // safe - a redex
+        redex.reduce(NOR, 1); // the redex itself is the leftmost outermost one in it
+
+        explanations.push(Explanation {
+            location,
+            abstraction,
+            argument,
+            occurrences,
+            result: term.clone()
+        });
+    }
+
+    explanations
+}
+
+/// Returns the location of the redex normal-order reduction contracts next, if there is one.
+///
+/// # Example
+/// ```
+/// use blc::encoding::binary::from_bits;
+/// use blc::execution::explain::{next_redex, Branch};
+///
+/// let term = from_bits(b"0001100100100010").unwrap(); // λ1 ((λ1) (λ1))
+///
+/// assert_eq!(next_redex(&term), Some(vec![Branch::Body, Branch::Argument]));
+/// assert_eq!(next_redex(&from_bits(b"0010").unwrap()), None);
+/// ```
+pub fn next_redex(term: &Term) -> Option<Vec<Branch>> {
+    let mut location = Vec::new();
+    if find_redex(term, &mut location) { Some(location) } else { None }
+}
+
+/// Finds the leftmost outermost redex, i.e. the first one in preorder, and stores its location.
+fn find_redex(term: &Term, location: &mut Vec<Branch>) -> bool {
+    match *term {
+        Var(_) => false,
+        Abs(ref body) => {
+            location.push(Branch::Body);
+            find_redex(body, location) || { location.pop(); false }
+        },
+        App(ref lhs, ref rhs) => {
+            if let Abs(_) = **lhs { return true }
+
+            location.push(Branch::Function);
+            if find_redex(lhs, location) { return true }
+            location.pop();
+            location.push(Branch::Argument);
+            find_redex(rhs, location) || { location.pop(); false }
+        }
+    }
+}
+
+fn subterm_mut<'a>(mut term: &'a mut Term, location: &[Branch]) -> &'a mut Term {
+    for branch in location {
+        term = match (term, *branch) {
+            (&mut Abs(ref mut body), Branch::Body) => body,
+            (&mut App(ref mut lhs, _), Branch::Function) => lhs,
+            (&mut App(_, ref mut rhs), Branch::Argument) => rhs,
+            _ => unreachable!() // safe - the location was found in this term
+        }
+    }
+    term
+}
+
+/// Counts the occurrences of the variable bound `depth` abstractions above `term`.
+fn count_bound(term: &Term, depth: usize) -> usize {
+    match *term {
+        Var(i) => (i == depth) as usize,
+        Abs(ref body) => count_bound(body, depth + 1),
+        App(ref lhs, ref rhs) => count_bound(lhs, depth) + count_bound(rhs, depth)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use lambda_calculus::combinators::{I, K, O};
+
+    #[test]
+    fn locations_and_results() {
+        let term = abs(app(Var(1), app(I(), app(K(), Var(1)))));
+        let steps = explain(term, 0);
+
+        assert_eq!(steps.len(), 2);
+        assert_eq!(steps[0].location, [Branch::Body, Branch::Argument]);
+        assert_eq!(steps[0].result, abs(app(Var(1), app(K(), Var(1)))));
+        assert_eq!(steps[0].to_string(), concat!(
+            "at body/argument, (λ1) is applied to ((λλ2)1): ",
+            "the argument replaces 1 occurrence of the variable"
+        ));
+        assert_eq!(steps[1].result, abs(app(Var(1), abs(Var(2)))));
+    }
+
+    #[test]
+    fn occurrences() {
+        let steps = explain(app!(K(), I(), O()), 0);
+
+        assert_eq!(steps.len(), 2);
+        assert_eq!(steps[0].occurrences, 1);
+        assert_eq!(steps[1].occurrences, 0);
+        assert_eq!(steps[1].result, I());
+        assert_eq!(explain(O(), 3)[2].occurrences, 2);
+    }
+}
@@ -0,0 +1,109 @@
+//! Asynchronous execution
+//!
+//! `run_async` returns a `Future` that performs a bounded number of reductions every time it is
+//! polled and then yields, asking to be polled again right away, so a long execution shares the
+//! thread of an asynchronous runtime with the other tasks instead of blocking it until it
+//! finishes. It doesn't depend on any particular runtime. It is available with the `async`
+//! feature.
+
+use lambda_calculus::*;
+use execution::{prepare, Error, Input};
+use execution::output::decoded;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// The number of reductions `RunFuture` performs every time it is polled.
+const REDUCTIONS_PER_POLL: usize = 1024;
+
+/// The `Future` of an execution started by `run_async`.
+#[derive(Debug)]
+pub struct RunFuture {
+    calculation: Result<Term, Error>,
+    done: bool
+}
+
+impl Future for RunFuture {
+    type Output = Result<String, Error>;
+
+    fn poll(mut self: Pin<&mut Self>, context: &mut Context) -> Poll<Self::Output> {
+        if self.done { panic!("RunFuture polled after completion") }
+
+        let finished = match self.calculation {
+            Ok(ref mut term) => term.reduce(NOR, REDUCTIONS_PER_POLL) < REDUCTIONS_PER_POLL,
+            Err(_) => true
+        };
+        if !finished {
+            context.waker().wake_by_ref();
+            return Poll::Pending
+        }
+
+        self.done = true;
+        let calculation = std::mem::replace(&mut self.calculation, Err(Error::InvalidProgram));
+        Poll::Ready(calculation.and_then(decoded))
+    }
+}
+
+/// Executes a binary lambda calculus program like `run` does, asynchronously.
+///
+/// # Example
+/// ```
+/// use blc::execution::run_async;
+/// use blc::execution::Input::Bytes;
+/// use std::future::Future;
+/// use std::pin::pin;
+/// use std::sync::Arc;
+/// use std::task::{Context, Poll, Wake};
+///
+/// struct Noop;
+///
+/// impl Wake for Noop {
+///     fn wake(self: Arc<Self>) {}
+/// }
+///
+/// let reverse_blc = b"0001011001000110100000000001011100111110111100001011011110110000010";
+/// let waker = Arc::new(Noop).into();
+/// let mut context = Context::from_waker(&waker);
+/// let mut future = pin!(run_async(reverse_blc, Bytes(b"herp")));
+///
+/// let output = loop {
+///     if let Poll::Ready(output) = future.as_mut().poll(&mut context) { break output }
+/// };
+/// assert_eq!(output, Ok("preh".into()));
+/// ```
+/// # Errors
+///
+/// The future resolves to the same `Error`s as `run` returns.
+pub fn run_async(blc_program: &[u8], input: Input) -> RunFuture {
+    RunFuture { calculation: prepare(blc_program, input), done: false }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use execution::Input::Bytes;
+    use std::sync::Arc;
+    use std::task::Wake;
+
+    struct Noop;
+
+    impl Wake for Noop {
+        fn wake(self: Arc<Self>) {}
+    }
+
+    #[test]
+    fn yielding_between_chunks() {
+        let waker = Arc::new(Noop).into();
+        let mut context = Context::from_waker(&waker);
+
+        let omega = b"010001101000011010";
+        let mut future = run_async(omega, Bytes(b""));
+        for _ in 0..10 {
+            assert_eq!(Pin::new(&mut future).poll(&mut context), Poll::Pending);
+        }
+
+        let mut future = run_async(b"12", Bytes(b""));
+        let output = Pin::new(&mut future).poll(&mut context);
+        assert_eq!(output, Poll::Ready(Err(Error::InvalidProgram)));
+    }
+}
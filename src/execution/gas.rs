@@ -0,0 +1,145 @@
+//! Metered execution
+//!
+//! Limiting the number of β-reductions bounds an execution only loosely, since a single reduction
+//! can copy an argument of any size any number of times. `run_metered` has a `GasMeter` charge
+//! every reduction in normal order before it is performed, knowing how much copying it entails,
+//! and stops the execution as soon as the meter refuses a charge, so embedders can bound the work
+//! precisely and deterministically.
+
+use lambda_calculus::*;
+use analysis::size;
+use execution::{prepare, Error, Input};
+use execution::output::decoded;
+
+/// A β-reduction about to be performed.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Reduction {
+    /// the size of the argument, in nodes
+    pub argument_size: usize,
+    /// the number of occurrences of the variable the argument is substituted for
+    pub substitutions: usize
+}
+
+impl Reduction {
+    /// Returns the number of nodes the reduction copies, i.e. `substitutions` times
+    /// `argument_size`.
+    pub fn copied(&self) -> usize { self.substitutions * self.argument_size }
+}
+
+/// A meter charged for every β-reduction of an execution.
+pub trait GasMeter {
+    /// Charges for `reduction`; returns `false` if there is not enough gas left to perform it.
+    fn charge(&mut self, reduction: &Reduction) -> bool;
+}
+
+impl<F: FnMut(&Reduction) -> bool> GasMeter for F {
+    fn charge(&mut self, reduction: &Reduction) -> bool { self(reduction) }
+}
+
+/// A fixed amount of gas, with every reduction costing one unit plus one for every node it
+/// copies.
+///
+/// # Example
+/// ```
+/// use blc::execution::gas::{GasLimit, GasMeter, Reduction};
+///
+/// let mut gas = GasLimit(10);
+///
+/// assert!(gas.charge(&Reduction { argument_size: 4, substitutions: 2 }));
+/// assert!(!gas.charge(&Reduction { argument_size: 1, substitutions: 1 }));
+/// assert_eq!(gas.0, 1);
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct GasLimit(pub u64);
+
+impl GasMeter for GasLimit {
+    fn charge(&mut self, reduction: &Reduction) -> bool {
+        let cost = 1 + reduction.copied() as u64;
+        if cost > self.0 { return false }
+
+        self.0 -= cost;
+        true
+    }
+}
+
+/// Executes a binary lambda calculus program like `run` does, charging `meter` for every
+/// β-reduction.
+///
+/// # Example
+/// ```
+/// use blc::execution::gas::{run_metered, GasLimit};
+/// use blc::execution::Error::OutOfGas;
+/// use blc::execution::Input::Bytes;
+///
+/// let reverse_blc = b"0001011001000110100000000001011100111110111100001011011110110000010";
+///
+/// assert_eq!(run_metered(reverse_blc, Bytes(b"herp"), &mut GasLimit(10_000)), Ok("preh".into()));
+/// assert_eq!(run_metered(reverse_blc, Bytes(b"herp"), &mut GasLimit(100)), Err(OutOfGas));
+/// ```
+/// # Errors
+///
+/// Returns `Error::OutOfGas` if the meter refuses to be charged for a reduction, or the same
+/// `Error`s as `run`.
+pub fn run_metered<M: GasMeter>(
+    blc_program: &[u8],
+    input: Input,
+    meter: &mut M
+) -> Result<String, Error> {
+    let mut calculation = prepare(blc_program, input)?;
+
+    while let Some(reduction) = next_reduction(&calculation) {
+        if !meter.charge(&reduction) { return Err(Error::OutOfGas) }
+        calculation.reduce(NOR, 1);
+    }
+
+    decoded(calculation)
+}
+
+/// Describes the leftmost outermost β-reduction of `term`, if it has a redex.
+fn next_reduction(term: &Term) -> Option<Reduction> {
+    match *term {
+        Var(_) => None,
+        Abs(ref body) => next_reduction(body),
+        App(ref lhs, ref rhs) => match **lhs {
+            Abs(ref body) => Some(Reduction {
+                argument_size: size(rhs),
+                substitutions: occurrences(body, 1)
+            }),
+            _ => next_reduction(lhs).or_else(|| next_reduction(rhs))
+        }
+    }
+}
+
+/// Returns the number of occurrences of the variable with the given De Bruijn index in `term`.
+fn occurrences(term: &Term, index: usize) -> usize {
+    match *term {
+        Var(i) => usize::from(i == index),
+        Abs(ref body) => occurrences(body, index + 1),
+        App(ref lhs, ref rhs) => occurrences(lhs, index) + occurrences(rhs, index)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn charges() {
+        // (λ1 1)(λ1), then (λ1)(λ1)
+        let mut charged = Vec::new();
+        let output = run_metered(b"01 00011010 0010", Input::Nothing, &mut |r: &Reduction| {
+            charged.push(*r);
+            true
+        });
+
+        assert_eq!(output, Ok("(λ1)".into()));
+        assert_eq!(charged, vec![
+            Reduction { argument_size: 2, substitutions: 2 },
+            Reduction { argument_size: 2, substitutions: 1 }
+        ]);
+        let mut refusing = |_: &Reduction| false;
+        let omega = b"01 00011010 00011010";
+        assert_eq!(run_metered(b"0010", Input::Nothing, &mut refusing), Ok("(λ1)".into()));
+        assert_eq!(run_metered(omega, Input::Nothing, &mut GasLimit(1_000)), Err(Error::OutOfGas));
+    }
+}
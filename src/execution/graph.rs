@@ -0,0 +1,392 @@
+//! Graph reduction
+//!
+//! Substituting an argument for a variable that occurs several times copies it, so every copy is
+//! reduced separately and a program that duplicates its input over and over does exponentially
+//! more work than necessary. `Graph` represents terms as graphs instead: a β-reduction makes all
+//! the occurrences of the variable point to the same argument node and overwrites the redex with
+//! its result, so a shared argument, and anything reduced inside it, is only reduced once, even
+//! under abstractions. Only the parts of the body of an abstraction that contain its variable are
+//! copied when it is applied; the rest is shared as well.
+//!
+//! Variables refer to the abstractions binding them instead of having De Bruijn indices, so shared
+//! nodes mean the same wherever they are used.
+
+use lambda_calculus::*;
+use execution::machine::Machine;
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+
+/// A graph reducer evaluating terms in normal order with sharing.
+///
+/// The nodes of the graph are kept in an arena. Whenever as many nodes have been added to it as
+/// were still in use after the previous collection, the nodes no longer reachable from the root
+/// are collected and their slots reused, so a long reduction only holds on to the nodes it can
+/// still use.
+///
+/// If it runs out of reductions before reaching the normal form, the term is replaced with the
+/// one represented by the graph at that point, which has the work performed until then unshared.
+///
+/// # Example
+/// ```
+/// use blc::execution::machine::{Machine, BigStep};
+/// use blc::execution::graph::Graph;
+/// use blc::encoding::binary::from_bits;
+///
+/// // (λ1 1 1) applied to (λλ1)(λ1)(λ1), whose duplicated result is only computed once
+/// let term = from_bits(b"01000101101010010100001000100010").unwrap();
+///
+/// assert_eq!(Graph.normalize(term.clone()), BigStep.normalize(term.clone()));
+/// assert!(Graph.reduce(&mut term.clone(), 0) < BigStep.reduce(&mut term.clone(), 0));
+/// ```
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub struct Graph;
+
+impl Machine for Graph {
+    fn reduce(&mut self, term: &mut Term, limit: usize) -> usize {
+        let mut graph = Arena::new(limit);
+        graph.root = graph.build(term, &mut Vec::new());
+
+        let _ = graph.normalize(graph.root); // exhaustion is read back as well
+        *term = graph.read_back(graph.root, &mut Vec::new());
+        graph.count
+    }
+}
+
+/// An index of a node in the `Arena`.
+type Id = usize;
+
+#[derive(Clone)]
+enum Node {
+    /// a variable bound by the abstraction node with the given `Id`
+    Bound(Id),
+    /// a free variable of the reduced term, by its De Bruijn index counted from the root
+    Free(usize),
+    /// an abstraction; its own `Id` identifies the variable it binds
+    Abs(Id),
+    App(Id, Id),
+    /// a reduced redex, standing for its result
+    Ind(Id)
+}
+
+struct Slot {
+    node: Node,
+    /// the abstractions with variables occurring in the node, or possibly occurring in it before
+    /// it was reduced, sorted
+    bound: Rc<Vec<Id>>
+}
+
+/// Signals that the reducer ran out of reductions.
+struct Exhausted;
+
+/// The number of nodes added before the first collection.
+const FIRST_COLLECTION: usize = 1 << 12;
+
+struct Arena {
+    nodes: Vec<Slot>,
+    /// the slots of the collected nodes, to be reused
+    free: Vec<Id>,
+    /// the node of the reduced term
+    root: Id,
+    /// the nodes already in normal form
+    normalized: HashSet<Id>,
+    /// the nodes added since the previous collection, and how many trigger the next one
+    added: usize,
+    threshold: usize,
+    limit: usize,
+    count: usize
+}
+
+impl Arena {
+    fn new(limit: usize) -> Self {
+        Arena {
+            nodes: Vec::new(),
+            free: Vec::new(),
+            root: 0,
+            normalized: HashSet::new(),
+            added: 0,
+            threshold: FIRST_COLLECTION,
+            limit,
+            count: 0
+        }
+    }
+
+    fn add(&mut self, node: Node) -> Id {
+        self.added += 1;
+        match self.free.pop() {
+            Some(id) => {
+                self.nodes[id] = self.slot(node, id);
+                id
+            },
+            None => {
+                let slot = self.slot(node, self.nodes.len());
+                self.nodes.push(slot);
+                self.nodes.len() - 1
+            }
+        }
+    }
+
+    /// Adds a placeholder for an abstraction whose body is not there yet, so its variables can
+    /// refer to it.
+    fn reserve(&mut self) -> Id {
+        self.add(Node::Free(0))
+    }
+
+    /// Replaces the placeholder with the abstraction of `body`.
+    fn fill(&mut self, abstraction: Id, body: Id) -> Id {
+        self.nodes[abstraction] = self.slot(Node::Abs(body), abstraction);
+        abstraction
+    }
+
+    /// Creates the slot of a node with the given `Id`.
+    fn slot(&self, node: Node, id: Id) -> Slot {
+        let bound = match node {
+            Node::Bound(binder) => Rc::new(vec![binder]),
+            Node::Free(_) => Rc::new(Vec::new()),
+            Node::Abs(body) => {
+                let body = &self.nodes[body].bound;
+                if body.binary_search(&id).is_err() { body.clone() } else {
+                    Rc::new(body.iter().cloned().filter(|&binder| binder != id).collect())
+                }
+            },
+            Node::App(lhs, rhs) => union(&self.nodes[lhs].bound, &self.nodes[rhs].bound),
+            Node::Ind(target) => self.nodes[target].bound.clone()
+        };
+        Slot { node, bound }
+    }
+
+    /// Adds `term`, under the abstractions with the `Id`s in `binders`, innermost last.
+    fn build(&mut self, term: &Term, binders: &mut Vec<Id>) -> Id {
+        match *term {
+            Var(i) if i <= binders.len() => self.add(Node::Bound(binders[binders.len() - i])),
+            Var(i) => self.add(Node::Free(i - binders.len())),
+            Abs(ref body) => {
+                let id = self.reserve();
+                binders.push(id);
+                let body = self.build(body, binders);
+                binders.pop();
+                self.fill(id, body)
+            },
+            App(ref lhs, ref rhs) => {
+                let (lhs, rhs) = (self.build(lhs, binders), self.build(rhs, binders));
+                self.add(Node::App(lhs, rhs))
+            }
+        }
+    }
+
+    fn follow(&self, mut id: Id) -> Id {
+        while let Node::Ind(target) = self.nodes[id].node { id = target }
+        id
+    }
+
+    /// Reduces the node to weak head normal form and returns the resulting node.
+    fn whnf(&mut self, start: Id) -> Result<Id, Exhausted> {
+        loop {
+            let id = self.follow(start);
+            let (lhs, rhs) = match self.nodes[id].node {
+                Node::App(lhs, rhs) => (lhs, rhs),
+                _ => return Ok(id)
+            };
+
+            let function = self.whnf(lhs)?;
+            let body = match self.nodes[function].node {
+                Node::Abs(body) => body,
+                _ => return Ok(id)
+            };
+
+            if self.limit != 0 && self.count == self.limit { return Err(Exhausted) }
+            self.count += 1;
+
+            let mut substitution = HashMap::new();
+            substitution.insert(function, rhs);
+            let result = self.instantiate(body, &mut substitution, &mut HashMap::new());
+            self.nodes[id].node = Node::Ind(result);
+            // skip the reduced redexes between `start` and the result, so they can be collected
+            self.nodes[start].node = Node::Ind(result);
+
+            // every node held by the callers is reachable from the root at this point
+            if self.added >= self.threshold { self.collect() }
+        }
+    }
+
+    /// Reduces the node to normal form.
+    fn normalize(&mut self, id: Id) -> Result<(), Exhausted> {
+        let id = self.whnf(id)?;
+        if !self.normalized.insert(id) { return Ok(()) }
+
+        match self.nodes[id].node {
+            Node::Abs(body) => self.normalize(body),
+            Node::App(lhs, rhs) => {
+                self.normalize(lhs)?;
+                self.normalize(rhs)
+            },
+            _ => Ok(())
+        }
+    }
+
+    /// Copies the parts of the graph under `id` that contain variables bound by the abstractions
+    /// in the `substitution`, replacing them with the nodes they map to.
+    fn instantiate(
+        &mut self,
+        id: Id,
+        substitution: &mut HashMap<Id, Id>,
+        copies: &mut HashMap<Id, Id>
+    ) -> Id {
+        let id = self.follow(id);
+        if !self.nodes[id].bound.iter().any(|binder| substitution.contains_key(binder)) {
+            return id
+        }
+        if let Some(&copy) = copies.get(&id) { return copy }
+
+        let copy = match self.nodes[id].node {
+            Node::Bound(binder) => substitution[&binder],
+            Node::Abs(body) => {
+                let abstraction = self.reserve();
+                let variable = self.add(Node::Bound(abstraction));
+
+                substitution.insert(id, variable);
+                let body = self.instantiate(body, substitution, copies);
+                substitution.remove(&id);
+                self.fill(abstraction, body)
+            },
+            Node::App(lhs, rhs) => {
+                let lhs = self.instantiate(lhs, substitution, copies);
+                let rhs = self.instantiate(rhs, substitution, copies);
+                self.add(Node::App(lhs, rhs))
+            },
+            Node::Free(_) | Node::Ind(_) => id // `Ind`s were followed above
+        };
+        copies.insert(id, copy);
+        copy
+    }
+
+    /// Frees the slots of the nodes that are not reachable from the root.
+    fn collect(&mut self) {
+        let mut reachable = vec![false; self.nodes.len()];
+        let mut pending = vec![self.root];
+        while let Some(id) = pending.pop() {
+            if reachable[id] { continue }
+            reachable[id] = true;
+
+            match self.nodes[id].node {
+                // a variable keeps its abstraction, so its `Id` isn't reused while it's in use
+                Node::Bound(target) | Node::Abs(target) | Node::Ind(target) => pending.push(target),
+                Node::App(lhs, rhs) => pending.extend_from_slice(&[lhs, rhs]),
+                Node::Free(_) => ()
+            }
+        }
+
+        let empty = Rc::new(Vec::new());
+        let mut pruned = HashMap::new(); // by the address of the original, which it keeps alive
+        self.free.clear();
+        for (id, slot) in self.nodes.iter_mut().enumerate() {
+            if !reachable[id] {
+                *slot = Slot { node: Node::Free(0), bound: empty.clone() };
+                self.free.push(id);
+            } else if slot.bound.iter().any(|&binder| !reachable[binder]) {
+                // collected abstractions the node used to contain variables of
+                let (_, ref bound) = *pruned.entry(Rc::as_ptr(&slot.bound)).or_insert_with(|| {
+                    let bound = slot.bound.iter().cloned().filter(|&binder| reachable[binder]);
+                    (slot.bound.clone(), Rc::new(bound.collect::<Vec<Id>>()))
+                });
+                slot.bound = bound.clone();
+            }
+        }
+        self.free.reverse(); // reuse the lowest slots first
+
+        self.normalized.retain(|&id| reachable[id]);
+        self.added = 0;
+        self.threshold = (self.nodes.len() - self.free.len()).max(FIRST_COLLECTION);
+    }
+
+    /// Reads back the term under `id`, under the abstractions with the `Id`s in `binders`,
+    /// innermost last.
+    fn read_back(&self, id: Id, binders: &mut Vec<Id>) -> Term {
+        match self.nodes[self.follow(id)].node {
+            Node::Bound(binder) => match binders.iter().rposition(|&b| b == binder) {
+                Some(position) => Var(binders.len() - position),
+                None => unreachable!() // variables only occur under their abstractions
+            },
+            Node::Free(index) => Var(index + binders.len()),
+            Node::Abs(body) => {
+                binders.push(self.follow(id));
+                let body = self.read_back(body, binders);
+                binders.pop();
+                abs(body)
+            },
+            Node::App(lhs, rhs) => app(self.read_back(lhs, binders), self.read_back(rhs, binders)),
+            Node::Ind(_) => unreachable!() // followed above
+        }
+    }
+}
+
+fn union(lhs: &Rc<Vec<Id>>, rhs: &Rc<Vec<Id>>) -> Rc<Vec<Id>> {
+    if rhs.is_empty() || lhs == rhs { return lhs.clone() }
+    if lhs.is_empty() { return rhs.clone() }
+
+    let mut union = lhs.iter().chain(rhs.iter()).cloned().collect::<Vec<Id>>();
+    union.sort_unstable();
+    union.dedup();
+    Rc::new(union)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use lambda_calculus::data::num::church::mul;
+    use execution::machine::BigStep;
+
+    #[test]
+    fn sharing_under_abstractions() {
+        // (λx. x x) (λy. 2 * 2 y): the product is only computed once
+        let shared = abs(app!(mul(), 2.into_church(), 2.into_church(), Var(1)));
+        let term = app(abs(app(Var(1), Var(1))), shared);
+        let (mut graph, mut big) = (term.clone(), term);
+
+        assert!(Graph.reduce(&mut graph, 0) < BigStep.reduce(&mut big, 0));
+        assert_eq!(graph, big);
+    }
+
+    #[test]
+    fn exponential_duplication() {
+        // (λd. d (d (... (d I)))) (λx. x x): every application of `d` doubles the work in normal
+        // order, but only adds to it with sharing
+        let mut nested = abs(Var(1));
+        for _ in 0..10 { nested = app(Var(1), nested) }
+        let term = app(abs(nested), abs(app(Var(1), Var(1))));
+        let (mut graph, mut big) = (term.clone(), term);
+
+        assert!(Graph.reduce(&mut graph, 0) * 10 < BigStep.reduce(&mut big, 0));
+        assert_eq!(graph, big);
+    }
+
+    #[test]
+    fn limits_and_free_variables() {
+        let term = abs(app(abs(app(Var(3), Var(1))), Var(2)));
+        assert_eq!(Graph.normalize(term), abs(app(Var(2), Var(2))));
+
+        let mut partial = app!(abs(abs(Var(2))), abs(Var(1)), abs(Var(1)));
+        assert_eq!(Graph.reduce(&mut partial, 1), 1);
+        assert_eq!(partial, app(abs(abs(Var(1))), abs(Var(1))));
+    }
+
+    #[test]
+    fn collection() {
+        // (λx. x x) (λx. x x) adds a node with every reduction and discards the previous one
+        let omega = app(abs(app(Var(1), Var(1))), abs(app(Var(1), Var(1))));
+        let mut graph = Arena::new(100_000);
+        graph.root = graph.build(&omega, &mut Vec::new());
+
+        assert!(graph.normalize(graph.root).is_err());
+        assert!(graph.nodes.len() <= 2 * FIRST_COLLECTION);
+        assert_eq!(graph.read_back(graph.root, &mut Vec::new()), omega);
+
+        // the collected slots are reused for nodes that are still needed
+        let mut nested = abs(Var(1));
+        for _ in 0..14 { nested = app(Var(1), nested) }
+        let mut term = app(abs(nested), abs(app(Var(1), Var(1))));
+        let mut big = term.clone();
+        Graph.reduce(&mut term, 0);
+        BigStep.reduce(&mut big, 0);
+        assert_eq!(term, big);
+    }
+}
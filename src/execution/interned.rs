@@ -0,0 +1,194 @@
+//! Normal-order evaluation of hash-consed terms
+//!
+//! `Interned` reduces terms stored in a `repr::interned::Interner`, so structurally equal subterms,
+//! which meta-circular programs like self-interpreters produce in large numbers, only take up
+//! memory once. Since an interned term is immutable and can be compared in constant time, the
+//! results of substitutions and the normal forms of subterms are memoized as well: a subterm that
+//! occurs several times, or shows up again during the reduction, is only normalized once.
+
+use lambda_calculus::*;
+use execution::machine::Machine;
+use repr::interned::{Id, Interner, Node};
+use std::collections::HashMap;
+
+/// A normal-order evaluator of hash-consed terms.
+///
+/// The interned nodes and the memoized results are freed as a whole once the reduction ends. If it
+/// runs out of reductions before reaching the normal form, the term is left as it was.
+///
+/// # Example
+/// ```
+/// use blc::execution::machine::{Machine, BigStep};
+/// use blc::execution::interned::Interned;
+/// use blc::encoding::binary::from_bits;
+///
+/// // (λ1 1 1) applied to (λλ1)(λ1)(λ1), whose copies are only normalized once
+/// let term = from_bits(b"01000101101010010100001000100010").unwrap();
+///
+/// assert_eq!(Interned.normalize(term.clone()), BigStep.normalize(term.clone()));
+/// assert!(Interned.reduce(&mut term.clone(), 0) < BigStep.reduce(&mut term.clone(), 0));
+/// ```
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub struct Interned;
+
+impl Machine for Interned {
+    fn reduce(&mut self, term: &mut Term, limit: usize) -> usize {
+        let mut evaluator = Evaluator {
+            terms: Interner::default(),
+            normal: HashMap::new(),
+            whnf: HashMap::new(),
+            shifted: HashMap::new(),
+            limit,
+            count: 0
+        };
+        let root = evaluator.terms.intern(term);
+
+        if let Ok(normal) = evaluator.normalize(root) { *term = evaluator.terms.extract(normal) }
+        evaluator.count
+    }
+}
+
+/// Signals that the evaluator ran out of reductions.
+struct Exhausted;
+
+struct Evaluator {
+    terms: Interner,
+    /// the normal forms of the terms normalized so far
+    normal: HashMap<Id, Id>,
+    /// the weak head normal forms of the terms reduced to them so far
+    whnf: HashMap<Id, Id>,
+    /// terms with their free variables above a cutoff increased by an amount
+    shifted: HashMap<(Id, usize, usize), Id>,
+    limit: usize,
+    count: usize
+}
+
+impl Evaluator {
+    /// Contracts the redex of the abstraction with the given body and an argument.
+    fn beta(&mut self, body: Id, argument: Id) -> Result<Id, Exhausted> {
+        if self.limit != 0 && self.count == self.limit { return Err(Exhausted) }
+        self.count += 1;
+
+        Ok(self.substitute(body, argument, 0, &mut HashMap::new()))
+    }
+
+    /// Reduces the term in normal order without reducing inside abstractions.
+    fn whnf(&mut self, id: Id) -> Result<Id, Exhausted> {
+        let mut current = id;
+        let mut passed = Vec::new(); // all of them have the same weak head normal form
+
+        let reduced = loop {
+            if let Some(&reduced) = self.whnf.get(&current) { break reduced }
+            passed.push(current);
+
+            match self.terms.get(current) {
+                Node::App(lhs, rhs) => {
+                    let function = self.whnf(lhs)?;
+                    match self.terms.get(function) {
+                        Node::Abs(body) => current = self.beta(body, rhs)?,
+                        _ => break self.terms.app(function, rhs)
+                    }
+                },
+                _ => break current
+            }
+        };
+        for id in passed { self.whnf.insert(id, reduced); }
+        Ok(reduced)
+    }
+
+    fn normalize(&mut self, id: Id) -> Result<Id, Exhausted> {
+        if let Some(&normal) = self.normal.get(&id) { return Ok(normal) }
+
+        let head = self.whnf(id)?;
+        let normal = match self.terms.get(head) {
+            Node::Var(_) => head,
+            Node::Abs(body) => {
+                let body = self.normalize(body)?;
+                self.terms.abs(body)
+            },
+            Node::App(lhs, rhs) => {
+                let (lhs, rhs) = (self.normalize(lhs)?, self.normalize(rhs)?);
+                self.terms.app(lhs, rhs)
+            }
+        };
+        self.normal.insert(id, normal);
+        Ok(normal)
+    }
+
+    /// Substitutes `argument` for the variable bound `depth` abstractions above `id` and removes
+    /// its binder; `done` memoizes the substitution.
+    fn substitute(
+        &mut self,
+        id: Id,
+        argument: Id,
+        depth: usize,
+        done: &mut HashMap<(Id, usize), Id>
+    ) -> Id {
+        if self.terms.loose(id) <= depth { return id }
+        if let Some(&result) = done.get(&(id, depth)) { return result }
+
+        let result = match self.terms.get(id) {
+            Node::Var(i) if i == depth + 1 => self.shift(argument, depth, 0),
+            Node::Var(i) => self.terms.var(i - 1), // bound above the removed binder
+            Node::Abs(body) => {
+                let body = self.substitute(body, argument, depth + 1, done);
+                self.terms.abs(body)
+            },
+            Node::App(lhs, rhs) => {
+                let lhs = self.substitute(lhs, argument, depth, done);
+                let rhs = self.substitute(rhs, argument, depth, done);
+                self.terms.app(lhs, rhs)
+            }
+        };
+        done.insert((id, depth), result);
+        result
+    }
+
+    /// Increases the variables of `id` that are free above `cutoff` abstractions by `by`.
+    fn shift(&mut self, id: Id, by: usize, cutoff: usize) -> Id {
+        if by == 0 || self.terms.loose(id) <= cutoff { return id }
+        if let Some(&result) = self.shifted.get(&(id, by, cutoff)) { return result }
+
+        let result = match self.terms.get(id) {
+            Node::Var(i) => self.terms.var(i + by), // free, since it is above the cutoff
+            Node::Abs(body) => {
+                let body = self.shift(body, by, cutoff + 1);
+                self.terms.abs(body)
+            },
+            Node::App(lhs, rhs) => {
+                let (lhs, rhs) = (self.shift(lhs, by, cutoff), self.shift(rhs, by, cutoff));
+                self.terms.app(lhs, rhs)
+            }
+        };
+        self.shifted.insert((id, by, cutoff), result);
+        result
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use lambda_calculus::data::num::church::mul;
+    use execution::machine::BigStep;
+
+    #[test]
+    fn shared_normalization() {
+        // (λx. x x) (λy. 2 * 2 y): both copies of the product are the same interned term
+        let shared = abs(app!(mul(), 2.into_church(), 2.into_church(), Var(1)));
+        let term = app(abs(app(Var(1), Var(1))), shared);
+        let (mut interned, mut big) = (term.clone(), term);
+
+        assert!(Interned.reduce(&mut interned, 0) < BigStep.reduce(&mut big, 0));
+        assert_eq!(interned, big);
+    }
+
+    #[test]
+    fn limits_and_free_variables() {
+        let term = abs(app(abs(app(Var(3), Var(1))), Var(2)));
+        assert_eq!(Interned.normalize(term), abs(app(Var(2), Var(2))));
+
+        let mut product = app!(mul(), 3.into_church(), 3.into_church());
+        assert_eq!(Interned.reduce(&mut product, 2), 2);
+        assert_eq!(product, app!(mul(), 3.into_church(), 3.into_church()));
+    }
+}
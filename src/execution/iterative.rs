@@ -0,0 +1,263 @@
+//! Normal-order reduction without recursion
+//!
+//! The reducers of `lambda_calculus` recurse on the native stack once for every level of nesting
+//! of the reduced term, so the deeply nested terms that encode long inputs or outputs can overflow
+//! it. `Iterative` keeps everything it would recurse on, both while looking for redexes and while
+//! substituting arguments, in stacks on the heap instead; it consumes the reduced term as it goes,
+//! so discarded subterms are freed without recursion as well.
+//!
+//! Dropping a `Term` still recurses on the native stack, so very deep terms should be freed with
+//! `dispose` instead.
+
+use lambda_calculus::*;
+use execution::machine::Machine;
+use std::mem;
+
+/// A normal-order reducer using heap-allocated stacks instead of the native one.
+///
+/// It performs the same β-reductions as normal-order reduction, in the same order. If it runs out
+/// of reductions before reaching the normal form, the term is left reduced as far as it got.
+///
+/// # Example
+/// ```
+/// use blc::execution::machine::{Machine, BigStep};
+/// use blc::execution::iterative::Iterative;
+/// use blc::encoding::binary::from_bits;
+///
+/// let term = from_bits(b"01000101101010010100001000100010").unwrap();
+///
+/// assert_eq!(Iterative.normalize(term.clone()), BigStep.normalize(term.clone()));
+/// assert_eq!(Iterative.reduce(&mut term.clone(), 0), BigStep.reduce(&mut term.clone(), 0));
+/// ```
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub struct Iterative;
+
+impl Machine for Iterative {
+    fn reduce(&mut self, term: &mut Term, limit: usize) -> usize {
+        let mut count = 0;
+        let mut tasks = vec![Task::Normalize(mem::replace(term, Var(1)))];
+        let mut terms = Vec::new();
+
+        while let Some(task) = tasks.pop() {
+            match task {
+                Task::Normalize(term) => {
+                    let (head, args) = head_reduce(term, limit, &mut count);
+
+                    if args.is_empty() {
+                        if let Abs(body) = head {
+                            tasks.push(Task::Abs);
+                            tasks.push(Task::Normalize(*body));
+                            continue
+                        }
+                    }
+                    // the head is a variable, or it can't be applied before running out of
+                    // reductions; its arguments come next in normal order
+                    tasks.push(Task::App(args.len()));
+                    terms.push(head);
+                    tasks.extend(args.into_iter().map(Task::Normalize)); // the first one on top
+                },
+                Task::Abs => {
+                    let body = terms.pop().unwrap(); // safe - pushed by the last task
+                    terms.push(abs(body));
+                },
+                Task::App(args) => {
+                    let args = terms.split_off(terms.len() - args);
+                    let head = terms.pop().unwrap(); // safe - pushed before the arguments
+                    terms.push(args.into_iter().fold(head, app));
+                }
+            }
+        }
+
+        *term = terms.pop().unwrap(); // safe - the result of the first task
+        count
+    }
+}
+
+/// The work left while normalizing a term.
+enum Task {
+    /// push the normal form of the term
+    Normalize(Term),
+    /// wrap the last pushed term in an abstraction
+    Abs,
+    /// apply the term pushed before the given number of last pushed ones to them
+    App(usize)
+}
+
+/// Contracts head redexes of `term` until its head is not an abstraction applied to an argument
+/// or `count` reaches `limit`; returns the head and the arguments it is applied to, last first.
+fn head_reduce(mut term: Term, limit: usize, count: &mut usize) -> (Term, Vec<Term>) {
+    let mut args = Vec::new();
+
+    loop {
+        while let App(lhs, rhs) = term {
+            args.push(*rhs);
+            term = *lhs;
+        }
+
+        match term {
+            Abs(body) if !args.is_empty() && (limit == 0 || *count < limit) => {
+                *count += 1;
+                let arg = args.pop().unwrap(); // safe - checked above
+                term = substitute(*body, &arg);
+                dispose(arg);
+            },
+            head => return (head, args)
+        }
+    }
+}
+
+/// The work left while rebuilding a term.
+enum Rebuild<T> {
+    /// push the rebuilt term, which is under the given number of abstractions
+    Visit(T, usize),
+    /// wrap the last pushed term in an abstraction
+    Abs,
+    /// apply the second to last pushed term to the last one
+    App
+}
+
+/// Substitutes `arg` for the variable bound by the abstraction of `body` and removes the binder.
+fn substitute(body: Term, arg: &Term) -> Term {
+    let mut tasks = vec![Rebuild::Visit(body, 0)];
+    let mut terms = Vec::new();
+
+    while let Some(task) = tasks.pop() {
+        match task {
+            Rebuild::Visit(Var(i), depth) => terms.push(match i {
+                i if i <= depth => Var(i),
+                i if i == depth + 1 => shifted(arg, depth),
+                i => Var(i - 1) // bound above the removed binder
+            }),
+            Rebuild::Visit(Abs(body), depth) => {
+                tasks.push(Rebuild::Abs);
+                tasks.push(Rebuild::Visit(*body, depth + 1));
+            },
+            Rebuild::Visit(App(lhs, rhs), depth) => {
+                tasks.push(Rebuild::App);
+                tasks.push(Rebuild::Visit(*rhs, depth));
+                tasks.push(Rebuild::Visit(*lhs, depth));
+            },
+            task => assemble(task, &mut terms)
+        }
+    }
+
+    terms.pop().unwrap() // safe - the result of the first task
+}
+
+/// Returns a copy of `term` with its free variables increased by `by`.
+fn shifted(term: &Term, by: usize) -> Term {
+    let mut tasks = vec![Rebuild::Visit(term, 0)];
+    let mut terms = Vec::new();
+
+    while let Some(task) = tasks.pop() {
+        match task {
+            Rebuild::Visit(&Var(i), depth) => terms.push(Var(if i > depth { i + by } else { i })),
+            Rebuild::Visit(Abs(body), depth) => {
+                tasks.push(Rebuild::Abs);
+                tasks.push(Rebuild::Visit(body, depth + 1));
+            },
+            Rebuild::Visit(App(lhs, rhs), depth) => {
+                tasks.push(Rebuild::App);
+                tasks.push(Rebuild::Visit(rhs, depth));
+                tasks.push(Rebuild::Visit(lhs, depth));
+            },
+            task => assemble(task, &mut terms)
+        }
+    }
+
+    terms.pop().unwrap() // safe - the result of the first task
+}
+
+/// Performs a task combining the last pushed terms.
+fn assemble<T>(task: Rebuild<T>, terms: &mut Vec<Term>) {
+    match task {
+        Rebuild::Abs => {
+            let body = terms.pop().unwrap(); // safe - pushed by the last task
+            terms.push(abs(body));
+        },
+        Rebuild::App => {
+            let rhs = terms.pop().unwrap(); // safe - pushed by the last task
+            let lhs = terms.pop().unwrap(); // safe - pushed by the one before
+            terms.push(app(lhs, rhs));
+        },
+        Rebuild::Visit(..) => unreachable!() // safe - only called with the other tasks
+    }
+}
+
+/// Frees a term without recursing on the native stack, which dropping it does.
+///
+/// # Example
+/// ```
+/// use blc::execution::iterative::dispose;
+/// use blc::encoding::lambda::encode;
+///
+/// dispose(encode(&[b'a'; 1_000]));
+/// ```
+pub fn dispose(term: Term) {
+    let mut terms = vec![term];
+
+    while let Some(term) = terms.pop() {
+        match term {
+            Var(_) => (),
+            Abs(body) => terms.push(*body),
+            App(lhs, rhs) => {
+                terms.push(*lhs);
+                terms.push(*rhs);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use lambda_calculus::combinators::{I, K, O};
+    use lambda_calculus::data::num::church::{mul, pred};
+    use execution::machine::BigStep;
+    use std::thread;
+
+    #[test]
+    fn same_as_normal_order() {
+        let terms = vec![
+            app!(K(), I(), O()),
+            app!(mul(), 3.into_church(), 4.into_church()),
+            app(pred(), 3.into_church()),
+            abs(app(abs(app(Var(3), Var(1))), Var(2)))
+        ];
+
+        for term in terms {
+            let (mut iterative, mut big) = (term.clone(), term);
+            assert_eq!(Iterative.reduce(&mut iterative, 0), BigStep.reduce(&mut big, 0));
+            assert_eq!(iterative, big);
+        }
+
+        let mut iterative = app!(mul(), 3.into_church(), 4.into_church());
+        let mut big = iterative.clone();
+        assert_eq!(Iterative.reduce(&mut iterative, 5), 5);
+        BigStep.reduce(&mut big, 5);
+        assert_eq!(iterative, big);
+    }
+
+    #[test]
+    fn deep_terms() {
+        // λλ...λ (λ1) 1, nested deeper than a small stack allows
+        let small_stack = thread::Builder::new().stack_size(64 * 1024);
+        let depth = small_stack.spawn(|| {
+            let mut term = app(I(), Var(1));
+            for _ in 0..100_000 { term = abs(term) }
+            Iterative.reduce(&mut term, 0);
+
+            let mut depth = 0;
+            let mut body = &term;
+            while let Abs(ref inner) = *body {
+                depth += 1;
+                body = inner;
+            }
+            let depth = if *body == Var(1) { Some(depth) } else { None };
+            dispose(term);
+            depth
+        });
+
+        assert_eq!(depth.unwrap().join().ok(), Some(Some(100_000)));
+    }
+}
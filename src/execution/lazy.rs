@@ -0,0 +1,235 @@
+//! Call-by-need evaluation
+//!
+//! Normal-order reduction substitutes arguments unevaluated, so an argument that is used several
+//! times is also reduced several times. `Lazy` evaluates every argument at most once, when it is
+//! first needed, and shares the result between all its uses; arguments that are never needed are
+//! never evaluated. The normal form is read back by evaluating the bodies of abstractions with
+//! fresh variables, so `Lazy` reaches the same normal forms as normal-order reduction, usually in
+//! far fewer β-reductions.
+
+use lambda_calculus::*;
+use execution::machine::Machine;
+use execution::{apply_input, ClosedTerm, Error, Input};
+use execution::output::decoded;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// A call-by-need evaluator.
+///
+/// If it runs out of reductions before reaching the normal form, the term is left as it was,
+/// since the work performed until then is spread among the evaluated arguments.
+///
+/// # Example
+/// ```
+/// use blc::execution::machine::{Machine, BigStep};
+/// use blc::execution::lazy::Lazy;
+/// use blc::encoding::binary::from_bits;
+///
+/// // (λ1 1 1) applied to (λλ1)(λ1)(λ1), whose duplicated result is only computed once
+/// let term = from_bits(b"01000101101010010100001000100010").unwrap();
+///
+/// assert_eq!(Lazy.normalize(term.clone()), BigStep.normalize(term.clone()));
+/// assert!(Lazy.reduce(&mut term.clone(), 0) < BigStep.reduce(&mut term.clone(), 0));
+/// ```
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub struct Lazy;
+
+impl Machine for Lazy {
+    fn reduce(&mut self, term: &mut Term, limit: usize) -> usize {
+        let mut evaluator = Evaluator { limit, count: 0 };
+        let normal = evaluator.eval(&compile(term), &None)
+            .and_then(|value| evaluator.quote(value, 0));
+
+        if let Ok(normal) = normal { *term = normal }
+        evaluator.count
+    }
+}
+
+/// Executes a binary lambda calculus program like `run` does, but with call-by-need evaluation.
+///
+/// # Example
+/// ```
+/// use blc::execution::lazy::run_lazy;
+/// use blc::execution::Input::Bytes;
+///
+/// let reverse_blc = b"0001011001000110100000000001011100111110111100001011011110110000010";
+///
+/// assert_eq!(run_lazy(&*reverse_blc, Bytes(b"herp derp")), Ok("pred preh".into()));
+/// ```
+/// # Errors
+///
+/// Returns the same `Error`s as `run`.
+pub fn run_lazy(blc_program: &[u8], input: Input) -> Result<String, Error> {
+    let program = ClosedTerm::from_bits(blc_program)?.into_term();
+    let mut calculation = apply_input(program, input)?;
+    Lazy.reduce(&mut calculation, 0);
+
+    decoded(calculation)
+}
+
+/// A term whose subterms can be shared by closures.
+enum Code {
+    Var(usize),
+    Abs(Rc<Code>),
+    App(Rc<Code>, Rc<Code>)
+}
+
+fn compile(term: &Term) -> Rc<Code> {
+    Rc::new(match *term {
+        Var(i) => Code::Var(i),
+        Abs(ref body) => Code::Abs(compile(body)),
+        App(ref lhs, ref rhs) => Code::App(compile(lhs), compile(rhs))
+    })
+}
+
+/// The values of the variables bound around a piece of `Code`, innermost first.
+type Env = Option<Rc<Frame>>;
+
+struct Frame {
+    thunk: Thunk,
+    next: Env
+}
+
+/// A possibly not yet evaluated argument, shared by all its uses.
+type Thunk = Rc<RefCell<State>>;
+
+enum State {
+    Delayed(Rc<Code>, Env),
+    Forced(Value)
+}
+
+#[derive(Clone)]
+enum Value {
+    /// an abstraction along with the values of its free variables
+    Closure(Rc<Code>, Env),
+    /// a variable, applied to arguments
+    Neutral(Head, Vec<Thunk>)
+}
+
+#[derive(Clone, Copy)]
+enum Head {
+    /// a variable bound by an abstraction being read back, by its depth counted from the root
+    Level(usize),
+    /// a free variable of the evaluated term, by its De Bruijn index counted from the root
+    Free(usize)
+}
+
+/// Signals that the evaluator ran out of reductions.
+struct Exhausted;
+
+struct Evaluator {
+    limit: usize,
+    count: usize
+}
+
+impl Evaluator {
+    fn eval(&mut self, code: &Rc<Code>, env: &Env) -> Result<Value, Exhausted> {
+        match **code {
+            Code::Var(i) => {
+                let mut frame = env;
+                for passed in 1..i {
+                    frame = match *frame {
+                        Some(ref f) => &f.next,
+                        None => return Ok(Value::Neutral(Head::Free(i - passed + 1), Vec::new()))
+                    };
+                }
+                match *frame {
+                    Some(ref f) => self.force(&f.thunk),
+                    None => Ok(Value::Neutral(Head::Free(1), Vec::new()))
+                }
+            },
+            Code::Abs(ref body) => Ok(Value::Closure(body.clone(), env.clone())),
+            Code::App(ref lhs, ref rhs) => {
+                let function = self.eval(lhs, env)?;
+                let argument = Rc::new(RefCell::new(State::Delayed(rhs.clone(), env.clone())));
+                self.apply(function, argument)
+            }
+        }
+    }
+
+    fn apply(&mut self, function: Value, argument: Thunk) -> Result<Value, Exhausted> {
+        match function {
+            Value::Closure(body, env) => {
+                if self.limit != 0 && self.count == self.limit { return Err(Exhausted) }
+                self.count += 1;
+
+                self.eval(&body, &Some(Rc::new(Frame { thunk: argument, next: env })))
+            },
+            Value::Neutral(head, mut args) => {
+                args.push(argument);
+                Ok(Value::Neutral(head, args))
+            }
+        }
+    }
+
+    fn force(&mut self, thunk: &Thunk) -> Result<Value, Exhausted> {
+        let (code, env) = match *thunk.borrow() {
+            State::Forced(ref value) => return Ok(value.clone()),
+            State::Delayed(ref code, ref env) => (code.clone(), env.clone())
+        };
+
+        let value = self.eval(&code, &env)?;
+        *thunk.borrow_mut() = State::Forced(value.clone());
+        Ok(value)
+    }
+
+    /// Reads back the normal form of `value` under `depth` abstractions.
+    fn quote(&mut self, value: Value, depth: usize) -> Result<Term, Exhausted> {
+        match value {
+            Value::Closure(body, env) => {
+                let fresh = Value::Neutral(Head::Level(depth), Vec::new());
+                let thunk = Rc::new(RefCell::new(State::Forced(fresh)));
+                let body = self.eval(&body, &Some(Rc::new(Frame { thunk, next: env })))?;
+
+                Ok(abs(self.quote(body, depth + 1)?))
+            },
+            Value::Neutral(head, args) => {
+                let mut term = match head {
+                    Head::Level(level) => Var(depth - level),
+                    Head::Free(index) => Var(index + depth)
+                };
+                for arg in args {
+                    let arg = self.force(&arg)?;
+                    term = app(term, self.quote(arg, depth)?);
+                }
+                Ok(term)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use lambda_calculus::combinators::I;
+    use lambda_calculus::data::num::church::{mul, pow};
+    use execution::machine::BigStep;
+
+    #[test]
+    fn sharing() {
+        // (λx λy. y x x) (3 * 3)
+        let product = app!(mul(), 3.into_church(), 3.into_church());
+        let term = app(abs(abs(app!(Var(1), Var(2), Var(2)))), product);
+        let (mut lazy, mut big) = (term.clone(), term);
+
+        assert!(Lazy.reduce(&mut lazy, 0) < BigStep.reduce(&mut big, 0));
+        assert_eq!(lazy, big);
+    }
+
+    #[test]
+    fn unneeded_arguments() {
+        // the exponentiation is discarded without being evaluated
+        let term = app!(abs(abs(Var(1))), app!(pow(), 9.into_church(), 9.into_church()), I());
+        let mut reduced = term.clone();
+
+        assert_eq!(Lazy.reduce(&mut reduced, 0), 2);
+        assert_eq!(reduced, I());
+    }
+
+    #[test]
+    fn free_variables() {
+        let term = abs(app(abs(app(Var(3), Var(1))), Var(2)));
+
+        assert_eq!(Lazy.normalize(term), abs(app(Var(2), Var(2))));
+    }
+}
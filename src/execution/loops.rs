@@ -0,0 +1,198 @@
+//! Normal-order reduction with constant-space numeral loops
+//!
+//! Applying the Church numeral `n` to a stepping function `f` and an initial value `x` unfolds to
+//! `f (f (... (f x)))`, a term that grows with `n` before any of its applications is contracted.
+//! When `f` and `x` are closed, `normalize` runs such loops itself instead: it applies `f` to the
+//! value accumulated so far and normalizes the result, `n` times, so that only a single pending
+//! application exists at a time. Every iteration is given a bounded number of reductions; if one
+//! needs more, the rest of the loop is left to ordinary normal-order reduction, which keeps its
+//! termination behavior.
+//!
+//! The normal forms are the same as with `NOR`, but the numbers of reductions they take can differ.
+
+use lambda_calculus::*;
+use std::mem;
+
+/// The maximum number of reductions spent on a single iteration of a loop.
+const ITERATION_FUEL: usize = 10_000;
+
+/// Reduces `term` to its normal form; returns the number of performed reductions.
+pub fn normalize(term: &mut Term) -> usize {
+    // the subterms left to normalize, kept on the heap, as lists can be longer than the stack
+    // allows recursing over
+    let mut pending = vec![term];
+    let mut count = 0;
+
+    while let Some(term) = pending.pop() {
+        count += head_normalize(term);
+
+        match *term {
+            Abs(ref mut body) => pending.push(body),
+            _ => {
+                let first = pending.len();
+                let mut spine = term;
+                while let App(ref mut lhs, ref mut rhs) = *spine {
+                    pending.push(rhs);
+                    spine = lhs;
+                }
+                pending[first..].reverse(); // normalize the arguments from left to right
+            }
+        }
+    }
+
+    count
+}
+
+/// Reduces `term` until it is an abstraction or a variable applied to arguments.
+fn head_normalize(term: &mut Term) -> usize {
+    let mut count = 0;
+
+    loop {
+        if let Some(site) = loop_site(term) {
+            count += unroll(site);
+        } else if let Some(redex) = head_redex(term) {
+            let (mut lhs, rhs) = mem::replace(redex, Var(0)).unapp().unwrap(); // safe - a redex
+            lhs.apply(&rhs).unwrap(); // safe - lhs is an abstraction
+            *redex = lhs;
+            count += 1;
+        } else {
+            return count
+        }
+    }
+}
+
+fn head_redex(term: &mut Term) -> Option<&mut Term> {
+    let is_redex = match *term {
+        App(ref lhs, _) => matches!(**lhs, Abs(_)),
+        _ => return None
+    };
+
+    if is_redex { return Some(term) }
+
+    match *term {
+        App(ref mut lhs, _) => head_redex(lhs),
+        _ => None
+    }
+}
+
+/// Finds an application of a Church numeral greater than 1 to two closed arguments in the head
+/// position of `term`.
+fn loop_site(term: &mut Term) -> Option<&mut Term> {
+    if is_loop(term) { return Some(term) }
+
+    match *term {
+        App(ref mut lhs, _) => loop_site(lhs),
+        _ => None
+    }
+}
+
+fn is_loop(term: &Term) -> bool {
+    if let App(ref lhs, ref x) = *term {
+        if let App(ref n, ref f) = **lhs {
+            return numeral(n).is_some_and(|n| n > 1)
+                && f.is_supercombinator()
+                && x.is_supercombinator()
+        }
+    }
+    false
+}
+
+/// Replaces the loop `n f x` with the normal form of its result, or with a shorter loop over a
+/// partially evaluated value if an iteration runs out of fuel.
+fn unroll(site: &mut Term) -> usize {
+    let (lhs, x) = mem::replace(site, Var(0)).unapp().unwrap(); // safe - a loop
+    let (n, f) = lhs.unapp().unwrap(); // safe - a loop
+    let n = numeral(&n).unwrap(); // safe - a loop
+    let mut count = 0;
+    let mut value = x;
+
+    for i in 1..=n {
+        value = app(f.clone(), value);
+        // reduce one step at a time, as `Term::reduce` recurses deeper with every reduction
+        let reductions = (0..ITERATION_FUEL).take_while(|_| value.reduce(NOR, 1) == 1).count();
+        count += reductions;
+
+        if reductions == ITERATION_FUEL {
+            *site = app!((n - i).into_church(), f, value);
+            return count
+        }
+    }
+
+    *site = value;
+    count
+}
+
+/// Returns `n` if `term` is the Church numeral `n`.
+fn numeral(term: &Term) -> Option<usize> {
+    let mut body = term.unabs_ref().and_then(|t| t.unabs_ref()).ok()?;
+    let mut n = 0;
+
+    while let App(ref lhs, ref rhs) = *body {
+        if **lhs != Var(2) { return None }
+        n += 1;
+        body = rhs;
+    }
+
+    if *body == Var(1) { Some(n) } else { None }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use lambda_calculus::combinators::{I, K, O};
+    use lambda_calculus::data::num::church::succ;
+    use encoding::lambda::encode;
+    use execution::iterative::dispose;
+    use std::thread;
+
+    #[test]
+    fn loops_are_unrolled() {
+        let mut term = app!(1000.into_church(), succ(), 0.into_church());
+
+        normalize(&mut term);
+
+        assert_eq!(term, 1000.into_church());
+    }
+
+    #[test]
+    fn same_normal_forms_as_nor() {
+        let terms = [
+            app!(3.into_church(), 2.into_church(), K(), I()),
+            app!(3.into_church(), abs(app!(Var(1), I(), K())), K()),
+            app!(K(), I(), O()),
+            abs(app!(2.into_church(), Var(1), I()))
+        ];
+
+        for term in terms.iter() {
+            let mut reduced = term.clone();
+            normalize(&mut reduced);
+
+            assert_eq!(reduced, beta(term.clone(), NOR, 0));
+        }
+    }
+
+    #[test]
+    fn long_lists() {
+        // a list longer than a small stack allows recursing over
+        let small_stack = thread::Builder::new().stack_size(64 * 1024);
+        let count = small_stack.spawn(|| {
+            let mut term = encode(&[b'a'; 10_000]);
+            let count = normalize(&mut term);
+            dispose(term);
+            count
+        });
+
+        assert_eq!(count.unwrap().join().ok(), Some(0));
+    }
+
+    #[test]
+    fn expensive_iterations_are_left_to_nor() {
+        // `f x` is `λ1Ω` and has no normal form, but `f (f x)` reduces to `I`
+        let f = abs(app(Var(1), app(K(), I())));
+        let x = abs(abs(app(Var(1), O())));
+        let mut term = app!(2.into_church(), f, x);
+
+        assert!(normalize(&mut term) > ITERATION_FUEL);
+        assert_eq!(term, I());
+    }
+}
@@ -0,0 +1,221 @@
+//! Interchangeable evaluators
+//!
+//! A `Machine` reduces terms to their normal forms. `SmallStep` performs one reduction at a time
+//! with any `Strategy`, which makes it easy to observe, while `BigStep` reduces whole terms at
+//! once in normal order, which is faster; `lazy::Lazy` shares the evaluation of arguments between
+//! their uses, `graph::Graph` shares whole subterms, `interned::Interned` memoizes the normal
+//! forms of hash-consed subterms, `iterative::Iterative` reduces in normal order without recursing
+//! on the native stack, `cek::Cek` evaluates in call-by-value order with an abstract machine and
+//! `closures::Closures` and `vm::Vm` evaluate like `Lazy` after compiling terms to Rust closures
+//! and to bytecode respectively, `memo::Memo` remembers normal forms across reductions and
+//! `strictness::Hybrid` evaluates the arguments that are certainly needed before passing them.
+//!
+//! `SmallStep` and `BigStep` own their terms as trees, so the subterms discarded by a reduction are
+//! freed as soon as it is performed and no separate garbage collection is needed; `graph::Graph`
+//! shares nodes, so it collects the ones that are no longer reachable instead.
+
+use lambda_calculus::*;
+use execution::{strict, Strategy};
+
+/// An evaluator reducing `Term`s to their normal forms.
+pub trait Machine {
+    /// Reduces `term` in place, performing at most `limit` β-reductions (`0` means no limit), and
+    /// returns the number of performed reductions.
+    fn reduce(&mut self, term: &mut Term, limit: usize) -> usize;
+
+    /// Reduces `term` to its normal form.
+    fn normalize(&mut self, mut term: Term) -> Term {
+        self.reduce(&mut term, 0);
+        term
+    }
+}
+
+/// A small-step evaluator, reducing a term one step at a time with the given strategy.
+///
+/// # Example
+/// ```
+/// use blc::execution::machine::{Machine, SmallStep};
+/// use blc::encoding::binary::{from_bits, to_bits};
+///
+/// let mut machine = SmallStep::default();
+/// let mut k_i = from_bits(b"0100001100010").unwrap(); // (λλ2)(λ1)
+///
+/// assert_eq!(machine.step(&mut k_i), 1);
+/// assert_eq!(to_bits(&k_i), b"000010"); // λλ1
+/// assert_eq!(machine.step(&mut k_i), 0);
+/// ```
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct SmallStep {
+    /// the strategy choosing the redex to contract in each step
+    pub strategy: Strategy
+}
+
+impl SmallStep {
+    /// Performs a single reduction step on `term`; returns the number of β-reductions it
+    /// consisted of, which is `0` if there was nothing left to reduce.
+    pub fn step(&mut self, term: &mut Term) -> usize {
+        self.strategy.step(term)
+    }
+}
+
+impl Default for SmallStep {
+    fn default() -> Self { SmallStep { strategy: Strategy::Normal } }
+}
+
+impl Machine for SmallStep {
+    fn reduce(&mut self, term: &mut Term, limit: usize) -> usize {
+        let mut count = 0;
+
+        while limit == 0 || count < limit {
+            // a strict step evaluates arguments too, so it can't be allowed all of its fuel
+            let step = match self.strategy {
+                Strategy::StrictData if limit != 0 => strict::step(term, limit - count),
+                _ => self.step(term)
+            };
+
+            match step {
+                0 => break,
+                n => count += n
+            }
+        }
+
+        count
+    }
+}
+
+/// A big-step evaluator, reducing a whole term in normal order at once.
+///
+/// # Example
+/// ```
+/// use blc::execution::machine::{Machine, BigStep};
+/// use blc::encoding::binary::{from_bits, to_bits};
+///
+/// let k_i = from_bits(b"0100001100010").unwrap(); // (λλ2)(λ1)
+///
+/// assert_eq!(to_bits(&BigStep.normalize(k_i)), b"000010"); // λλ1
+/// ```
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub struct BigStep;
+
+impl Machine for BigStep {
+    fn reduce(&mut self, term: &mut Term, limit: usize) -> usize {
+        term.reduce(NOR, limit)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use lambda_calculus::{parse, DeBruijn, IntoChurchNum};
+    use execution::cek::Cek;
+    use execution::closures::Closures;
+    use execution::delta::{Delta, NumeralOperator};
+    use execution::graph::Graph;
+    use execution::interned::Interned;
+    use execution::iterative::Iterative;
+    use execution::lazy::Lazy;
+    use execution::memo::Memo;
+    use execution::optimal::Optimal;
+    use execution::strictness::Hybrid;
+    use execution::vm::Vm;
+    use lambda_calculus::combinators::{S, K, I, O};
+    use lambda_calculus::data::num::church::{mul, pred};
+
+    fn semantics<M: Machine>(mut machine: M) {
+        let normalizing = vec![
+            (app!(S(), K(), K()), I()),
+            (app!(K(), I(), O()), I()),
+            (app!(mul(), 2.into_church(), 3.into_church()), 6.into_church()),
+            (app(pred(), 3.into_church()), 2.into_church()),
+            (parse("λ(λ1)2", DeBruijn).unwrap(), parse("λ2", DeBruijn).unwrap())
+        ];
+
+        for (term, normal) in normalizing {
+            assert_eq!(machine.normalize(term), normal);
+        }
+
+        let mut omega = O();
+        assert_eq!(machine.reduce(&mut omega, 10), 10);
+        assert_eq!(omega, O());
+
+        within_limits(machine);
+    }
+
+    fn within_limits<M: Machine>(mut machine: M) {
+        let nested = parse("(λ1)((λ1)(λ1)((λ1)(λ1)((λ1)(λ1))))", DeBruijn).unwrap();
+        for term in [nested, app!(mul(), 2.into_church(), 3.into_church())] {
+            for limit in 1..10 {
+                assert!(machine.reduce(&mut term.clone(), limit) <= limit, "{:?}", term);
+            }
+        }
+    }
+
+    #[test]
+    fn small_step() {
+        semantics(SmallStep::default());
+        semantics(SmallStep { strategy: Strategy::StrictData });
+    }
+
+    #[test]
+    fn big_step() {
+        semantics(BigStep);
+    }
+
+    #[test]
+    fn lazy() {
+        semantics(Lazy);
+    }
+
+    #[test]
+    fn graph() {
+        semantics(Graph);
+    }
+
+    #[test]
+    fn interned() {
+        semantics(Interned);
+    }
+
+    #[test]
+    fn iterative() {
+        semantics(Iterative);
+    }
+
+    #[test]
+    fn closures() {
+        semantics(Closures);
+    }
+
+    #[test]
+    fn memo() {
+        semantics(Memo::new());
+    }
+
+    #[test]
+    fn hybrid() {
+        semantics(Hybrid);
+    }
+
+    #[test]
+    fn vm() {
+        semantics(Vm);
+    }
+
+    #[test]
+    fn other_machines() {
+        let mut delta = Delta::new();
+        delta.register(NumeralOperator::multiplication());
+
+        within_limits(Cek);
+        within_limits(Optimal);
+        within_limits(delta);
+    }
+
+    #[test]
+    fn same_reduction_counts() {
+        let mut small = app!(mul(), 2.into_church(), 3.into_church());
+        let mut big = small.clone();
+
+        assert_eq!(SmallStep::default().reduce(&mut small, 0), BigStep.reduce(&mut big, 0));
+    }
+}
@@ -0,0 +1,144 @@
+//! Normal-order evaluation with memoized normal forms
+//!
+//! A program built from library combinators normalizes the same closed subterms, like arithmetic
+//! on Church numerals, over and over. `Memo` reduces in normal order, i.e. to head normal form
+//! first and then the arguments of the head from left to right, and remembers the normal form of
+//! every closed term it normalizes on the way, keyed by the term itself; when the same term has to
+//! be normalized again, during the same reduction or any later one, its normal form is reused.
+//! Only the terms normal order normalizes as a whole are remembered, so it doesn't normalize
+//! anything normal order wouldn't.
+
+use lambda_calculus::*;
+use execution::machine::Machine;
+use std::collections::HashMap;
+
+/// A normal-order evaluator that remembers the normal forms of closed subterms across reductions.
+///
+/// If it runs out of reductions before reaching the normal form, the term is left as it was, but
+/// the normal forms found until then are kept.
+///
+/// # Example
+/// ```
+/// extern crate blc;
+/// extern crate lambda_calculus;
+///
+/// use blc::execution::machine::{Machine, BigStep};
+/// use blc::execution::memo::Memo;
+/// use lambda_calculus::*;
+/// use lambda_calculus::data::num::church::mul;
+///
+/// // λ1 (3 * 3) (3 * 3), whose product is only computed once
+/// let product = app!(mul(), 3.into_church(), 3.into_church());
+/// let term = abs(app!(Var(1), product.clone(), product));
+/// let mut memo = Memo::new();
+///
+/// assert_eq!(memo.normalize(term.clone()), BigStep.normalize(term.clone()));
+/// assert!(memo.reduce(&mut term.clone(), 0) < BigStep.reduce(&mut term.clone(), 0));
+/// ```
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct Memo {
+    normal_forms: HashMap<Term, Term>
+}
+
+impl Memo {
+    /// Creates an evaluator that doesn't remember any normal forms yet.
+    pub fn new() -> Self { Memo::default() }
+
+    /// Returns the number of remembered normal forms.
+    pub fn len(&self) -> usize { self.normal_forms.len() }
+
+    /// Returns `true` if no normal forms are remembered.
+    pub fn is_empty(&self) -> bool { self.normal_forms.is_empty() }
+
+    /// Forgets all the remembered normal forms.
+    pub fn clear(&mut self) { self.normal_forms.clear() }
+
+    fn normal_form(&mut self, mut term: Term, budget: &mut Budget) -> Result<Term, Exhausted> {
+        let key = if term.is_supercombinator() {
+            if let Some(normal) = self.normal_forms.get(&term) { return Ok(normal.clone()) }
+            Some(term.clone())
+        } else {
+            None
+        };
+
+        budget.reduce(&mut term)?;
+        let normal = self.arguments_normalized(term, budget)?;
+
+        if let Some(key) = key { self.normal_forms.insert(key, normal.clone()); }
+        Ok(normal)
+    }
+
+    /// Normalizes the arguments along the spine of a term in head normal form.
+    fn arguments_normalized(&mut self, term: Term, budget: &mut Budget) -> Result<Term, Exhausted> {
+        Ok(match term {
+            Abs(body) => abs(self.arguments_normalized(*body, budget)?),
+            App(lhs, rhs) => {
+                let lhs = self.arguments_normalized(*lhs, budget)?;
+                app(lhs, self.normal_form(*rhs, budget)?)
+            },
+            var => var
+        })
+    }
+}
+
+impl Machine for Memo {
+    fn reduce(&mut self, term: &mut Term, limit: usize) -> usize {
+        let mut budget = Budget { limit, count: 0 };
+
+        if let Ok(normal) = self.normal_form(term.clone(), &mut budget) { *term = normal }
+        budget.count
+    }
+}
+
+/// Signals that the evaluator ran out of reductions.
+struct Exhausted;
+
+struct Budget {
+    limit: usize,
+    count: usize
+}
+
+impl Budget {
+    /// Reduces `term` to head normal form with the reductions left.
+    fn reduce(&mut self, term: &mut Term) -> Result<(), Exhausted> {
+        if self.limit == 0 {
+            self.count += term.reduce(HSP, 0);
+        } else if self.count < self.limit {
+            self.count += term.reduce(HSP, self.limit - self.count);
+        }
+
+        if is_head_normal(term) { Ok(()) } else { Err(Exhausted) }
+    }
+}
+
+fn is_head_normal(mut term: &Term) -> bool {
+    while let Abs(ref body) = *term { term = body }
+    while let App(ref lhs, _) = *term { term = lhs }
+
+    matches!(*term, Var(_))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use lambda_calculus::data::num::church::fac;
+    use execution::machine::BigStep;
+
+    #[test]
+    fn remembered_across_reductions() {
+        let factorial = app(fac(), 3.into_church());
+        let mut memo = Memo::new();
+
+        let reductions = BigStep.reduce(&mut factorial.clone(), 0);
+
+        assert_eq!(memo.reduce(&mut factorial.clone(), 0), reductions);
+        assert!(!memo.is_empty());
+
+        let mut pair = abs(app!(Var(1), factorial.clone(), factorial));
+        assert_eq!(memo.reduce(&mut pair, 0), 0);
+        assert_eq!(pair, abs(app!(Var(1), 6.into_church(), 6.into_church())));
+
+        memo.clear();
+        assert!(memo.is_empty());
+    }
+}
@@ -0,0 +1,1207 @@
+//! Binary lambda calculus execution
+
+use lambda_calculus::*;
+use lambda_calculus::data::boolean::{tru, fls};
+use analysis::size;
+use digest::Digest;
+use encoding::binary::{decompress, from_bits, to_bits};
+use encoding::lambda::{encode, encode_bits};
+use pair_list::is_list;
+use program::{Format, Program};
+use repr::{TermRep, convert};
+use self::Error::*;
+use self::machine::Machine;
+use self::output::{decoded, decoded_bits, decoded_bytes};
+use std::io::{self, Write};
+use std::mem;
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+pub use self::batch::{run_batch, run_parallel, reduce_with_fuel, Fuel};
+pub use self::closed::ClosedTerm;
+pub use self::eta::eta_reduce;
+pub use self::output::decode_strict;
+#[cfg(feature = "async")]
+pub use self::future::{run_async, RunFuture};
+pub use self::steps::Execution;
+pub use self::target::{reduce_to, Target};
+
+mod batch;
+pub mod cek;
+mod closed;
+pub mod closures;
+mod constructors;
+mod cycles;
+pub mod coverage;
+pub mod debugger;
+pub mod decoders;
+pub mod delta;
+pub mod demand;
+mod eta;
+pub mod explain;
+#[cfg(feature = "async")]
+mod future;
+pub mod gas;
+pub mod graph;
+pub mod interned;
+pub mod iterative;
+pub mod lazy;
+mod loops;
+pub mod machine;
+pub mod memo;
+pub mod optimal;
+pub mod oracle;
+mod output;
+pub mod quota;
+mod steps;
+mod strict;
+pub mod strictness;
+mod target;
+pub mod vm;
+
+/// An error that can occur during BLC execution.
+#[derive(Debug, PartialEq)]
+pub enum Error {
+    /// invalid BLC program
+    InvalidProgram,
+    /// invalid BLC argument
+    InvalidArgument,
+    /// the allowed number of reduction steps was exceeded
+    StepLimitExceeded {
+        /// the number of performed steps
+        steps: usize
+    },
+    /// the output was longer than allowed
+    OutputLimitExceeded,
+    /// there is no quota for the given tenant
+    UnknownTenant,
+    /// the tenant's quota was exhausted
+    QuotaExhausted,
+    /// none of the installed decoders applies to the output
+    NoDecoder,
+    /// the execution didn't finish in the allotted time
+    Timeout {
+        /// the allotted time
+        after: Duration
+    },
+    /// an intermediate term grew larger than allowed
+    SizeLimitExceeded,
+    /// the execution was aborted by its caller
+    Aborted,
+    /// the saved state of an execution is invalid
+    InvalidCheckpoint,
+    /// the input couldn't be read or the output couldn't be written
+    Io(io::ErrorKind),
+    /// a cell of the output list, or its end, is not an abstraction
+    NotAnAbstraction {
+        /// the number of cells before it
+        position: usize
+    },
+    /// a cell of the output list, or its end, is an abstraction, but not a list
+    OutputNotAList {
+        /// the number of cells before it
+        position: usize
+    },
+    /// an element of the output list is not a byte
+    OutputNotBytes {
+        /// the number of elements before it
+        position: usize
+    },
+    /// an element of the output list is a list instead of a bit, in bit mode
+    OutputNotBits {
+        /// the number of elements before it
+        position: usize
+    },
+    /// the output doesn't have the shape its decoder requires
+    UnexpectedShape {
+        /// the required shape
+        expected: decoders::Shape,
+        /// the shape of the output
+        found: decoders::Shape
+    },
+    /// the reduction returned to a term it had already reached, so it would never finish
+    LikelyDivergent {
+        /// the number of steps performed before the term recurred
+        steps: usize,
+        /// the number of steps since its previous occurrence
+        period: usize
+    },
+    /// the `gas::GasMeter` of the execution refused to be charged for a reduction
+    OutOfGas,
+    /// the execution panicked
+    Panicked
+}
+
+/// The type of input for BLC execution.
+#[derive(Debug, Clone, Copy)]
+pub enum Input<'a> {
+    /// no input parameter
+    Nothing,
+    /// BLC input
+    Bits(&'a [u8]),
+    /// unencoded byte input, encoded according to the `IoMode` of the execution
+    Bytes(&'a [u8])
+}
+
+/// The way byte input is encoded for a program and its output is decoded.
+///
+/// # Example
+/// ```
+/// use blc::execution::IoMode;
+///
+/// let identity = |mode: IoMode, input: &[u8]| mode.decode(mode.encode(input));
+///
+/// assert_eq!(identity(IoMode::Bytes, b"ab"), Ok("ab".into()));
+/// assert_eq!(identity(IoMode::Bits, b"0110"), Ok("0110".into()));
+/// ```
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum IoMode {
+    /// a list of bytes, each a list of 8 bits, most significant first; the mode used by `run`
+    Bytes,
+    /// a list of bits, like in the bit mode of John Tromp's universal machine (`uni -b`): every
+    /// input byte contributes its least significant bit and output bits are decoded as the
+    /// characters `0` and `1`
+    Bits
+}
+
+impl IoMode {
+    /// Encodes program input in this mode.
+    pub fn encode(self, input: &[u8]) -> Term {
+        match self {
+            IoMode::Bytes => encode(input),
+            IoMode::Bits  => encode_bits(input)
+        }
+    }
+
+    /// Decodes program output in this mode; bytes are decoded as the `char`s with the same
+    /// values, like `decode` does.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::OutputNotBytes` if an element of the output is a list, but not a byte, and
+    /// `Error::OutputNotBits` if an element of the output is a list in bit mode.
+    pub fn decode(self, output: Term) -> Result<String, Error> {
+        match self {
+            IoMode::Bytes => decoded(output),
+            IoMode::Bits  => {
+                // safe - the bits are ASCII `0`s and `1`s and the text fallen back to is a `String`
+                Ok(String::from_utf8(decoded_bits(output)?).unwrap())
+            }
+        }
+    }
+
+    /// Decodes program output in this mode as bytes, so output that is not valid UTF-8 is
+    /// preserved.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::OutputNotBytes` if an element of the output is a list, but not a byte, and
+    /// `Error::OutputNotBits` if an element of the output is a list in bit mode.
+    pub fn decode_bytes(self, output: Term) -> Result<Vec<u8>, Error> {
+        match self {
+            IoMode::Bytes => decoded_bytes(output),
+            IoMode::Bits  => decoded_bits(output)
+        }
+    }
+}
+
+/// The reduction strategy used to evaluate a BLC program.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Strategy {
+    /// leftmost outermost; the strategy used by `run`
+    Normal,
+    /// leftmost outermost, no reductions inside abstractions
+    CallByName,
+    /// leftmost outermost, abstractions reduced only in head position
+    HeadSpine,
+    /// a mix between the head spine and normal strategies
+    HybridNormal,
+    /// leftmost innermost; unfit for recursion combinators
+    Applicative,
+    /// leftmost innermost, no reductions inside abstractions
+    CallByValue,
+    /// a mix between the call-by-value and applicative strategies
+    HybridApplicative,
+    /// leftmost outermost, but closed arguments that quickly evaluate to booleans or lists of
+    /// them, like encoded input, are evaluated once before they are substituted
+    StrictData,
+    /// leftmost outermost, with every argument evaluated at most once and shared between its
+    /// uses; see `lazy::Lazy`. Sharing only applies to whole evaluations, so single steps are
+    /// the same as in `Normal` order
+    CallByNeed,
+    /// leftmost innermost, evaluated by an abstract machine instead of by substitution; see
+    /// `cek::Cek`. Single steps are the same as in `Applicative` order
+    Cek,
+    /// leftmost outermost, with heap-allocated stacks instead of recursion, so deeply nested terms
+    /// can't overflow the native stack; see `iterative::Iterative`. Single steps are the same as in
+    /// `Normal` order
+    Iterative,
+    /// leftmost outermost with sharing like `CallByNeed`, but with the term compiled to Rust
+    /// closures first; see `closures::Closures`. Single steps are the same as in `Normal` order
+    Closures
+}
+
+impl Strategy {
+    /// Performs a single reduction step on the given `Term`; returns the number of β-reductions
+    /// it consisted of, which is `0` if there was nothing left to reduce.
+    ///
+    /// # Example
+    /// ```
+    /// use blc::execution::Strategy;
+    /// use blc::encoding::binary::{from_bits, to_bits};
+    ///
+    /// let mut k_i = from_bits(b"0100001100010").unwrap(); // (λλ2)(λ1)
+    ///
+    /// assert_eq!(Strategy::Normal.step(&mut k_i), 1);
+    /// assert_eq!(to_bits(&k_i), b"000010"); // λλ1
+    /// assert_eq!(Strategy::Normal.step(&mut k_i), 0);
+    /// ```
+    pub fn step(self, term: &mut Term) -> usize {
+        let order = match self {
+            Strategy::Normal            => NOR,
+            Strategy::CallByNeed        => NOR,
+            Strategy::Cek               => APP,
+            Strategy::Iterative         => NOR,
+            Strategy::Closures          => NOR,
+            Strategy::CallByName        => CBN,
+            Strategy::HeadSpine         => HSP,
+            Strategy::HybridNormal      => HNO,
+            Strategy::Applicative       => APP,
+            Strategy::CallByValue       => CBV,
+            Strategy::HybridApplicative => HAP,
+            Strategy::StrictData        => return strict::step(term, 0)
+        };
+
+        term.reduce(order, 1)
+    }
+}
+
+/// Limits on the resources a single execution may use; `0` means no limit.
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub struct Limits {
+    /// the maximum number of reduction steps
+    pub steps: usize,
+    /// the maximum size of an intermediate term, in nodes
+    pub size: usize
+}
+
+/// The configuration of an execution.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Options {
+    /// if `true`, the output is normalized; otherwise it is only evaluated as far as decoding it
+    /// requires, i.e. its list cells, bytes and bits are only reduced to weak head normal form,
+    /// without reducing under any other abstractions
+    pub under_binders: bool,
+    /// the maximum number of elements (bytes or bits) of the output list; evaluation is aborted
+    /// as soon as more of them are produced (`0` means no limit)
+    pub max_output: usize,
+    /// the reduction strategy; with any other than `Strategy::Normal`, the output is reduced to
+    /// the normal form of that strategy regardless of `under_binders`, and its length is only
+    /// checked against `max_output` afterwards
+    pub strategy: Strategy,
+    /// the maximum size of an intermediate term, in nodes (`0` means no limit); the size is checked
+    /// after every step, so with a limit the output is reduced one step at a time to the normal
+    /// form of the `strategy` and without sharing, regardless of `under_binders`
+    pub max_size: usize,
+    /// the encoding of `Input::Bytes` and of the output
+    pub io_mode: IoMode,
+    /// the number of previous terms every intermediate term is compared with (`0` means none);
+    /// if it is equal to one of them, the reduction cycles and is given up on. Like with
+    /// `max_size`, the output is then reduced one step at a time without sharing
+    pub divergence_window: usize,
+    /// if `true`, the evaluated output is η-reduced before it is decoded, so outputs that only
+    /// differ by η-expansions are decoded the same
+    pub eta: bool
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            under_binders: true,
+            max_output: 0,
+            strategy: Strategy::Normal,
+            max_size: 0,
+            io_mode: IoMode::Bytes,
+            divergence_window: 0,
+            eta: false
+        }
+    }
+}
+
+/// Parses a binary lambda calculus program and applies it to the given argument without reducing
+/// the result.
+///
+/// # Example
+/// ```
+/// use blc::execution::prepare;
+/// use blc::execution::Input::Bits;
+/// use blc::encoding::binary::to_bits;
+///
+/// let k_i = prepare(b"0000110", Bits(b"0010")).unwrap();
+///
+/// assert_eq!(to_bits(&k_i), b"0100001100010"); // (λλ2)(λ1)
+/// ```
+/// # Errors
+///
+/// Returns an `Error` if the program or the argument are not valid BLC.
+pub fn prepare(blc_program: &[u8], input: Input) -> Result<Term, Error> {
+    apply_input(ClosedTerm::from_bits(blc_program)?.into_term(), input)
+}
+
+/// Applies an already parsed program to the given argument without reducing the result.
+///
+/// # Errors
+///
+/// Returns an `Error` if the argument is not valid BLC.
+pub fn apply_input(program: Term, input: Input) -> Result<Term, Error> {
+    apply_input_in(program, input, IoMode::Bytes)
+}
+
+/// Applies an already parsed program to the given argument like `apply_input` does, encoding
+/// `Input::Bytes` in the given `IoMode`.
+///
+/// # Errors
+///
+/// Returns an `Error` if the argument is not valid BLC.
+pub fn apply_input_in(program: Term, input: Input, io_mode: IoMode) -> Result<Term, Error> {
+    match input {
+        Input::Nothing    => Ok(program),
+        Input::Bytes(arg) => Ok(app(program, io_mode.encode(arg))),
+        Input::Bits(arg)  => {
+            let arg = ClosedTerm::from_bits(arg).or(Err(InvalidArgument))?;
+            Ok(app(program, arg.into_term()))
+        }
+    }
+}
+
+/// Executes a binary lambda calculus program, optionally feeding it the given argument.
+/// More programs can be found in the `tests` directory.
+///
+/// # Example
+/// ```
+/// use blc::execution::run;
+/// use blc::execution::Input::Bytes;
+///
+/// let reverse_blc = b"0001011001000110100000000001011100111110111100001011011110110000010";
+///
+/// assert_eq!(run(&*reverse_blc, Bytes(b"herp derp")), Ok("pred preh".into()));
+/// ```
+pub fn run(blc_program: &[u8], input: Input) -> Result<String, Error> {
+    run_with(blc_program, input, Options::default())
+}
+
+/// Executes a binary lambda calculus program that takes no input, like a quine, without applying
+/// it to anything; equivalent to `run` with `Input::Nothing`.
+///
+/// # Example
+/// ```
+/// use blc::execution::run_unapplied;
+/// use blc::encoding::binary::to_bits;
+/// use blc::encoding::lambda::encode;
+///
+/// let constant = to_bits(&encode(b"hi")); // a program that is its own output
+///
+/// assert_eq!(run_unapplied(&constant), Ok("hi".into()));
+/// ```
+/// # Errors
+///
+/// Returns the same `Error`s as `run`.
+pub fn run_unapplied(blc_program: &[u8]) -> Result<String, Error> {
+    run(blc_program, Input::Nothing)
+}
+
+/// Executes a packed binary lambda calculus program, with 8 bits in every byte, most significant
+/// first, like in `.Blc` files, like `run` does.
+///
+/// # Example
+/// ```
+/// use blc::execution::run_compressed;
+/// use blc::execution::Input::Bytes;
+/// use blc::encoding::binary::compress;
+///
+/// let reverse_blc = b"0001011001000110100000000001011100111110111100001011011110110000010";
+///
+/// assert_eq!(run_compressed(&compress(reverse_blc), Bytes(b"herp")), Ok("preh".into()));
+/// ```
+/// # Errors
+///
+/// Returns the same `Error`s as `run`.
+pub fn run_compressed(program_bytes: &[u8], input: Input) -> Result<String, Error> {
+    let program = from_bits(&decompress(program_bytes)).or(Err(InvalidProgram))?;
+
+    run_term(ClosedTerm::new(program)?, input, Options::default())
+}
+
+/// Executes a binary lambda calculus program stored in any `program::Format`, which is detected
+/// from its contents, like `run` does.
+///
+/// # Example
+/// ```
+/// use blc::execution::run_detected;
+/// use blc::execution::Input::Bytes;
+/// use blc::encoding::binary::compress;
+///
+/// let reverse_blc = b"0001011001000110100000000001011100111110111100001011011110110000010";
+///
+/// assert_eq!(run_detected(reverse_blc, Bytes(b"herp")), Ok("preh".into()));
+/// assert_eq!(run_detected(&compress(reverse_blc), Bytes(b"herp")), Ok("preh".into()));
+/// ```
+/// # Errors
+///
+/// Returns the same `Error`s as `run`.
+pub fn run_detected(program: &[u8], input: Input) -> Result<String, Error> {
+    let format = Format::detect(Path::new(""), program);
+    let program = Program::parse(program, format).or(Err(InvalidProgram))?;
+
+    run_term(ClosedTerm::new(program.into_term())?, input, Options::default())
+}
+
+/// Executes a binary lambda calculus program curried over several inputs, applying it to the
+/// encodings of all the `args` in order.
+///
+/// # Example
+/// ```
+/// use blc::execution::run_many;
+///
+/// let second = b"000010"; // λλ1
+/// let first = b"0000110"; // λλ2
+///
+/// assert_eq!(run_many(second, &[b"key", b"message"]), Ok("message".into()));
+/// assert_eq!(run_many(first, &[b"key", b"message"]), Ok("key".into()));
+/// ```
+/// # Errors
+///
+/// Returns the same `Error`s as `run`.
+pub fn run_many(blc_program: &[u8], args: &[&[u8]]) -> Result<String, Error> {
+    let program = ClosedTerm::from_bits(blc_program)?.into_term();
+    let mut calculation = args.iter().fold(program, |applied, arg| app(applied, encode(arg)));
+    evaluate(&mut calculation, Options::default())?;
+
+    decoded(calculation)
+}
+
+/// Executes a binary lambda calculus program like `run` does, but with the given `Options`.
+///
+/// # Example
+/// ```
+/// use blc::execution::{run_with, Options, Strategy};
+/// use blc::execution::Error::{OutputLimitExceeded, SizeLimitExceeded};
+/// use blc::execution::Input::Bytes;
+///
+/// let reverse_blc = b"0001011001000110100000000001011100111110111100001011011110110000010";
+/// let options = Options { under_binders: false, ..Options::default() };
+/// let limited = Options { max_output: 4, ..Options::default() };
+/// let lazy = Options { strategy: Strategy::CallByNeed, ..Options::default() };
+/// let small = Options { max_size: 100, ..Options::default() };
+///
+/// assert_eq!(run_with(&*reverse_blc, Bytes(b"herp derp"), options), Ok("pred preh".into()));
+/// assert_eq!(run_with(&*reverse_blc, Bytes(b"herp derp"), limited), Err(OutputLimitExceeded));
+/// assert_eq!(run_with(&*reverse_blc, Bytes(b"herp derp"), lazy), Ok("pred preh".into()));
+/// assert_eq!(run_with(&*reverse_blc, Bytes(b"herp derp"), small), Err(SizeLimitExceeded));
+/// ```
+/// # Errors
+///
+/// Returns an `Error` if the program or the input are not valid, closed BLC terms or if the output
+/// or an intermediate term exceed the limits set in the `options`.
+pub fn run_with(blc_program: &[u8], input: Input, options: Options) -> Result<String, Error> {
+    run_term(ClosedTerm::from_bits(blc_program)?, input, options)
+}
+
+/// Executes a binary lambda calculus program like `run` does, but returns the output as bytes,
+/// so output that is not valid UTF-8 is preserved.
+///
+/// # Example
+/// ```
+/// use blc::execution::run_bytes;
+/// use blc::execution::Input::Bytes;
+///
+/// let reverse_blc = b"0001011001000110100000000001011100111110111100001011011110110000010";
+///
+/// assert_eq!(run_bytes(&*reverse_blc, Bytes(b"\x80\xff")), Ok(b"\xff\x80".to_vec()));
+/// ```
+/// # Errors
+///
+/// Returns the same `Error`s as `run`.
+pub fn run_bytes(blc_program: &[u8], input: Input) -> Result<Vec<u8>, Error> {
+    let mut calculation = prepare(blc_program, input)?;
+    evaluate(&mut calculation, Options::default())?;
+
+    decoded_bytes(calculation)
+}
+
+/// Executes a binary lambda calculus program in bit mode, like the `-b` option of John Tromp's
+/// universal machine does: the program receives a list of bits, one for every byte of the input,
+/// which only contributes its least significant bit, and its output list of bits is returned as
+/// the characters `0` and `1`.
+///
+/// # Example
+/// ```
+/// use blc::execution::run_bits;
+///
+/// let reverse_blc = b"0001011001000110100000000001011100111110111100001011011110110000010";
+///
+/// assert_eq!(run_bits(&*reverse_blc, b"0011"), Ok(b"1100".to_vec()));
+/// ```
+/// # Errors
+///
+/// Returns the same `Error`s as `run`.
+pub fn run_bits(blc_program: &[u8], input: &[u8]) -> Result<Vec<u8>, Error> {
+    let options = Options { io_mode: IoMode::Bits, ..Options::default() };
+
+    Ok(run_with(blc_program, Input::Bytes(input), options)?.into_bytes())
+}
+
+/// Executes a binary lambda calculus program like `run_bytes` does, but writes every byte of the
+/// output to `writer` as soon as it is known instead of returning the whole output at the end.
+///
+/// The output list is evaluated one cell at a time and only the head of each cell is normalized,
+/// so the output of programs that run for long or never finish becomes available gradually; the
+/// `writer` is flushed after every written cell.
+///
+/// # Example
+/// ```
+/// use blc::execution::run_streaming;
+/// use blc::execution::Input::Bytes;
+///
+/// let reverse_blc = b"0001011001000110100000000001011100111110111100001011011110110000010";
+/// let mut output = Vec::new();
+///
+/// assert_eq!(run_streaming(&*reverse_blc, Bytes(b"herp"), &mut output), Ok(()));
+/// assert_eq!(output, b"preh");
+/// ```
+/// # Errors
+///
+/// Returns `Error::Io` if the output can't be written, or the same `Error`s as `run_bytes`.
+pub fn run_streaming<W: Write>(
+    blc_program: &[u8],
+    input: Input,
+    mut writer: W
+) -> Result<(), Error> {
+    let mut rest = prepare(blc_program, input)?;
+    let mut written = 0;
+
+    loop {
+        rest.reduce(CBN, 0);
+        if let Abs(ref mut body) = rest { body.reduce(CBN, 0); }
+
+        let (element, tail) = match constructors::cell_mut(&mut rest) {
+            Some((head, tail)) => {
+                head.reduce(NOR, 0);
+                // anything else than a byte or a bit is decoded as a whole, like `run_bytes` does
+                if !(is_list(head) || *head == tru() || *head == fls()) { break }
+                (mem::replace(head, fls()), mem::replace(tail, fls()))
+            },
+            None => break
+        };
+
+        let element = decoded_bytes(abs(app!(Var(1), element, fls())))
+            .or(Err(OutputNotBytes { position: written }))?;
+        write_flushed(&mut writer, &element)?;
+        written += 1;
+        rest = tail;
+    }
+
+    rest.reduce(NOR, 0);
+    let rest = decoded_bytes(rest).map_err(|error| match error {
+        OutputNotBytes { position } => OutputNotBytes { position: written + position },
+        error => error
+    })?;
+    write_flushed(&mut writer, &rest)
+}
+
+fn write_flushed<W: Write>(writer: &mut W, bytes: &[u8]) -> Result<(), Error> {
+    writer.write_all(bytes).and_then(|_| writer.flush()).map_err(|e| Io(e.kind()))
+}
+
+/// Executes a binary lambda calculus program like `run_with` does, but returns the resulting term
+/// instead of decoding it, so it can be inspected, applied further or encoded again.
+///
+/// # Example
+/// ```
+/// use blc::execution::{run_to_term, Options};
+/// use blc::execution::Input::Bits;
+/// use blc::encoding::binary::to_bits;
+///
+/// let k = b"0000110";
+/// let result = run_to_term(k, Bits(b"0010"), Options::default()).unwrap();
+///
+/// assert_eq!(to_bits(&result), b"000010"); // λλ1
+/// ```
+/// # Errors
+///
+/// Returns the same `Error`s as `run_with`, except for the ones about decoding the output.
+pub fn run_to_term(blc_program: &[u8], input: Input, options: Options) -> Result<Term, Error> {
+    let program = ClosedTerm::from_bits(blc_program)?.into_term();
+    let mut calculation = apply_input_in(program, input, options.io_mode)?;
+    evaluate(&mut calculation, options)?;
+
+    Ok(calculation)
+}
+
+/// Executes a binary lambda calculus program like `run` does, but aborts it after `max_steps`
+/// β-reductions (`0` means no limit).
+///
+/// # Example
+/// ```
+/// use blc::execution::run_limited;
+/// use blc::execution::Error::StepLimitExceeded;
+/// use blc::execution::Input::{Bytes, Nothing};
+///
+/// let reverse_blc = b"0001011001000110100000000001011100111110111100001011011110110000010";
+/// let omega = b"010001101000011010";
+///
+/// assert_eq!(run_limited(&*reverse_blc, Bytes(b"herp"), 1_000), Ok("preh".into()));
+/// assert_eq!(run_limited(&*omega, Nothing, 1_000), Err(StepLimitExceeded { steps: 1_000 }));
+/// ```
+/// # Errors
+///
+/// Returns `Error::StepLimitExceeded` if the normal form is not reached within `max_steps`
+/// β-reductions, or the same `Error`s as `run`.
+pub fn run_limited(blc_program: &[u8], input: Input, max_steps: usize) -> Result<String, Error> {
+    let mut calculation = prepare(blc_program, input)?;
+    if max_steps == 0 {
+        calculation.reduce(NOR, 0);
+    } else if calculation.reduce(NOR, max_steps.saturating_add(1)) > max_steps {
+        // the extra reduction tells whether the normal form was reached in time
+        return Err(StepLimitExceeded { steps: max_steps })
+    }
+
+    decoded(calculation)
+}
+
+/// Executes a binary lambda calculus program like `run` does, but gives up on it once `timeout`
+/// passes.
+///
+/// The program is reduced by a separate thread, so the deadline is kept even if a single
+/// reduction takes long; the thread is told to stop and exits after its current reduction.
+///
+/// # Example
+/// ```
+/// use blc::execution::run_with_timeout;
+/// use blc::execution::Error::Timeout;
+/// use blc::execution::Input::{Bytes, Nothing};
+/// use std::time::{Duration, Instant};
+///
+/// let reverse_blc = b"0001011001000110100000000001011100111110111100001011011110110000010";
+/// let omega = b"010001101000011010";
+/// let timeout = Duration::from_millis(100);
+///
+/// assert_eq!(run_with_timeout(&*reverse_blc, Bytes(b"herp"), timeout), Ok("preh".into()));
+/// assert_eq!(run_with_timeout(&*omega, Nothing, timeout), Err(Timeout { after: timeout }));
+/// ```
+/// # Errors
+///
+/// Returns `Error::Timeout` if the execution doesn't finish in time, or the same `Error`s as
+/// `run`.
+pub fn run_with_timeout(
+    blc_program: &[u8],
+    input: Input,
+    timeout: Duration
+) -> Result<String, Error> {
+    let mut calculation = prepare(blc_program, input)?;
+    let token = CancellationToken::new();
+    let (sender, receiver) = mpsc::channel();
+
+    let cancelled_by_caller = token.clone();
+    thread::spawn(move || {
+        if reduce_cancellable(&mut calculation, &cancelled_by_caller).is_ok() {
+            // the caller may have given up already, in which case nobody is listening
+            let _ = sender.send(decoded(calculation));
+        }
+    });
+
+    receiver.recv_timeout(timeout).unwrap_or_else(|_| {
+        token.cancel();
+        Err(Timeout { after: timeout })
+    })
+}
+
+/// The number of reductions performed between checks for cancellation.
+const CANCELLATION_CHECK_INTERVAL: usize = 16;
+
+/// A flag that aborts the executions it is passed to once it is set, possibly by another thread.
+///
+/// # Example
+/// ```
+/// use blc::execution::{run_cancellable, CancellationToken};
+/// use blc::execution::Error::Aborted;
+/// use blc::execution::Input::Nothing;
+/// use std::thread;
+/// use std::time::Duration;
+///
+/// let omega = b"010001101000011010";
+/// let token = CancellationToken::new();
+///
+/// let canceller = token.clone();
+/// thread::spawn(move || {
+///     thread::sleep(Duration::from_millis(10));
+///     canceller.cancel();
+/// });
+///
+/// assert_eq!(run_cancellable(omega, Nothing, &token), Err(Aborted));
+/// assert!(token.is_cancelled());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    /// Creates a token that is not cancelled yet.
+    pub fn new() -> Self {
+        CancellationToken::default()
+    }
+
+    /// Cancels the executions the token or any of its clones were passed to.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    /// Returns `true` if the token was cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Executes a binary lambda calculus program like `run` does, but aborts it once `token` is
+/// cancelled; the token is checked every few reductions.
+///
+/// # Errors
+///
+/// Returns `Error::Aborted` if the `token` is cancelled before the execution finishes, or the same
+/// `Error`s as `run`.
+pub fn run_cancellable(
+    blc_program: &[u8],
+    input: Input,
+    token: &CancellationToken
+) -> Result<String, Error> {
+    let mut calculation = prepare(blc_program, input)?;
+    reduce_cancellable(&mut calculation, token)?;
+
+    decoded(calculation)
+}
+
+/// Reduces `term` to its normal form in normal order unless `token` is cancelled first.
+fn reduce_cancellable(term: &mut Term, token: &CancellationToken) -> Result<(), Error> {
+    while !token.is_cancelled() {
+        if term.reduce(NOR, CANCELLATION_CHECK_INTERVAL) < CANCELLATION_CHECK_INTERVAL {
+            return Ok(())
+        }
+    }
+    Err(Aborted)
+}
+
+/// Executes a binary lambda calculus program like `run` does, reducing it one step at a time in
+/// normal order and calling `observer` with the number of performed steps and the current term
+/// after every `every` steps (`0` is treated as `1`); the execution is aborted as soon as the
+/// `observer` returns `false`.
+///
+/// # Example
+/// ```
+/// use blc::execution::run_traced;
+/// use blc::execution::Error::Aborted;
+/// use blc::execution::Input::{Bytes, Nothing};
+///
+/// let reverse_blc = b"0001011001000110100000000001011100111110111100001011011110110000010";
+/// let omega = b"010001101000011010";
+/// let mut calls = 0;
+/// let output = run_traced(reverse_blc, Bytes(b"herp"), 10, |_, _| { calls += 1; true });
+///
+/// assert_eq!(output, Ok("preh".into()));
+/// assert!(calls > 0);
+/// assert_eq!(run_traced(omega, Nothing, 1, |step, _| step < 1_000), Err(Aborted));
+/// ```
+/// # Errors
+///
+/// Returns `Error::Aborted` if the `observer` aborts the execution, or the same `Error`s as `run`.
+pub fn run_traced<F>(
+    blc_program: &[u8],
+    input: Input,
+    every: usize,
+    mut observer: F
+) -> Result<String, Error>
+    where F: FnMut(usize, &Term) -> bool
+{
+    let mut execution = Execution::from_program(blc_program, input)?;
+    let every = every.max(1);
+
+    while execution.step() {
+        if execution.steps() % every == 0 && !observer(execution.steps(), execution.term()) {
+            return Err(Aborted)
+        }
+    }
+
+    decoded(execution.into_term())
+}
+
+/// Executes an already parsed and validated program with the given `Options`.
+///
+/// # Example
+/// ```
+/// use blc::execution::{run_term, ClosedTerm, Options};
+/// use blc::execution::Input::Bytes;
+///
+/// let identity = ClosedTerm::from_bits(b"0010").unwrap();
+///
+/// assert_eq!(run_term(identity, Bytes(b"herp derp"), Options::default()), Ok("herp derp".into()));
+/// ```
+/// # Errors
+///
+/// Returns an `Error` if the input is not a valid, closed BLC term or if the output exceeds the
+/// limit set in the `options`.
+pub fn run_term(program: ClosedTerm, input: Input, options: Options) -> Result<String, Error> {
+    let mut calculation = apply_input_in(program.into_term(), input, options.io_mode)?;
+    evaluate(&mut calculation, options)?;
+
+    options.io_mode.decode(calculation)
+}
+
+/// Executes an already parsed and validated program like `run_term` does, but returns the output
+/// as bytes, so output that is not valid UTF-8 is preserved.
+///
+/// # Errors
+///
+/// Returns the same `Error`s as `run_term`.
+pub fn run_term_bytes(
+    program: ClosedTerm,
+    input: Input,
+    options: Options
+) -> Result<Vec<u8>, Error> {
+    let mut calculation = apply_input_in(program.into_term(), input, options.io_mode)?;
+    evaluate(&mut calculation, options)?;
+
+    options.io_mode.decode_bytes(calculation)
+}
+
+/// Evaluates an applied program as far as the `options` require; returns the number of performed
+/// reductions.
+fn evaluate(calculation: &mut Term, options: Options) -> Result<usize, Error> {
+    let reductions = reduce_output(calculation, options)?;
+    if options.eta { eta_reduce(calculation); }
+
+    Ok(reductions)
+}
+
+fn reduce_output(calculation: &mut Term, options: Options) -> Result<usize, Error> {
+    let reductions = match options.strategy {
+        _ if options.max_size != 0 || options.divergence_window != 0 => {
+            evaluate_bounded(calculation, options)?.0
+        },
+        Strategy::Normal => return evaluate_normal(calculation, options),
+        Strategy::CallByNeed => lazy::Lazy.reduce(calculation, 0),
+        Strategy::Cek => cek::Cek.reduce(calculation, 0),
+        Strategy::Iterative => iterative::Iterative.reduce(calculation, 0),
+        Strategy::Closures => closures::Closures.reduce(calculation, 0),
+        strategy => machine::SmallStep { strategy }.reduce(calculation, 0)
+    };
+
+    if options.max_output != 0 {
+        constructors::force_list(calculation, options.max_output, false)?;
+    }
+    Ok(reductions)
+}
+
+/// Reduces an applied program one step at a time, checking its size and whether it cycles before
+/// every step; returns the number of performed reductions and the largest size the term had.
+fn evaluate_bounded(calculation: &mut Term, options: Options) -> Result<(usize, usize), Error> {
+    let (mut steps, mut reductions, mut peak_size) = (0, 0, 0);
+    let mut cycles = cycles::Cycles::new(options.divergence_window);
+
+    loop {
+        let size = size(calculation);
+        if options.max_size != 0 && size > options.max_size { return Err(SizeLimitExceeded) }
+        peak_size = peak_size.max(size);
+
+        if options.divergence_window != 0 {
+            if let Some(period) = cycles.record(calculation) {
+                return Err(LikelyDivergent { steps, period })
+            }
+        }
+
+        match options.strategy.step(calculation) {
+            0 => return Ok((reductions, peak_size)),
+            n => {
+                steps += 1;
+                reductions += n;
+            }
+        }
+    }
+}
+
+fn evaluate_normal(calculation: &mut Term, options: Options) -> Result<usize, Error> {
+    if options.max_output != 0 {
+        constructors::force_list(calculation, options.max_output, options.under_binders)
+    } else if options.under_binders {
+        Ok(loops::normalize(calculation))
+    } else {
+        Ok(constructors::force(calculation))
+    }
+}
+
+/// The cost of an execution in the model used in John Tromp's papers on BLC: the number of
+/// β-reductions plus the number of bits of the program and of the input that were read.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct Cost {
+    /// the number of performed β-reductions
+    pub reductions: usize,
+    /// the length of the program, in bits
+    pub program_bits: usize,
+    /// the length of the input, in bits, as encoded in the `IoMode` of the execution; since the
+    /// input is encoded before the execution, all of it counts as read
+    pub input_bits: usize
+}
+
+impl Cost {
+    /// Returns the total cost.
+    pub fn total(&self) -> usize {
+        self.reductions + self.program_bits + self.input_bits
+    }
+}
+
+/// Executes a binary lambda calculus program like `run_with` does and also returns its `Cost`.
+///
+/// # Example
+/// ```
+/// use blc::execution::{run_with_cost, Cost, Options};
+/// use blc::execution::Input::Bytes;
+///
+/// let identity = b"0010";
+/// let (output, cost) = run_with_cost(identity, Bytes(b"a"), Options::default()).unwrap();
+///
+/// assert_eq!(output, "a");
+/// assert_eq!(cost, Cost { reductions: 1, program_bits: 4, input_bits: 8 });
+/// assert_eq!(cost.total(), 13);
+/// ```
+/// # Errors
+///
+/// Returns the same `Error`s as `run_with`.
+pub fn run_with_cost(
+    blc_program: &[u8],
+    input: Input,
+    options: Options
+) -> Result<(String, Cost), Error> {
+    let program = ClosedTerm::from_bits(blc_program)?.into_term();
+    let program_bits = to_bits(&program).len();
+    let input_bits = match (input, options.io_mode) {
+        (Input::Nothing, _)   => 0,
+        (Input::Bits(arg), _) => arg.iter().filter(|b| !b" \t\r\n".contains(b)).count(),
+        (Input::Bytes(arg), IoMode::Bytes) => arg.len() * 8,
+        (Input::Bytes(arg), IoMode::Bits)  => arg.len() // a bit per byte
+    };
+
+    let mut calculation = apply_input_in(program, input, options.io_mode)?;
+    let reductions = evaluate(&mut calculation, options)?;
+    let output = options.io_mode.decode(calculation)?;
+
+    Ok((output, Cost { reductions, program_bits, input_bits }))
+}
+
+/// Statistics of an execution.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct Stats {
+    /// the number of performed β-reductions
+    pub reductions: usize,
+    /// the largest size of the term during the execution, in the sense of `analysis::size`
+    pub peak_size: usize,
+    /// the size of the term the output was decoded from
+    pub final_size: usize,
+    /// the time the whole execution took, including parsing the program and decoding the output
+    pub elapsed: Duration
+}
+
+/// Executes a binary lambda calculus program like `run_with` does and also returns its `Stats`.
+///
+/// Measuring the size of the term requires reducing it one step at a time with the `Strategy` of
+/// the `options`, like when `max_size` is set, which is slower than an unmeasured execution.
+///
+/// # Example
+/// ```
+/// use blc::execution::{run_with_stats, Options};
+/// use blc::execution::Input::Bytes;
+///
+/// let identity = b"0010";
+/// let (output, stats) = run_with_stats(identity, Bytes(b"a"), Options::default()).unwrap();
+///
+/// assert_eq!(output, "a");
+/// assert_eq!(stats.reductions, 1);
+/// assert!(stats.peak_size > stats.final_size);
+/// ```
+/// # Errors
+///
+/// Returns the same `Error`s as `run_with`.
+pub fn run_with_stats(
+    blc_program: &[u8],
+    input: Input,
+    options: Options
+) -> Result<(String, Stats), Error> {
+    let start = Instant::now();
+    let program = ClosedTerm::from_bits(blc_program)?.into_term();
+    let mut calculation = apply_input_in(program, input, options.io_mode)?;
+
+    let (reductions, peak_size) = evaluate_bounded(&mut calculation, options)?;
+    if options.max_output != 0 {
+        constructors::force_list(&mut calculation, options.max_output, false)?;
+    }
+    if options.eta { eta_reduce(&mut calculation); }
+    let final_size = size(&calculation);
+    let output = options.io_mode.decode(calculation)?;
+
+    Ok((output, Stats { reductions, peak_size, final_size, elapsed: start.elapsed() }))
+}
+
+/// Executes a binary lambda calculus program like `run_with` does and also returns the SHA-256
+/// digest of the raw bytes of its output.
+///
+/// # Example
+/// ```
+/// use blc::digest::Digest;
+/// use blc::execution::{run_with_digest, Options};
+/// use blc::execution::Input::Bytes;
+///
+/// let reverse_blc = b"0001011001000110100000000001011100111110111100001011011110110000010";
+/// let options = Options::default();
+/// let (output, digest) = run_with_digest(reverse_blc, Bytes(b"abc"), options).unwrap();
+///
+/// assert_eq!(output, "cba");
+/// assert_eq!(digest, Digest::of(b"cba"));
+/// ```
+/// # Errors
+///
+/// Returns the same `Error`s as `run_with`.
+pub fn run_with_digest(
+    blc_program: &[u8],
+    input: Input,
+    options: Options
+) -> Result<(String, Digest), Error> {
+    let output = run_with(blc_program, input, options)?;
+    let digest = Digest::of_output(&output);
+
+    Ok((output, digest))
+}
+
+/// Reduces a term in any `TermRep`resentation to its normal form.
+///
+/// # Example
+/// ```
+/// use blc::execution::normalize;
+/// use blc::encoding::binary::{from_bits, to_bits};
+///
+/// let k_i = from_bits(b"0100001100010").unwrap(); // (λλ2)(λ1)
+///
+/// assert_eq!(to_bits(&normalize(&k_i)), b"000010"); // λλ1
+/// ```
+pub fn normalize<T: TermRep>(term: &T) -> T {
+    convert(&beta(convert::<T, Term>(term), NOR, 0))
+}
+
+/// Reduces a `Term` to its head normal form and then does the same with the arguments of its head
+/// variable, down to the given depth of nesting; deeper subterms are left unevaluated. Depth `0`
+/// leaves the term intact, and a depth large enough results in the normal form.
+///
+/// It makes it possible to peek at the outermost structure of results too big to normalize.
+///
+/// # Example
+/// ```
+/// extern crate blc;
+/// extern crate lambda_calculus;
+///
+/// use blc::execution::reduce_to_depth;
+/// use lambda_calculus::{parse, DeBruijn};
+///
+/// let pair = parse("(λ1)(λ1((λ1)(λ1))((λ1)(λ1)))", DeBruijn).unwrap();
+///
+/// assert_eq!(reduce_to_depth(pair.clone(), 1), parse("λ1((λ1)(λ1))((λ1)(λ1))", DeBruijn).unwrap());
+/// assert_eq!(reduce_to_depth(pair, 2), parse("λ1(λ1)(λ1)", DeBruijn).unwrap());
+/// ```
+pub fn reduce_to_depth(mut term: Term, depth: usize) -> Term {
+    if depth == 0 { return term }
+
+    term.reduce(HSP, 0);
+    head_args_to_depth(&mut term, depth - 1);
+
+    term
+}
+
+fn head_args_to_depth(term: &mut Term, depth: usize) {
+    match *term {
+        Abs(ref mut body) => head_args_to_depth(body, depth),
+        App(ref mut lhs, ref mut rhs) => {
+            head_args_to_depth(lhs, depth);
+            let arg = mem::replace(&mut **rhs, Var(0));
+            **rhs = reduce_to_depth(arg, depth);
+        },
+        Var(_) => ()
+    }
+}
+
+/// Reduces a term in any `TermRep`resentation to its βη-normal form, so that terms that differ
+/// only by η-expansions are normalized to the same term.
+///
+/// # Example
+/// ```
+/// use blc::execution::normalize_eta;
+/// use blc::encoding::binary::{from_bits, to_bits};
+///
+/// let succ_zero = from_bits(b"01000000011100101111011010000010").unwrap(); // succ 0
+///
+/// assert_eq!(to_bits(&normalize_eta(&succ_zero)), b"0010"); // λ1, the η-normal form of 1
+/// ```
+pub fn normalize_eta<T: TermRep>(term: &T) -> T {
+    let mut normal = beta(convert::<T, Term>(term), NOR, 0);
+    eta_reduce(&mut normal);
+
+    convert(&normal)
+}
+
+/*
+#[cfg(test)]
+mod test {
+    use super::*;
+    use encoding::binary::{decompress, to_bits};
+
+    #[test] /* WIP; this one parses properly, but doesn't return the expected result */
+    fn hilbert() {
+        // program code from http://www.ioccc.org/2012/tromp/hilbert.Blc
+        let hilbert_compressed =
+            [0x18, 0x18, 0x18, 0x18, 0x11, 0x11, 0x54, 0x68, 0x06, 0x04, 0x15, 0x5f, 0xf0, 0x41,
+             0x9d, 0xf9, 0xde, 0x16, 0xff, 0xfe, 0x5f, 0x3f, 0xef, 0xf6, 0x15, 0xff, 0x94, 0x68,
+             0x40, 0x58, 0x11, 0x7e, 0x05, 0xcb, 0xfe, 0xbc, 0xbf, 0xee, 0x86, 0xcb, 0x94, 0x68,
+             0x16, 0x00, 0x5c, 0x0b, 0xfa, 0xcb, 0xfb, 0xf7, 0x1a, 0x85, 0xe0, 0x5c, 0xf4, 0x14,
+             0xd5, 0xfe, 0x08, 0x18, 0x0b, 0x04, 0x8d, 0x08, 0x00, 0xe0, 0x78, 0x01, 0x64, 0x45,
+             0xff, 0xe5, 0xff, 0x7f, 0xff, 0xfe, 0x5f, 0xff, 0x2f, 0xc0, 0x2f, 0x7a, 0xd9, 0x7f,
+             0x5b, 0xff, 0xff, 0xfb, 0xff, 0xfc, 0xaa, 0xff, 0xf7, 0x81, 0x7f, 0xfa, 0xdf, 0x76,
+             0x69, 0x54, 0x68, 0x06, 0x01, 0x57, 0xf7, 0xe1, 0x60, 0x5c, 0x13, 0xfe, 0x80, 0xb2,
+             0x2c, 0x18, 0x58, 0x1b, 0xfe, 0x5c, 0x10, 0x42, 0xff, 0x80, 0x5d, 0xee, 0xc0, 0x6c,
+             0x2c, 0x0c, 0x06, 0x08, 0x19, 0x1a, 0x00, 0x16, 0x7f, 0xbc, 0xbc, 0xfd, 0xf6, 0x5f,
+             0x7c, 0x0a, 0x20];
+
+        let hilbert_blc = decompress(&hilbert_compressed);
+        let arg = b"1234";
+
+        assert_eq!(run(&hilbert_blc[..], Bytes(&arg[..])), Ok("WIP".into()));
+    }
+*/
+/*
+    #[test] /* WIP; this just returns λ1 */
+    fn brainfuck() {
+        // program code from http://www.ioccc.org/2012/tromp/bf.Blc
+        let bf_interpreter_compressed =
+            [0x44, 0x51, 0xa1, 0x01, 0x84, 0x55, 0xd5, 0x02, 0xb7, 0x70, 0x30, 0x22, 0xff, 0x32,
+             0xf0, 0x00, 0xbf, 0xf9, 0x85, 0x7f, 0x5e, 0xe1, 0x6f, 0x95, 0x7f, 0x7d, 0xee, 0xc0,
+             0xe5, 0x54, 0x68, 0x00, 0x58, 0x55, 0xfd, 0xfb, 0xe0, 0x45, 0x57, 0xfd, 0xeb, 0xfb,
+             0xf0, 0xb6, 0xf0, 0x2f, 0xd6, 0x07, 0xe1, 0x6f, 0x73, 0xd7, 0xf1, 0x14, 0xbc, 0xc0,
+             0x0b, 0xff, 0x2e, 0x1f, 0xa1, 0x6f, 0x66, 0x17, 0xe8, 0x5b, 0xef, 0x2f, 0xcf, 0xff,
+             0x13, 0xff, 0xe1, 0xca, 0x34, 0x20, 0x0a, 0xc8, 0xd0, 0x0b, 0x99, 0xee, 0x1f, 0xe5,
+             0xff, 0x7f, 0x5a, 0x6a, 0x1f, 0xff, 0x0f, 0xff, 0x87, 0x9d, 0x04, 0xd0, 0xab, 0x00,
+             0x05, 0xdb, 0x23, 0x40, 0xb7, 0x3b, 0x28, 0xcc, 0xc0, 0xb0, 0x6c, 0x0e, 0x74, 0x10];
+
+        let bf_interpreter_blc = decompress(&bf_interpreter_compressed);
+        let bf_hello =
+            b"++++++++[>++++[>++>+++>+++>+<<<<-]>+>+>->>+[<]<-]>>.>---.+++++++..+++.>>.<-.<.+++.------.--------.>>+.>++.";
+
+        assert_eq!(run(&bf_interpreter_blc, Bytes(&bf_hello[..])), Ok("Hello World!".into()));
+    }
+}
+*/
@@ -0,0 +1,401 @@
+//! Optimal reduction with interaction nets (experimental)
+//!
+//! `Optimal` translates a term into an interaction net, in which abstractions, applications and
+//! the duplication of variables that occur several times are nodes connected by wires, and reduces
+//! it by rewriting pairs of nodes facing each other. A β-reduction only reconnects wires, and
+//! duplication happens one node at a time and only as far as it is needed, so redexes are never
+//! duplicated: a redex in a shared subterm is contracted once for all its uses, even if the
+//! sharing can't be expressed as a term, which is what makes the reduction optimal in Lévy's
+//! sense.
+//!
+//! This is Lamping's abstract algorithm, i.e. optimal reduction without the bookkeeping nodes (the
+//! "oracle") that tell apart duplications of duplications. Without them, the results are only
+//! guaranteed to be correct for terms typable in elementary affine logic, which include Church
+//! numeral arithmetic like addition, multiplication and exponentiation, but not for every term;
+//! this backend is meant for experiments with reduction complexity, not for running programs.
+
+use lambda_calculus::*;
+use execution::machine::Machine;
+use std::collections::{HashMap, VecDeque};
+
+/// An optimal reducer for terms typable in elementary affine logic.
+///
+/// All the redexes of the net are contracted, including the ones in arguments that are discarded,
+/// so terms that only have a normal form thanks to discarding a divergent argument are never
+/// normalized. If it runs out of β-reductions before reaching the normal form, if the net grows
+/// too large, or if the result can't be read back as a term, the term is left as it
+/// was.
+///
+/// # Example
+/// ```
+/// extern crate blc;
+/// extern crate lambda_calculus;
+///
+/// use blc::execution::machine::{Machine, BigStep};
+/// use blc::execution::optimal::Optimal;
+/// use lambda_calculus::*;
+/// use lambda_calculus::data::num::church::mul;
+///
+/// let product = app!(mul(), 4.into_church(), 4.into_church());
+///
+/// assert_eq!(Optimal.normalize(product.clone()), 16.into_church());
+/// assert!(Optimal.reduce(&mut product.clone(), 0) <= BigStep.reduce(&mut product.clone(), 0));
+/// ```
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub struct Optimal;
+
+impl Machine for Optimal {
+    fn reduce(&mut self, term: &mut Term, limit: usize) -> usize {
+        let mut net = Net::default();
+        net.node(Kind::Root);
+        net.build(term, ROOT_PORT, &mut Vec::new());
+
+        match net.run(limit) {
+            Ok(count) => {
+                if let Some(normal) = net.read_back(net.peer(ROOT_PORT)) { *term = normal }
+                count
+            },
+            Err(count) => count
+        }
+    }
+}
+
+/// A node of the net and the index of one of its ports.
+type Port = (usize, usize);
+
+/// The node the net is connected to, at its only port.
+const ROOT_PORT: Port = (0, 0);
+
+/// The kinds of nodes; port `0` is the principal port of every node, through which it interacts.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Kind {
+    /// the context of the whole term
+    Root,
+    /// erases whatever it is connected to
+    Eraser,
+    /// a free variable of the reduced term, by its De Bruijn index counted from the root
+    Free(usize),
+    /// an abstraction: port `1` is its variable and port `2` its body
+    Abs,
+    /// an application: port `0` is the function, port `1` the argument and port `2` the result
+    App,
+    /// a duplicator with the given label: port `0` is the duplicated term, ports `1` and `2` are
+    /// its copies
+    Dup(usize)
+}
+
+#[derive(Default)]
+struct Net {
+    kinds: Vec<Kind>,
+    wires: Vec<[Port; 3]>,
+    /// pairs of nodes connected by their principal ports
+    active: VecDeque<(usize, usize)>,
+    labels: usize
+}
+
+impl Net {
+    fn node(&mut self, kind: Kind) -> usize {
+        self.kinds.push(kind);
+        self.wires.push([(0, 0); 3]);
+        self.kinds.len() - 1
+    }
+
+    fn peer(&self, (node, port): Port) -> Port {
+        self.wires[node][port]
+    }
+
+    fn link(&mut self, a: Port, b: Port) {
+        self.wires[a.0][a.1] = b;
+        self.wires[b.0][b.1] = a;
+
+        if a.1 == 0 && b.1 == 0 && interacts(self.kinds[a.0], self.kinds[b.0]) {
+            self.active.push_back((a.0, b.0));
+        }
+    }
+
+    /// Adds `term`, under the abstractions with the given nodes, innermost last, along with the
+    /// ports their variables occur at, and connects it to `destination`.
+    fn build(&mut self, term: &Term, destination: Port, binders: &mut Vec<(usize, Vec<Port>)>) {
+        match *term {
+            Var(i) if i <= binders.len() => {
+                let depth = binders.len();
+                binders[depth - i].1.push(destination);
+            },
+            Var(i) => {
+                let free = self.node(Kind::Free(i - binders.len()));
+                self.link((free, 0), destination);
+            },
+            Abs(ref body) => {
+                let abstraction = self.node(Kind::Abs);
+                self.link((abstraction, 0), destination);
+
+                binders.push((abstraction, Vec::new()));
+                self.build(body, (abstraction, 2), binders);
+                let (_, occurrences) = binders.pop().unwrap(); // safe - pushed above
+                self.share((abstraction, 1), occurrences);
+            },
+            App(ref lhs, ref rhs) => {
+                let application = self.node(Kind::App);
+                self.link((application, 2), destination);
+                self.build(lhs, (application, 0), binders);
+                self.build(rhs, (application, 1), binders);
+            }
+        }
+    }
+
+    /// Connects a variable to all its occurrences, through a chain of duplicators.
+    fn share(&mut self, variable: Port, mut occurrences: Vec<Port>) {
+        match occurrences.len() {
+            0 => {
+                let eraser = self.node(Kind::Eraser);
+                self.link(variable, (eraser, 0));
+            },
+            1 => self.link(variable, occurrences[0]),
+            _ => {
+                let last = occurrences.pop().unwrap(); // safe - at least 2 occurrences
+                let mut source = variable;
+
+                for occurrence in occurrences {
+                    let duplicator = self.node(Kind::Dup(self.labels));
+                    self.labels += 1;
+                    self.link(source, (duplicator, 0));
+                    self.link((duplicator, 1), occurrence);
+                    source = (duplicator, 2);
+                }
+                self.link(source, last);
+            }
+        }
+    }
+
+    /// Rewrites active pairs until there are none left; returns the number of β-reductions.
+    ///
+    /// Only β-reductions count against the `limit`, but every other interaction except the
+    /// annihilation of duplicators adds nodes, so capping their number bounds the work between
+    /// β-reductions as well.
+    fn run(&mut self, limit: usize) -> Result<usize, usize> {
+        let mut count = 0;
+
+        while let Some((a, b)) = self.active.pop_front() {
+            if self.kinds.len() > MAX_NODES { return Err(count) }
+            let (a, b) = if rank(self.kinds[a]) <= rank(self.kinds[b]) { (a, b) } else { (b, a) };
+
+            match (self.kinds[a], self.kinds[b]) {
+                (Kind::Abs, Kind::App) => {
+                    if limit != 0 && count == limit { return Err(count) }
+                    count += 1;
+                    self.rewire(a, b);
+                },
+                (Kind::Dup(x), Kind::Dup(y)) if x == y => self.rewire(a, b),
+                (Kind::Eraser, other) => {
+                    for port in 1..arity(other) {
+                        let eraser = self.node(Kind::Eraser);
+                        let peer = self.peer((b, port));
+                        self.link((eraser, 0), peer);
+                    }
+                },
+                (Kind::Free(index), Kind::Dup(_)) => {
+                    for port in 1..3 {
+                        let free = self.node(Kind::Free(index));
+                        let peer = self.peer((b, port));
+                        self.link((free, 0), peer);
+                    }
+                },
+                _ => self.commute(a, b)
+            }
+        }
+
+        Ok(count)
+    }
+
+    /// Connects the auxiliary ports of `a` to the matching ones of `b`.
+    fn rewire(&mut self, a: usize, b: usize) {
+        for port in 1..3 {
+            let (lhs, rhs) = (self.peer((a, port)), self.peer((b, port)));
+            self.link(lhs, rhs);
+        }
+    }
+
+    /// Lets `a` and `b` pass through each other, making two copies of each.
+    fn commute(&mut self, a: usize, b: usize) {
+        let (a_kind, b_kind) = (self.kinds[a], self.kinds[b]);
+        let a_copies = [self.node(a_kind), self.node(a_kind)];
+        let b_copies = [self.node(b_kind), self.node(b_kind)];
+
+        for i in 0..2 {
+            let peer = self.peer((b, i + 1));
+            self.link((a_copies[i], 0), peer);
+            let peer = self.peer((a, i + 1));
+            self.link((b_copies[i], 0), peer);
+        }
+        for (i, &a_copy) in a_copies.iter().enumerate() {
+            for (j, &b_copy) in b_copies.iter().enumerate() {
+                self.link((a_copy, j + 1), (b_copy, i + 1));
+            }
+        }
+    }
+
+    /// Reads back the term connected to `root`; returns `None` if it is not a term or if it is
+    /// larger than `MAX_READ_BACK` nodes, which is bound to happen if the abstract algorithm
+    /// didn't handle the reduced term.
+    fn read_back(&self, root: Port) -> Option<Term> {
+        let mut tasks = vec![Task::Read(root)];
+        let mut terms = Vec::new();
+        let mut binders = Vec::new();
+        // which copy of the duplicators with every label is being read
+        let mut exits: HashMap<usize, Vec<usize>> = HashMap::new();
+        let mut read = 0;
+
+        while let Some(task) = tasks.pop() {
+            match task {
+                Task::Read((node, port)) => {
+                    read += 1;
+                    if read > MAX_READ_BACK { return None }
+
+                    match (self.kinds[node], port) {
+                        (Kind::Abs, 0) => {
+                            binders.push(node);
+                            tasks.push(Task::Abs);
+                            tasks.push(Task::Read(self.peer((node, 2))));
+                        },
+                        (Kind::Abs, 1) => {
+                            let position = binders.iter().rposition(|&binder| binder == node)?;
+                            terms.push(Var(binders.len() - position));
+                        },
+                        (Kind::App, 2) => {
+                            tasks.push(Task::App);
+                            tasks.push(Task::Read(self.peer((node, 1))));
+                            tasks.push(Task::Read(self.peer((node, 0))));
+                        },
+                        (Kind::Dup(label), 0) => {
+                            let exit = exits.get_mut(&label)?.pop()?;
+                            tasks.push(Task::Enter(label, exit));
+                            tasks.push(Task::Read(self.peer((node, exit))));
+                        },
+                        (Kind::Dup(label), exit) => {
+                            exits.entry(label).or_default().push(exit);
+                            tasks.push(Task::Leave(label));
+                            tasks.push(Task::Read(self.peer((node, 0))));
+                        },
+                        (Kind::Free(index), 0) => terms.push(Var(index + binders.len())),
+                        _ => return None
+                    }
+                },
+                Task::Abs => {
+                    binders.pop();
+                    let body = terms.pop()?;
+                    terms.push(abs(body));
+                },
+                Task::App => {
+                    let (rhs, lhs) = (terms.pop()?, terms.pop()?);
+                    terms.push(app(lhs, rhs));
+                },
+                Task::Enter(label, exit) => exits.entry(label).or_default().push(exit),
+                Task::Leave(label) => { exits.get_mut(&label)?.pop(); }
+            }
+        }
+
+        terms.pop()
+    }
+}
+
+/// The largest number of nodes the net of `Optimal` grows to.
+const MAX_NODES: usize = 1 << 20;
+
+/// The largest number of nodes of a term `Optimal` reads back.
+const MAX_READ_BACK: usize = 1 << 20;
+
+/// The work left while reading back a term.
+enum Task {
+    /// read back the term connected to the port
+    Read(Port),
+    /// wrap the last read term in an abstraction
+    Abs,
+    /// apply the second to last read term to the last one
+    App,
+    /// restore the exit taken through a duplicator with the label when reading its copy
+    Enter(usize, usize),
+    /// forget the exit taken through a duplicator with the label
+    Leave(usize)
+}
+
+fn interacts(a: Kind, b: Kind) -> bool {
+    match (a, b) {
+        (Kind::Root, _) | (_, Kind::Root) => false,
+        (Kind::Free(_), Kind::Dup(_)) | (Kind::Dup(_), Kind::Free(_)) => true,
+        (Kind::Free(_), Kind::Eraser) | (Kind::Eraser, Kind::Free(_)) => true,
+        (Kind::Free(_), _) | (_, Kind::Free(_)) => false, // a free variable applied to something
+        _ => true
+    }
+}
+
+/// Orders the kinds of interacting nodes, so every rule only has to handle one order.
+fn rank(kind: Kind) -> usize {
+    match kind {
+        Kind::Eraser => 0,
+        Kind::Free(_) => 1,
+        Kind::Abs => 2,
+        Kind::App => 3,
+        Kind::Dup(_) => 4,
+        Kind::Root => 5
+    }
+}
+
+fn arity(kind: Kind) -> usize {
+    match kind {
+        Kind::Root | Kind::Eraser | Kind::Free(_) => 1,
+        Kind::Abs | Kind::App | Kind::Dup(_) => 3
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use lambda_calculus::combinators::{I, K, O, S};
+    use lambda_calculus::data::num::church::{add, mul, pow};
+    use encoding::binary::from_bits;
+    use execution::machine::BigStep;
+
+    #[test]
+    fn normal_forms() {
+        let terms = vec![
+            app!(S(), K(), K()),
+            app!(K(), I(), I()),
+            app!(add(), 2.into_church(), 3.into_church()),
+            app!(mul(), 3.into_church(), app(I(), 4.into_church())),
+            app!(pow(), 2.into_church(), 3.into_church()),
+            abs(app(abs(app(Var(3), Var(1))), Var(2)))
+        ];
+
+        for term in terms {
+            assert_eq!(Optimal.normalize(term.clone()), BigStep.normalize(term));
+        }
+    }
+
+    #[test]
+    fn shared_redexes() {
+        // (λx. x x) (λy. (λz. z) y): the inner redex is contracted once, although it is only
+        // reached after the abstraction is duplicated
+        let term = app(abs(app(Var(1), Var(1))), abs(app(I(), Var(1))));
+
+        assert_eq!(Optimal.reduce(&mut term.clone(), 0), 3);
+        assert_eq!(BigStep.reduce(&mut term.clone(), 0), 4);
+    }
+
+    #[test]
+    fn limits() {
+        let mut product = app!(mul(), 3.into_church(), 3.into_church());
+        assert_eq!(Optimal.reduce(&mut product, 2), 2);
+        assert_eq!(product, app!(mul(), 3.into_church(), 3.into_church()));
+
+        // Ω is beyond the abstract algorithm, but it doesn't crash the reducer
+        let mut omega = O();
+        assert!(Optimal.reduce(&mut omega, 10) <= 10);
+        assert_eq!(omega, O());
+
+        // the net of this term grows without bound between its β-reductions
+        let term = from_bits(b"010100100001101000011000110").unwrap();
+        let mut copy = term.clone();
+        assert!(Optimal.reduce(&mut copy, 150) <= 150);
+        assert_eq!(copy, term);
+    }
+}
@@ -0,0 +1,90 @@
+//! Auxiliary input
+//!
+//! Probabilistic programs take a list of bits they can draw random choices from, an oracle, as
+//! a second argument after their input. `run_with_oracle` applies a program to both, and `Oracle`
+//! is a deterministic source of pseudo-random bits to build the oracle from, so the executions can
+//! be reproduced from the seed.
+
+use lambda_calculus::*;
+use encoding::lambda::encode_bits;
+use execution::{evaluate, prepare, Error, Input, Options};
+use execution::output::decoded;
+
+/// An endless stream of pseudo-random bits determined by a seed, generated with SplitMix64.
+///
+/// # Example
+/// ```
+/// use blc::execution::oracle::Oracle;
+///
+/// let bits = Oracle::new(42).take(16).collect::<Vec<bool>>();
+///
+/// assert_eq!(bits, Oracle::new(42).take(16).collect::<Vec<bool>>());
+/// assert_ne!(bits, Oracle::new(43).take(16).collect::<Vec<bool>>());
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Oracle(u64);
+
+impl Oracle {
+    /// Creates an oracle that generates the bits determined by `seed`.
+    pub fn new(seed: u64) -> Self { Oracle(seed) }
+
+    /// Returns the next `count` bits as the bytes `0` and `1`, the way `run_with_oracle` takes
+    /// them.
+    pub fn bits(&mut self, count: usize) -> Vec<u8> {
+        self.take(count).map(u8::from).collect()
+    }
+}
+
+impl Iterator for Oracle {
+    type Item = bool;
+
+    fn next(&mut self) -> Option<bool> {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+
+        Some((z ^ (z >> 31)) >> 63 == 1)
+    }
+}
+
+/// Executes a binary lambda calculus program like `run` does, but applies it to the encoding of
+/// the `oracle` bits as well, after the input; like the input of `run_bits`, only the least
+/// significant bit of every byte of the `oracle` is used.
+///
+/// # Example
+/// ```
+/// use blc::execution::oracle::{run_with_oracle, Oracle};
+/// use blc::execution::Input::Bytes;
+///
+/// let second = b"000010"; // λλ1, which returns the oracle
+/// let oracle = Oracle::new(7).bits(64);
+///
+/// assert_eq!(run_with_oracle(second, Bytes(b"ignored"), b"0110"), Ok("0110".into()));
+/// assert_eq!(run_with_oracle(second, Bytes(b""), &oracle).unwrap().len(), 64);
+/// ```
+/// # Errors
+///
+/// Returns the same `Error`s as `run`.
+pub fn run_with_oracle(blc_program: &[u8], input: Input, oracle: &[u8]) -> Result<String, Error> {
+    let mut calculation = app(prepare(blc_program, input)?, encode_bits(oracle));
+    evaluate(&mut calculation, Options::default())?;
+
+    decoded(calculation)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn oracle_bits() {
+        let bits = Oracle::new(0).bits(1_000);
+        let ones = bits.iter().filter(|&&bit| bit == 1).count();
+
+        assert!(bits.iter().all(|&bit| bit <= 1));
+        assert!(ones > 400 && ones < 600);
+        // λλ2: the input, unaffected by the oracle
+        assert_eq!(run_with_oracle(b"0000110", Input::Bytes(b"in"), &bits), Ok("in".into()));
+    }
+}
@@ -0,0 +1,189 @@
+//! Decoding of program output
+//!
+//! The lenient decoders of `encoding::lambda` render anything that is not a list of bytes or bits
+//! as text, so they only fail on a list element that is a list, but not of bits; `decoded` and
+//! `decoded_bytes` report the position of that element. `decode_strict` doesn't fall back to text
+//! at all and tells where the output stops being a list of bytes. In bit mode, `decoded_bits`
+//! reports the position of a list element that is a list instead of a bit.
+
+use lambda_calculus::*;
+use lambda_calculus::data::boolean::{tru, fls};
+use encoding::lambda::{decode, decode_bits, decode_bytes};
+use execution::Error;
+use pair_list::{head_ref, is_list, uncons_ref};
+
+/// Decodes program output like `encoding::lambda::decode` does.
+///
+/// # Errors
+///
+/// Returns `Error::OutputNotBytes` if an element of the output is a list that is not a byte.
+pub fn decoded(output: Term) -> Result<String, Error> {
+    match undecodable_byte(&output) {
+        Some(position) => Err(Error::OutputNotBytes { position }),
+        None => Ok(decode(output).unwrap()) // safe - the elements were checked above
+    }
+}
+
+/// Decodes program output like `encoding::lambda::decode_bytes` does.
+///
+/// # Errors
+///
+/// Returns `Error::OutputNotBytes` if an element of the output is a list that is not a byte.
+pub fn decoded_bytes(output: Term) -> Result<Vec<u8>, Error> {
+    match undecodable_byte(&output) {
+        Some(position) => Err(Error::OutputNotBytes { position }),
+        None => Ok(decode_bytes(output).unwrap()) // safe - the elements were checked above
+    }
+}
+
+/// Decodes program output like `encoding::lambda::decode_bits` does.
+///
+/// # Errors
+///
+/// Returns `Error::OutputNotBits` if an element of the output is a list instead of a bit.
+pub fn decoded_bits(output: Term) -> Result<Vec<u8>, Error> {
+    match list_instead_of_bit(&output) {
+        Some(position) => Err(Error::OutputNotBits { position }),
+        None => Ok(decode_bits(output).unwrap()) // safe - decoding bits falls back to text
+    }
+}
+
+/// Returns the position of the first element the lenient decoders would fail to decode, which is
+/// a list that is not a list of exactly 8 Church booleans.
+fn undecodable_byte(mut output: &Term) -> Option<usize> {
+    let mut position = 0;
+
+    loop {
+        match head_ref(output) {
+            Ok(head) if is_list(output) && is_list(head) => {
+                if decode_byte(head).is_none() { return Some(position) }
+            },
+            Ok(head) if *head == tru() || *head == fls() => (),
+            _ => return None // the end of the output, or text is fallen back to
+        }
+
+        output = uncons_ref(output).unwrap().1; // safe - the head was found above
+        position += 1;
+    }
+}
+
+/// Returns the position of the first element of a list of bits that is a list, like a byte, which
+/// `decode_bits` renders as text along with the rest of the output.
+fn list_instead_of_bit(mut output: &Term) -> Option<usize> {
+    let mut position = 0;
+
+    loop {
+        match head_ref(output) {
+            Ok(head) if *head == tru() || *head == fls() => (),
+            Ok(head) if is_list(output) && is_list(head) => return Some(position),
+            _ => return None // the end of the output, or text is fallen back to
+        }
+
+        output = uncons_ref(output).unwrap().1; // safe - the head was found above
+        position += 1;
+    }
+}
+
+/// Decodes program output as a list of bytes, each a list of 8 Church booleans, most significant
+/// first, without falling back to text for output that is not one.
+///
+/// # Example
+/// ```
+/// use blc::execution::decode_strict;
+/// use blc::execution::Error::OutputNotBytes;
+/// use blc::encoding::lambda::{encode, encode_bits};
+///
+/// assert_eq!(decode_strict(&encode(b"hi")), Ok(b"hi".to_vec()));
+/// assert_eq!(decode_strict(&encode_bits(b"01")), Err(OutputNotBytes { position: 0 }));
+/// ```
+/// # Errors
+///
+/// Returns `Error::NotAnAbstraction` if a cell of the output list or its end is not an
+/// abstraction, `Error::OutputNotAList` if it is an abstraction, but neither a cell nor the end of
+/// the list, and `Error::OutputNotBytes` if an element of the list is not a byte; all of them
+/// carry the position of the offending cell.
+pub fn decode_strict(output: &Term) -> Result<Vec<u8>, Error> {
+    let mut bytes = Vec::new();
+    let mut rest = output;
+
+    while *rest != fls() {
+        let position = bytes.len();
+        let (head, tail) = match *rest {
+            Abs(ref body) => match **body {
+                App(ref lhs, ref tail) => match **lhs {
+                    App(ref var, ref head) if **var == Var(1) => (head, tail),
+                    _ => return Err(Error::OutputNotAList { position })
+                },
+                _ => return Err(Error::OutputNotAList { position })
+            },
+            _ => return Err(Error::NotAnAbstraction { position })
+        };
+
+        bytes.push(decode_byte(head).ok_or(Error::OutputNotBytes { position })?);
+        rest = tail;
+    }
+
+    Ok(bytes)
+}
+
+/// Decodes a list of exactly 8 Church booleans.
+fn decode_byte(mut bits: &Term) -> Option<u8> {
+    let mut byte = 0u8;
+
+    for _ in 0..8 {
+        let (bit, rest) = uncons_ref(bits).ok()?;
+        byte = match *bit {
+            ref bit if *bit == tru() => byte << 1,
+            ref bit if *bit == fls() => byte << 1 | 1,
+            _ => return None
+        };
+        bits = rest;
+    }
+
+    if *bits == fls() { Some(byte) } else { None }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use encoding::binary::to_bits;
+    use encoding::lambda::{encode, encode_bits};
+    use execution::{run, Input};
+    use pair_list::listify_terms;
+
+    #[test]
+    fn lenient_decoding() {
+        let a = head_ref(&encode(b"a")).unwrap().clone();
+        let bad_byte = listify_terms(vec![abs(Var(1)), fls()]);
+        let output = listify_terms(vec![a, tru(), bad_byte, Var(1)]);
+
+        assert_eq!(decoded(output), Err(Error::OutputNotBytes { position: 2 }));
+        assert_eq!(decoded(encode(b"ab")), Ok("ab".into()));
+        assert_eq!(decoded_bytes(abs(Var(1))), Ok("(λ1)".into()));
+
+        // a list of 9 bits is not a byte
+        let nine_bits = listify_terms(vec![listify_terms(vec![fls(); 9])]);
+        let program = to_bits(&nine_bits);
+        assert_eq!(decoded(nine_bits), Err(Error::OutputNotBytes { position: 0 }));
+        assert_eq!(run(&program, Input::Nothing), Err(Error::OutputNotBytes { position: 0 }));
+        assert_eq!(decoded(encode_bits(b"0110")), Ok("0110".into()));
+
+        let output = listify_terms(vec![tru(), fls(), encode(b"a")]);
+        assert_eq!(decoded_bits(output), Err(Error::OutputNotBits { position: 2 }));
+        assert_eq!(decoded_bits(encode_bits(b"10")), Ok(b"10".to_vec()));
+        assert_eq!(decoded_bits(abs(Var(1))), Ok("(λ1)".into()));
+    }
+
+    #[test]
+    fn strict_decoding() {
+        let cell = |head, tail| abs(app!(Var(1), head, tail));
+        let a = head_ref(&encode(b"a")).unwrap().clone();
+
+        let end = |tail| decode_strict(&cell(a.clone(), tail));
+
+        assert_eq!(end(encode(b"b")), Ok(b"ab".to_vec()));
+        assert_eq!(end(Var(1)), Err(Error::NotAnAbstraction { position: 1 }));
+        assert_eq!(end(abs(Var(1))), Err(Error::OutputNotAList { position: 1 }));
+        assert_eq!(decode_strict(&cell(tru(), fls())), Err(Error::OutputNotBytes { position: 0 }));
+    }
+}
@@ -0,0 +1,249 @@
+//! Per-tenant execution quotas
+//!
+//! A `QuotaManager` holds named buckets of β-reductions and term memory. Every execution started
+//! on behalf of a tenant draws reductions from the tenant's bucket, which is refilled at a fixed
+//! rate, and reserves the memory its intermediate terms take for as long as it runs, so that
+//! concurrent executions of many tenants can't starve each other.
+
+use lambda_calculus::*;
+use analysis::size;
+use execution::{prepare, Error, Input};
+use execution::output::decoded;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// The number of reductions drawn from a bucket at a time.
+const CHUNK: usize = 1024;
+
+/// The configuration of a tenant's bucket.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Quota {
+    /// the maximum number of β-reductions the bucket can hold
+    pub steps: usize,
+    /// the number of β-reductions added back to the bucket every `refill_interval`
+    pub refill_steps: usize,
+    /// how often the bucket is refilled
+    pub refill_interval: Duration,
+    /// the maximum total size, in nodes, of the terms of all of the tenant's running executions
+    /// (`0` means no limit)
+    pub memory: usize
+}
+
+#[derive(Debug)]
+struct Bucket {
+    quota: Quota,
+    steps: usize,
+    memory: usize,
+    refilled: Instant
+}
+
+impl Bucket {
+    fn refill(&mut self) {
+        if self.quota.refill_steps == 0 || self.quota.refill_interval == Duration::from_secs(0) {
+            return
+        }
+        let now = Instant::now();
+        let elapsed = now.saturating_duration_since(self.refilled);
+        let intervals = elapsed.as_nanos() / self.quota.refill_interval.as_nanos();
+
+        if intervals != 0 {
+            let added = usize::try_from(intervals).unwrap_or(usize::MAX)
+                .saturating_mul(self.quota.refill_steps);
+            self.steps = self.steps.saturating_add(added).min(self.quota.steps);
+
+            let refills = u32::try_from(intervals).ok()
+                .and_then(|intervals| self.quota.refill_interval.checked_mul(intervals));
+            self.refilled = match refills {
+                Some(refills) if self.steps < self.quota.steps => self.refilled + refills,
+                _ => now // a full bucket doesn't save up the time it spends full
+            };
+        }
+    }
+}
+
+/// A set of named buckets that executions draw their resources from.
+///
+/// # Example
+/// ```
+/// use blc::execution::quota::{QuotaManager, Quota};
+/// use blc::execution::Input::Bytes;
+/// use std::time::Duration;
+///
+/// let reverse_blc = b"0001011001000110100000000001011100111110111100001011011110110000010";
+/// let quotas = QuotaManager::new();
+/// quotas.add_bucket("alice", Quota {
+///     steps: 100_000,
+///     refill_steps: 1_000,
+///     refill_interval: Duration::from_secs(1),
+///     memory: 1_000_000
+/// });
+///
+/// assert_eq!(quotas.run("alice", &*reverse_blc, Bytes(b"herp")), Ok("preh".into()));
+/// assert!(quotas.remaining_steps("alice").unwrap() < 100_000);
+/// ```
+#[derive(Debug, Default)]
+pub struct QuotaManager {
+    buckets: Mutex<HashMap<String, Bucket>>
+}
+
+impl QuotaManager {
+    /// Creates a manager with no buckets.
+    pub fn new() -> Self { QuotaManager::default() }
+
+    /// Adds a full bucket with the given `Quota` for the given tenant, replacing any previous one.
+    pub fn add_bucket(&self, tenant: &str, quota: Quota) {
+        let bucket = Bucket { quota, steps: quota.steps, memory: 0, refilled: Instant::now() };
+        self.buckets.lock().unwrap().insert(tenant.into(), bucket);
+    }
+
+    /// Removes the bucket of the given tenant; returns `false` if there was none.
+    pub fn remove_bucket(&self, tenant: &str) -> bool {
+        self.buckets.lock().unwrap().remove(tenant).is_some()
+    }
+
+    /// Returns the number of β-reductions currently left in the tenant's bucket.
+    pub fn remaining_steps(&self, tenant: &str) -> Option<usize> {
+        let mut buckets = self.buckets.lock().unwrap();
+
+        buckets.get_mut(tenant).map(|bucket| {
+            bucket.refill();
+            bucket.steps
+        })
+    }
+
+    /// Executes a binary lambda calculus program like `execution::run` does, drawing the needed
+    /// resources from the tenant's bucket.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Error` if the tenant has no bucket, if the program or the input are not valid
+    /// BLC or if the tenant's quota is exhausted before the execution finishes.
+    pub fn run(&self, tenant: &str, blc_program: &[u8], input: Input) -> Result<String, Error> {
+        if !self.buckets.lock().unwrap().contains_key(tenant) { return Err(Error::UnknownTenant) }
+
+        let mut calculation = prepare(blc_program, input)?;
+        let mut reserved = 0;
+        let result = self.reduce(tenant, &mut calculation, &mut reserved);
+        self.reserve(tenant, reserved, 0);
+
+        result.and_then(|_| decoded(calculation))
+    }
+
+    fn reduce(&self, tenant: &str, term: &mut Term, reserved: &mut usize) -> Result<(), Error> {
+        loop {
+            let current_size = size(term);
+            if !self.reserve(tenant, *reserved, current_size) { return Err(Error::QuotaExhausted) }
+            *reserved = current_size;
+
+            let drawn = self.draw(tenant, CHUNK);
+            if drawn == 0 { return Err(Error::QuotaExhausted) }
+
+            let performed = term.reduce(NOR, drawn);
+            if performed < drawn {
+                self.refund(tenant, drawn - performed);
+                return Ok(())
+            }
+        }
+    }
+
+    fn draw(&self, tenant: &str, wanted: usize) -> usize {
+        let mut buckets = self.buckets.lock().unwrap();
+
+        buckets.get_mut(tenant).map_or(0, |bucket| {
+            bucket.refill();
+            let drawn = bucket.steps.min(wanted);
+            bucket.steps -= drawn;
+            drawn
+        })
+    }
+
+    fn refund(&self, tenant: &str, steps: usize) {
+        if let Some(bucket) = self.buckets.lock().unwrap().get_mut(tenant) {
+            bucket.steps = bucket.steps.saturating_add(steps).min(bucket.quota.steps);
+        }
+    }
+
+    /// Replaces a reservation of `from` nodes with one of `to` nodes; returns `false` if that
+    /// would exceed the tenant's memory quota.
+    fn reserve(&self, tenant: &str, from: usize, to: usize) -> bool {
+        let mut buckets = self.buckets.lock().unwrap();
+
+        match buckets.get_mut(tenant) {
+            Some(bucket) => {
+                let memory = bucket.memory - from.min(bucket.memory) + to;
+                if bucket.quota.memory != 0 && to > from && memory > bucket.quota.memory {
+                    return false
+                }
+                bucket.memory = memory;
+                true
+            },
+            None => to <= from
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::thread;
+
+    const REVERSE: &[u8] = b"0001011001000110100000000001011100111110111100001011011110110000010";
+
+    fn quota(steps: usize, memory: usize) -> Quota {
+        Quota { steps, refill_steps: 0, refill_interval: Duration::from_secs(1), memory }
+    }
+
+    #[test]
+    fn tenants_are_separate() {
+        let quotas = QuotaManager::new();
+        quotas.add_bucket("a", quota(100_000, 0));
+        quotas.add_bucket("b", quota(10, 0));
+
+        assert_eq!(quotas.run("a", REVERSE, Input::Bytes(b"abc")), Ok("cba".into()));
+        assert_eq!(quotas.run("b", REVERSE, Input::Bytes(b"abc")), Err(Error::QuotaExhausted));
+        assert_eq!(quotas.remaining_steps("b"), Some(0));
+        assert_eq!(quotas.run("c", REVERSE, Input::Bytes(b"abc")), Err(Error::UnknownTenant));
+    }
+
+    #[test]
+    fn memory_is_released() {
+        let quotas = QuotaManager::new();
+        quotas.add_bucket("a", quota(1_000_000, 2_000));
+        quotas.add_bucket("b", quota(1_000_000, 10));
+
+        assert!(quotas.run("a", REVERSE, Input::Bytes(b"abc")).is_ok());
+        assert!(quotas.run("a", REVERSE, Input::Bytes(b"abc")).is_ok());
+        assert_eq!(quotas.run("b", REVERSE, Input::Bytes(b"abc")), Err(Error::QuotaExhausted));
+    }
+
+    #[test]
+    fn refills() {
+        let quotas = QuotaManager::new();
+        quotas.add_bucket("a", Quota {
+            steps: 10,
+            refill_steps: 10,
+            refill_interval: Duration::from_millis(50),
+            memory: 0
+        });
+        assert_eq!(quotas.run("a", REVERSE, Input::Bytes(b"abc")), Err(Error::QuotaExhausted));
+        thread::sleep(Duration::from_millis(120));
+
+        assert!(quotas.remaining_steps("a").unwrap() >= 10);
+    }
+
+    #[test]
+    fn long_idle_periods() {
+        let refill_interval = Duration::from_nanos(1);
+        let quota = Quota { steps: 10, refill_steps: 1, refill_interval, memory: 0 };
+        let start = Instant::now();
+        // more intervals than fit in a `u32`
+        let refilled = start - Duration::from_secs(5);
+        let mut bucket = Bucket { quota, steps: 0, memory: 0, refilled };
+
+        bucket.refill();
+        assert_eq!(bucket.steps, 10);
+        assert!(bucket.refilled >= start);
+    }
+}
@@ -0,0 +1,243 @@
+//! Step-by-step execution
+//!
+//! An `Execution` owns a term and reduces it one step at a time with a `Strategy`, so the caller
+//! decides when to stop and can inspect every intermediate term, e.g. to visualize the reduction
+//! or to give up on it by its own criteria.
+//!
+//! An `Execution` can also be `save`d as a checkpoint and `restore`d later, possibly by another
+//! process, to continue where it left off. Its layout is:
+//!
+//! | field      | size     | contents                                       |
+//! |------------|----------|------------------------------------------------|
+//! | magic      | 4 bytes  | `BLCS`                                         |
+//! | version    | 1 byte   | `1`                                            |
+//! | strategy   | 1 byte   | the index of the `Strategy` in its declaration |
+//! | steps      | 8 bytes  | the number of performed steps                  |
+//! | reductions | 8 bytes  | the number of performed β-reductions           |
+//! | finished   | 1 byte   | `1` if nothing is left to reduce, else `0`     |
+//! | term       | variable | a `program::container` with the current term   |
+//!
+//! Multi-byte numbers are little-endian.
+
+use lambda_calculus::*;
+use encoding::binary::{from_bits, to_bits};
+use execution::{prepare, Error, Input, Strategy};
+use program::Metadata;
+use program::container;
+use std::iter::FusedIterator;
+
+/// The bytes every checkpoint starts with.
+pub const MAGIC: &[u8; 4] = b"BLCS";
+
+const VERSION: u8 = 1;
+
+/// The strategies in the order of their declaration, indexed by checkpoints.
+const STRATEGIES: [Strategy; 12] = [
+    Strategy::Normal,
+    Strategy::CallByName,
+    Strategy::HeadSpine,
+    Strategy::HybridNormal,
+    Strategy::Applicative,
+    Strategy::CallByValue,
+    Strategy::HybridApplicative,
+    Strategy::StrictData,
+    Strategy::CallByNeed,
+    Strategy::Cek,
+    Strategy::Iterative,
+    Strategy::Closures
+];
+
+/// A reduction in progress, iterating over the terms after each of its steps.
+///
+/// # Example
+/// ```
+/// use blc::execution::Execution;
+/// use blc::execution::Input::Bytes;
+/// use blc::encoding::binary::to_bits;
+///
+/// let identity = b"0010";
+/// let steps = Execution::from_program(identity, Bytes(b"")).unwrap().collect::<Vec<_>>();
+///
+/// assert_eq!(steps.len(), 1);
+/// assert_eq!(to_bits(&steps[0]), b"000010"); // the empty input, λλ1
+/// ```
+#[derive(Debug, Clone)]
+pub struct Execution {
+    term: Term,
+    strategy: Strategy,
+    steps: usize,
+    reductions: usize,
+    finished: bool
+}
+
+impl Execution {
+    /// Starts the reduction of `term` with the given `Strategy`.
+    pub fn new(term: Term, strategy: Strategy) -> Self {
+        Execution { term, strategy, steps: 0, reductions: 0, finished: false }
+    }
+
+    /// Starts the normal-order execution of a binary lambda calculus program applied to `input`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Error` if the program or the argument are not valid BLC.
+    pub fn from_program(blc_program: &[u8], input: Input) -> Result<Self, Error> {
+        Ok(Execution::new(prepare(blc_program, input)?, Strategy::Normal))
+    }
+
+    /// Performs a single reduction step; returns `false` if there was nothing left to reduce.
+    pub fn step(&mut self) -> bool {
+        if self.finished { return false }
+
+        match self.strategy.step(&mut self.term) {
+            0 => self.finished = true,
+            n => {
+                self.steps += 1;
+                self.reductions += n;
+            }
+        }
+        !self.finished
+    }
+
+    /// Performs at most `limit` reduction steps (`0` means no limit); returns the number of
+    /// performed steps.
+    pub fn advance(&mut self, limit: usize) -> usize {
+        let mut performed = 0;
+        while (limit == 0 || performed < limit) && self.step() { performed += 1 }
+        performed
+    }
+
+    /// Serializes the state of the `Execution` as a checkpoint.
+    ///
+    /// # Example
+    /// ```
+    /// use blc::execution::{Execution, Strategy};
+    /// use blc::encoding::binary::from_bits;
+    ///
+    /// let omega = from_bits(b"010001101000011010").unwrap();
+    /// let mut execution = Execution::new(omega, Strategy::Normal);
+    /// execution.advance(1_000);
+    ///
+    /// let mut restored = Execution::restore(&execution.save()).unwrap();
+    /// assert_eq!(restored.steps(), 1_000);
+    /// assert_eq!(restored.advance(1_000), 1_000);
+    /// ```
+    pub fn save(&self) -> Vec<u8> {
+        let strategy = STRATEGIES.iter().position(|&s| s == self.strategy)
+            .unwrap(); // safe - all the strategies are listed
+        let mut output = MAGIC.to_vec();
+
+        output.push(VERSION);
+        output.push(strategy as u8);
+        output.extend_from_slice(&(self.steps as u64).to_le_bytes());
+        output.extend_from_slice(&(self.reductions as u64).to_le_bytes());
+        output.push(self.finished as u8);
+        output.extend(container::write(&to_bits(&self.term), &Metadata::default()));
+
+        output
+    }
+
+    /// Deserializes an `Execution` from a checkpoint produced by `save`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::InvalidCheckpoint` if `data` is not a valid checkpoint.
+    pub fn restore(data: &[u8]) -> Result<Self, Error> {
+        if !data.starts_with(MAGIC) || data.get(4) != Some(&VERSION) {
+            return Err(Error::InvalidCheckpoint)
+        }
+        let header = data.get(5..23).ok_or(Error::InvalidCheckpoint)?;
+
+        let strategy = *STRATEGIES.get(header[0] as usize).ok_or(Error::InvalidCheckpoint)?;
+        let number = |bytes: &[u8]| {
+            let mut buffer = [0; 8];
+            buffer.copy_from_slice(bytes);
+            u64::from_le_bytes(buffer) as usize
+        };
+        let (steps, reductions) = (number(&header[1..9]), number(&header[9..17]));
+        let finished = match header[17] {
+            0 => false,
+            1 => true,
+            _ => return Err(Error::InvalidCheckpoint)
+        };
+        let (_, bits) = container::read(&data[23..]).or(Err(Error::InvalidCheckpoint))?;
+        let term = from_bits(&bits).or(Err(Error::InvalidCheckpoint))?;
+
+        Ok(Execution { term, strategy, steps, reductions, finished })
+    }
+
+    /// Returns the current term.
+    pub fn term(&self) -> &Term { &self.term }
+
+    /// Consumes the `Execution`, returning the current term.
+    pub fn into_term(self) -> Term { self.term }
+
+    /// Returns the number of steps performed so far.
+    pub fn steps(&self) -> usize { self.steps }
+
+    /// Returns the number of β-reductions the performed steps consisted of.
+    pub fn reductions(&self) -> usize { self.reductions }
+
+    /// Returns `true` if a step found nothing left to reduce.
+    pub fn is_finished(&self) -> bool { self.finished }
+}
+
+impl Iterator for Execution {
+    type Item = Term;
+
+    fn next(&mut self) -> Option<Term> {
+        if self.step() { Some(self.term.clone()) } else { None }
+    }
+}
+
+impl FusedIterator for Execution {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use lambda_calculus::combinators::O;
+    use lambda_calculus::data::num::church::mul;
+
+    #[test]
+    fn steps_and_reductions() {
+        let product = app!(mul(), 2.into_church(), 3.into_church());
+        let mut execution = Execution::new(product.clone(), Strategy::Normal);
+        let terms = execution.by_ref().collect::<Vec<Term>>();
+
+        assert_eq!(terms.last(), Some(&6.into_church()));
+        assert_eq!(execution.steps(), terms.len());
+        assert_eq!(execution.reductions(), product.clone().reduce(NOR, 0));
+        assert!(execution.is_finished());
+        assert_eq!(execution.next(), None);
+    }
+
+    #[test]
+    fn stopping_early() {
+        let mut execution = Execution::new(O(), Strategy::Normal);
+
+        assert_eq!(execution.by_ref().take(100).filter(|term| *term == O()).count(), 100);
+        assert_eq!(execution.steps(), 100);
+        assert_eq!(execution.into_term(), O());
+    }
+
+    #[test]
+    fn checkpoints() {
+        let product = app!(mul(), 2.into_church(), 3.into_church());
+        let mut execution = Execution::new(product, Strategy::CallByNeed);
+        execution.advance(2);
+
+        let checkpoint = execution.save();
+        let mut restored = Execution::restore(&checkpoint).unwrap();
+        assert_eq!((restored.term(), restored.steps()), (execution.term(), 2));
+        assert_eq!(restored.by_ref().last(), execution.by_ref().last());
+        assert_eq!(restored.reductions(), execution.reductions());
+
+        assert_eq!(Execution::restore(&checkpoint[..20]).err(), Some(Error::InvalidCheckpoint));
+        assert_eq!(Execution::restore(b"BLCC").err(), Some(Error::InvalidCheckpoint));
+
+        // a truncated term
+        let mut corrupt = checkpoint[..23].to_vec();
+        corrupt.extend(container::write(b"111", &Metadata::default()));
+        assert_eq!(Execution::restore(&corrupt).err(), Some(Error::InvalidCheckpoint));
+    }
+}
@@ -0,0 +1,147 @@
+//! Normal-order reduction with strict evaluation of data arguments
+//!
+//! Redexes are chosen in normal order, but before a closed argument is substituted into the body
+//! of an abstraction it is given a bounded number of reductions to become data: a boolean or a
+//! list of data, such as an encoded byte or string. If it gets there, its normal form is
+//! substituted instead of the unevaluated argument, so it is only evaluated once no matter how
+//! many times it is used. Arguments that don't become data within the budget are substituted
+//! unevaluated, which keeps the termination behavior of normal-order reduction.
+
+use lambda_calculus::*;
+use lambda_calculus::data::boolean::{tru, fls};
+use std::mem;
+
+/// The maximum number of reductions spent on evaluating a single argument.
+const DATA_FUEL: usize = 1_000;
+
+/// Contracts the leftmost outermost redex of `term`, performing at most `limit` reductions (`0`
+/// means no limit); returns the number of performed reductions, including the ones spent on
+/// evaluating its argument.
+pub fn step(term: &mut Term, limit: usize) -> usize {
+    if is_redex(term) { return contract(term, limit) }
+
+    match *term {
+        Var(_) => 0,
+        Abs(ref mut body) => step(body, limit),
+        App(ref mut lhs, ref mut rhs) => {
+            let count = step(lhs, limit);
+            if count != 0 { count } else { step(rhs, limit) }
+        }
+    }
+}
+
+fn is_redex(term: &Term) -> bool {
+    if let App(ref lhs, _) = *term {
+        if let Abs(_) = **lhs { return true }
+    }
+    false
+}
+
+fn contract(term: &mut Term, limit: usize) -> usize {
+    let (mut lhs, mut rhs) = mem::replace(term, Var(0)).unapp().unwrap(); // safe - a redex
+    let fuel = if limit == 0 { DATA_FUEL } else { DATA_FUEL.min(limit - 1) };
+    let mut count = 1;
+
+    if fuel != 0 && rhs.is_supercombinator() && has_redex(&rhs) {
+        let mut evaluated = rhs.clone();
+        // reduce one step at a time, as `Term::reduce` recurses deeper with every reduction
+        let reductions = (0..fuel).take_while(|_| evaluated.reduce(NOR, 1) == 1).count();
+        count += reductions;
+
+        if reductions < fuel && is_data(&evaluated) { rhs = evaluated }
+    }
+
+    lhs.apply(&rhs).unwrap(); // safe - lhs is an abstraction
+    *term = lhs;
+
+    count
+}
+
+fn has_redex(term: &Term) -> bool {
+    let mut stack = vec![term];
+
+    while let Some(term) = stack.pop() {
+        if is_redex(term) { return true }
+
+        match *term {
+            Var(_) => (),
+            Abs(ref t) => stack.push(t),
+            App(ref t1, ref t2) => {
+                stack.push(t1);
+                stack.push(t2)
+            }
+        }
+    }
+
+    false
+}
+
+/// Returns `true` if `term` is a boolean or a list of data.
+fn is_data(term: &Term) -> bool {
+    let (tru, fls) = (tru(), fls());
+    let mut stack = vec![term];
+
+    while let Some(term) = stack.pop() {
+        if *term == tru || *term == fls { continue }
+
+        let cell = term.unabs_ref()
+            .and_then(|t| t.unapp_ref())
+            .and_then(|(pair, tail)| Ok((pair.unapp_ref()?, tail)));
+
+        match cell {
+            Ok(((&Var(1), head), tail)) => {
+                stack.push(head);
+                stack.push(tail)
+            },
+            _ => return false
+        }
+    }
+
+    true
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use lambda_calculus::combinators::{K, I, O};
+    use lambda_calculus::data::boolean::not;
+    use encoding::binary::from_bits;
+    use encoding::lambda::{decode, encode};
+
+    fn normalize(mut term: Term) -> Term {
+        while step(&mut term, 0) != 0 {}
+        term
+    }
+
+    #[test]
+    fn data_arguments_are_shared() {
+        let dup = abs(app(Var(1), Var(1)));
+        let mut term = app(dup, app(not(), tru()));
+
+        assert!(step(&mut term, 0) > 1);
+        assert_eq!(term, app(fls(), fls()));
+    }
+
+    #[test]
+    fn limits() {
+        let dup = abs(app(Var(1), Var(1)));
+        let mut term = app(dup, app(not(), tru()));
+
+        assert_eq!(step(&mut term, 1), 1);
+        assert_eq!(term, app(app(not(), tru()), app(not(), tru())));
+    }
+
+    #[test]
+    fn other_arguments_are_lazy() {
+        assert_eq!(normalize(app!(K(), I(), O())), I());
+        assert_eq!(normalize(app(abs(Var(1)), I())), I());
+    }
+
+    #[test]
+    fn same_results_as_normal_order() {
+        let reverse = from_bits(b"0001011001000110100000000001011100111110111100001011011110110000010");
+        let term = app(reverse.unwrap(), encode(b"herp derp"));
+
+        assert_eq!(decode(normalize(term.clone())), decode(beta(term, NOR, 0)));
+    }
+}
@@ -0,0 +1,165 @@
+//! Strictness analysis and evaluation using it
+//!
+//! An abstraction is strict if evaluating its body to weak head normal form certainly evaluates
+//! its variable to weak head normal form as well, e.g. because the variable is the head of the
+//! body. `is_strict` tells that from the structure of the body alone, without reducing it, and
+//! errs on the side of `false`.
+//!
+//! Normal-order reduction substitutes arguments unevaluated, so an argument that is used several
+//! times is also evaluated several times. `Hybrid` reduces in normal order as well, but evaluates
+//! the argument of a strict abstraction to weak head normal form before substituting it; since the
+//! abstraction would evaluate it anyway, that can't make the reduction diverge, and the work is
+//! only done once. Other arguments are substituted unevaluated, so it reaches the normal forms
+//! normal order does.
+
+use lambda_calculus::*;
+use execution::machine::Machine;
+use std::mem;
+
+/// Returns `true` if the abstraction with the given body is strict in its variable.
+///
+/// # Example
+/// ```
+/// extern crate blc;
+/// extern crate lambda_calculus;
+///
+/// use blc::execution::strictness::is_strict;
+/// use lambda_calculus::*;
+///
+/// assert!(is_strict(&app(Var(1), Var(2)))); // λx. x y
+/// assert!(!is_strict(&app(Var(2), Var(1)))); // λx. y x
+/// assert!(!is_strict(&abs(Var(2)))); // λx. λy. x
+/// ```
+pub fn is_strict(body: &Term) -> bool {
+    demands(body, 1)
+}
+
+/// Returns `true` if evaluating `term` to weak head normal form evaluates the variable with the
+/// given De Bruijn index.
+fn demands(term: &Term, index: usize) -> bool {
+    let mut head = term;
+    let mut first_argument = None;
+    while let App(ref lhs, ref rhs) = *head {
+        head = lhs;
+        first_argument = Some(rhs);
+    }
+
+    match *head {
+        Var(i) => i == index,
+        Abs(ref body) => first_argument.is_some_and(|argument| {
+            demands(body, index + 1) || demands(body, 1) && demands(argument, index)
+        }),
+        App(..) => unreachable!() // the whole spine was walked above
+    }
+}
+
+/// A normal-order evaluator evaluating the arguments of strict abstractions before substituting
+/// them.
+///
+/// # Example
+/// ```
+/// use blc::execution::machine::{Machine, BigStep};
+/// use blc::execution::strictness::Hybrid;
+/// use blc::encoding::binary::from_bits;
+///
+/// // (λ1 1 1) applied to (λλ1)(λ1)(λ1), which is evaluated before it is passed
+/// let term = from_bits(b"01000101101010010100001000100010").unwrap();
+///
+/// assert_eq!(Hybrid.normalize(term.clone()), BigStep.normalize(term.clone()));
+/// assert!(Hybrid.reduce(&mut term.clone(), 0) < BigStep.reduce(&mut term.clone(), 0));
+/// ```
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub struct Hybrid;
+
+impl Machine for Hybrid {
+    fn reduce(&mut self, term: &mut Term, limit: usize) -> usize {
+        let mut count = 0;
+
+        while limit == 0 || count < limit {
+            let left = if limit == 0 { 0 } else { limit - count };
+            match step(term, left) {
+                0 => break,
+                n => count += n
+            }
+        }
+
+        count
+    }
+}
+
+/// Contracts the leftmost outermost redex of `term`, spending at most `limit` reductions (`0`
+/// means no limit) in total on it and its argument; returns the number of performed reductions.
+fn step(term: &mut Term, limit: usize) -> usize {
+    if is_redex(term) { return contract(term, limit) }
+
+    match *term {
+        Var(_) => 0,
+        Abs(ref mut body) => step(body, limit),
+        App(ref mut lhs, ref mut rhs) => {
+            let count = step(lhs, limit);
+            if count != 0 { count } else { step(rhs, limit) }
+        }
+    }
+}
+
+fn is_redex(term: &Term) -> bool {
+    if let App(ref lhs, _) = *term {
+        if let Abs(_) = **lhs { return true }
+    }
+    false
+}
+
+fn contract(term: &mut Term, limit: usize) -> usize {
+    let (mut lhs, mut rhs) = mem::replace(term, Var(0)).unapp().unwrap(); // safe - a redex
+    let mut count = 0;
+
+    if is_strict(lhs.unabs_ref().unwrap()) { // safe - lhs is an abstraction
+        if limit == 0 {
+            count += rhs.reduce(CBN, 0);
+        } else if limit > 1 {
+            count += rhs.reduce(CBN, limit - 1);
+        }
+    }
+    if limit != 0 && count == limit {
+        *term = app(lhs, rhs);
+        return count
+    }
+
+    lhs.apply(&rhs).unwrap(); // safe - lhs is an abstraction
+    *term = lhs;
+
+    count + 1
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use lambda_calculus::combinators::{K, I, O};
+    use execution::machine::BigStep;
+
+    #[test]
+    fn analysis() {
+        // λx. (λy. y) x
+        assert!(is_strict(&app(I(), Var(1))));
+        // λx. (λy. x) z
+        assert!(is_strict(&app(abs(Var(2)), Var(2))));
+        // λx. (λy. z) x
+        assert!(!is_strict(&app(abs(Var(3)), Var(1))));
+        // λx. K z x
+        assert!(!is_strict(&app!(K(), Var(2), Var(1))));
+    }
+
+    #[test]
+    fn shared_evaluation() {
+        // (λx. x x)(I I)
+        let term = app(abs(app(Var(1), Var(1))), app(I(), I()));
+        let (mut hybrid, mut normal) = (term.clone(), term);
+
+        assert_eq!(Hybrid.reduce(&mut hybrid, 0), 3);
+        assert_eq!(BigStep.reduce(&mut normal, 0), 4);
+        assert_eq!(hybrid, normal);
+
+        // the divergent argument is discarded without being evaluated
+        assert_eq!(Hybrid.normalize(app!(K(), I(), O())), I());
+    }
+}
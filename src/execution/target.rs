@@ -0,0 +1,78 @@
+//! Partial normalization
+//!
+//! A consumer of a term often needs less than its normal form: deciding what a term is at the
+//! top, e.g. whether it is a list cell or its end, only takes its weak head normal form, and
+//! knowing how it begins under its abstractions only takes its head normal form; `reduce_to`
+//! stops as soon as the requested form is reached.
+
+use lambda_calculus::*;
+use lambda_calculus::reduction::Order;
+
+/// The form a term is reduced to.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Target {
+    /// weak head normal form: an abstraction or a variable applied to arguments, without any
+    /// reductions under abstractions
+    Whnf,
+    /// head normal form: abstractions over a variable applied to arguments, without any
+    /// reductions in the arguments
+    Hnf,
+    /// normal form
+    Nf
+}
+
+impl Target {
+    /// Returns the reduction order that reduces a term exactly as far as this form.
+    pub fn order(self) -> Order {
+        match self {
+            Target::Whnf => CBN,
+            Target::Hnf  => HSP,
+            Target::Nf   => NOR
+        }
+    }
+}
+
+/// Reduces `term` to the given form with at most `limit` β-reductions (`0` means no limit), in
+/// normal order; returns the number of performed reductions.
+///
+/// # Example
+/// ```
+/// use blc::execution::{reduce_to, Target};
+/// use blc::encoding::binary::{from_bits, to_bits};
+///
+/// // λ(λ1)((λ1)(λ1))
+/// let term = from_bits(b"00 01 0010 01 0010 0010").unwrap();
+///
+/// let mut whnf = term.clone();
+/// assert_eq!(reduce_to(&mut whnf, Target::Whnf, 0), 0);
+///
+/// let mut hnf = term.clone();
+/// assert_eq!(reduce_to(&mut hnf, Target::Hnf, 0), 2);
+/// assert_eq!(to_bits(&hnf), b"000010");
+/// ```
+pub fn reduce_to(term: &mut Term, target: Target, limit: usize) -> usize {
+    term.reduce(target.order(), limit)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use lambda_calculus::combinators::{I, O};
+
+    #[test]
+    fn forms() {
+        // λ I (1 (I Ω)): an abstraction whose head normal form has a diverging argument
+        let term = abs(app(I(), app(Var(1), app(I(), O()))));
+
+        let mut whnf = app(I(), term.clone());
+        assert_eq!(reduce_to(&mut whnf, Target::Whnf, 0), 1);
+        assert_eq!(whnf, term);
+
+        let mut hnf = term.clone();
+        assert_eq!(reduce_to(&mut hnf, Target::Hnf, 0), 1);
+        assert_eq!(hnf, abs(app(Var(1), app(I(), O()))));
+
+        let mut nf = term;
+        assert_eq!(reduce_to(&mut nf, Target::Nf, 10), 10);
+    }
+}
@@ -0,0 +1,335 @@
+//! A bytecode virtual machine
+//!
+//! Terms are compiled to `Bytecode`, a flat sequence of `Op`s in prefix order: a variable is a
+//! single instruction, an abstraction is followed by its body and an application by its function
+//! and then its argument, with the distance to the argument stored in the instruction. Offsets
+//! are relative, so the code of every subterm can be moved around as a whole, and applying a
+//! program to its input only needs one more instruction in front of both.
+//!
+//! The `Vm` evaluates bytecode with a lazy Krivine machine: an application pushes a shared thunk
+//! of its argument and goes on with its function, an abstraction pops one and goes on with its
+//! body, and only a variable looks anything up, so the spine of an application is evaluated in a
+//! loop instead of recursively. The normal form is read back like in `lazy::Lazy`, which it shares
+//! the reduction order and counts with.
+
+use lambda_calculus::*;
+use encoding::lambda::encode;
+use execution::machine::Machine;
+use execution::{ClosedTerm, Error, Input};
+use execution::output::decoded;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// A bytecode instruction.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Op {
+    /// a variable with the given De Bruijn index
+    Var(u32),
+    /// an abstraction; its body follows
+    Abs,
+    /// an application; its function follows, and its argument starts the given number of
+    /// instructions after this one
+    App(u32)
+}
+
+/// A compiled term.
+///
+/// # Example
+/// ```
+/// use blc::execution::vm::{Bytecode, Op};
+/// use blc::encoding::binary::from_bits;
+///
+/// let k_i = from_bits(b"0100001100010").unwrap(); // (λλ2)(λ1)
+/// let bytecode = Bytecode::compile(&k_i);
+///
+/// assert_eq!(bytecode.ops(), [Op::App(4), Op::Abs, Op::Abs, Op::Var(2), Op::Abs, Op::Var(1)]);
+/// assert_eq!(bytecode.to_term(), k_i);
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Bytecode {
+    ops: Vec<Op>,
+    closed: bool
+}
+
+impl Bytecode {
+    /// Compiles `term` to bytecode.
+    pub fn compile(term: &Term) -> Self {
+        let mut ops = Vec::new();
+        emit(term, &mut ops);
+
+        Bytecode { ops, closed: term.is_supercombinator() }
+    }
+
+    /// Returns the instructions of the bytecode.
+    pub fn ops(&self) -> &[Op] { &self.ops }
+
+    /// Decompiles the bytecode back to the `Term` it was compiled from.
+    pub fn to_term(&self) -> Term {
+        decompile(&self.ops, 0).0
+    }
+
+    /// Returns the bytecode of `self` applied to `argument`.
+    fn applied(&self, argument: &Term) -> Bytecode {
+        let mut ops = Vec::with_capacity(self.ops.len() + 1);
+        ops.push(Op::App(self.ops.len() as u32 + 1));
+        ops.extend_from_slice(&self.ops);
+        emit(argument, &mut ops);
+
+        Bytecode { ops, closed: self.closed && argument.is_supercombinator() }
+    }
+}
+
+fn emit(term: &Term, ops: &mut Vec<Op>) {
+    match *term {
+        Var(i) => ops.push(Op::Var(i as u32)),
+        Abs(ref body) => {
+            ops.push(Op::Abs);
+            emit(body, ops);
+        },
+        App(ref lhs, ref rhs) => {
+            let at = ops.len();
+            ops.push(Op::App(0));
+            emit(lhs, ops);
+            ops[at] = Op::App((ops.len() - at) as u32);
+            emit(rhs, ops);
+        }
+    }
+}
+
+/// Decompiles the term starting at `pc`; returns it and the position right after its code.
+fn decompile(ops: &[Op], pc: usize) -> (Term, usize) {
+    match ops[pc] {
+        Op::Var(i) => (Var(i as usize), pc + 1),
+        Op::Abs => {
+            let (body, end) = decompile(ops, pc + 1);
+            (abs(body), end)
+        },
+        Op::App(_) => {
+            let (lhs, rhs_pc) = decompile(ops, pc + 1);
+            let (rhs, end) = decompile(ops, rhs_pc);
+            (app(lhs, rhs), end)
+        }
+    }
+}
+
+/// A virtual machine evaluating `Bytecode` in call-by-need order.
+///
+/// If it runs out of reductions before reaching the normal form, the term is left as it was.
+///
+/// # Example
+/// ```
+/// use blc::execution::vm::Vm;
+/// use blc::execution::Input::Bytes;
+/// use blc::program::Program;
+///
+/// let reverse_blc = b"0001011001000110100000000001011100111110111100001011011110110000010";
+/// let bytecode = Program::from_bits(reverse_blc).unwrap().compile();
+///
+/// assert_eq!(Vm.run(&bytecode, Bytes(b"herp")), Ok("preh".into()));
+/// assert_eq!(Vm.run(&bytecode, Bytes(b"derp")), Ok("pred".into()));
+/// ```
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub struct Vm;
+
+impl Vm {
+    /// Executes a compiled program like `run` does.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same `Error`s as `run`.
+    pub fn run(&mut self, bytecode: &Bytecode, input: Input) -> Result<String, Error> {
+        if !bytecode.closed { return Err(Error::InvalidProgram) }
+
+        let applied = match input {
+            Input::Nothing    => bytecode.clone(),
+            Input::Bytes(arg) => bytecode.applied(&encode(arg)),
+            Input::Bits(arg)  => {
+                let arg = ClosedTerm::from_bits(arg).or(Err(Error::InvalidArgument))?;
+                bytecode.applied(arg.as_term())
+            }
+        };
+        let mut evaluator = Evaluator { ops: &applied.ops, limit: 0, count: 0 };
+        let normal = evaluator.eval(0, None).and_then(|value| evaluator.quote(value, 0));
+
+        decoded(normal.ok().unwrap()) // safe - without a limit, the evaluation can't be exhausted
+    }
+}
+
+impl Machine for Vm {
+    fn reduce(&mut self, term: &mut Term, limit: usize) -> usize {
+        let bytecode = Bytecode::compile(term);
+        let mut evaluator = Evaluator { ops: &bytecode.ops, limit, count: 0 };
+        let normal = evaluator.eval(0, None).and_then(|value| evaluator.quote(value, 0));
+
+        if let Ok(normal) = normal { *term = normal }
+        evaluator.count
+    }
+}
+
+/// The values of the variables bound around a piece of bytecode, innermost first.
+type Env = Option<Rc<Frame>>;
+
+struct Frame {
+    thunk: Thunk,
+    next: Env
+}
+
+/// A possibly not yet evaluated argument, shared by all its uses.
+type Thunk = Rc<RefCell<State>>;
+
+enum State {
+    Delayed(usize, Env),
+    Forced(Value)
+}
+
+#[derive(Clone)]
+enum Value {
+    /// the body of an abstraction, by the position of its code, along with the values of its
+    /// free variables
+    Closure(usize, Env),
+    /// a variable, applied to arguments
+    Neutral(Head, Vec<Thunk>)
+}
+
+#[derive(Clone, Copy)]
+enum Head {
+    /// a variable bound by an abstraction being read back, by its depth counted from the root
+    Level(usize),
+    /// a free variable of the evaluated term, by its De Bruijn index counted from the root
+    Free(usize)
+}
+
+/// Signals that the evaluator ran out of reductions.
+struct Exhausted;
+
+struct Evaluator<'a> {
+    ops: &'a [Op],
+    limit: usize,
+    count: usize
+}
+
+impl Evaluator<'_> {
+    /// Evaluates the code at `pc` to weak head normal form.
+    fn eval(&mut self, mut pc: usize, mut env: Env) -> Result<Value, Exhausted> {
+        let mut args = Vec::new(); // the pushed arguments, the next one to be popped last
+
+        loop {
+            let value = match self.ops[pc] {
+                Op::App(offset) => {
+                    let thunk = State::Delayed(pc + offset as usize, env.clone());
+                    args.push(Rc::new(RefCell::new(thunk)));
+                    pc += 1;
+                    continue
+                },
+                Op::Abs => Value::Closure(pc + 1, env),
+                Op::Var(i) => lookup(i as usize, &env).map_or_else(
+                    |free| Ok(Value::Neutral(Head::Free(free), Vec::new())),
+                    |thunk| self.force(&thunk)
+                )?
+            };
+
+            match value {
+                Value::Closure(body, closure_env) => match args.pop() {
+                    Some(thunk) => {
+                        if self.limit != 0 && self.count == self.limit { return Err(Exhausted) }
+                        self.count += 1;
+
+                        pc = body;
+                        env = Some(Rc::new(Frame { thunk, next: closure_env }));
+                    },
+                    None => return Ok(Value::Closure(body, closure_env))
+                },
+                Value::Neutral(head, mut applied) => {
+                    applied.extend(args.into_iter().rev());
+                    return Ok(Value::Neutral(head, applied))
+                }
+            }
+        }
+    }
+
+    fn force(&mut self, thunk: &Thunk) -> Result<Value, Exhausted> {
+        let (pc, env) = match *thunk.borrow() {
+            State::Forced(ref value) => return Ok(value.clone()),
+            State::Delayed(pc, ref env) => (pc, env.clone())
+        };
+
+        let value = self.eval(pc, env)?;
+        *thunk.borrow_mut() = State::Forced(value.clone());
+        Ok(value)
+    }
+
+    /// Reads back the normal form of `value` under `depth` abstractions.
+    fn quote(&mut self, value: Value, depth: usize) -> Result<Term, Exhausted> {
+        match value {
+            Value::Closure(body, env) => {
+                let fresh = Value::Neutral(Head::Level(depth), Vec::new());
+                let thunk = Rc::new(RefCell::new(State::Forced(fresh)));
+                let body = self.eval(body, Some(Rc::new(Frame { thunk, next: env })))?;
+
+                Ok(abs(self.quote(body, depth + 1)?))
+            },
+            Value::Neutral(head, args) => {
+                let mut term = match head {
+                    Head::Level(level) => Var(depth - level),
+                    Head::Free(index) => Var(index + depth)
+                };
+                for arg in args {
+                    let arg = self.force(&arg)?;
+                    term = app(term, self.quote(arg, depth)?);
+                }
+                Ok(term)
+            }
+        }
+    }
+}
+
+/// Finds the thunk of the variable with the De Bruijn index `index` in `env`, or the index of
+/// the free variable it refers to counted from the root.
+fn lookup(index: usize, env: &Env) -> Result<Thunk, usize> {
+    let mut frame = env;
+    for passed in 1..index {
+        frame = match *frame {
+            Some(ref f) => &f.next,
+            None => return Err(index - passed + 1)
+        };
+    }
+
+    match *frame {
+        Some(ref f) => Ok(f.thunk.clone()),
+        None => Err(1)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use execution::lazy::Lazy;
+    use lambda_calculus::data::num::church::{fac, mul};
+
+    #[test]
+    fn same_as_lazy() {
+        let terms = vec![
+            app!(mul(), 3.into_church(), 3.into_church()),
+            app(fac(), 4.into_church()),
+            abs(app(abs(app(Var(3), Var(1))), Var(2)))
+        ];
+
+        for term in terms {
+            let (mut compiled, mut interpreted) = (term.clone(), term.clone());
+
+            assert_eq!(Bytecode::compile(&term).to_term(), term);
+            assert_eq!(Vm.reduce(&mut compiled, 0), Lazy.reduce(&mut interpreted, 0));
+            assert_eq!(compiled, interpreted);
+        }
+    }
+
+    #[test]
+    fn invalid_programs() {
+        let open = Bytecode::compile(&Var(1));
+        let identity = Bytecode::compile(&abs(Var(1)));
+
+        assert_eq!(Vm.run(&open, Input::Nothing), Err(Error::InvalidProgram));
+        assert_eq!(Vm.run(&identity, Input::Bits(b"110")), Err(Error::InvalidArgument));
+        assert_eq!(Vm.run(&identity, Input::Bits(b"0010")), Ok("(λ1)".into()));
+    }
+}
@@ -39,12 +39,44 @@
 
 #![deny(missing_docs)]
 
-#[macro_use]
+#[cfg(feature = "lambda_calculus")]
 extern crate lambda_calculus;
 
+#[cfg(feature = "lambda_calculus")]
 pub use self::execution::run;
-pub use self::encoding::binary::{to_bits, from_bits};
+pub use self::encoding::binary::to_bits;
+#[cfg(feature = "lambda_calculus")]
+pub use self::encoding::binary::from_bits;
 
+#[cfg(feature = "lambda_calculus")]
+pub mod analysis;
+#[cfg(feature = "bench")]
+pub mod bench;
+#[cfg(feature = "big_indices")]
+pub mod big_indices;
+#[cfg(feature = "lambda_calculus")]
+pub mod builder;
+pub mod diagnostics;
+pub mod digest;
 pub mod encoding;
+#[cfg(feature = "lambda_calculus")]
 pub mod execution;
+#[cfg(feature = "native")]
+pub mod native;
+#[cfg(feature = "lambda_calculus")]
 mod pair_list;
+#[cfg(feature = "lambda_calculus")]
+pub mod pretty;
+#[cfg(feature = "lambda_calculus")]
+pub mod program;
+pub mod repr;
+#[cfg(feature = "lambda_calculus")]
+pub mod report;
+#[cfg(feature = "lambda_calculus")]
+pub mod spec;
+#[cfg(feature = "lambda_calculus")]
+pub mod stdlib;
+#[cfg(feature = "lambda_calculus")]
+pub mod test_utils;
+#[cfg(feature = "lambda_calculus")]
+pub mod types;
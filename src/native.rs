@@ -0,0 +1,250 @@
+//! A minimal lambda `Term` with normal-order reduction that doesn't depend on `lambda_calculus`
+//!
+//! It is available with `features = ["native"]`; when the `lambda_calculus` feature is enabled as
+//! well, `Term`s can be converted to and from `lambda_calculus::Term`s with `From`.
+
+pub use self::Term::*;
+pub use encoding::binary::Error;
+use encoding::binary::from_bits_as;
+use repr::{TermRep, View};
+use std::fmt;
+use std::mem;
+
+/// A lambda term that is either a variable with a De Bruijn index, an abstraction over a term or
+/// an application of one term to another.
+#[derive(PartialEq, Eq, Clone, Hash)]
+pub enum Term {
+    /// a variable
+    Var(usize),
+    /// an abstraction
+    Abs(Box<Term>),
+    /// an application
+    App(Box<Term>, Box<Term>)
+}
+
+/// Wraps a `Term` in an `Abs`traction.
+pub fn abs(term: Term) -> Term { Abs(Box::new(term)) }
+
+/// Produces an `App`lication of two given `Term`s without any reduction.
+pub fn app(lhs: Term, rhs: Term) -> Term { App(Box::new(lhs), Box::new(rhs)) }
+
+/// Reduces a `Term` to its normal form using the normal evaluation order.
+///
+/// # Example
+/// ```
+/// use blc::native::{beta, from_bits};
+/// use blc::to_bits;
+///
+/// let k_i = from_bits(b"0100001100010").unwrap(); // (λλ2)(λ1)
+///
+/// assert_eq!(to_bits(&beta(k_i)), b"000010"); // λλ1
+/// ```
+pub fn beta(mut term: Term) -> Term {
+    term.reduce(0);
+    term
+}
+
+impl Term {
+    /// Performs normal-order β-reduction with an optional limit on the number of reductions (`0`
+    /// means no limit) and returns the number of performed reductions.
+    pub fn reduce(&mut self, limit: usize) -> usize {
+        let mut count = 0;
+        self.beta_nor(limit, &mut count);
+
+        count
+    }
+
+    fn beta_cbn(&mut self, limit: usize, count: &mut usize) {
+        if limit != 0 && *count == limit { return }
+
+        if let App(ref mut lhs, _) = *self {
+            lhs.beta_cbn(limit, count);
+        } else {
+            return
+        }
+
+        if self.is_reducible(limit, *count) {
+            self.eval(count);
+            self.beta_cbn(limit, count);
+        }
+    }
+
+    fn beta_nor(&mut self, limit: usize, count: &mut usize) {
+        if limit != 0 && *count == limit { return }
+
+        match *self {
+            Abs(ref mut body) => return body.beta_nor(limit, count),
+            App(ref mut lhs, _) => lhs.beta_cbn(limit, count),
+            Var(_) => return
+        }
+
+        if self.is_reducible(limit, *count) {
+            self.eval(count);
+            self.beta_nor(limit, count);
+        } else if let App(ref mut lhs, ref mut rhs) = *self {
+            lhs.beta_nor(limit, count);
+            rhs.beta_nor(limit, count);
+        }
+    }
+
+    fn is_reducible(&self, limit: usize, count: usize) -> bool {
+        if let App(ref lhs, _) = *self {
+            if let Abs(_) = **lhs { return limit == 0 || count < limit }
+        }
+        false
+    }
+
+    fn eval(&mut self, count: &mut usize) {
+        if let App(lhs, rhs) = mem::replace(self, Var(0)) {
+            if let Abs(mut body) = *lhs {
+                body.substitute(&rhs, 1);
+                *self = *body;
+                *count += 1;
+            }
+        }
+    }
+
+    fn substitute(&mut self, rhs: &Term, depth: usize) {
+        match *self {
+            Var(i) => if i == depth {
+                *self = rhs.clone();
+                self.shift(depth - 1, 0);
+            } else if i > depth {
+                *self = Var(i - 1)
+            },
+            Abs(ref mut body) => body.substitute(rhs, depth + 1),
+            App(ref mut lhs, ref mut rhs2) => {
+                lhs.substitute(rhs, depth);
+                rhs2.substitute(rhs, depth)
+            }
+        }
+    }
+
+    fn shift(&mut self, by: usize, depth: usize) {
+        match *self {
+            Var(ref mut i) => if *i > depth { *i += by },
+            Abs(ref mut body) => body.shift(by, depth + 1),
+            App(ref mut lhs, ref mut rhs) => {
+                lhs.shift(by, depth);
+                rhs.shift(by, depth)
+            }
+        }
+    }
+}
+
+/// Parse a blc-encoded `Term`.
+///
+/// # Example
+/// ```
+/// use blc::native::{from_bits, abs, Var};
+///
+/// assert_eq!(from_bits(b"0000110"), Ok(abs(abs(Var(2)))));
+/// ```
+pub fn from_bits(input: &[u8]) -> Result<Term, Error> {
+    from_bits_as(input)
+}
+
+impl TermRep for Term {
+    fn var(index: usize) -> Self { Var(index) }
+
+    fn abs(body: Self) -> Self { abs(body) }
+
+    fn app(lhs: Self, rhs: Self) -> Self { app(lhs, rhs) }
+
+    fn view(&self) -> View<'_, Self> {
+        match *self {
+            Var(i) => View::Var(i),
+            Abs(ref t) => View::Abs(t),
+            App(ref t1, ref t2) => View::App(t1, t2)
+        }
+    }
+}
+
+impl fmt::Debug for Term {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        show(self, 0, f)
+    }
+}
+
+fn show(term: &Term, context_precedence: usize, f: &mut fmt::Formatter) -> fmt::Result {
+    match *term {
+        Var(0) => write!(f, "undefined"),
+        Var(i) => write!(f, "{:X}", i),
+        Abs(ref t) => {
+            if context_precedence > 1 { write!(f, "(")? }
+            write!(f, "λ")?;
+            show(t, 0, f)?;
+            if context_precedence > 1 { write!(f, ")")? }
+            Ok(())
+        },
+        App(ref t1, ref t2) => {
+            if context_precedence == 3 { write!(f, "(")? }
+            show(t1, 2, f)?;
+            show(t2, 3, f)?;
+            if context_precedence == 3 { write!(f, ")")? }
+            Ok(())
+        }
+    }
+}
+
+#[cfg(feature = "lambda_calculus")]
+impl From<::lambda_calculus::Term> for Term {
+    fn from(term: ::lambda_calculus::Term) -> Self {
+        match term {
+            ::lambda_calculus::Var(i) => Var(i),
+            ::lambda_calculus::Abs(t) => abs(Term::from(*t)),
+            ::lambda_calculus::App(t1, t2) => app(Term::from(*t1), Term::from(*t2))
+        }
+    }
+}
+
+#[cfg(feature = "lambda_calculus")]
+impl From<Term> for ::lambda_calculus::Term {
+    fn from(term: Term) -> Self {
+        match term {
+            Var(i) => ::lambda_calculus::Var(i),
+            Abs(t) => ::lambda_calculus::abs((*t).into()),
+            App(t1, t2) => ::lambda_calculus::app((*t1).into(), (*t2).into())
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use encoding::binary::to_bits;
+
+    #[test]
+    fn parsing() {
+        assert_eq!(from_bits(b"10"),       Ok(Var(1)));
+        assert_eq!(from_bits(b"00 00\t10"), Ok(abs(abs(Var(1)))));
+        assert_eq!(from_bits(b"0111010"),  Ok(app(Var(2), Var(1))));
+        assert_eq!(from_bits(b"01"),       Err(Error::NotATerm));
+    }
+
+    #[test]
+    fn reduction() {
+        let succ = from_bits(b"000000011100101111011010").unwrap();
+        let zero = from_bits(b"000010").unwrap();
+
+        assert_eq!(format!("{:?}", beta(app(succ.clone(), zero))), "λλ21");
+        assert_eq!(format!("{:?}", succ), "λλλ2(321)");
+    }
+
+    #[test]
+    fn reduction_limit() {
+        let mut omega = from_bits(b"010001101000011010").unwrap(); // (λ11)(λ11)
+
+        assert_eq!(omega.reduce(5), 5);
+        assert_eq!(to_bits(&omega), b"010001101000011010");
+    }
+
+    #[cfg(feature = "lambda_calculus")]
+    #[test]
+    fn conversions() {
+        use lambda_calculus::combinators::S;
+
+        assert_eq!(::lambda_calculus::Term::from(Term::from(S())), S());
+        assert_eq!(to_bits(&Term::from(S())), to_bits(&S()));
+    }
+}
@@ -0,0 +1,174 @@
+//! Pretty-printing with known combinators
+//!
+//! Normal forms of real programs are long strings of De Bruijn indices that are hard to read.
+//! `pretty` prints them like the `Debug` implementation of `Term` does, but replaces well-known
+//! closed subterms with symbolic names:
+//!
+//! | term                 | printed as   |
+//! |----------------------|--------------|
+//! | λ1                   | `I`          |
+//! | λλ2                  | `K`          |
+//! | λλλ31(21)            | `S`          |
+//! | λ11                  | `ω`          |
+//! | (λ11)(λ11)           | `Ω`          |
+//! | λ(λ2(11))(λ2(11))    | `Y`          |
+//! | λλ1                  | `nil`        |
+//! | λλ2(2(...(21)))      | `#n`         |
+//! | λ1AB, B a list       | `[A, ...]`   |
+//! | λ1AB, B not a list   | `<A, B>`     |
+//!
+//! Since `λλ1` is also the numeral 0 and `λλ21` is the numeral 1, only numerals greater than 1
+//! are printed as numbers; the components of lists and pairs are recognized only if they are
+//! closed, as otherwise their indices would have to be read relative to the cell.
+
+use lambda_calculus::*;
+use lambda_calculus::combinators::{I, K, S, Y, O};
+
+/// Prints `term` with its well-known closed subterms replaced with their names; with `expand`,
+/// nothing is replaced and the result is the same as the `Debug` output.
+///
+/// # Example
+/// ```
+/// use blc::encoding::binary::from_bits;
+/// use blc::encoding::lambda::encode;
+/// use blc::pretty::pretty;
+///
+/// let k_s = from_bits(b"01000011000000001011110100111010").unwrap();
+///
+/// assert_eq!(pretty(&k_s, false), "KS");
+/// assert_eq!(pretty(&k_s, true), "(λλ2)(λλλ31(21))");
+/// assert_eq!(pretty(&encode(b"\x00"), false), "[[K, K, K, K, K, K, K, K]]");
+/// ```
+pub fn pretty(term: &Term, expand: bool) -> String {
+    if expand { return format!("{:?}", term) }
+
+    let mut out = String::new();
+    show(term, 0, &mut out);
+    out
+}
+
+fn show(term: &Term, context_precedence: usize, out: &mut String) {
+    if let Some(name) = name(term) { return out.push_str(name) }
+    if let Some(n) = numeral(term).filter(|&n| n > 1) { return out.push_str(&format!("#{}", n)) }
+    if let Some((head, tail)) = closed_cell(term) { return show_cell(head, tail, out) }
+
+    match *term {
+        Var(i) => out.push_str(&format!("{:X}", i)),
+        Abs(ref body) => {
+            let parenthesize = context_precedence > 1;
+            if parenthesize { out.push('(') }
+            out.push('λ');
+            show(body, 0, out);
+            if parenthesize { out.push(')') }
+        },
+        App(ref lhs, ref rhs) => {
+            let parenthesize = context_precedence == 3;
+            if parenthesize { out.push('(') }
+            show(lhs, 2, out);
+            show(rhs, 3, out);
+            if parenthesize { out.push(')') }
+        }
+    }
+}
+
+fn show_cell(head: &Term, tail: &Term, out: &mut String) {
+    let mut elements = vec![head];
+    let mut end = tail;
+    while let Some((head, tail)) = closed_cell(end) {
+        elements.push(head);
+        end = tail;
+    }
+    let is_list = name(end) == Some("nil");
+
+    if !is_list {
+        // not a list: show the first cell as a pair and its tail as it is
+        out.push('<');
+        show(head, 0, out);
+        out.push_str(", ");
+        show(tail, 0, out);
+        return out.push('>')
+    }
+
+    out.push('[');
+    for (i, element) in elements.into_iter().enumerate() {
+        if i != 0 { out.push_str(", ") }
+        show(element, 0, out);
+    }
+    out.push(']');
+}
+
+fn name(term: &Term) -> Option<&'static str> {
+    let known = [
+        (I(), "I"),
+        (K(), "K"),
+        (S(), "S"),
+        (Y(), "Y"),
+        (O(), "Ω"),
+        (abs(app(Var(1), Var(1))), "ω"),
+        (abs(abs(Var(1))), "nil")
+    ];
+
+    known.iter().find(|(known, _)| known == term).map(|&(_, name)| name)
+}
+
+/// Returns `n` if `term` is the Church numeral `n`.
+fn numeral(term: &Term) -> Option<usize> {
+    let mut body = term.unabs_ref().and_then(|t| t.unabs_ref()).ok()?;
+    let mut n = 0;
+
+    while let App(ref lhs, ref rhs) = *body {
+        if **lhs != Var(2) { return None }
+        n += 1;
+        body = rhs;
+    }
+
+    if *body == Var(1) { Some(n) } else { None }
+}
+
+fn closed_cell(term: &Term) -> Option<(&Term, &Term)> {
+    let (pair, tail) = term.unabs_ref().and_then(|t| t.unapp_ref()).ok()?;
+    let (var, head) = pair.unapp_ref().ok()?;
+
+    if *var == Var(1) && head.is_supercombinator() && tail.is_supercombinator() {
+        Some((head, tail))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use lambda_calculus::data::num::church::succ;
+
+    #[test]
+    fn combinators() {
+        assert_eq!(pretty(&app!(S(), K(), K()), false), "SKK");
+        assert_eq!(pretty(&app(Y(), app(K(), I())), false), "Y(KI)");
+        assert_eq!(pretty(&O(), false), "Ω");
+        assert_eq!(pretty(&abs(app(Var(1), K())), false), "λ1K");
+    }
+
+    #[test]
+    fn numerals() {
+        assert_eq!(pretty(&3.into_church(), false), "#3");
+        assert_eq!(pretty(&1.into_church(), false), "λλ21");
+        assert_eq!(pretty(&app(succ(), 5.into_church()), false), "(λλλ2(321))#5");
+    }
+
+    #[test]
+    fn lists_and_pairs() {
+        let cell = |head, tail| abs(app!(Var(1), head, tail));
+
+        assert_eq!(pretty(&cell(I(), cell(K(), abs(abs(Var(1))))), false), "[I, K]");
+        assert_eq!(pretty(&cell(I(), K()), false), "<I, K>");
+        assert_eq!(pretty(&cell(Var(2), abs(abs(Var(1)))), false), "λ12nil");
+    }
+
+    #[test]
+    fn expanding() {
+        let term = app!(S(), K(), 2.into_church());
+
+        assert_eq!(pretty(&term, true), format!("{:?}", term));
+    }
+}
@@ -0,0 +1,210 @@
+//! The BLC bundle format
+//!
+//! A bundle stores a collection of named programs, e.g. a set of submissions, in a single file.
+//! Its layout is:
+//!
+//! | field   | size     | contents                                                  |
+//! |---------|----------|-----------------------------------------------------------|
+//! | magic   | 4 bytes  | `BLCB`                                                    |
+//! | version | 1 byte   | `1`                                                       |
+//! | entries | variable | a 2-byte name length, a UTF-8 name, a 4-byte length and a |
+//! |         |          | `container` with the program                              |
+//!
+//! Multi-byte numbers are little-endian. Names are unique within a bundle; ones longer than 65535
+//! bytes are truncated to their longest prefix that fits and is valid UTF-8.
+
+use program::{container, truncated, Error, Format, Program};
+use std::fs;
+use std::path::Path;
+
+/// The bytes every bundle starts with.
+pub const MAGIC: &[u8; 4] = b"BLCB";
+
+const VERSION: u8 = 1;
+
+/// Returns `true` if `data` starts with the bundle `MAGIC`.
+pub fn is_bundle(data: &[u8]) -> bool {
+    data.starts_with(MAGIC)
+}
+
+/// A collection of named programs.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct Bundle {
+    entries: Vec<(String, Program)>
+}
+
+impl Bundle {
+    /// Creates an empty bundle.
+    pub fn new() -> Self { Bundle::default() }
+
+    /// Adds a program to the bundle under the given name, replacing the program previously
+    /// stored under it, if any.
+    ///
+    /// # Example
+    /// ```
+    /// use blc::program::{Program, Format};
+    /// use blc::program::bundle::Bundle;
+    ///
+    /// let mut bundle = Bundle::new();
+    /// bundle.insert("id", Program::parse(b"0010", Format::Text).unwrap());
+    /// bundle.insert("k", Program::parse(b"0000110", Format::Text).unwrap());
+    ///
+    /// assert_eq!(bundle.names().collect::<Vec<_>>(), ["id", "k"]);
+    /// assert_eq!(Bundle::from_bytes(&bundle.to_bytes()).unwrap(), bundle);
+    /// ```
+    pub fn insert<S: Into<String>>(&mut self, name: S, program: Program) {
+        let name = name.into();
+
+        match self.entries.iter_mut().find(|(entry, _)| *entry == name) {
+            Some(entry) => entry.1 = program,
+            None => self.entries.push((name, program))
+        }
+    }
+
+    /// Returns the program stored under the given name.
+    pub fn get(&self, name: &str) -> Option<&Program> {
+        self.entries.iter().find(|(entry, _)| entry == name).map(|(_, program)| program)
+    }
+
+    /// Removes the program stored under the given name and returns it.
+    pub fn remove(&mut self, name: &str) -> Option<Program> {
+        let index = self.entries.iter().position(|(entry, _)| entry == name)?;
+
+        Some(self.entries.remove(index).1)
+    }
+
+    /// Returns the names of the stored programs, in the order they were added in.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.entries.iter().map(|(name, _)| name.as_str())
+    }
+
+    /// Returns the stored programs along with their names, in the order they were added in.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Program)> {
+        self.entries.iter().map(|(name, program)| (name.as_str(), program))
+    }
+
+    /// Returns the number of stored programs.
+    pub fn len(&self) -> usize { self.entries.len() }
+
+    /// Returns `true` if the bundle contains no programs.
+    pub fn is_empty(&self) -> bool { self.entries.is_empty() }
+
+    /// Stores the bundle in the bundle format.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut output = MAGIC.to_vec();
+        output.push(VERSION);
+
+        for (name, program) in &self.entries {
+            let name = truncated(name).as_bytes();
+            let container = program.to_container();
+
+            output.extend_from_slice(&(name.len() as u16).to_le_bytes());
+            output.extend_from_slice(name);
+            output.extend_from_slice(&(container.len() as u32).to_le_bytes());
+            output.extend_from_slice(&container);
+        }
+
+        output
+    }
+
+    /// Reads a bundle stored in the bundle format.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::InvalidBundle` if `data` is not a valid bundle, or an `Error` if any of its
+    /// programs is not valid.
+    pub fn from_bytes(data: &[u8]) -> Result<Bundle, Error> {
+        if !is_bundle(data) || data.get(4) != Some(&VERSION) { return Err(Error::InvalidBundle) }
+
+        let mut bundle = Bundle::new();
+        let mut rest = &data[5..];
+
+        while !rest.is_empty() {
+            let (name, tail) = split_field(rest, 2)?;
+            let name = String::from_utf8(name.to_vec()).or(Err(Error::InvalidBundle))?;
+            let (contents, tail) = split_field(tail, 4)?;
+
+            if !container::is_container(contents) || bundle.get(&name).is_some() {
+                return Err(Error::InvalidBundle)
+            }
+            bundle.entries.push((name, Program::parse(contents, Format::Container)?));
+            rest = tail;
+        }
+
+        Ok(bundle)
+    }
+
+    /// Loads a bundle from a file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Error` if the file can't be read or doesn't contain a valid bundle.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Bundle, Error> {
+        Bundle::from_bytes(&fs::read(path)?)
+    }
+}
+
+/// Splits a field preceded by its little-endian length of `width` bytes from the rest of `data`.
+fn split_field(data: &[u8], width: usize) -> Result<(&[u8], &[u8]), Error> {
+    let len = data.get(..width).ok_or(Error::InvalidBundle)?;
+    let len = len.iter().rev().fold(0, |acc, &byte| acc << 8 | byte as usize);
+    let field = data.get(width..width + len).ok_or(Error::InvalidBundle)?;
+
+    Ok((field, &data[width + len..]))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use program::Metadata;
+
+    const REVERSE: &[u8] = b"0001011001000110100000000001011100111110111100001011011110110000010";
+
+    #[test]
+    fn round_trips() {
+        let reverse = Program::parse(REVERSE, Format::Text).unwrap()
+            .with_metadata(Metadata { name: None, author: Some("Tromp".into()) });
+        let mut bundle = Bundle::new();
+        assert_eq!(Bundle::from_bytes(&bundle.to_bytes()).unwrap(), bundle);
+
+        bundle.insert("reverse", reverse.clone());
+        bundle.insert("id", Program::parse(b"0010", Format::Text).unwrap());
+        let read = Bundle::from_bytes(&bundle.to_bytes()).unwrap();
+
+        assert_eq!(read.len(), 2);
+        assert_eq!(read.get("reverse"), Some(&reverse));
+    }
+
+    #[test]
+    fn replacing_and_removing() {
+        let mut bundle = Bundle::new();
+        bundle.insert("p", Program::parse(b"0010", Format::Text).unwrap());
+        bundle.insert("p", Program::parse(REVERSE, Format::Text).unwrap());
+
+        assert_eq!(bundle.len(), 1);
+        assert_eq!(bundle.remove("p").unwrap().bit_count(), REVERSE.len());
+        assert!(bundle.is_empty());
+    }
+
+    #[test]
+    fn long_names() {
+        let mut bundle = Bundle::new();
+        bundle.insert("é".repeat(40_000), Program::parse(b"0010", Format::Text).unwrap());
+        let read = Bundle::from_bytes(&bundle.to_bytes()).unwrap();
+
+        assert_eq!(read.iter().next().unwrap().0, "é".repeat(32_767));
+    }
+
+    #[test]
+    fn invalid_bundles() {
+        let mut bundle = Bundle::new();
+        bundle.insert("id", Program::parse(b"0010", Format::Text).unwrap());
+        let bytes = bundle.to_bytes();
+        let mut duplicated = bytes.clone();
+        duplicated.extend_from_slice(&bytes[5..]);
+
+        assert!(matches!(Bundle::from_bytes(b"BLCC"), Err(Error::InvalidBundle)));
+        assert!(matches!(Bundle::from_bytes(&bytes[..bytes.len() - 1]), Err(Error::InvalidBundle)));
+        assert!(matches!(Bundle::from_bytes(&duplicated), Err(Error::InvalidBundle)));
+    }
+}
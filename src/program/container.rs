@@ -0,0 +1,190 @@
+//! The BLC container format
+//!
+//! A container stores a packed program along with its exact length in bits, so that the padding
+//! of its last byte can't be mistaken for a part of it, and optional metadata. Its layout is:
+//!
+//! | field     | size          | contents                                            |
+//! |-----------|---------------|-----------------------------------------------------|
+//! | magic     | 4 bytes       | `BLCC`                                              |
+//! | version   | 1 byte        | `1`                                                 |
+//! | metadata  | variable      | fields: a tag byte, a 2-byte length, UTF-8 contents |
+//! | end       | 1 byte        | `0`                                                 |
+//! | bit count | 8 bytes       | the number of bits of the program                   |
+//! | program   | bit count / 8 | the packed program, padded with `0`s                |
+//!
+//! Multi-byte numbers are little-endian. Tag `1` stands for the name of the program and tag `2`
+//! for its author; fields with unknown tags are skipped, so that more can be added later. Values
+//! longer than 65535 bytes are truncated to their longest prefix that fits and is valid UTF-8.
+
+use program::{truncated, Error, Metadata};
+
+/// The bytes every container starts with.
+pub const MAGIC: &[u8; 4] = b"BLCC";
+
+const VERSION: u8 = 1;
+const END: u8 = 0;
+const NAME: u8 = 1;
+const AUTHOR: u8 = 2;
+
+/// Returns `true` if `data` starts with the container `MAGIC`.
+pub fn is_container(data: &[u8]) -> bool {
+    data.starts_with(MAGIC)
+}
+
+/// Stores a textual program and its metadata in a container.
+///
+/// # Example
+/// ```
+/// use blc::program::Metadata;
+/// use blc::program::container::{read, write};
+///
+/// let metadata = Metadata { name: Some("identity".into()), author: None };
+/// let container = write(b"0010", &metadata);
+///
+/// assert_eq!(read(&container).unwrap(), (metadata, b"0010".to_vec()));
+/// ```
+pub fn write(bits: &[u8], metadata: &Metadata) -> Vec<u8> {
+    let bits = bits.iter().cloned().filter(|b| !b" \t\r\n".contains(b)).collect::<Vec<u8>>();
+    let mut output = MAGIC.to_vec();
+    output.push(VERSION);
+
+    for &(tag, value) in &[(NAME, &metadata.name), (AUTHOR, &metadata.author)] {
+        if let Some(ref value) = *value {
+            let value = truncated(value).as_bytes();
+            output.push(tag);
+            output.extend_from_slice(&(value.len() as u16).to_le_bytes());
+            output.extend_from_slice(value);
+        }
+    }
+    output.push(END);
+
+    output.extend_from_slice(&(bits.len() as u64).to_le_bytes());
+    for chunk in bits.chunks(8) {
+        let byte = chunk.iter().fold(0u8, |acc, &b| acc << 1 | (b == b'1') as u8);
+        output.push(byte << (8 - chunk.len()));
+    }
+
+    output
+}
+
+/// Reads the metadata and the textual program from a container.
+///
+/// # Errors
+///
+/// Returns `Error::InvalidContainer` if `data` is not a valid container.
+pub fn read(data: &[u8]) -> Result<(Metadata, Vec<u8>), Error> {
+    let (metadata, rest) = read_header(data)?;
+
+    let count = rest.get(..8).ok_or(Error::InvalidContainer)?;
+    let mut count_bytes = [0; 8];
+    count_bytes.copy_from_slice(count);
+    let bit_count = u64::from_le_bytes(count_bytes) as usize;
+
+    let packed = &rest[8..];
+    if packed.len() != bit_count.div_ceil(8) { return Err(Error::InvalidContainer) }
+
+    let bits = packed.iter()
+        .flat_map(|byte| (0..8).rev().map(move |i| if byte >> i & 1 == 1 { b'1' } else { b'0' }))
+        .take(bit_count)
+        .collect();
+
+    Ok((metadata, bits))
+}
+
+/// Reads only the metadata of a container.
+///
+/// # Errors
+///
+/// Returns `Error::InvalidContainer` if `data` doesn't start with a valid container header.
+pub fn read_metadata(data: &[u8]) -> Result<Metadata, Error> {
+    Ok(read_header(data)?.0)
+}
+
+/// Removes all the metadata from a container.
+///
+/// # Errors
+///
+/// Returns `Error::InvalidContainer` if `data` is not a valid container.
+pub fn strip_metadata(data: &[u8]) -> Result<Vec<u8>, Error> {
+    let (_, bits) = read(data)?;
+
+    Ok(write(&bits, &Metadata::default()))
+}
+
+fn read_header(data: &[u8]) -> Result<(Metadata, &[u8]), Error> {
+    if !is_container(data) || data.get(4) != Some(&VERSION) { return Err(Error::InvalidContainer) }
+
+    let mut metadata = Metadata::default();
+    let mut pos = 5;
+
+    loop {
+        let tag = *data.get(pos).ok_or(Error::InvalidContainer)?;
+        pos += 1;
+        if tag == END { return Ok((metadata, &data[pos..])) }
+
+        let len = data.get(pos..pos + 2).ok_or(Error::InvalidContainer)?;
+        let len = u16::from_le_bytes([len[0], len[1]]) as usize;
+        let value = data.get(pos + 2..pos + 2 + len).ok_or(Error::InvalidContainer)?;
+        let value = String::from_utf8(value.to_vec()).or(Err(Error::InvalidContainer))?;
+        pos += 2 + len;
+
+        match tag {
+            NAME => metadata.name = Some(value),
+            AUTHOR => metadata.author = Some(value),
+            _ => ()
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn round_trips() {
+        let metadata = Metadata { name: Some("reverse".into()), author: Some("Tromp".into()) };
+        let bits = b"0001011001000110100000000001011100111110111100001011011110110000010";
+
+        for len in 0..bits.len() {
+            let container = write(&bits[..len], &metadata);
+
+            assert_eq!(read(&container).unwrap(), (metadata.clone(), bits[..len].to_vec()));
+        }
+    }
+
+    #[test]
+    fn metadata() {
+        let metadata = Metadata { name: None, author: Some("me".into()) };
+        let container = write(b"0010", &metadata);
+
+        assert_eq!(read_metadata(&container).unwrap(), metadata);
+        let stripped = strip_metadata(&container).unwrap();
+
+        assert_eq!(read_metadata(&stripped).unwrap(), Metadata::default());
+    }
+
+    #[test]
+    fn unknown_fields_are_skipped() {
+        let mut container = write(b"0010", &Metadata::default());
+        container.splice(5..5, vec![7, 2, 0, b'h', b'i']);
+
+        assert_eq!(read(&container).unwrap(), (Metadata::default(), b"0010".to_vec()));
+    }
+
+    #[test]
+    fn long_values() {
+        let metadata = Metadata { name: Some("é".repeat(40_000)), author: None };
+        let container = write(b"0010", &metadata);
+        let name = read_metadata(&container).unwrap().name.unwrap();
+
+        assert_eq!(name, "é".repeat(32_767));
+    }
+
+    #[test]
+    fn invalid_containers() {
+        let container = write(b"0010", &Metadata::default());
+
+        assert!(matches!(read(b"0010"), Err(Error::InvalidContainer)));
+        assert!(matches!(read(&container[..container.len() - 1]), Err(Error::InvalidContainer)));
+    }
+}
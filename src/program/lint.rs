@@ -0,0 +1,307 @@
+//! Static analysis of programs
+//!
+//! `lint` examines a program without executing it: it reports problems that make it invalid, like
+//! unbound variables, patterns that are likely mistakes, like arguments that are discarded without
+//! ever being used or an immediate `Ω`, and issues with the way it is stored, like non-zero padding
+//! of a packed program; it also measures the size of the program.
+
+use lambda_calculus::*;
+use lambda_calculus::combinators::O;
+use diagnostics::diagnose;
+use encoding::binary::{decompress, from_bits, to_bits};
+use execution::explain::Branch;
+use program::{container, skip_header, uncompress, Error, Format};
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+/// The severity of a `Finding`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, PartialOrd, Ord)]
+pub enum Severity {
+    /// something worth knowing, but usually intentional
+    Note,
+    /// something that is probably a mistake
+    Warning,
+    /// something that makes the program invalid
+    Error
+}
+
+/// A problem found in a program.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Finding {
+    /// how serious the problem is
+    pub severity: Severity,
+    /// the description of the problem
+    pub message: String,
+    /// the path from the root of the term to the offending subterm; empty for problems with the
+    /// storage of the program
+    pub location: Vec<Branch>
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Note => "note",
+            Severity::Warning => "warning",
+            Severity::Error => "error"
+        };
+        write!(f, "{}: {}", severity, self.message)?;
+
+        if !self.location.is_empty() {
+            let path = self.location.iter().map(|branch| match *branch {
+                Branch::Body => "body",
+                Branch::Function => "function",
+                Branch::Argument => "argument"
+            }).collect::<Vec<_>>();
+            write!(f, " (at {})", path.join("/"))?;
+        }
+        Ok(())
+    }
+}
+
+/// Size measurements of a program.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct Metrics {
+    /// the length of the program, in bits
+    pub bits: usize,
+    /// the number of abstractions
+    pub abstractions: usize,
+    /// the number of applications
+    pub applications: usize,
+    /// the number of variables
+    pub variables: usize,
+    /// the largest number of nested abstractions
+    pub depth: usize,
+    /// the largest De Bruijn index
+    pub max_index: usize
+}
+
+/// The results of linting a program.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Report {
+    /// the size of the program, if it could be parsed
+    pub metrics: Option<Metrics>,
+    /// the problems found in the program, in the order they were found in
+    pub findings: Vec<Finding>
+}
+
+impl Report {
+    /// Returns `true` if no `Error`s were found.
+    pub fn is_valid(&self) -> bool {
+        self.findings.iter().all(|finding| finding.severity != Severity::Error)
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for finding in &self.findings {
+            writeln!(f, "{}", finding)?;
+        }
+        if let Some(m) = self.metrics {
+            writeln!(
+                f,
+                "{} bits, {} abstractions, {} applications, {} variables, depth {}, max index {}",
+                m.bits, m.abstractions, m.applications, m.variables, m.depth, m.max_index
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Lints a program stored in the given `Format`.
+///
+/// # Example
+/// ```
+/// use blc::program::Format;
+/// use blc::program::lint::{lint, Severity};
+///
+/// let report = lint(b"01 010001101000011010 0010", Format::Text); // (λ11)(λ11)(λ1)
+///
+/// assert!(report.is_valid());
+/// assert_eq!(report.findings[0].severity, Severity::Warning);
+/// assert_eq!(report.findings[0].message, "Ω never reaches a normal form");
+/// assert_eq!(report.metrics.unwrap().bits, 24);
+///
+/// assert!(!lint(b"0110", Format::Text).is_valid());
+/// ```
+pub fn lint(contents: &[u8], format: Format) -> Report {
+    let mut findings = Vec::new();
+    let mut report_unbound = true;
+
+    let term = match format {
+        Format::Text => {
+            let source = skip_header(contents);
+            let diagnostics = diagnose(source);
+            findings.extend(diagnostics.iter().map(|diagnostic| Finding {
+                severity: Severity::Error,
+                message: format!("{} at bytes {:?}", diagnostic.message, diagnostic.span),
+                location: Vec::new()
+            }));
+            // the diagnostics already cover unbound variables, with their positions in the source
+            report_unbound = false;
+
+            match from_bits(source) {
+                Ok(term) => Some(term),
+                Err(_) if !diagnostics.is_empty() => return Report { metrics: None, findings },
+                Err(_) => None
+            }
+        },
+        Format::Packed => {
+            let term = from_bits(&decompress(contents)).ok();
+            if let Some(ref term) = term { check_packing(term, contents, &mut findings) }
+            term
+        },
+        Format::Container => container::read(contents).ok().and_then(|(_, bits)| {
+            from_bits(&bits).ok()
+        })
+    };
+
+    let term = match term {
+        Some(term) => term,
+        None => {
+            findings.push(storage_finding(Severity::Error, "the program is not valid BLC".into()));
+            return Report { metrics: None, findings }
+        }
+    };
+
+    let mut metrics = Metrics { bits: to_bits(&term).len(), ..Metrics::default() };
+    check_term(&term, 0, report_unbound, &mut Vec::new(), &mut metrics, &mut findings);
+
+    Report { metrics: Some(metrics), findings }
+}
+
+/// Loads a program from a file like `Program::from_path` does and lints it.
+///
+/// # Errors
+///
+/// Returns an `Error` if the file can't be read or decompressed.
+pub fn lint_path<P: AsRef<Path>>(path: P) -> Result<Report, Error> {
+    let (path, contents) = uncompress(path.as_ref(), fs::read(path.as_ref())?)?;
+
+    Ok(lint(&contents, Format::detect(&path, &contents)))
+}
+
+fn storage_finding(severity: Severity, message: String) -> Finding {
+    Finding { severity, message, location: Vec::new() }
+}
+
+fn check_packing(term: &Term, contents: &[u8], findings: &mut Vec<Finding>) {
+    let bits = to_bits(term).len();
+    let used_bytes = bits.div_ceil(8);
+
+    if contents.len() > used_bytes {
+        let message = format!("{} bytes after the end of the program", contents.len() - used_bytes);
+        findings.push(storage_finding(Severity::Warning, message));
+    }
+    let last_byte = contents.get(used_bytes - 1).cloned().unwrap_or_default();
+    if !bits.is_multiple_of(8) && last_byte & (0xff >> (bits % 8)) != 0 {
+        let message = "the padding of the last byte of the program is not zero".into();
+        findings.push(storage_finding(Severity::Warning, message));
+    }
+}
+
+fn check_term(
+    term: &Term,
+    depth: usize,
+    report_unbound: bool,
+    location: &mut Vec<Branch>,
+    metrics: &mut Metrics,
+    findings: &mut Vec<Finding>
+) {
+    let mut find = |severity, message: String, location: &[Branch]| {
+        findings.push(Finding { severity, message, location: location.to_vec() })
+    };
+
+    match *term {
+        Var(i) => {
+            metrics.variables += 1;
+            metrics.max_index = metrics.max_index.max(i);
+            if i > depth && report_unbound {
+                find(Severity::Error, format!("variable {} is not bound", i), location);
+            }
+        },
+        Abs(ref body) => {
+            metrics.abstractions += 1;
+            metrics.depth = metrics.depth.max(depth + 1);
+            if !occurs(body, 1) {
+                find(Severity::Note, "the variable bound here is never used".into(), location);
+            }
+
+            location.push(Branch::Body);
+            check_term(body, depth + 1, report_unbound, location, metrics, findings);
+            location.pop();
+        },
+        App(ref lhs, ref rhs) => {
+            metrics.applications += 1;
+            if *term == O() {
+                find(Severity::Warning, "Ω never reaches a normal form".into(), location);
+            } else if let Abs(ref body) = **lhs {
+                if !occurs(body, 1) {
+                    let message = "the argument is discarded without being used".into();
+                    find(Severity::Warning, message, location);
+                }
+            }
+
+            location.push(Branch::Function);
+            check_term(lhs, depth, report_unbound, location, metrics, findings);
+            location.pop();
+            location.push(Branch::Argument);
+            check_term(rhs, depth, report_unbound, location, metrics, findings);
+            location.pop();
+        }
+    }
+}
+
+/// Returns `true` if the variable bound `depth` abstractions above `term` occurs in it.
+fn occurs(term: &Term, depth: usize) -> bool {
+    match *term {
+        Var(i) => i == depth,
+        Abs(ref body) => occurs(body, depth + 1),
+        App(ref lhs, ref rhs) => occurs(lhs, depth) || occurs(rhs, depth)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use encoding::binary::compress;
+
+    #[test]
+    fn term_findings() {
+        let messages = |report: Report| {
+            report.findings.iter().map(|f| f.to_string()).collect::<Vec<_>>()
+        };
+        let program = from_bits(b"01 000010 00 01 10 1110").unwrap(); // (λλ1)(λ1 3)
+
+        assert_eq!(messages(lint(&compress(&to_bits(&program)), Format::Packed)), [
+            "warning: the argument is discarded without being used",
+            "note: the variable bound here is never used (at function)",
+            "error: variable 3 is not bound (at argument/body/argument)"
+        ]);
+        assert_eq!(messages(lint(b"01 000010 00 01 10 1110", Format::Text)), [
+            "error: variable 3 is not bound at bytes 19..23",
+            "warning: the argument is discarded without being used",
+            "note: the variable bound here is never used (at function)"
+        ]);
+    }
+
+    #[test]
+    fn storage_findings() {
+        assert!(!lint(b"0011", Format::Text).is_valid());
+        for truncated in [&b"0"[..], b"111"] {
+            let report = lint(truncated, Format::Text);
+            assert_eq!(report.findings.len(), 1);
+            assert!(report.findings[0].message.starts_with("unexpected end of input"));
+            assert_eq!(report.metrics, None);
+        }
+        assert_eq!(lint(b"", Format::Packed).findings[0].severity, Severity::Error);
+
+        // λ1 is `0010`, followed by a non-zero padding and a whole byte
+        let report = lint(&[0x21, 0x00], Format::Packed);
+        assert!(report.is_valid());
+        assert_eq!(report.findings.len(), 2);
+        assert_eq!(report.metrics, Some(Metrics {
+            bits: 4, abstractions: 1, applications: 0, variables: 1, depth: 1, max_index: 1
+        }));
+    }
+}
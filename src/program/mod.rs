@@ -0,0 +1,510 @@
+//! BLC programs and their files
+//!
+//! Programs are stored either as text, with every bit written as an ASCII `0` or `1` (usually in
+//! `.blc` files), or packed, with 8 bits in every byte (usually in `.Blc` files). Program files
+//! can also be compressed with gzip (`.gz`) or zstd (`.zst`), which is handled transparently with
+//! the `gzip` and `zstd` features respectively.
+//!
+//! Programs can also be stored in `container`s, which can carry their `Metadata`, and collections
+//! of named programs in a `bundle`. The definitions and settings of interactive sessions are kept
+//! in `session` files.
+
+pub mod bundle;
+pub mod container;
+pub mod lint;
+pub mod session;
+
+use lambda_calculus::*;
+use encoding::binary::{self, from_bits, to_bits, decompress};
+#[cfg(feature = "gzip")]
+use encoding::gzip;
+#[cfg(feature = "zstd")]
+use encoding::zstd;
+use execution::{self, run_term, run_term_bytes, ClosedTerm, Input, Options};
+use execution::vm::Bytecode;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// The format of a stored BLC program.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Format {
+    /// ASCII `0`s and `1`s, possibly separated by whitespace and preceded by a header of lines
+    /// starting with `#`
+    Text,
+    /// bits packed into bytes, most significant first
+    Packed,
+    /// a packed program with its exact length and `Metadata`; see the `container` module
+    Container
+}
+
+impl Format {
+    /// Guesses the format of a program from its contents if it is a `Container` or otherwise from
+    /// the extension of its path, `.blc` for `Text` and `.Blc` for `Packed`, falling back to the
+    /// contents if the extension is not one of them.
+    ///
+    /// # Example
+    /// ```
+    /// use blc::program::Format;
+    ///
+    /// assert_eq!(Format::detect("id.Blc".as_ref(), b"\x20"), Format::Packed);
+    /// assert_eq!(Format::detect("id.txt".as_ref(), b"0010\n"), Format::Text);
+    /// ```
+    pub fn detect(path: &Path, contents: &[u8]) -> Format {
+        if container::is_container(contents) { return Format::Container }
+
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("blc") => Format::Text,
+            Some("Blc") => Format::Packed,
+            _ if is_text(contents) => Format::Text,
+            _ => Format::Packed
+        }
+    }
+}
+
+fn is_text(contents: &[u8]) -> bool {
+    let contents = skip_header(contents);
+
+    !contents.is_empty() && contents.iter().all(|b| b"01 \t\r\n".contains(b))
+}
+
+// the longest prefix of `value` that fits a 2-byte length and doesn't split a character
+fn truncated(value: &str) -> &str {
+    let mut len = value.len().min(u16::MAX as usize);
+    while !value.is_char_boundary(len) { len -= 1 }
+
+    &value[..len]
+}
+
+/// Skips the leading lines of a textual program that are blank or start with `#`, like a `#!`
+/// interpreter line or comments.
+///
+/// # Example
+/// ```
+/// use blc::program::skip_header;
+///
+/// assert_eq!(skip_header(b"#!/usr/bin/env blc exec\n# identity\n\n0010\n"), b"0010\n");
+/// ```
+pub fn skip_header(mut contents: &[u8]) -> &[u8] {
+    loop {
+        let line_end = contents.iter()
+            .position(|&b| b == b'\n')
+            .map_or(contents.len(), |pos| pos + 1);
+        let line = &contents[..line_end];
+        let is_blank = line.iter().all(|b| b" \t\r\n".contains(b));
+
+        if line_end == 0 || !(line.starts_with(b"#") || is_blank) { return contents }
+        contents = &contents[line_end..];
+    }
+}
+
+/// An error that can occur when loading or running a program.
+#[derive(Debug)]
+pub enum Error {
+    /// the program or its input couldn't be read
+    Io(io::Error),
+    /// the program is not valid BLC
+    Parse(binary::Error),
+    /// the program failed to execute
+    Execution(execution::Error),
+    /// the program container is malformed
+    InvalidContainer,
+    /// the program bundle is malformed
+    InvalidBundle,
+    /// the session file is malformed at the given line
+    InvalidSession(usize),
+    /// the session entry with the given name or key can't be stored in a session file
+    InvalidSessionEntry(String),
+    /// the program file is compressed with the given unsupported method
+    UnsupportedCompression(&'static str),
+    /// the gzip-compressed program file is corrupted
+    #[cfg(feature = "gzip")]
+    Gzip(gzip::Error),
+    /// the zstd-compressed program file is corrupted
+    #[cfg(feature = "zstd")]
+    Zstd(zstd::Error)
+}
+
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Self { Error::Io(error) }
+}
+
+impl From<binary::Error> for Error {
+    fn from(error: binary::Error) -> Self { Error::Parse(error) }
+}
+
+#[cfg(feature = "gzip")]
+impl From<gzip::Error> for Error {
+    fn from(error: gzip::Error) -> Self { Error::Gzip(error) }
+}
+
+#[cfg(feature = "zstd")]
+impl From<zstd::Error> for Error {
+    fn from(error: zstd::Error) -> Self { Error::Zstd(error) }
+}
+
+impl From<execution::Error> for Error {
+    fn from(error: execution::Error) -> Self { Error::Execution(error) }
+}
+
+/// Optional information about a program.
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct Metadata {
+    /// the name of the program
+    pub name: Option<String>,
+    /// the author of the program
+    pub author: Option<String>
+}
+
+/// A parsed BLC program; it can be executed any number of times without being parsed again.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Program {
+    term: Term,
+    metadata: Metadata
+}
+
+impl Program {
+    /// Parses a program stored in the given `Format`.
+    ///
+    /// # Example
+    /// ```
+    /// use blc::program::{Program, Format};
+    ///
+    /// let text = Program::parse(b"0010", Format::Text).unwrap();
+    /// let packed = Program::parse(&[0x20], Format::Packed).unwrap();
+    ///
+    /// assert_eq!(text, packed);
+    /// ```
+    /// # Errors
+    ///
+    /// Returns an `Error` if the program is not valid BLC.
+    pub fn parse(contents: &[u8], format: Format) -> Result<Program, Error> {
+        let (term, metadata) = match format {
+            Format::Text      => (from_bits(skip_header(contents))?, Metadata::default()),
+            Format::Packed    => (from_bits(&decompress(contents))?, Metadata::default()),
+            Format::Container => {
+                let (metadata, bits) = container::read(contents)?;
+                (from_bits(&bits)?, metadata)
+            }
+        };
+
+        Ok(Program { term, metadata })
+    }
+
+    /// Parses a program written as ASCII `0`s and `1`s; equivalent to `parse` with `Format::Text`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Error` if the program is not valid BLC.
+    pub fn from_bits(blc_program: &[u8]) -> Result<Program, Error> {
+        Program::parse(blc_program, Format::Text)
+    }
+
+    /// Parses a packed program, with 8 bits in every byte; equivalent to `parse` with
+    /// `Format::Packed`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Error` if the program is not valid BLC.
+    pub fn from_bytes(packed: &[u8]) -> Result<Program, Error> {
+        Program::parse(packed, Format::Packed)
+    }
+
+    /// Loads a program from a file, decompressing it if needed and detecting its `Format` with
+    /// `Format::detect`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Error` if the file can't be read or decompressed or doesn't contain a valid BLC
+    /// program.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Program, Error> {
+        let (path, contents) = uncompress(path.as_ref(), fs::read(path.as_ref())?)?;
+
+        Program::parse(&contents, Format::detect(&path, &contents))
+    }
+
+    /// Returns the `Metadata` of the program.
+    pub fn metadata(&self) -> &Metadata { &self.metadata }
+
+    /// Replaces the `Metadata` of the program.
+    ///
+    /// # Example
+    /// ```
+    /// use blc::program::{Program, Format, Metadata};
+    ///
+    /// let metadata = Metadata { name: Some("identity".into()), author: None };
+    /// let program = Program::parse(b"0010", Format::Text).unwrap();
+    /// let program = program.with_metadata(metadata.clone());
+    /// let stored = program.to_container();
+    ///
+    /// assert_eq!(Program::parse(&stored, Format::Container).unwrap().metadata(), &metadata);
+    /// assert_eq!(program.strip_metadata().metadata(), &Metadata::default());
+    /// ```
+    pub fn with_metadata(self, metadata: Metadata) -> Program {
+        Program { metadata, ..self }
+    }
+
+    /// Removes all the `Metadata` of the program.
+    pub fn strip_metadata(self) -> Program {
+        self.with_metadata(Metadata::default())
+    }
+
+    /// Returns the length of the program in bits.
+    pub fn bit_count(&self) -> usize {
+        to_bits(&self.term).len()
+    }
+
+    /// Stores the program along with its `Metadata` in a `Container`.
+    pub fn to_container(&self) -> Vec<u8> {
+        container::write(&to_bits(&self.term), &self.metadata)
+    }
+
+    /// Executes the program like `execution::run` does, without parsing it again.
+    ///
+    /// # Example
+    /// ```
+    /// use blc::program::Program;
+    /// use blc::execution::Input::Bytes;
+    ///
+    /// let reverse_blc = b"0001011001000110100000000001011100111110111100001011011110110000010";
+    /// let reverse = Program::from_bits(reverse_blc).unwrap();
+    ///
+    /// assert_eq!(reverse.run(Bytes(b"herp")), Ok("preh".into()));
+    /// assert_eq!(reverse.run(Bytes(b"derp")), Ok("pred".into()));
+    /// ```
+    /// # Errors
+    ///
+    /// Returns the same `Error`s as `execution::run`.
+    pub fn run(&self, input: Input) -> Result<String, execution::Error> {
+        self.run_with(input, Options::default())
+    }
+
+    /// Executes the program like `execution::run_with` does, with the given `Options`.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same `Error`s as `execution::run_with`.
+    pub fn run_with(&self, input: Input, options: Options) -> Result<String, execution::Error> {
+        run_term(ClosedTerm::new(self.term.clone())?, input, options)
+    }
+
+    /// Executes the program like `execution::run_bytes` does, without parsing it again.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same `Error`s as `execution::run_bytes`.
+    pub fn run_bytes(&self, input: Input) -> Result<Vec<u8>, execution::Error> {
+        run_term_bytes(ClosedTerm::new(self.term.clone())?, input, Options::default())
+    }
+
+    /// Reduces the program ahead of its executions, so they don't repeat the work: the whole
+    /// program is normalized if that takes at most `PRECOMPILATION_FUEL` β-reductions, and
+    /// otherwise each of its subterms that does, like the function a fixed-point combinator is
+    /// applied to. The precompiled program is β-equivalent to the original one, so it has the same
+    /// outputs, but it can be longer.
+    ///
+    /// # Example
+    /// ```
+    /// use blc::program::Program;
+    /// use blc::encoding::binary::to_bits;
+    ///
+    /// let identity = Program::from_bits(b"0001001010").unwrap(); // λ(λ1)1
+    ///
+    /// assert_eq!(to_bits(identity.precompile().term()), b"0010"); // λ1
+    /// ```
+    pub fn precompile(self) -> Program {
+        Program { term: precompiled(self.term), ..self }
+    }
+
+    /// Compiles the program to `Bytecode` for `vm::Vm`.
+    pub fn compile(&self) -> Bytecode {
+        Bytecode::compile(&self.term)
+    }
+
+    /// Returns the lambda `Term` of the program.
+    pub fn term(&self) -> &Term { &self.term }
+
+    /// Converts the program into its lambda `Term`.
+    pub fn into_term(self) -> Term { self.term }
+}
+
+/// The number of β-reductions `Program::precompile` spends trying to normalize a subterm.
+pub const PRECOMPILATION_FUEL: usize = 1024;
+
+/// Normalizes `term` if it has a normal form reachable within `PRECOMPILATION_FUEL` reductions,
+/// or otherwise its subterms that do.
+fn precompiled(term: Term) -> Term {
+    let mut normalized = term.clone();
+    if normalized.reduce(NOR, PRECOMPILATION_FUEL) < PRECOMPILATION_FUEL { return normalized }
+
+    match term {
+        Abs(body)      => abs(precompiled(*body)),
+        App(lhs, rhs)  => app(precompiled(*lhs), precompiled(*rhs)),
+        var            => var
+    }
+}
+
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+
+/// Removes a compression wrapper from the contents of the file at `path`; returns the path without
+/// the compression extension along with the uncompressed contents.
+fn uncompress(path: &Path, contents: Vec<u8>) -> Result<(PathBuf, Vec<u8>), Error> {
+    let extension = path.extension().and_then(|ext| ext.to_str());
+    let inner_path = match extension {
+        Some("gz") | Some("zst") => path.with_extension(""),
+        _ => path.to_path_buf()
+    };
+
+    if extension == Some("zst") || contents.starts_with(&ZSTD_MAGIC) {
+        #[cfg(feature = "zstd")]
+        return Ok((inner_path, zstd::decompress(&contents)?));
+        #[cfg(not(feature = "zstd"))]
+        return Err(Error::UnsupportedCompression("zstd"));
+    } else if extension == Some("gz") || contents.starts_with(&[0x1f, 0x8b]) {
+        #[cfg(feature = "gzip")]
+        return Ok((inner_path, gzip::decompress(&contents)?));
+        #[cfg(not(feature = "gzip"))]
+        return Err(Error::UnsupportedCompression("gzip"));
+    } else {
+        Ok((inner_path, contents))
+    }
+}
+
+/// Loads a program with `Program::from_path` and executes it with the contents of the input file
+/// as unencoded byte input.
+///
+/// # Errors
+///
+/// Returns an `Error` if either of the files can't be read, if the program is not valid BLC or if
+/// it fails to execute.
+pub fn run_file<P: AsRef<Path>, Q: AsRef<Path>>(program_path: P, input_path: Q)
+    -> Result<String, Error>
+{
+    let program = Program::from_path(program_path)?;
+    let input = fs::read(input_path)?;
+
+    let program = ClosedTerm::new(program.into_term())?;
+
+    Ok(run_term(program, Input::Bytes(&input), Options::default())?)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use lambda_calculus::combinators::{I, O};
+    use std::env;
+    use std::path::PathBuf;
+
+    const REVERSE: &[u8] = b"0001011001000110100000000001011100111110111100001011011110110000010";
+
+    fn temp_file(name: &str, contents: &[u8]) -> PathBuf {
+        let path = env::temp_dir().join(format!("blc-program-test-{}", name));
+        fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn format_detection() {
+        assert_eq!(Format::detect("a.blc".as_ref(), &[0xff]), Format::Text);
+        assert_eq!(Format::detect("a.Blc".as_ref(), b"0010"), Format::Packed);
+        assert_eq!(Format::detect("a".as_ref(), b"00 10\n"), Format::Text);
+        assert_eq!(Format::detect("a".as_ref(), b"\x01\xcb\xda"), Format::Packed);
+    }
+
+    #[test]
+    fn running_files() {
+        let program = temp_file("reverse.blc", REVERSE);
+        let packed = temp_file("reverse.Blc", &binary::compress(REVERSE));
+        let input = temp_file("reverse-input", b"herp derp");
+
+        assert_eq!(run_file(&program, &input).unwrap(), "pred preh");
+        assert_eq!(Program::from_path(&packed).unwrap(), Program::from_path(&program).unwrap());
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn compressed_files() {
+        let gzipped = [
+            0x1f, 0x8b, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02, 0x03, 0x33, 0x30, 0x30, 0x34,
+            0x00, 0x00, 0x33, 0xf5, 0x80, 0x15, 0x04, 0x00, 0x00, 0x00
+        ];
+        let program = temp_file("id.blc.gz", &gzipped);
+        let id = Program::parse(b"0010", Format::Text).unwrap();
+
+        assert_eq!(Program::from_path(&program).unwrap(), id);
+    }
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn zstd_compressed_files() {
+        let zstd = [
+            0x28, 0xb5, 0x2f, 0xfd, 0x04, 0x58, 0x21, 0x00, 0x00, 0x30, 0x30, 0x31, 0x30, 0x01,
+            0x21, 0xc5, 0xbc
+        ];
+        let program = temp_file("id.blc.zst", &zstd);
+        let truncated = temp_file("truncated.blc.zst", &zstd[..4]);
+
+        let id = Program::parse(b"0010", Format::Text).unwrap();
+
+        assert_eq!(Program::from_path(&program).unwrap(), id);
+        assert!(matches!(Program::from_path(&truncated), Err(Error::Zstd(_))));
+    }
+
+    #[test]
+    fn containers() {
+        let program = Program::parse(REVERSE, Format::Text).unwrap()
+            .with_metadata(Metadata { name: Some("reverse".into()), author: None });
+        let path = temp_file("reverse.Blc", &program.to_container());
+
+        assert_eq!(Program::from_path(&path).unwrap(), program);
+        assert_eq!(program.bit_count(), REVERSE.len());
+    }
+
+    #[test]
+    fn scripts() {
+        let mut script = b"#!/usr/bin/env blc exec\n\n# reverses its input\n".to_vec();
+        script.extend_from_slice(REVERSE);
+        let path = temp_file("reverse-script", &script);
+
+        assert_eq!(Format::detect(&path, &script), Format::Text);
+        let reverse = Program::parse(REVERSE, Format::Text).unwrap();
+
+        assert_eq!(Program::from_path(&path).unwrap(), reverse);
+        assert_eq!(skip_header(b"0010\n# not a header"), b"0010\n# not a header");
+    }
+
+    #[test]
+    fn running_parsed_programs() {
+        let reverse = Program::from_bytes(&binary::compress(REVERSE)).unwrap();
+        let open = Program::from_bits(b"110").unwrap();
+
+        assert_eq!(reverse, Program::from_bits(REVERSE).unwrap());
+        assert_eq!(reverse.run_bytes(Input::Bytes(b"\x80\xff")), Ok(b"\xff\x80".to_vec()));
+        assert_eq!(reverse.run(Input::Bytes(b"herp derp")), Ok("pred preh".into()));
+        assert_eq!(open.run(Input::Nothing), Err(execution::Error::InvalidProgram));
+    }
+
+    #[test]
+    fn precompilation() {
+        let diverging = abs(app!(O(), Var(1), app(I(), Var(1))));
+        let program = Program { term: diverging, metadata: Metadata::default() };
+        let reverse = Program::from_bits(REVERSE).unwrap().precompile();
+
+        assert_eq!(program.precompile().into_term(), abs(app!(O(), Var(1), Var(1))));
+        assert_eq!(reverse.run(Input::Bytes(b"herp derp")), Ok("pred preh".into()));
+    }
+
+    #[test]
+    fn errors() {
+        let invalid = temp_file("invalid", b"01");
+        let truncated = temp_file("truncated", b"0");
+        let unterminated = temp_file("unterminated", b"111");
+        let input = temp_file("errors-input", b"");
+
+        assert!(matches!(Program::from_path(&invalid), Err(Error::Parse(_))));
+        assert!(matches!(Program::from_path(&truncated), Err(Error::Parse(_))));
+        assert!(matches!(run_file(&unterminated, &input), Err(Error::Parse(_))));
+        assert!(matches!(Program::parse(b"111", Format::Text), Err(Error::Parse(_))));
+        assert!(matches!(Program::from_path("/nonexistent/program.blc"), Err(Error::Io(_))));
+    }
+}
@@ -0,0 +1,234 @@
+//! Interactive sessions and their files
+//!
+//! A `Session` holds the named definitions and the settings of an interactive exploration, so it
+//! can be saved to a `.blcrepl` file, resumed later or shared with others. The file is text, one
+//! entry per line:
+//!
+//! ```text
+//! # lines starting with `#` and blank lines are ignored
+//! :set limit 1000
+//! id = 0010
+//! k = 0000110
+//! ```
+//!
+//! Settings are `:set <key> <value>` lines, with the value running to the end of the line;
+//! definitions are `<name> = <program>` lines, with the program written as BLC text. Names and
+//! keys can't contain whitespace or `=` and can't start with `#`, and values can't contain line
+//! breaks or start or end with whitespace.
+
+use lambda_calculus::Term;
+use encoding::binary::{from_bits, to_bits};
+use program::Error;
+use std::fs;
+use std::path::Path;
+
+/// The usual extension of session files.
+pub const EXTENSION: &str = "blcrepl";
+
+/// Named definitions and settings of an interactive session.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct Session {
+    definitions: Vec<(String, Term)>,
+    settings: Vec<(String, String)>
+}
+
+impl Session {
+    /// Creates a session without definitions and settings.
+    pub fn new() -> Self { Session::default() }
+
+    /// Defines `name` as `term`, replacing its previous definition, if any.
+    ///
+    /// # Example
+    /// ```
+    /// extern crate blc;
+    /// extern crate lambda_calculus;
+    ///
+    /// use blc::program::session::Session;
+    /// use lambda_calculus::combinators::{I, K};
+    ///
+    /// let mut session = Session::new();
+    /// session.define("id", I()).unwrap();
+    /// session.define("k", K()).unwrap();
+    /// session.set("limit", "1000").unwrap();
+    ///
+    /// assert_eq!(session.to_text(), ":set limit 1000\nid = 0010\nk = 0000110\n");
+    /// assert_eq!(Session::parse(&session.to_text()).unwrap(), session);
+    /// assert!(session.define("the id", I()).is_err());
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::InvalidSessionEntry` if `name` can't be stored in a session file.
+    pub fn define<S: Into<String>>(&mut self, name: S, term: Term) -> Result<(), Error> {
+        let name = name.into();
+        if !is_name(&name) { return Err(Error::InvalidSessionEntry(name)) }
+
+        insert(&mut self.definitions, name, term);
+        Ok(())
+    }
+
+    /// Returns the definition of `name`.
+    pub fn definition(&self, name: &str) -> Option<&Term> {
+        self.definitions.iter().find(|(entry, _)| entry == name).map(|(_, term)| term)
+    }
+
+    /// Removes the definition of `name` and returns it.
+    pub fn undefine(&mut self, name: &str) -> Option<Term> {
+        let index = self.definitions.iter().position(|(entry, _)| entry == name)?;
+
+        Some(self.definitions.remove(index).1)
+    }
+
+    /// Returns the definitions along with their names, in the order they were made in.
+    pub fn definitions(&self) -> impl Iterator<Item = (&str, &Term)> {
+        self.definitions.iter().map(|(name, term)| (name.as_str(), term))
+    }
+
+    /// Sets `key` to `value`, replacing its previous value, if any.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::InvalidSessionEntry` if `key` or `value` can't be stored in a session file.
+    pub fn set<K: Into<String>, V: Into<String>>(&mut self, key: K, value: V) -> Result<(), Error> {
+        let (key, value) = (key.into(), value.into());
+        if !is_name(&key) || value.trim() != value || value.contains(['\n', '\r']) {
+            return Err(Error::InvalidSessionEntry(key))
+        }
+
+        insert(&mut self.settings, key, value);
+        Ok(())
+    }
+
+    /// Returns the value of the setting `key`.
+    pub fn setting(&self, key: &str) -> Option<&str> {
+        self.settings.iter().find(|(entry, _)| entry == key).map(|(_, value)| value.as_str())
+    }
+
+    /// Returns the settings along with their keys, in the order they were made in.
+    pub fn settings(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.settings.iter().map(|(key, value)| (key.as_str(), value.as_str()))
+    }
+
+    /// Stores the session in the session file format; settings come before definitions.
+    pub fn to_text(&self) -> String {
+        let settings = self.settings.iter().map(|(key, value)| format!(":set {} {}\n", key, value));
+        let definitions = self.definitions.iter().map(|(name, term)| {
+            format!("{} = {}\n", name, String::from_utf8_lossy(&to_bits(term)))
+        });
+
+        settings.chain(definitions).collect()
+    }
+
+    /// Reads a session stored in the session file format.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::InvalidSession` with the number of the first malformed line, counting from
+    /// 1, if `text` is not a valid session.
+    pub fn parse(text: &str) -> Result<Session, Error> {
+        let mut session = Session::new();
+
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            let invalid = || Error::InvalidSession(number + 1);
+            if line.is_empty() || line.starts_with('#') { continue }
+
+            if let Some(setting) = line.strip_prefix(":set ") {
+                let mut parts = setting.trim_start().splitn(2, char::is_whitespace);
+                let key = parts.next().filter(|key| is_name(key)).ok_or_else(invalid)?;
+                session.set(key, parts.next().unwrap_or_default().trim()).map_err(|_| invalid())?;
+            } else {
+                let (name, program) = line.split_once('=').ok_or_else(invalid)?;
+                let name = Some(name.trim()).filter(|name| is_name(name)).ok_or_else(invalid)?;
+                let term = from_bits(program.trim().as_bytes()).map_err(|_| invalid())?;
+                session.define(name, term).map_err(|_| invalid())?;
+            }
+        }
+
+        Ok(session)
+    }
+
+    /// Loads a session from a file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Error` if the file can't be read or doesn't contain a valid session.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Session, Error> {
+        let text = fs::read_to_string(path)?;
+
+        Session::parse(&text)
+    }
+
+    /// Saves the session to a file.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::Io` if the file can't be written.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+        Ok(fs::write(path, self.to_text())?)
+    }
+}
+
+fn insert<T>(entries: &mut Vec<(String, T)>, key: String, value: T) {
+    match entries.iter_mut().find(|(entry, _)| *entry == key) {
+        Some(entry) => entry.1 = value,
+        None => entries.push((key, value))
+    }
+}
+
+fn is_name(name: &str) -> bool {
+    // a `#` would make the entry a comment and `:set` a setting
+    !name.is_empty() && !name.contains(|c: char| c.is_whitespace() || c == '=') &&
+        !name.starts_with('#') && name != ":set"
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use lambda_calculus::combinators::{I, K};
+    use std::env;
+
+    #[test]
+    fn parsing() {
+        let text = "# a session\n\n:set strategy  call by name \nid=0010\n  k = 00 00 110\n\
+                    id = 0000110\n";
+        let session = Session::parse(text).unwrap();
+
+        assert_eq!(session.setting("strategy"), Some("call by name"));
+        assert_eq!(session.definition("id"), Some(&K()));
+        assert_eq!(session.definitions().map(|(name, _)| name).collect::<Vec<_>>(), ["id", "k"]);
+
+        assert!(matches!(Session::parse("id = 0010\nbroken"), Err(Error::InvalidSession(2))));
+        assert!(matches!(Session::parse("= 0010"), Err(Error::InvalidSession(1))));
+        assert!(matches!(Session::parse("id = 22"), Err(Error::InvalidSession(1))));
+        assert!(matches!(Session::parse(":set \n"), Err(Error::InvalidSession(1))));
+    }
+
+    #[test]
+    fn invalid_entries() {
+        let mut session = Session::new();
+        let invalid = |result| matches!(result, Err(Error::InvalidSessionEntry(_)));
+
+        for name in ["", "the id", "id=", "#id", ":set"] {
+            assert!(invalid(session.define(name, I())), "{:?}", name);
+            assert!(invalid(session.set(name, "1")), "{:?}", name);
+        }
+        assert!(invalid(session.set("escape", "hex\n:set limit 1")));
+        assert!(invalid(session.set("escape", " hex")));
+        assert_eq!(session, Session::new());
+    }
+
+    #[test]
+    fn files() {
+        let path = env::temp_dir().join(format!("blc-session-test.{}", EXTENSION));
+        let mut session = Session::new();
+        session.define("id", I()).unwrap();
+        session.set("escape", "hex").unwrap();
+        session.save(&path).unwrap();
+
+        assert_eq!(Session::from_path(&path).unwrap(), session);
+        assert_eq!(session.undefine("id"), Some(I()));
+        assert_eq!(session.definition("id"), None);
+        assert!(matches!(Session::from_path("/nonexistent/session.blcrepl"), Err(Error::Io(_))));
+    }
+}
@@ -0,0 +1,161 @@
+//! HTML reports of reduction sequences
+//!
+//! `html_report` renders the normal-order reduction sequence of a term as a standalone HTML page:
+//! every step is a collapsible section with the term in De Bruijn notation and the redex that is
+//! contracted next highlighted, its abstraction and argument told apart.
+
+use lambda_calculus::*;
+use analysis::size;
+
+const STYLE: &str = "\
+body { font-family: sans-serif; margin: 2em; }
+code { font-family: monospace; font-size: 1.1em; word-break: break-all; }
+details { margin: 0.5em 0; padding: 0.5em; border: 1px solid #ccc; border-radius: 4px; }
+summary { cursor: pointer; }
+.redex { background: #fff3b0; }
+.abstraction { color: #1d4ed8; }
+.argument { color: #b91c1c; }";
+
+/// Renders the normal-order reduction of `term` as a standalone HTML page, with at most `limit`
+/// steps (`0` means no limit, which is only safe for terms with a normal form).
+///
+/// # Example
+/// ```
+/// use blc::encoding::binary::from_bits;
+/// use blc::report::html_report;
+///
+/// let k_i = from_bits(b"0100001100010").unwrap(); // (λλ2)(λ1)
+/// let report = html_report(k_i, 10);
+///
+/// assert!(report.starts_with("<!DOCTYPE html>"));
+/// assert!(report.contains("normal form reached after 1 step"));
+/// ```
+pub fn html_report(mut term: Term, limit: usize) -> String {
+    let mut steps = vec![term.clone()];
+
+    while (limit == 0 || steps.len() <= limit) && term.reduce(NOR, 1) == 1 {
+        steps.push(term.clone());
+    }
+
+    let normal = !has_redex(&term);
+    let count = steps.len() - 1;
+    let mut html = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>BLC reduction</title>\n\
+         <style>\n{}\n</style>\n</head>\n<body>\n<h1>BLC reduction</h1>\n",
+        STYLE
+    );
+
+    for (i, step) in steps.iter().enumerate() {
+        let open = if i == 0 || i == count { " open" } else { "" };
+        let mut redex_found = false;
+        let mut rendered = String::new();
+        render(step, 0, &mut redex_found, &mut rendered);
+
+        html.push_str(&format!(
+            "<details{}>\n<summary>step {} ({} nodes)</summary>\n<code>{}</code>\n</details>\n",
+            open, i, size(step), rendered
+        ));
+    }
+
+    let plural = if count == 1 { "" } else { "s" };
+    let conclusion = if normal {
+        format!("normal form reached after {} step{}", count, plural)
+    } else {
+        format!("stopped after {} step{} without reaching a normal form", count, plural)
+    };
+    html.push_str(&format!("<p>{}</p>\n</body>\n</html>\n", conclusion));
+
+    html
+}
+
+/// Renders `term` like its `Debug` implementation does, wrapping the leftmost outermost redex,
+/// which is also the first one in preorder, in highlighting markup.
+fn render(term: &Term, context_precedence: usize, redex_found: &mut bool, out: &mut String) {
+    match *term {
+        Var(i) => out.push_str(&format!("{:X}", i)),
+        Abs(ref body) => {
+            let parenthesize = context_precedence > 1;
+            if parenthesize { out.push('(') }
+            out.push('λ');
+            render(body, 0, redex_found, out);
+            if parenthesize { out.push(')') }
+        },
+        App(ref lhs, ref rhs) => {
+            let parenthesize = context_precedence == 3;
+            let is_redex = !*redex_found && matches!(**lhs, Abs(_));
+            if parenthesize { out.push('(') }
+
+            if is_redex {
+                *redex_found = true;
+                out.push_str("<span class=\"redex\"><span class=\"abstraction\">");
+                render(lhs, 2, redex_found, out);
+                out.push_str("</span><span class=\"argument\">");
+                render(rhs, 3, redex_found, out);
+                out.push_str("</span></span>");
+            } else {
+                render(lhs, 2, redex_found, out);
+                render(rhs, 3, redex_found, out);
+            }
+
+            if parenthesize { out.push(')') }
+        }
+    }
+}
+
+fn has_redex(term: &Term) -> bool {
+    match *term {
+        Var(_) => false,
+        Abs(ref body) => has_redex(body),
+        App(ref lhs, ref rhs) => matches!(**lhs, Abs(_)) || has_redex(lhs) || has_redex(rhs)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use lambda_calculus::combinators::{I, K, O};
+
+    fn strip_tags(html: &str) -> String {
+        let mut text = String::new();
+        let mut in_tag = false;
+
+        for c in html.chars() {
+            match c {
+                '<' => in_tag = true,
+                '>' => in_tag = false,
+                c if !in_tag => text.push(c),
+                _ => ()
+            }
+        }
+        text
+    }
+
+    #[test]
+    fn steps_are_rendered() {
+        let term = app!(K(), I(), O());
+        let report = html_report(term.clone(), 0);
+
+        assert_eq!(report.matches("<details").count(), 3);
+        assert!(strip_tags(&report).contains(&format!("{:?}", term)));
+        assert!(report.contains("normal form reached after 2 steps"));
+    }
+
+    #[test]
+    fn the_next_redex_is_highlighted() {
+        let report = html_report(app(I(), app(I(), Var(1))), 0);
+
+        assert!(report.contains(concat!(
+            "<span class=\"redex\"><span class=\"abstraction\">(λ1)</span>",
+            "<span class=\"argument\">((λ1)1)</span></span>"
+        )));
+        assert_eq!(report.matches("class=\"redex\"").count(), 2);
+    }
+
+    #[test]
+    fn limits() {
+        let report = html_report(O(), 3);
+
+        assert_eq!(report.matches("<details").count(), 4);
+        assert!(report.contains("stopped after 3 steps without reaching a normal form"));
+    }
+}
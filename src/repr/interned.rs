@@ -0,0 +1,153 @@
+//! Hash-consed terms
+//!
+//! An `Interner` stores every distinct term only once: building a node that is structurally equal
+//! to one built before returns the same `Id` instead of allocating it again, so equal subterms
+//! share memory and comparing two interned terms means comparing two integers. Nodes are never
+//! freed before the `Interner` itself.
+
+use repr::{TermRep, View};
+use std::collections::HashMap;
+
+/// An interned term; only meaningful for the `Interner` that produced it.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash)]
+pub struct Id(usize);
+
+/// A single node of an interned term.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum Node {
+    /// a variable with a De Bruijn index
+    Var(usize),
+    /// an abstraction over an interned term
+    Abs(Id),
+    /// an application of one interned term to another
+    App(Id, Id)
+}
+
+/// A store of hash-consed terms.
+///
+/// # Example
+#[cfg_attr(feature = "lambda_calculus", doc = "```")]
+#[cfg_attr(not(feature = "lambda_calculus"), doc = "```ignore")]
+/// extern crate blc;
+/// extern crate lambda_calculus;
+///
+/// use blc::repr::interned::Interner;
+/// use lambda_calculus::*;
+///
+/// let mut interner = Interner::default();
+/// let twice = interner.intern(&app(abs(Var(1)), abs(Var(1))));
+///
+/// assert_eq!(interner.len(), 3); // λ1 is only stored once
+/// assert_eq!(interner.extract::<Term>(twice), app(abs(Var(1)), abs(Var(1))));
+/// ```
+#[derive(Debug, Default)]
+pub struct Interner {
+    nodes: Vec<Node>,
+    /// the largest free De Bruijn index in every node, `0` if it is closed
+    loose: Vec<usize>,
+    ids: HashMap<Node, Id>
+}
+
+impl Interner {
+    /// Returns the `Id` of the given node, storing it if it is new.
+    pub fn node(&mut self, node: Node) -> Id {
+        if let Some(&id) = self.ids.get(&node) { return id }
+
+        let loose = match node {
+            Node::Var(i) => i,
+            Node::Abs(body) => self.loose(body).saturating_sub(1),
+            Node::App(lhs, rhs) => self.loose(lhs).max(self.loose(rhs))
+        };
+        let id = Id(self.nodes.len());
+        self.nodes.push(node);
+        self.loose.push(loose);
+        self.ids.insert(node, id);
+        id
+    }
+
+    /// Returns the `Id` of a variable with the given De Bruijn index.
+    pub fn var(&mut self, index: usize) -> Id { self.node(Node::Var(index)) }
+
+    /// Returns the `Id` of an abstraction over the given term.
+    pub fn abs(&mut self, body: Id) -> Id { self.node(Node::Abs(body)) }
+
+    /// Returns the `Id` of an application of one term to another.
+    pub fn app(&mut self, lhs: Id, rhs: Id) -> Id { self.node(Node::App(lhs, rhs)) }
+
+    /// Returns the outermost node of an interned term.
+    pub fn get(&self, id: Id) -> Node { self.nodes[id.0] }
+
+    /// Returns the largest De Bruijn index that is free in an interned term, or `0` if it is
+    /// closed.
+    pub fn loose(&self, id: Id) -> usize { self.loose[id.0] }
+
+    /// Returns the number of distinct nodes stored.
+    pub fn len(&self) -> usize { self.nodes.len() }
+
+    /// Returns `true` if no nodes are stored.
+    pub fn is_empty(&self) -> bool { self.nodes.is_empty() }
+
+    /// Interns a term in any representation.
+    pub fn intern<T: TermRep>(&mut self, term: &T) -> Id {
+        match term.view() {
+            View::Var(i) => self.var(i),
+            View::Abs(body) => {
+                let body = self.intern(body);
+                self.abs(body)
+            },
+            View::App(lhs, rhs) => {
+                let (lhs, rhs) = (self.intern(lhs), self.intern(rhs));
+                self.app(lhs, rhs)
+            }
+        }
+    }
+
+    /// Converts an interned term to any representation, unsharing its subterms.
+    pub fn extract<T: TermRep>(&self, id: Id) -> T {
+        match self.get(id) {
+            Node::Var(i) => T::var(i),
+            Node::Abs(body) => T::abs(self.extract(body)),
+            Node::App(lhs, rhs) => T::app(self.extract(lhs), self.extract(rhs))
+        }
+    }
+}
+
+#[cfg(all(test, feature = "lambda_calculus"))]
+mod test {
+    use super::*;
+    use lambda_calculus::{parse, DeBruijn, Term};
+
+    #[test]
+    fn sharing() {
+        let mut interner = Interner::default();
+        let (x, y) = (interner.var(1), interner.var(1));
+        let (xx, yy) = (interner.app(x, x), interner.app(y, y));
+
+        assert_eq!(x, y);
+        assert_eq!(xx, yy);
+        assert_ne!(interner.abs(xx), xx);
+        assert_eq!(interner.len(), 3);
+    }
+
+    #[test]
+    fn loose_indices() {
+        let mut interner = Interner::default();
+        let body = interner.intern(&parse("1 3", DeBruijn).unwrap());
+        let abstraction = interner.abs(body);
+        let k = interner.intern(&parse("λλ2", DeBruijn).unwrap());
+
+        assert_eq!(interner.loose(body), 3);
+        assert_eq!(interner.loose(abstraction), 2);
+        assert_eq!(interner.loose(k), 0);
+    }
+
+    #[test]
+    fn round_trip() {
+        let s = parse("λλλ3 1 (2 1)", DeBruijn).unwrap();
+        let mut interner = Interner::default();
+        let id = interner.intern(&s);
+
+        assert_eq!(interner.extract::<Term>(id), s);
+        assert_eq!(interner.len(), 9); // the second 1 is shared
+    }
+}
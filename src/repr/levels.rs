@@ -0,0 +1,649 @@
+//! The De Bruijn level term representation
+//!
+//! Variables refer to their binders by counting abstractions from the root of the term instead of
+//! from the variable itself, starting with level 0 for the outermost abstraction. A variable has
+//! the same level wherever it occurs, so the evaluator provided here never needs to shift indices
+//! during substitution, and it shares evaluated arguments between their uses. Terms can also be
+//! printed in and `parse`d from a textual level notation.
+
+use repr::{TermRep, View};
+use std::cell::{Cell, RefCell};
+use std::fmt;
+use std::rc::Rc;
+use self::Error::*;
+pub use self::Term::*;
+
+/// A lambda term with De Bruijn levels.
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub enum Term {
+    /// a variable with a De Bruijn level
+    Var(usize),
+    /// an abstraction
+    Abs(Box<Term>),
+    /// an application
+    App(Box<Term>, Box<Term>)
+}
+
+/// An error that can occur during conversion between indices and levels or normalization.
+#[derive(Debug, PartialEq)]
+pub enum Error {
+    /// a variable with the given De Bruijn index is not bound by any abstraction
+    FreeIndex(usize),
+    /// a variable with the given De Bruijn level is not bound by any abstraction
+    FreeLevel(usize),
+    /// the level notation is invalid at the given byte offset
+    InvalidNotation(usize)
+}
+
+/// Wraps a `Term` in an `Abs`traction.
+pub fn abs(term: Term) -> Term { Abs(Box::new(term)) }
+
+/// Produces an `App`lication of two given `Term`s.
+pub fn app(lhs: Term, rhs: Term) -> Term { App(Box::new(lhs), Box::new(rhs)) }
+
+/// Prints the term in level notation: like the De Bruijn notation of `lambda_calculus`, but with
+/// decimal levels, which are separated with spaces where they would otherwise be adjacent.
+///
+/// # Example
+/// ```
+/// use blc::repr::levels::{abs, app, Var};
+///
+/// let s = abs(abs(abs(app(app(Var(0), Var(2)), app(Var(1), Var(2))))));
+///
+/// assert_eq!(s.to_string(), "λλλ0 2(1 2)");
+/// ```
+impl fmt::Display for Term {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        show(self, 0, f)
+    }
+}
+
+fn show(term: &Term, context_precedence: usize, f: &mut fmt::Formatter) -> fmt::Result {
+    match *term {
+        Var(l) => write!(f, "{}", l),
+        Abs(ref t) => {
+            let parenthesize = context_precedence > 1;
+            if parenthesize { write!(f, "(")? }
+            write!(f, "λ")?;
+            show(t, 0, f)?;
+            if parenthesize { write!(f, ")")? }
+            Ok(())
+        },
+        App(ref t1, ref t2) => {
+            let parenthesize = context_precedence == 3;
+            if parenthesize { write!(f, "(")? }
+            show(t1, 2, f)?;
+            if is_var(t2) && ends_with_var(t1) { write!(f, " ")? }
+            show(t2, 3, f)?;
+            if parenthesize { write!(f, ")")? }
+            Ok(())
+        }
+    }
+}
+
+fn is_var(term: &Term) -> bool {
+    matches!(*term, Var(_))
+}
+
+/// Returns `true` if `term` is printed with a variable at its end when it is the left-hand side of
+/// an application.
+fn ends_with_var(term: &Term) -> bool {
+    match *term {
+        Var(_) => true,
+        Abs(_) => false,
+        App(_, ref t2) => is_var(t2)
+    }
+}
+
+/// Parses a term in the level notation produced by its `Display` implementation; `\` can be used
+/// instead of `λ` and whitespace is allowed anywhere between levels and other symbols.
+///
+/// # Example
+/// ```
+/// use blc::repr::levels::{parse, abs, app, Var};
+///
+/// let k = abs(abs(Var(0)));
+///
+/// assert_eq!(parse("λλ0"), Ok(k.clone()));
+/// assert_eq!(parse(r"\ \ 0").unwrap(), k);
+/// assert_eq!(parse("λ0 0").unwrap().to_string(), "λ0 0");
+/// ```
+/// # Errors
+///
+/// Returns `Error::InvalidNotation` with the offset of the first invalid character if `input` is
+/// not a valid term.
+pub fn parse(input: &str) -> Result<Term, Error> {
+    let mut parser = Parser { input: input.as_bytes(), pos: 0 };
+    let term = parser.term()?;
+
+    parser.skip_whitespace();
+    if parser.pos != parser.input.len() { return Err(InvalidNotation(parser.pos)) }
+    Ok(term)
+}
+
+struct Parser<'a> {
+    input: &'a [u8],
+    pos: usize
+}
+
+impl<'a> Parser<'a> {
+    fn skip_whitespace(&mut self) {
+        while self.input.get(self.pos).is_some_and(|b| b.is_ascii_whitespace()) { self.pos += 1 }
+    }
+
+    /// Consumes a `λ` or a `\`, if there is one.
+    fn lambda(&mut self) -> bool {
+        if self.input[self.pos..].starts_with("λ".as_bytes()) {
+            self.pos += "λ".len();
+            true
+        } else if self.input.get(self.pos) == Some(&b'\\') {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn term(&mut self) -> Result<Term, Error> {
+        let mut term = None;
+
+        loop {
+            self.skip_whitespace();
+            let start = self.pos;
+
+            let atom = if self.lambda() {
+                Some(abs(self.term()?))
+            } else {
+                match self.input.get(self.pos) {
+                    Some(b'(') => {
+                        self.pos += 1;
+                        let inner = self.term()?;
+                        self.skip_whitespace();
+                        if self.input.get(self.pos) != Some(&b')') {
+                            return Err(InvalidNotation(self.pos))
+                        }
+                        self.pos += 1;
+                        Some(inner)
+                    },
+                    Some(b) if b.is_ascii_digit() => {
+                        while self.input.get(self.pos).is_some_and(|b| b.is_ascii_digit()) {
+                            self.pos += 1
+                        }
+                        let digits = &self.input[start..self.pos];
+                        let level = String::from_utf8_lossy(digits).parse()
+                            .or(Err(InvalidNotation(start)))?;
+                        Some(Var(level))
+                    },
+                    _ => None
+                }
+            };
+
+            term = match (term, atom) {
+                (None, None) => return Err(InvalidNotation(start)),
+                (Some(term), None) => return Ok(term),
+                (None, Some(atom)) => Some(atom),
+                (Some(term), Some(atom)) => Some(app(term, atom))
+            };
+        }
+    }
+}
+
+/// Converts a closed term with De Bruijn indices to one with De Bruijn levels.
+///
+/// # Example
+#[cfg_attr(feature = "lambda_calculus", doc = "```")]
+#[cfg_attr(not(feature = "lambda_calculus"), doc = "```ignore")]
+/// extern crate blc;
+/// extern crate lambda_calculus;
+///
+/// use blc::repr::levels::{from_indices, abs, Var};
+/// use lambda_calculus::combinators::K;
+///
+/// assert_eq!(from_indices(&K()), Ok(abs(abs(Var(0)))));
+/// ```
+/// # Errors
+///
+/// Returns an `Error` if the term is not closed.
+pub fn from_indices<T: TermRep>(term: &T) -> Result<Term, Error> {
+    _from_indices(term, 0)
+}
+
+/// Converts a term with De Bruijn indices that occurs under `depth` abstractions, whose free
+/// variables are bound by them, to one with De Bruijn levels counted from the outermost of them.
+///
+/// # Example
+#[cfg_attr(feature = "lambda_calculus", doc = "```")]
+#[cfg_attr(not(feature = "lambda_calculus"), doc = "```ignore")]
+/// extern crate blc;
+/// extern crate lambda_calculus;
+///
+/// use blc::repr::levels::{from_indices_under, abs, app, Var};
+/// use lambda_calculus as lc;
+///
+/// let term = lc::abs(lc::app(lc::Var(1), lc::Var(3))); // λ1 3 under λλ
+///
+/// assert_eq!(from_indices_under(&term, 2), Ok(abs(app(Var(2), Var(0)))));
+/// ```
+/// # Errors
+///
+/// Returns an `Error` if a variable of the term is not bound by the term or those abstractions.
+pub fn from_indices_under<T: TermRep>(term: &T, depth: usize) -> Result<Term, Error> {
+    _from_indices(term, depth)
+}
+
+/// Returns the De Bruijn level of the variable with the De Bruijn `index` under `depth`
+/// abstractions, or `None` if none of them binds it.
+///
+/// # Example
+/// ```
+/// use blc::repr::levels::{index_to_level, level_to_index};
+///
+/// assert_eq!(index_to_level(1, 3), Some(2));
+/// assert_eq!(index_to_level(4, 3), None);
+/// assert_eq!(level_to_index(2, 3), Some(1));
+/// ```
+pub fn index_to_level(index: usize, depth: usize) -> Option<usize> {
+    if index == 0 || index > depth { None } else { Some(depth - index) }
+}
+
+/// Returns the De Bruijn index of the variable with the De Bruijn `level` under `depth`
+/// abstractions, or `None` if none of them binds it.
+pub fn level_to_index(level: usize, depth: usize) -> Option<usize> {
+    if level >= depth { None } else { Some(depth - level) }
+}
+
+/// Returns a copy of a term with De Bruijn indices with its variables that are free above
+/// `cutoff` abstractions shifted by `by`, like when the term is moved under or out of `by`
+/// abstractions.
+///
+/// # Example
+#[cfg_attr(feature = "lambda_calculus", doc = "```")]
+#[cfg_attr(not(feature = "lambda_calculus"), doc = "```ignore")]
+/// extern crate blc;
+/// extern crate lambda_calculus;
+///
+/// use blc::repr::levels::{shift, Error::FreeIndex};
+/// use lambda_calculus::*;
+///
+/// let term = abs(app(Var(1), Var(3)));
+///
+/// assert_eq!(shift(&term, 2, 0), Ok(abs(app(Var(1), Var(5)))));
+/// assert_eq!(shift(&term, -1, 0), Ok(abs(app(Var(1), Var(2)))));
+/// assert_eq!(shift(&term, -2, 0), Err(FreeIndex(3)));
+/// ```
+/// # Errors
+///
+/// Returns `Error::FreeIndex` with the index of a free variable that would be shifted below
+/// `cutoff + 1`, i.e. captured by one of the abstractions it is shifted out of.
+pub fn shift<T: TermRep>(term: &T, by: isize, cutoff: usize) -> Result<T, Error> {
+    match term.view() {
+        View::Var(i) if i > cutoff => match i.checked_add_signed(by) {
+            Some(shifted) if shifted > cutoff => Ok(T::var(shifted)),
+            _ => Err(FreeIndex(i))
+        },
+        View::Var(i) => Ok(T::var(i)),
+        View::Abs(t) => Ok(T::abs(shift(t, by, cutoff + 1)?)),
+        View::App(t1, t2) => Ok(T::app(shift(t1, by, cutoff)?, shift(t2, by, cutoff)?))
+    }
+}
+
+fn _from_indices<T: TermRep>(term: &T, depth: usize) -> Result<Term, Error> {
+    match term.view() {
+        View::Var(i) => if i == 0 || i > depth { Err(FreeIndex(i)) } else { Ok(Var(depth - i)) },
+        View::Abs(t) => Ok(abs(_from_indices(t, depth + 1)?)),
+        View::App(t1, t2) => Ok(app(_from_indices(t1, depth)?, _from_indices(t2, depth)?))
+    }
+}
+
+impl Term {
+    /// Converts a closed term with De Bruijn levels to one with De Bruijn indices.
+    ///
+    /// # Example
+    #[cfg_attr(feature = "lambda_calculus", doc = "```")]
+    #[cfg_attr(not(feature = "lambda_calculus"), doc = "```ignore")]
+    /// extern crate blc;
+    /// extern crate lambda_calculus;
+    ///
+    /// use blc::repr::levels::{abs, Var};
+    /// use lambda_calculus::Term;
+    /// use lambda_calculus::combinators::K;
+    ///
+    /// assert_eq!(abs(abs(Var(0))).to_indices::<Term>(), Ok(K()));
+    /// ```
+    /// # Errors
+    ///
+    /// Returns an `Error` if `self` is not closed.
+    pub fn to_indices<T: TermRep>(&self) -> Result<T, Error> {
+        self._to_indices(0)
+    }
+
+    /// Converts a term with De Bruijn levels that occurs under `depth` abstractions, whose free
+    /// variables are bound by them, to one with De Bruijn indices; the inverse of
+    /// `from_indices_under`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Error` if a level of `self` is not bound by the term or those abstractions.
+    pub fn to_indices_under<T: TermRep>(&self, depth: usize) -> Result<T, Error> {
+        self._to_indices(depth)
+    }
+
+    /// Returns a copy of the term with its levels of at least `at` increased by `by`, like when
+    /// `by` abstractions are inserted at the depth `at` above it.
+    ///
+    /// # Example
+    /// ```
+    /// use blc::repr::levels::{abs, app, Var};
+    ///
+    /// let term = abs(abs(app(Var(0), Var(1))));
+    ///
+    /// assert_eq!(term.lifted(1, 2), abs(abs(app(Var(0), Var(3)))));
+    /// ```
+    pub fn lifted(&self, at: usize, by: usize) -> Term {
+        match *self {
+            Var(l) => Var(if l >= at { l + by } else { l }),
+            Abs(ref t) => abs(t.lifted(at, by)),
+            App(ref t1, ref t2) => app(t1.lifted(at, by), t2.lifted(at, by))
+        }
+    }
+
+    fn _to_indices<T: TermRep>(&self, depth: usize) -> Result<T, Error> {
+        match *self {
+            Var(l) => if l >= depth { Err(FreeLevel(l)) } else { Ok(T::var(depth - l)) },
+            Abs(ref t) => Ok(T::abs(t._to_indices(depth + 1)?)),
+            App(ref t1, ref t2) => Ok(T::app(t1._to_indices(depth)?, t2._to_indices(depth)?))
+        }
+    }
+
+    /// Reduces a closed `Term` to its normal form.
+    ///
+    /// The evaluation is lazy and arguments are shared, so it terminates for every term that has a
+    /// normal form, like normal-order reduction does.
+    ///
+    /// # Example
+    /// ```
+    /// use blc::repr::levels::{abs, app, Var};
+    ///
+    /// let succ = abs(abs(abs(app(Var(1), app(app(Var(0), Var(1)), Var(2))))));
+    /// let zero = abs(abs(Var(1)));
+    ///
+    /// assert_eq!(app(succ, zero).normalize(), Ok(abs(abs(app(Var(0), Var(1))))));
+    /// ```
+    /// # Errors
+    ///
+    /// Returns `Error::FreeLevel` if `self` is not closed.
+    pub fn normalize(&self) -> Result<Term, Error> {
+        Ok(self.normalize_with_stats()?.0)
+    }
+
+    /// Reduces a closed `Term` to its normal form like `normalize` and reports how much work the
+    /// sharing of arguments saved.
+    ///
+    /// # Example
+    /// ```
+    /// use blc::repr::levels::{abs, app, Var};
+    ///
+    /// let twice = abs(app(app(Var(0), Var(0)), Var(0))); // λx.x x x
+    /// let id = abs(Var(0));
+    /// let (_, stats) = app(twice, app(id.clone(), id)).normalize_with_stats().unwrap();
+    ///
+    /// assert_eq!(stats.shared_forces, 2);
+    /// assert_eq!(stats.saved_reductions, 2);
+    /// ```
+    /// # Errors
+    ///
+    /// Returns `Error::FreeLevel` if `self` is not closed.
+    pub fn normalize_with_stats(&self) -> Result<(Term, SharingStats), Error> {
+        if let Some(level) = self.free_level(0) { return Err(FreeLevel(level)) }
+
+        let stats = Counters::default();
+        let normal = readback(&eval(self, &Env::empty(), &stats), 0, &stats);
+
+        Ok((normal, stats.get()))
+    }
+
+    /// Returns a level of the term that is not bound by it or the `depth` abstractions above it.
+    fn free_level(&self, depth: usize) -> Option<usize> {
+        match *self {
+            Var(l) => if l >= depth { Some(l) } else { None },
+            Abs(ref t) => t.free_level(depth + 1),
+            App(ref t1, ref t2) => t1.free_level(depth).or_else(|| t2.free_level(depth))
+        }
+    }
+}
+
+/// Statistics about the sharing of arguments during an evaluation.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct SharingStats {
+    /// the number of performed β-reductions
+    pub reductions: usize,
+    /// the number of times an argument was needed again after it had already been evaluated
+    pub shared_forces: usize,
+    /// the number of β-reductions re-evaluating those arguments would have taken, i.e. the ones
+    /// substitution-based reduction would have performed on top of `reductions`
+    pub saved_reductions: usize
+}
+
+#[derive(Default)]
+struct Counters {
+    reductions: Cell<usize>,
+    shared_forces: Cell<usize>,
+    saved_reductions: Cell<usize>
+}
+
+impl Counters {
+    fn get(&self) -> SharingStats {
+        SharingStats {
+            reductions: self.reductions.get(),
+            shared_forces: self.shared_forces.get(),
+            saved_reductions: self.saved_reductions.get()
+        }
+    }
+}
+
+#[derive(Clone)]
+enum Value<'a> {
+    Closure(Env<'a>, &'a Term),
+    Neutral(usize, Vec<Thunk<'a>>)
+}
+
+#[derive(Clone)]
+struct Thunk<'a>(Rc<RefCell<Suspension<'a>>>);
+
+enum Suspension<'a> {
+    Delayed(Env<'a>, &'a Term),
+    /// an evaluated value along with the number of reductions its evaluation took
+    Forced(Value<'a>, usize)
+}
+
+/// A persistent environment; its last element is bound to the innermost abstraction.
+#[derive(Clone)]
+struct Env<'a> {
+    len: usize,
+    node: Option<Rc<EnvNode<'a>>>
+}
+
+struct EnvNode<'a> {
+    value: Thunk<'a>,
+    rest: Env<'a>
+}
+
+impl<'a> Env<'a> {
+    fn empty() -> Self { Env { len: 0, node: None } }
+
+    fn push(&self, value: Thunk<'a>) -> Self {
+        Env { len: self.len + 1, node: Some(Rc::new(EnvNode { value, rest: self.clone() })) }
+    }
+
+    /// Returns the value bound to a level; the evaluated terms are closed, so it is bound.
+    fn get(&self, level: usize) -> Thunk<'a> {
+        let mut env = self;
+        for _ in 0..(self.len - level - 1) {
+            env = &env.node.as_ref().unwrap().rest; // safe - shorter than `len`
+        }
+
+        env.node.as_ref().unwrap().value.clone() // safe - `level` is less than `len`
+    }
+}
+
+impl<'a> Thunk<'a> {
+    fn delay(env: Env<'a>, term: &'a Term) -> Self {
+        Thunk(Rc::new(RefCell::new(Suspension::Delayed(env, term))))
+    }
+
+    fn ready(value: Value<'a>) -> Self {
+        Thunk(Rc::new(RefCell::new(Suspension::Forced(value, 0))))
+    }
+
+    fn force(&self, stats: &Counters) -> Value<'a> {
+        let delayed = match *self.0.borrow() {
+            Suspension::Forced(ref value, cost) => {
+                if cost != 0 {
+                    stats.shared_forces.set(stats.shared_forces.get() + 1);
+                    stats.saved_reductions.set(stats.saved_reductions.get() + cost);
+                }
+                return value.clone()
+            },
+            Suspension::Delayed(ref env, term) => (env.clone(), term)
+        };
+        let before = stats.reductions.get();
+        let value = eval(delayed.1, &delayed.0, stats);
+        let cost = stats.reductions.get() - before;
+        *self.0.borrow_mut() = Suspension::Forced(value.clone(), cost);
+
+        value
+    }
+}
+
+fn eval<'a>(term: &'a Term, env: &Env<'a>, stats: &Counters) -> Value<'a> {
+    match *term {
+        Var(l) => env.get(l).force(stats),
+        Abs(ref body) => Value::Closure(env.clone(), body),
+        App(ref lhs, ref rhs) => {
+            apply(eval(lhs, env, stats), Thunk::delay(env.clone(), rhs), stats)
+        }
+    }
+}
+
+fn apply<'a>(function: Value<'a>, arg: Thunk<'a>, stats: &Counters) -> Value<'a> {
+    match function {
+        Value::Closure(env, body) => {
+            stats.reductions.set(stats.reductions.get() + 1);
+            eval(body, &env.push(arg), stats)
+        },
+        Value::Neutral(level, mut spine) => {
+            spine.push(arg);
+            Value::Neutral(level, spine)
+        }
+    }
+}
+
+fn readback(value: &Value, depth: usize, stats: &Counters) -> Term {
+    match *value {
+        Value::Closure(ref env, body) => {
+            let var = Thunk::ready(Value::Neutral(depth, Vec::new()));
+            abs(readback(&eval(body, &env.push(var), stats), depth + 1, stats))
+        },
+        Value::Neutral(level, ref spine) => {
+            spine.iter()
+                .fold(Var(level), |acc, arg| app(acc, readback(&arg.force(stats), depth, stats)))
+        }
+    }
+}
+
+#[cfg(all(test, feature = "lambda_calculus"))]
+mod test {
+    use super::*;
+    use lambda_calculus as lc;
+    use lambda_calculus::{beta, DeBruijn, NOR, IntoChurchNum};
+    use lambda_calculus::parse as parse_indices;
+    use lambda_calculus::combinators::{S, K, I, O};
+    use lambda_calculus::data::num::church::{mul, pow, pred};
+
+    type DbTerm = lc::Term;
+
+    #[test]
+    fn conversions() {
+        let s = from_indices(&S()).unwrap();
+
+        assert_eq!(s, abs(abs(abs(app(app(Var(0), Var(2)), app(Var(1), Var(2)))))));
+        assert_eq!(s.to_indices::<DbTerm>(), Ok(S()));
+        assert_eq!(from_indices(&lc::abs(lc::Var(2))), Err(FreeIndex(2)));
+        assert_eq!(abs(Var(1)).to_indices::<DbTerm>(), Err(FreeLevel(1)));
+    }
+
+    #[test]
+    fn open_terms() {
+        let term = parse_indices("λ1 2(λ3 1)", DeBruijn).unwrap();
+        let levels = from_indices_under(&term, 1).unwrap();
+
+        assert_eq!(levels.to_string(), "λ1 0(λ0 2)");
+        assert_eq!(levels.to_indices_under::<DbTerm>(1), Ok(term.clone()));
+        assert_eq!(levels.to_indices_under::<DbTerm>(0), Err(FreeLevel(1)));
+        assert_eq!(from_indices_under(&term, 0), Err(FreeIndex(2)));
+
+        let shifted = shift(&term, 1, 0).unwrap();
+        assert_eq!(from_indices_under(&shifted, 2), Ok(levels.lifted(1, 1)));
+        assert_eq!(shift(&shifted, -1, 0), Ok(term));
+    }
+
+    #[test]
+    fn level_notation() {
+        let terms = vec![S(), O(), parse_indices("λλ1(λ12)(λλ3 1)2", DeBruijn).unwrap()];
+
+        for term in terms {
+            let levels = from_indices(&term).unwrap();
+            assert_eq!(parse(&levels.to_string()), Ok(levels));
+        }
+
+        assert_eq!(parse("λλ12 ( 0 )"), Ok(abs(abs(app(Var(12), Var(0))))));
+        assert_eq!(parse("(λ0)λ0").unwrap().to_string(), "(λ0)(λ0)");
+        assert_eq!(parse("λ0 )"), Err(InvalidNotation(4)));
+        assert_eq!(parse("(λ0"), Err(InvalidNotation(4)));
+        assert_eq!(parse(""), Err(InvalidNotation(0)));
+    }
+
+    #[test]
+    fn normalization_matches_beta() {
+        let terms = vec![
+            lc::app(lc::app(S(), K()), K()),
+            lc::app(lc::app(mul(), 3.into_church()), 4.into_church()),
+            lc::app(lc::app(pow(), 2.into_church()), 3.into_church()),
+            lc::app(pred(), 3.into_church()),
+            parse_indices("λ1((λ11)(λλλ3))1", DeBruijn).unwrap()
+        ];
+
+        for term in terms {
+            let normal = from_indices(&term).unwrap().normalize().unwrap();
+            assert_eq!(normal.to_indices::<DbTerm>(), Ok(beta(term, NOR, 0)));
+        }
+    }
+
+    #[test]
+    fn laziness() {
+        let k_i_omega = from_indices(&lc::app(lc::app(K(), I()), O())).unwrap();
+
+        assert_eq!(k_i_omega.normalize(), Ok(abs(Var(0))));
+    }
+
+    #[test]
+    fn normalizing_open_terms() {
+        assert_eq!(abs(app(Var(0), Var(1))).normalize(), Err(FreeLevel(1)));
+        assert_eq!(app(abs(Var(0)), Var(0)).normalize_with_stats().err(), Some(FreeLevel(0)));
+    }
+
+    #[test]
+    fn sharing_stats() {
+        let linear = from_indices(&lc::app(I(), lc::app(I(), I()))).unwrap();
+        let square = from_indices(&lc::app(
+            lc::abs(lc::app(lc::app(mul(), lc::Var(1)), lc::Var(1))),
+            lc::app(lc::app(mul(), 2.into_church()), 3.into_church())
+        )).unwrap();
+        let (normal, stats) = square.normalize_with_stats().unwrap();
+
+        assert_eq!(linear.normalize_with_stats().unwrap().1.shared_forces, 0);
+        assert_eq!(normal.to_indices::<DbTerm>(), Ok(36.into_church()));
+        assert!(stats.shared_forces > 0);
+        assert!(stats.saved_reductions > 0);
+    }
+}
@@ -0,0 +1,168 @@
+//! Lambda term representations
+//!
+//! The `TermRep` trait describes what blc needs to know about a term representation; any type
+//! implementing it can be parsed from and serialized to BLC as well as executed.
+
+pub mod interned;
+pub mod levels;
+pub mod nameless;
+
+/// A borrowed view of a single node of a term representation.
+#[derive(Debug, PartialEq)]
+pub enum View<'a, T: 'a> {
+    /// a variable with a De Bruijn index
+    Var(usize),
+    /// an abstraction over a term
+    Abs(&'a T),
+    /// an application of one term to another
+    App(&'a T, &'a T)
+}
+
+/// A representation of lambda terms with De Bruijn indices.
+pub trait TermRep: Sized {
+    /// Produces a variable with the given De Bruijn index.
+    fn var(index: usize) -> Self;
+    /// Produces an abstraction over the given term.
+    fn abs(body: Self) -> Self;
+    /// Produces an application of one term to another.
+    fn app(lhs: Self, rhs: Self) -> Self;
+    /// Returns a view of the outermost node of the term.
+    fn view(&self) -> View<'_, Self>;
+}
+
+/// Converts a term between two representations.
+///
+/// # Example
+#[cfg_attr(feature = "lambda_calculus", doc = "```")]
+#[cfg_attr(not(feature = "lambda_calculus"), doc = "```ignore")]
+/// extern crate blc;
+/// extern crate lambda_calculus;
+///
+/// use blc::repr::convert;
+/// use lambda_calculus::*;
+///
+/// let i: Term = convert(&abs(Var(1)));
+///
+/// assert_eq!(i, abs(Var(1)));
+/// ```
+pub fn convert<A: TermRep, B: TermRep>(term: &A) -> B {
+    match term.view() {
+        View::Var(i) => B::var(i),
+        View::Abs(t) => B::abs(convert(t)),
+        View::App(t1, t2) => B::app(convert(t1), convert(t2))
+    }
+}
+
+#[cfg(feature = "lambda_calculus")]
+impl TermRep for ::lambda_calculus::Term {
+    fn var(index: usize) -> Self { ::lambda_calculus::Var(index) }
+
+    fn abs(body: Self) -> Self { ::lambda_calculus::abs(body) }
+
+    fn app(lhs: Self, rhs: Self) -> Self { ::lambda_calculus::app(lhs, rhs) }
+
+    fn view(&self) -> View<'_, Self> {
+        match *self {
+            ::lambda_calculus::Var(i) => View::Var(i),
+            ::lambda_calculus::Abs(ref t) => View::Abs(t),
+            ::lambda_calculus::App(ref t1, ref t2) => View::App(t1, t2)
+        }
+    }
+}
+
+/// An error that can occur when importing a `Term` of another version of `lambda_calculus`.
+#[cfg(feature = "interop")]
+#[derive(Debug, PartialEq)]
+pub enum Error {
+    /// a variant of the foreign `Term` with no counterpart in blc's `TermRep`resentations
+    UnsupportedTerm
+}
+
+/// Generates a module with conversions between blc's `TermRep`resentations and the `Term` type of
+/// any version of the `lambda_calculus` crate, which blc itself only builds against at version
+/// `2.x`.
+///
+/// It accepts the name of the generated module, the path to the module containing the `Term` type
+/// and the shape of its applications: `pair` for `App(Box<Term>, Box<Term>)` (versions `1.x` and
+/// `2.x`) or `tuple` for `App(Box<(Term, Term)>)` (version `3.x` onwards). The generated module
+/// contains the functions `import`, converting a foreign `Term` into a `TermRep`, and `export`,
+/// converting in the other direction.
+///
+/// The macro is only available with the `interop` feature.
+///
+/// # Example
+/// ```
+/// #[macro_use]
+/// extern crate blc;
+/// extern crate lambda_calculus;
+///
+/// lambda_calculus_adapter!(lc2, lambda_calculus::term, pair);
+///
+/// fn main() {
+///     use blc::encoding::binary::{from_bits, to_bits};
+///     use lambda_calculus::combinators::I;
+///
+///     let i: lambda_calculus::Term = from_bits(b"0010").unwrap();
+///
+///     assert_eq!(lc2::export(&i), I());
+///     assert_eq!(to_bits(&lc2::import::<lambda_calculus::Term>(&I()).unwrap()), b"0010");
+/// }
+/// ```
+#[cfg(feature = "interop")]
+#[macro_export]
+macro_rules! lambda_calculus_adapter {
+    ($module:ident, $($path:ident)::+, pair) => {
+        lambda_calculus_adapter!(@module $module, $($path)::+,
+            fn split(term: &Term) -> Option<(&Term, &Term)> {
+                if let App(ref lhs, ref rhs) = *term { Some((lhs, rhs)) } else { None }
+            }
+
+            fn join(lhs: Term, rhs: Term) -> Term { App(Box::new(lhs), Box::new(rhs)) }
+        );
+    };
+    ($module:ident, $($path:ident)::+, tuple) => {
+        lambda_calculus_adapter!(@module $module, $($path)::+,
+            fn split(term: &Term) -> Option<(&Term, &Term)> {
+                if let App(ref boxed) = *term { Some((&boxed.0, &boxed.1)) } else { None }
+            }
+
+            fn join(lhs: Term, rhs: Term) -> Term { App(Box::new((lhs, rhs))) }
+        );
+    };
+    (@module $module:ident, $($path:ident)::+, $($helper:item)*) => {
+        /// Conversions between blc's term representations and a foreign `Term`.
+        pub mod $module {
+            use $crate::repr::{Error, TermRep, View};
+            use ::$($path)::+::Term;
+            use ::$($path)::+::Term::*;
+
+            /// Converts a foreign `Term` into a `TermRep`resentation.
+            ///
+            /// # Errors
+            ///
+            /// Returns `Error::UnsupportedTerm` if the term contains a variant other than a
+            /// variable, an abstraction or an application.
+            pub fn import<T: TermRep>(term: &Term) -> Result<T, Error> {
+                match *term {
+                    Var(i) => Ok(T::var(i)),
+                    Abs(ref t) => Ok(T::abs(import(t)?)),
+                    _ => {
+                        let (lhs, rhs) = split(term).ok_or(Error::UnsupportedTerm)?;
+                        Ok(T::app(import(lhs)?, import(rhs)?))
+                    }
+                }
+            }
+
+            /// Converts a `TermRep`resentation into a foreign `Term`.
+            pub fn export<T: TermRep>(term: &T) -> Term {
+                match term.view() {
+                    View::Var(i) => Var(i),
+                    View::Abs(t) => Abs(Box::new(export(t))),
+                    View::App(t1, t2) => join(export(t1), export(t2))
+                }
+            }
+
+            $($helper)*
+        }
+    };
+}
@@ -0,0 +1,312 @@
+//! The locally nameless term representation
+//!
+//! Bound variables are represented with De Bruijn indices and free variables with names; since a
+//! locally closed term never contains dangling indices, substitution and other transformations
+//! don't need to shift anything and names can't be captured.
+
+use repr::{TermRep, View};
+use std::collections::BTreeSet;
+use self::Error::*;
+pub use self::Term::*;
+
+/// A lambda term in the locally nameless representation.
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub enum Term {
+    /// a bound variable with a De Bruijn index
+    BVar(usize),
+    /// a free variable with a name
+    FVar(String),
+    /// an abstraction
+    Abs(Box<Term>),
+    /// an application
+    App(Box<Term>, Box<Term>)
+}
+
+/// An error that can occur during conversion between representations.
+#[derive(Debug, PartialEq)]
+pub enum Error {
+    /// a free De Bruijn index with no corresponding name in the context
+    UnnamedIndex(usize),
+    /// a free variable that is not present in the context
+    UnknownName(String),
+    /// a bound variable that is not bound by any abstraction
+    DanglingIndex(usize)
+}
+
+/// Wraps a `Term` in an `Abs`traction.
+pub fn abs(term: Term) -> Term { Abs(Box::new(term)) }
+
+/// Produces an `App`lication of two given `Term`s.
+pub fn app(lhs: Term, rhs: Term) -> Term { App(Box::new(lhs), Box::new(rhs)) }
+
+/// Produces an abstraction over the free variable with the given name.
+///
+/// # Example
+/// ```
+/// use blc::repr::nameless::{lam, abs, app, FVar, BVar};
+///
+/// let k = lam("x", lam("y", FVar("x".into())));
+///
+/// assert_eq!(k, abs(abs(BVar(2))));
+/// ```
+pub fn lam(name: &str, body: Term) -> Term { abs(body.close(name)) }
+
+/// Converts a term with De Bruijn indices to the locally nameless representation; the free index
+/// `i` (counting from outside of the term) is named `context[i - 1]`.
+///
+/// # Example
+#[cfg_attr(feature = "lambda_calculus", doc = "```")]
+#[cfg_attr(not(feature = "lambda_calculus"), doc = "```ignore")]
+/// extern crate blc;
+/// extern crate lambda_calculus;
+///
+/// use blc::repr::nameless::{from_de_bruijn, abs, app, BVar, FVar};
+/// use lambda_calculus::{Var, abs as dabs, app as dapp};
+///
+/// let term = dabs(dapp(Var(1), Var(2)));
+///
+/// assert_eq!(from_de_bruijn(&term, &["f"]), Ok(abs(app(BVar(1), FVar("f".into())))));
+/// ```
+/// # Errors
+///
+/// Returns an `Error` if a free variable has no name in the `context`.
+pub fn from_de_bruijn<T: TermRep>(term: &T, context: &[&str]) -> Result<Term, Error> {
+    _from_de_bruijn(term, context, 0)
+}
+
+fn _from_de_bruijn<T: TermRep>(term: &T, context: &[&str], depth: usize) -> Result<Term, Error> {
+    match term.view() {
+        View::Var(i) if i > depth => {
+            context.get(i - depth - 1)
+                .map(|&name| FVar(name.into()))
+                .ok_or(UnnamedIndex(i - depth))
+        },
+        View::Var(i) => Ok(BVar(i)),
+        View::Abs(t) => Ok(abs(_from_de_bruijn(t, context, depth + 1)?)),
+        View::App(t1, t2) => Ok(app(
+            _from_de_bruijn(t1, context, depth)?,
+            _from_de_bruijn(t2, context, depth)?
+        ))
+    }
+}
+
+impl Term {
+    /// Converts `self` to a term with De Bruijn indices; the free variable named `context[i - 1]`
+    /// becomes the free index `i` (counting from outside of the term).
+    ///
+    /// # Example
+    #[cfg_attr(feature = "lambda_calculus", doc = "```")]
+    #[cfg_attr(not(feature = "lambda_calculus"), doc = "```ignore")]
+    /// extern crate blc;
+    /// extern crate lambda_calculus;
+    ///
+    /// use blc::repr::nameless::{lam, app, FVar};
+    /// use lambda_calculus::{Term, Var, abs, app as dapp};
+    ///
+    /// let term = lam("x", app(FVar("x".into()), FVar("f".into())));
+    ///
+    /// assert_eq!(term.to_de_bruijn::<Term>(&["f"]), Ok(abs(dapp(Var(1), Var(2)))));
+    /// ```
+    /// # Errors
+    ///
+    /// Returns an `Error` if `self` is not locally closed or a free variable is not present in the
+    /// `context`.
+    pub fn to_de_bruijn<T: TermRep>(&self, context: &[&str]) -> Result<T, Error> {
+        self._to_de_bruijn(context, 0)
+    }
+
+    fn _to_de_bruijn<T: TermRep>(&self, context: &[&str], depth: usize) -> Result<T, Error> {
+        match *self {
+            BVar(i) => if i > depth { Err(DanglingIndex(i)) } else { Ok(T::var(i)) },
+            FVar(ref name) => {
+                context.iter()
+                    .position(|n| n == name)
+                    .map(|pos| T::var(pos + 1 + depth))
+                    .ok_or_else(|| UnknownName(name.clone()))
+            },
+            Abs(ref t) => Ok(T::abs(t._to_de_bruijn(context, depth + 1)?)),
+            App(ref t1, ref t2) => Ok(T::app(
+                t1._to_de_bruijn(context, depth)?,
+                t2._to_de_bruijn(context, depth)?
+            ))
+        }
+    }
+
+    /// Opens the body of an abstraction by replacing its bound variable with the given term.
+    ///
+    /// # Example
+    /// ```
+    /// use blc::repr::nameless::{abs, app, BVar, FVar};
+    ///
+    /// let body = app(BVar(1), abs(BVar(2)));
+    ///
+    /// assert_eq!(body.open(&FVar("x".into())), app(FVar("x".into()), abs(FVar("x".into()))));
+    /// ```
+    pub fn open(&self, with: &Term) -> Term {
+        self.open_at(with, 1)
+    }
+
+    fn open_at(&self, with: &Term, depth: usize) -> Term {
+        match *self {
+            BVar(i) if i == depth => with.clone(),
+            BVar(i) => BVar(i),
+            FVar(ref name) => FVar(name.clone()),
+            Abs(ref t) => abs(t.open_at(with, depth + 1)),
+            App(ref t1, ref t2) => app(t1.open_at(with, depth), t2.open_at(with, depth))
+        }
+    }
+
+    /// Closes `self` over the free variable with the given name, producing the body of an
+    /// abstraction; it is the inverse of `open`.
+    ///
+    /// # Example
+    /// ```
+    /// use blc::repr::nameless::{abs, app, BVar, FVar};
+    ///
+    /// let term = app(FVar("x".into()), abs(FVar("x".into())));
+    ///
+    /// assert_eq!(term.close("x"), app(BVar(1), abs(BVar(2))));
+    /// ```
+    pub fn close(&self, name: &str) -> Term {
+        self.close_at(name, 1)
+    }
+
+    fn close_at(&self, name: &str, depth: usize) -> Term {
+        match *self {
+            BVar(i) => BVar(i),
+            FVar(ref n) => if n == name { BVar(depth) } else { FVar(n.clone()) },
+            Abs(ref t) => abs(t.close_at(name, depth + 1)),
+            App(ref t1, ref t2) => app(t1.close_at(name, depth), t2.close_at(name, depth))
+        }
+    }
+
+    /// Substitutes the free variable with the given name with a term; no name can be captured,
+    /// because bound variables are nameless.
+    ///
+    /// # Example
+    /// ```
+    /// use blc::repr::nameless::{lam, app, FVar};
+    ///
+    /// let term = lam("y", app(FVar("x".into()), FVar("y".into())));
+    /// let subst = term.substitute("x", &FVar("y".into()));
+    ///
+    /// assert_eq!(subst, lam("z", app(FVar("y".into()), FVar("z".into()))));
+    /// ```
+    pub fn substitute(&self, name: &str, with: &Term) -> Term {
+        match *self {
+            BVar(i) => BVar(i),
+            FVar(ref n) => if n == name { with.clone() } else { FVar(n.clone()) },
+            Abs(ref t) => abs(t.substitute(name, with)),
+            App(ref t1, ref t2) => app(t1.substitute(name, with), t2.substitute(name, with))
+        }
+    }
+
+    /// Returns the names of the free variables of `self`.
+    pub fn free_vars(&self) -> BTreeSet<String> {
+        let mut vars = BTreeSet::new();
+        self.collect_free_vars(&mut vars);
+
+        vars
+    }
+
+    fn collect_free_vars(&self, vars: &mut BTreeSet<String>) {
+        match *self {
+            BVar(_) => (),
+            FVar(ref name) => { vars.insert(name.clone()); },
+            Abs(ref t) => t.collect_free_vars(vars),
+            App(ref t1, ref t2) => {
+                t1.collect_free_vars(vars);
+                t2.collect_free_vars(vars)
+            }
+        }
+    }
+
+    /// Returns `true` if every bound variable of `self` is bound by an abstraction.
+    ///
+    /// # Example
+    /// ```
+    /// use blc::repr::nameless::{abs, BVar};
+    ///
+    /// assert!(abs(BVar(1)).is_locally_closed());
+    /// assert!(!abs(BVar(2)).is_locally_closed());
+    /// ```
+    pub fn is_locally_closed(&self) -> bool {
+        self.is_closed_at(0)
+    }
+
+    fn is_closed_at(&self, depth: usize) -> bool {
+        match *self {
+            BVar(i) => i <= depth,
+            FVar(_) => true,
+            Abs(ref t) => t.is_closed_at(depth + 1),
+            App(ref t1, ref t2) => t1.is_closed_at(depth) && t2.is_closed_at(depth)
+        }
+    }
+}
+
+/// Returns a name based on `hint` that does not occur in `avoid`.
+///
+/// # Example
+/// ```
+/// use blc::repr::nameless::fresh;
+///
+/// let taken = ["x".to_string(), "x1".to_string()].iter().cloned().collect();
+///
+/// assert_eq!(fresh("y", &taken), "y");
+/// assert_eq!(fresh("x", &taken), "x2");
+/// ```
+pub fn fresh(hint: &str, avoid: &BTreeSet<String>) -> String {
+    if !avoid.contains(hint) { return hint.into() }
+
+    (1..).map(|n| format!("{}{}", hint, n)).find(|name| !avoid.contains(name)).unwrap() // safe
+}
+
+#[cfg(all(test, feature = "lambda_calculus"))]
+mod test {
+    use super::*;
+    use lambda_calculus::{parse, DeBruijn};
+
+    type DbTerm = ::lambda_calculus::Term;
+
+    #[test]
+    fn de_bruijn_round_trip() {
+        for code in &["λλλ31(21)", "λ1((λ11)(λλλλλ14(3(55)2)))1", "λλ2(2(21))"] {
+            let term = parse(code, DeBruijn).unwrap();
+            let ln = from_de_bruijn(&term, &[]).unwrap();
+
+            assert!(ln.is_locally_closed());
+            assert!(ln.free_vars().is_empty());
+            assert_eq!(ln.to_de_bruijn::<DbTerm>(&[]), Ok(term));
+        }
+    }
+
+    #[test]
+    fn free_variables() {
+        let term = parse("λ123", DeBruijn).unwrap();
+        let ln = from_de_bruijn(&term, &["a", "b"]).unwrap();
+
+        assert_eq!(ln, abs(app(app(BVar(1), FVar("a".into())), FVar("b".into()))));
+        assert_eq!(from_de_bruijn(&term, &["a"]), Err(UnnamedIndex(2)));
+        assert_eq!(ln.to_de_bruijn::<DbTerm>(&["b", "a"]), Ok(parse("λ132", DeBruijn).unwrap()));
+        assert_eq!(ln.to_de_bruijn::<DbTerm>(&["a"]), Err(UnknownName("b".into())));
+    }
+
+    #[test]
+    fn open_close() {
+        let body = app(BVar(1), abs(app(BVar(1), BVar(2))));
+        let x = FVar("x".into());
+
+        assert_eq!(body.open(&x).close("x"), body);
+        assert_eq!(abs(BVar(2)).to_de_bruijn::<DbTerm>(&[]), Err(DanglingIndex(2)));
+    }
+
+    #[test]
+    fn capture_avoidance() {
+        // (λx.λy.x) y reduces to λz.y, not λy.y
+        let k = lam("x", lam("y", FVar("x".into())));
+        let reduced = if let Abs(body) = k { body.open(&FVar("y".into())) } else { unreachable!() };
+
+        assert_eq!(reduced, lam("z", FVar("y".into())));
+        assert_ne!(reduced, lam("y", FVar("y".into())));
+    }
+}
@@ -0,0 +1,160 @@
+//! A conformance suite for evaluators
+//!
+//! `verify` checks that a `Machine` obeys the following laws:
+//!
+//! - **parsing**: parsing the encoding of a term results in the same term, and an encoded term
+//!   takes 2 bits per abstraction and application and `i + 1` bits per variable with index `i`
+//! - **normal forms**: a set of terms with known normal forms is normalized to them
+//! - **fixed points**: normalizing a term that is already in normal form performs no reductions
+//!   and leaves it intact
+//! - **limits**: reduction never performs more reductions than its limit allows
+//! - **data**: normalizing the identity applied to lambda-encoded bytes results in the same bytes
+
+use lambda_calculus::*;
+use lambda_calculus::combinators::{S, K, I, O};
+use lambda_calculus::data::num::church::{add, mul, pred, is_zero};
+use lambda_calculus::data::boolean::{tru, fls};
+use encoding::binary::{from_bits, to_bits};
+use encoding::lambda::{decode, encode};
+use execution::machine::Machine;
+
+/// A broken law, along with the term it was broken for.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Violation {
+    /// the name of the law
+    pub law: &'static str,
+    /// the term the law was checked with, in BLC
+    pub term: String,
+    /// what the law demanded
+    pub expected: String,
+    /// what happened instead
+    pub actual: String
+}
+
+/// Checks `machine` against the laws described in the module documentation.
+///
+/// # Example
+/// ```
+/// use blc::execution::machine::{BigStep, SmallStep};
+/// use blc::spec::verify;
+///
+/// assert_eq!(verify(&mut BigStep), Ok(()));
+/// assert_eq!(verify(&mut SmallStep::default()), Ok(()));
+/// ```
+/// # Errors
+///
+/// Returns all the `Violation`s of the laws.
+pub fn verify<M: Machine>(machine: &mut M) -> Result<(), Vec<Violation>> {
+    let mut violations = Vec::new();
+
+    for (term, normal) in normal_forms() {
+        check_parsing(&term, &mut violations);
+        check_parsing(&normal, &mut violations);
+
+        let actual = machine.normalize(term.clone());
+        check(&mut violations, "normal forms", &term, &normal, &actual);
+
+        let mut fixed = normal.clone();
+        let reductions = machine.reduce(&mut fixed, 0);
+        check(&mut violations, "fixed points", &normal, &0, &reductions);
+        check(&mut violations, "fixed points", &normal, &normal, &fixed);
+    }
+
+    for &limit in &[1, 2, 10, 100] {
+        let mut omega = O();
+        let reductions = machine.reduce(&mut omega, limit);
+        if reductions > limit {
+            violations.push(violation("limits", &O(), format!("≤ {}", limit), reductions));
+        }
+    }
+
+    for data in &[&b""[..], b"a", b"\x00\xff", b"herp derp"] {
+        let term = app(I(), encode(data));
+        let expected = decode(encode(data)).unwrap(); // safe - valid data
+        let actual = decode(machine.normalize(term.clone())).unwrap_or_default();
+        check(&mut violations, "data", &term, &expected, &actual);
+    }
+
+    if violations.is_empty() { Ok(()) } else { Err(violations) }
+}
+
+fn normal_forms() -> Vec<(Term, Term)> {
+    vec![
+        (app!(S(), K(), K()), I()),
+        (app!(K(), I(), O()), I()),
+        (app!(add(), 2.into_church(), 3.into_church()), 5.into_church()),
+        (app!(mul(), 3.into_church(), 4.into_church()), 12.into_church()),
+        (app(pred(), 3.into_church()), 2.into_church()),
+        (app(is_zero(), 0.into_church()), tru()),
+        (app(is_zero(), 2.into_church()), fls()),
+        (app(I(), abs(app(Var(1), app(K(), Var(1))))), abs(app(Var(1), abs(Var(2)))))
+    ]
+}
+
+fn check_parsing(term: &Term, violations: &mut Vec<Violation>) {
+    let bits = to_bits(term);
+    check(violations, "parsing", term, &Ok(term.clone()), &from_bits(&bits));
+    check(violations, "parsing", term, &expected_size(term), &bits.len());
+}
+
+fn expected_size(term: &Term) -> usize {
+    match *term {
+        Var(i) => i + 1,
+        Abs(ref body) => 2 + expected_size(body),
+        App(ref lhs, ref rhs) => 2 + expected_size(lhs) + expected_size(rhs)
+    }
+}
+
+fn check<T: PartialEq + ::std::fmt::Debug>(
+    violations: &mut Vec<Violation>,
+    law: &'static str,
+    term: &Term,
+    expected: &T,
+    actual: &T
+) {
+    if expected != actual { violations.push(violation(law, term, expected, actual)) }
+}
+
+fn violation<E: ::std::fmt::Debug, A: ::std::fmt::Debug>(
+    law: &'static str,
+    term: &Term,
+    expected: E,
+    actual: A
+) -> Violation {
+    Violation {
+        law,
+        term: String::from_utf8_lossy(&to_bits(term)).into_owned(),
+        expected: format!("{:?}", expected),
+        actual: format!("{:?}", actual)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use execution::Strategy;
+    use execution::machine::SmallStep;
+
+    struct Lazy;
+
+    impl Machine for Lazy {
+        fn reduce(&mut self, term: &mut Term, limit: usize) -> usize {
+            term.reduce(CBN, limit)
+        }
+    }
+
+    #[test]
+    fn conforming_machines() {
+        for &strategy in &[Strategy::Normal, Strategy::StrictData] {
+            assert_eq!(verify(&mut SmallStep { strategy }), Ok(()));
+        }
+    }
+
+    #[test]
+    fn violations() {
+        let violations = verify(&mut Lazy).unwrap_err();
+
+        assert!(violations.iter().all(|v| v.law == "normal forms" || v.law == "data"));
+        assert!(violations.iter().any(|v| v.law == "normal forms"));
+    }
+}
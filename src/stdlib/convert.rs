@@ -0,0 +1,172 @@
+//! Conversions between Church and Scott encodings of data
+//!
+//! Church numerals and lists are their own folds, while Scott numerals and lists are their own
+//! case analyses; programs and decoders written for either convention can interoperate by
+//! converting the normal forms of their data. The conversions are structural, so they require the
+//! data in normal form, and the elements of lists are only moved, not converted themselves; they
+//! have to be closed terms.
+//!
+//! | data       | Church                | Scott                     |
+//! |------------|-----------------------|---------------------------|
+//! | 0          | `λλ1`                 | `λλ2`                     |
+//! | n + 1      | `λλ2(n 2 1)`          | `λλ1 n`                   |
+//! | nil        | `λλ2`                 | `λλ2`                     |
+//! | cons h t   | `λλ1 h (t 1 2)`       | `λλ1 h t`                 |
+
+use lambda_calculus::*;
+use lambda_calculus::data::boolean::tru;
+
+/// Converts a Church numeral to the equivalent Scott numeral.
+///
+/// # Example
+/// ```
+/// use blc::stdlib::convert::church_to_scott_numeral;
+/// use blc::stdlib::gen::church;
+///
+/// assert_eq!(format!("{:?}", church_to_scott_numeral(&church(1)).unwrap()), "λλ1(λλ2)");
+/// ```
+pub fn church_to_scott_numeral(numeral: &Term) -> Option<Term> {
+    let mut body = numeral.unabs_ref().and_then(|t| t.unabs_ref()).ok()?;
+    let mut scott = tru();
+
+    while let App(ref lhs, ref rhs) = *body {
+        if **lhs != Var(2) { return None }
+        scott = abs(abs(app(Var(1), scott)));
+        body = rhs;
+    }
+
+    if *body == Var(1) { Some(scott) } else { None }
+}
+
+/// Converts a Scott numeral to the equivalent Church numeral.
+///
+/// # Example
+/// ```
+/// use blc::stdlib::convert::{church_to_scott_numeral, scott_to_church_numeral};
+/// use blc::stdlib::gen::church;
+///
+/// let scott = church_to_scott_numeral(&church(5)).unwrap();
+///
+/// assert_eq!(scott_to_church_numeral(&scott), Some(church(5)));
+/// ```
+pub fn scott_to_church_numeral(mut numeral: &Term) -> Option<Term> {
+    let mut church = Var(1);
+
+    loop {
+        let body = numeral.unabs_ref().and_then(|t| t.unabs_ref()).ok()?;
+
+        match *body {
+            Var(2) => return Some(abs(abs(church))),
+            App(ref lhs, ref rhs) if **lhs == Var(1) => {
+                church = app(Var(2), church);
+                numeral = rhs;
+            },
+            _ => return None
+        }
+    }
+}
+
+/// Converts a Church list to the equivalent Scott list.
+///
+/// # Example
+/// ```
+/// extern crate blc;
+/// extern crate lambda_calculus;
+///
+/// use blc::stdlib::convert::church_to_scott_list;
+/// use blc::stdlib::gen::church;
+/// use lambda_calculus::{IntoChurchList, IntoScottList};
+///
+/// let church_list = vec![church(1), church(2)].into_church();
+/// let scott_list = vec![church(1), church(2)].into_scott();
+///
+/// assert_eq!(church_to_scott_list(&church_list), Some(scott_list));
+/// ```
+pub fn church_to_scott_list(list: &Term) -> Option<Term> {
+    let mut body = list.unabs_ref().and_then(|t| t.unabs_ref()).ok()?;
+    let mut elements = Vec::new();
+
+    while let Some((head, tail)) = cell(body) {
+        elements.push(head.clone());
+        body = tail;
+    }
+
+    if *body == Var(2) { Some(elements.into_scott()) } else { None }
+}
+
+/// Converts a Scott list to the equivalent Church list.
+///
+/// # Example
+/// ```
+/// extern crate blc;
+/// extern crate lambda_calculus;
+///
+/// use blc::stdlib::convert::scott_to_church_list;
+/// use blc::stdlib::gen::church;
+/// use lambda_calculus::{IntoChurchList, IntoScottList};
+///
+/// let scott_list = vec![church(1), church(2)].into_scott();
+/// let church_list = vec![church(1), church(2)].into_church();
+///
+/// assert_eq!(scott_to_church_list(&scott_list), Some(church_list));
+/// ```
+pub fn scott_to_church_list(mut list: &Term) -> Option<Term> {
+    let mut elements = Vec::new();
+
+    loop {
+        let body = list.unabs_ref().and_then(|t| t.unabs_ref()).ok()?;
+
+        if *body == Var(2) { return Some(elements.into_church()) }
+        let (head, tail) = cell(body)?;
+        elements.push(head.clone());
+        list = tail;
+    }
+}
+
+/// Returns the head and the tail of `1 head tail` if the head is closed.
+fn cell(term: &Term) -> Option<(&Term, &Term)> {
+    let (pair, tail) = term.unapp_ref().ok()?;
+    let (var, head) = pair.unapp_ref().ok()?;
+
+    if *var == Var(1) && head.is_supercombinator() { Some((head, tail)) } else { None }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use lambda_calculus::data::boolean::fls;
+    use lambda_calculus::data::num::{church, scott};
+    use stdlib::gen::church as church_numeral;
+
+    #[test]
+    fn numerals() {
+        for n in 0..10 {
+            let scott = (n as usize).into_scott();
+
+            assert_eq!(church_to_scott_numeral(&church_numeral(n)), Some(scott.clone()));
+            assert_eq!(scott_to_church_numeral(&scott), Some(church_numeral(n)));
+        }
+        assert_eq!(church_to_scott_numeral(&abs(Var(1))), None);
+        assert_eq!(scott_to_church_numeral(&fls()), None);
+    }
+
+    #[test]
+    fn converted_numerals_compute() {
+        let scott = church_to_scott_numeral(&church_numeral(3)).unwrap();
+        let pred = beta(app(scott::pred(), scott), NOR, 0);
+        let back = scott_to_church_numeral(&pred).unwrap();
+
+        assert_eq!(beta(app(church::succ(), back), NOR, 0), church_numeral(3));
+    }
+
+    #[test]
+    fn lists() {
+        let elements = vec![tru(), fls(), tru()];
+        let (church, scott) = (elements.clone().into_church(), elements.into_scott());
+
+        assert_eq!(church_to_scott_list(&church), Some(scott.clone()));
+        assert_eq!(scott_to_church_list(&scott), Some(church));
+        assert_eq!(church_to_scott_list(&tru()), Some(tru()));
+        assert_eq!(church_to_scott_list(&abs(abs(app!(Var(1), Var(1), Var(2))))), None);
+    }
+}
@@ -0,0 +1,83 @@
+//! Parameterized term generators
+//!
+//! Lists are encoded the same way as the input of BLC programs: as cells `λ1HT` ending with `λλ1`.
+//! The generated terms can be serialized with `encoding::binary::to_bits`.
+
+use lambda_calculus::*;
+use lambda_calculus::data::boolean::{tru, fls};
+use pair_list::listify_terms;
+
+/// Produces the Church numeral `n`, `λλ2(2(...(21)))`; its size is linear in `n`.
+///
+/// # Example
+/// ```
+/// use blc::stdlib::gen::church;
+/// use blc::encoding::binary::to_bits;
+///
+/// assert_eq!(to_bits(&church(2)), b"0000011100111010");
+/// ```
+pub fn church(n: u64) -> Term {
+    let body = (0..n).fold(Var(1), |body, _| app(Var(2), body));
+
+    abs(abs(body))
+}
+
+/// Produces a list of Church numerals.
+///
+/// # Example
+/// ```
+/// use blc::stdlib::gen::list_of_nats;
+///
+/// assert_eq!(format!("{:?}", list_of_nats(&[0])), "λ1(λλ1)(λλ1)");
+/// ```
+pub fn list_of_nats(nats: &[u64]) -> Term {
+    listify_terms(nats.iter().map(|&n| church(n)).collect())
+}
+
+/// Produces a list of bits, encoded the way the bits of BLC input are: `false` (a `0` bit) as
+/// `λλ2` and `true` (a `1` bit) as `λλ1`.
+///
+/// # Example
+/// ```
+/// use blc::stdlib::gen::bitlist;
+///
+/// assert_eq!(format!("{:?}", bitlist(&[false, true])), "λ1(λλ2)(λ1(λλ1)(λλ1))");
+/// ```
+pub fn bitlist(bits: &[bool]) -> Term {
+    listify_terms(bits.iter().map(|&bit| if bit { fls() } else { tru() }).collect())
+}
+
+/// Produces a list of the Unicode code points of the characters of `string` as Church numerals.
+///
+/// # Example
+/// ```
+/// use blc::stdlib::gen::{church_string, list_of_nats};
+///
+/// assert_eq!(church_string("hi"), list_of_nats(&[104, 105]));
+/// ```
+pub fn church_string(string: &str) -> Term {
+    listify_terms(string.chars().map(|c| church(c as u64)).collect())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use lambda_calculus::data::num::church::add;
+    use encoding::lambda::encode;
+    use pair_list::head_ref;
+
+    #[test]
+    fn numerals() {
+        assert_eq!(church(0), fls());
+        assert_eq!(church(7), 7.into_church());
+        assert_eq!(beta(app!(add(), church(2), church(3)), NOR, 0), church(5));
+    }
+
+    #[test]
+    fn lists() {
+        assert_eq!(list_of_nats(&[]), fls());
+        assert_eq!(list_of_nats(&[1, 2]), vec![1.into_church(), 2.into_church()].into_pair_list());
+        let a = [false, true, true, false, false, false, false, true];
+        assert_eq!(&bitlist(&a), head_ref(&encode(b"a")).unwrap());
+    }
+}
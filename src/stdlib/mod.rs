@@ -0,0 +1,8 @@
+//! Standard lambda terms
+//!
+//! The `gen` module constructs structured terms, such as numerals and lists, to be used as inputs
+//! of programs in tests and demos; the `convert` module converts data between Church and Scott
+//! encodings.
+
+pub mod convert;
+pub mod gen;
@@ -0,0 +1,218 @@
+//! Reusable property checks
+//!
+//! Every law is checked for a set of samples, with the implementations under test passed in as
+//! functions or `Machine`s, so that downstream encoders and backends can check themselves against
+//! the same properties as the ones of this crate:
+//!
+//! - **lambda encoding**: decoding encoded bytes results in the same bytes
+//! - **binary encoding**: parsing the binary encoding of a term results in the same term
+//! - **compression**: decompressing compressed bits results in the same bits
+//! - **strategy agreement**: a machine that reaches a normal form reaches the same one as
+//!   normal-order reduction
+//!
+//! `SAMPLE_BYTES`, `SAMPLE_BITS` and `sample_terms` are default sets of samples.
+
+use lambda_calculus::*;
+use lambda_calculus::combinators::{S, K, I, O, Y};
+use lambda_calculus::data::num::church::{add, pred};
+use encoding::binary::to_bits;
+use encoding::lambda::encode;
+use execution::machine::Machine;
+use spec::Violation;
+use std::fmt::Debug;
+
+/// Byte strings to check the lambda encoding with.
+pub const SAMPLE_BYTES: &[&[u8]] = &[b"", b"a", b"\x00\xff", b"herp derp", b"0110"];
+
+/// Bit strings to check compression with; their lengths are divisible by 8, as otherwise the
+/// padding of the last byte can't be told apart from the bits.
+pub const SAMPLE_BITS: &[&[u8]] = &[
+    b"00000010",
+    b"0001011001000110100000000001011100111110111100001011011110110000010\
+      00000",
+    b"1111111100000000"
+];
+
+/// Returns closed terms with normal forms to check the binary encoding and strategies with.
+pub fn sample_terms() -> Vec<Term> {
+    vec![
+        I(),
+        K(),
+        S(),
+        app!(S(), K(), K()),
+        app!(K(), I(), O()),
+        app!(add(), 2.into_church(), 3.into_church()),
+        app(pred(), 3.into_church()),
+        app!(Y(), app(K(), I())),
+        abs(app(Var(1), app(I(), abs(Var(2))))),
+        encode(b"ok")
+    ]
+}
+
+/// Checks that `decode` reverses `encode` for every sample.
+///
+/// # Example
+/// ```
+/// use blc::encoding::lambda::{decode, encode};
+/// use blc::test_utils::laws::{lambda_encoding, SAMPLE_BYTES};
+///
+/// let decode_bytes = |term| decode(term).map(|s| s.chars().map(|c| c as u8).collect());
+///
+/// assert_eq!(lambda_encoding(SAMPLE_BYTES, encode, decode_bytes), Ok(()));
+/// ```
+/// # Errors
+///
+/// Returns a `Violation` for every sample that doesn't survive the round trip.
+pub fn lambda_encoding<E, D, F>(samples: &[&[u8]], encode: E, decode: D)
+    -> Result<(), Vec<Violation>>
+where
+    E: Fn(&[u8]) -> Term,
+    D: Fn(Term) -> Result<Vec<u8>, F>,
+    F: PartialEq + Debug
+{
+    check_all(samples.iter().map(|&sample| {
+        let term = encode(sample);
+        let blc = blc_string(&term);
+        (blc, Ok(sample.to_vec()), decode(term))
+    }), "lambda encoding")
+}
+
+/// Checks that `parse` reverses `to_binary` for every sample.
+///
+/// # Example
+/// ```
+/// use blc::encoding::binary::{from_bits, to_bits};
+/// use blc::test_utils::laws::{binary_encoding, sample_terms};
+///
+/// assert_eq!(binary_encoding(&sample_terms(), to_bits, from_bits), Ok(()));
+/// ```
+/// # Errors
+///
+/// Returns a `Violation` for every sample that doesn't survive the round trip.
+pub fn binary_encoding<B, P, F>(samples: &[Term], to_binary: B, parse: P)
+    -> Result<(), Vec<Violation>>
+where
+    B: Fn(&Term) -> Vec<u8>,
+    P: Fn(&[u8]) -> Result<Term, F>,
+    F: PartialEq + Debug
+{
+    check_all(samples.iter().map(|sample| {
+        (blc_string(sample), Ok(sample.clone()), parse(&to_binary(sample)))
+    }), "binary encoding")
+}
+
+/// Checks that `decompress` reverses `compress` for every sample of bits.
+///
+/// # Example
+/// ```
+/// use blc::encoding::binary::{compress, decompress};
+/// use blc::test_utils::laws::{compression, SAMPLE_BITS};
+///
+/// assert_eq!(compression(SAMPLE_BITS, compress, decompress), Ok(()));
+/// ```
+/// # Errors
+///
+/// Returns a `Violation` for every sample that doesn't survive the round trip.
+pub fn compression<C, D>(samples: &[&[u8]], compress: C, decompress: D)
+    -> Result<(), Vec<Violation>>
+where
+    C: Fn(&[u8]) -> Vec<u8>,
+    D: Fn(&[u8]) -> Vec<u8>
+{
+    check_all(samples.iter().map(|&sample| {
+        let bits = String::from_utf8_lossy(sample).into_owned();
+        (bits, sample.to_vec(), decompress(&compress(sample)))
+    }), "compression")
+}
+
+/// Checks that whenever `machine` normalizes a sample within `limit` reductions, the result is the
+/// same as with normal-order reduction; samples it doesn't normalize in time are skipped, so
+/// strategies that may diverge where normal order doesn't can be checked too.
+///
+/// # Example
+/// ```
+/// use blc::execution::Strategy;
+/// use blc::execution::machine::SmallStep;
+/// use blc::test_utils::laws::{sample_terms, strategy_agreement};
+///
+/// let mut applicative = SmallStep { strategy: Strategy::Applicative };
+///
+/// assert_eq!(strategy_agreement(&sample_terms(), &mut applicative, 1_000), Ok(()));
+/// ```
+/// # Errors
+///
+/// Returns a `Violation` for every sample normalized to a different term.
+pub fn strategy_agreement<M: Machine>(samples: &[Term], machine: &mut M, limit: usize)
+    -> Result<(), Vec<Violation>>
+{
+    check_all(samples.iter().filter_map(|sample| {
+        let mut reduced = sample.clone();
+        if machine.reduce(&mut reduced, limit) == limit && limit != 0 { return None }
+
+        let normal = beta(sample.clone(), NOR, 0);
+        Some((blc_string(sample), blc_string(&normal), blc_string(&reduced)))
+    }), "strategy agreement")
+}
+
+fn check_all<T, I>(checks: I, law: &'static str) -> Result<(), Vec<Violation>>
+where
+    T: PartialEq + Debug,
+    I: Iterator<Item = (String, T, T)>
+{
+    let violations = checks
+        .filter(|(_, expected, actual)| expected != actual)
+        .map(|(term, expected, actual)| Violation {
+            law,
+            term,
+            expected: format!("{:?}", expected),
+            actual: format!("{:?}", actual)
+        })
+        .collect::<Vec<_>>();
+
+    if violations.is_empty() { Ok(()) } else { Err(violations) }
+}
+
+fn blc_string(term: &Term) -> String {
+    String::from_utf8_lossy(&to_bits(term)).into_owned()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use encoding::binary::{compress, from_bits};
+    use execution::Strategy;
+    use execution::lazy::Lazy;
+    use execution::machine::{BigStep, SmallStep};
+
+    #[test]
+    fn broken_implementations() {
+        let lossy = |_| -> Result<Vec<u8>, ()> { Ok(vec![]) };
+        let violations = lambda_encoding(SAMPLE_BYTES, encode, lossy).unwrap_err();
+        assert_eq!(violations.len(), SAMPLE_BYTES.len() - 1);
+        assert!(violations.iter().all(|v| v.law == "lambda encoding"));
+
+        let truncating = |bits: &[u8]| bits.iter().skip(1).cloned().collect();
+        assert_eq!(compression(SAMPLE_BITS, compress, truncating).unwrap_err().len(), 3);
+
+        let no_parse = |_: &[u8]| -> Result<Term, ()> { Err(()) };
+        let violations = binary_encoding(&[I()], to_bits, no_parse).unwrap_err();
+        assert_eq!(violations[0].term, "0010");
+        assert_eq!(violations[0].actual, "Err(())");
+    }
+
+    #[test]
+    fn machines() {
+        assert_eq!(binary_encoding(&sample_terms(), to_bits, from_bits), Ok(()));
+        assert_eq!(strategy_agreement(&sample_terms(), &mut BigStep, 0), Ok(()));
+        assert_eq!(strategy_agreement(&sample_terms(), &mut Lazy, 0), Ok(()));
+
+        for &strategy in &[Strategy::Normal, Strategy::HybridNormal, Strategy::StrictData] {
+            let mut machine = SmallStep { strategy };
+            assert_eq!(strategy_agreement(&sample_terms(), &mut machine, 1_000), Ok(()));
+        }
+
+        // call-by-name stops at weak head normal forms
+        let mut lazy = SmallStep { strategy: Strategy::CallByName };
+        assert!(strategy_agreement(&sample_terms(), &mut lazy, 1_000).is_err());
+    }
+}
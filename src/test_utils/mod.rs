@@ -0,0 +1,3 @@
+//! Utilities for testing code built on top of BLC
+
+pub mod laws;
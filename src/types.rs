@@ -0,0 +1,257 @@
+//! Simple type inference
+//!
+//! `infer` finds the principal simple type of a term, with type variables and function types
+//! only, by Hindley-Milner-style unification; pure lambda terms have no `let`s, so no type is
+//! ever generalized. Simply typed terms are strongly normalizing: every reduction order reaches
+//! their normal form, so a program with a simple type is known to terminate before it is run.
+//! Most useful programs are not simply typed, though, since recursion combinators and the
+//! self-application they rely on have no simple type.
+//!
+//! The types are unified as a graph of shared nodes, so `is_simply_typed` takes about linear time
+//! even for the terms whose principal types, as built by `infer`, are exponentially large.
+
+use lambda_calculus::*;
+use std::collections::HashMap;
+use std::fmt;
+
+/// A simple type.
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub enum Type {
+    /// a type variable
+    Var(usize),
+    /// the type of functions from the first type to the second one
+    Arrow(Box<Type>, Box<Type>)
+}
+
+/// Produces the type of functions from `from` to `to`.
+pub fn arrow(from: Type, to: Type) -> Type { Type::Arrow(Box::new(from), Box::new(to)) }
+
+/// Prints the type with its variables named `a` to `z` and then `t26`, `t27` and so on; arrows
+/// associate to the right.
+///
+/// # Example
+/// ```
+/// use blc::types::{arrow, Type};
+///
+/// let apply = arrow(arrow(Type::Var(0), Type::Var(1)), arrow(Type::Var(0), Type::Var(1)));
+///
+/// assert_eq!(apply.to_string(), "(a -> b) -> a -> b");
+/// ```
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Type::Var(v) if v < 26 => write!(f, "{}", (b'a' + v as u8) as char),
+            Type::Var(v) => write!(f, "t{}", v),
+            Type::Arrow(ref from, ref to) => match **from {
+                Type::Arrow(..) => write!(f, "({}) -> {}", from, to),
+                _ => write!(f, "{} -> {}", from, to)
+            }
+        }
+    }
+}
+
+/// Infers the principal simple type of `term`, or returns `None` if it has none or is not a valid
+/// term, i.e. contains the De Bruijn index `0`; the free variables of `term` can have any types.
+/// The type variables are numbered from `0` in the order of their first occurrence.
+///
+/// # Example
+/// ```
+/// extern crate blc;
+/// extern crate lambda_calculus;
+///
+/// use blc::types::infer;
+/// use lambda_calculus::combinators::{S, K, O};
+///
+/// assert_eq!(infer(&K()).unwrap().to_string(), "a -> b -> a");
+/// assert_eq!(infer(&S()).unwrap().to_string(), "(a -> b -> c) -> (a -> b) -> a -> c");
+/// assert_eq!(infer(&O()), None);
+/// ```
+pub fn infer(term: &Term) -> Option<Type> {
+    let (mut inference, inferred) = inferred(term)?;
+
+    Some(inference.expanded(inferred, &mut HashMap::new()))
+}
+
+/// Returns `true` if `term` has a simple type, which guarantees that it has a normal form.
+///
+/// Unlike `infer`, this doesn't build the type, which can be exponentially larger than `term`.
+pub fn is_simply_typed(term: &Term) -> bool {
+    inferred(term).is_some()
+}
+
+/// Infers the type of `term` as a node of the type graph of the returned `Inference`.
+fn inferred(term: &Term) -> Option<(Inference, usize)> {
+    let mut inference = Inference { nodes: Vec::new(), parents: Vec::new(), free: HashMap::new() };
+    let inferred = inference.infer(term, &mut Vec::new())?;
+
+    if inference.is_acyclic() { Some((inference, inferred)) } else { None }
+}
+
+/// A node of a type graph.
+#[derive(Clone, Copy)]
+enum Node {
+    Var,
+    Arrow(usize, usize)
+}
+
+/// Unification over a graph of shared type nodes, merged into classes of equal types with a
+/// union-find structure, so that types which are unified repeatedly are only traversed once.
+struct Inference {
+    /// the type nodes by their numbers
+    nodes: Vec<Node>,
+    /// the numbers of the nodes the nodes were merged into, or their own ones for the
+    /// representatives of their classes
+    parents: Vec<usize>,
+    /// the types of the free variables of the term, by their De Bruijn indices counted from the
+    /// root
+    free: HashMap<usize, usize>
+}
+
+impl Inference {
+    fn node(&mut self, node: Node) -> usize {
+        self.nodes.push(node);
+        self.parents.push(self.nodes.len() - 1);
+        self.nodes.len() - 1
+    }
+
+    /// Infers the type of `term` under abstractions whose variables have the types in `context`,
+    /// the innermost last.
+    fn infer(&mut self, term: &Term, context: &mut Vec<usize>) -> Option<usize> {
+        match *term {
+            Var(0) => None,
+            Var(i) if i <= context.len() => Some(context[context.len() - i]),
+            Var(i) => {
+                let index = i - context.len();
+                if !self.free.contains_key(&index) {
+                    let fresh = self.node(Node::Var);
+                    self.free.insert(index, fresh);
+                }
+                Some(self.free[&index])
+            },
+            Abs(ref body) => {
+                let parameter = self.node(Node::Var);
+                context.push(parameter);
+                let result = self.infer(body, context);
+                context.pop();
+
+                Some(self.node(Node::Arrow(parameter, result?)))
+            },
+            App(ref lhs, ref rhs) => {
+                let function = self.infer(lhs, context)?;
+                let argument = self.infer(rhs, context)?;
+                let result = self.node(Node::Var);
+                let expected = self.node(Node::Arrow(argument, result));
+                self.unify(function, expected);
+
+                Some(result)
+            }
+        }
+    }
+
+    /// Returns the representative of the class of `node`, compressing the path to it.
+    fn find(&mut self, node: usize) -> usize {
+        let mut root = node;
+        while self.parents[root] != root { root = self.parents[root] }
+
+        let mut node = node;
+        while self.parents[node] != root {
+            let parent = self.parents[node];
+            self.parents[node] = root;
+            node = parent;
+        }
+        root
+    }
+
+    /// Merges the classes of `a` and `b`; the occurs check is left to `is_acyclic`.
+    fn unify(&mut self, a: usize, b: usize) {
+        let (a, b) = (self.find(a), self.find(b));
+        if a == b { return }
+
+        match (self.nodes[a], self.nodes[b]) {
+            (Node::Var, _) => self.parents[a] = b,
+            (_, Node::Var) => self.parents[b] = a,
+            (Node::Arrow(a1, a2), Node::Arrow(b1, b2)) => {
+                // merged first, so that unifying them again stops right away
+                self.parents[a] = b;
+                self.unify(a1, b1);
+                self.unify(a2, b2);
+            }
+        }
+    }
+
+    /// Returns `true` if no type contains itself, i.e. if the unified types are finite.
+    fn is_acyclic(&mut self) -> bool {
+        let mut visited = vec![None; self.nodes.len()];
+
+        (0..self.nodes.len()).all(|node| self.visit(node, &mut visited))
+    }
+
+    /// Visits the nodes reachable from `node` depth first; `visited` holds whether they are
+    /// finished, or not yet while they are being visited.
+    fn visit(&mut self, node: usize, visited: &mut Vec<Option<bool>>) -> bool {
+        let node = self.find(node);
+
+        match visited[node] {
+            Some(finished) => finished,
+            None => {
+                visited[node] = Some(false);
+                let acyclic = match self.nodes[node] {
+                    Node::Var => true,
+                    Node::Arrow(from, to) => self.visit(from, visited) && self.visit(to, visited)
+                };
+                visited[node] = Some(true);
+
+                acyclic
+            }
+        }
+    }
+
+    /// Builds the type with the representative `node`, with its type variables numbered in the
+    /// order of their first occurrence.
+    fn expanded(&mut self, node: usize, numbers: &mut HashMap<usize, usize>) -> Type {
+        let node = self.find(node);
+
+        match self.nodes[node] {
+            Node::Var => {
+                let next = numbers.len();
+                Type::Var(*numbers.entry(node).or_insert(next))
+            },
+            Node::Arrow(from, to) => {
+                let from = self.expanded(from, numbers);
+                arrow(from, self.expanded(to, numbers))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use lambda_calculus::combinators::{I, Y};
+    use lambda_calculus::data::num::church::{add, succ};
+
+    #[test]
+    fn inference() {
+        let numeral = "(a -> a) -> a -> a";
+
+        assert_eq!(infer(&I()), Some(arrow(Type::Var(0), Type::Var(0))));
+        assert_eq!(infer(&3.into_church()).unwrap().to_string(), numeral);
+        assert_eq!(infer(&app(succ(), 3.into_church())).unwrap().to_string(), numeral);
+        assert!(is_simply_typed(&add()));
+        assert!(!is_simply_typed(&Y()));
+        // the free variable is applied to itself
+        assert_eq!(infer(&app(Var(1), Var(1))), None);
+        assert_eq!(infer(&abs(app(Var(2), Var(1)))).unwrap().to_string(), "a -> b");
+        assert_eq!(infer(&abs(Var(0))), None);
+    }
+
+    #[test]
+    fn exponentially_large_types() {
+        // `λx.λf.f x x` doubles the size of the type of its argument
+        let dup = abs(abs(app!(Var(1), Var(2), Var(2))));
+        let term = (0..100).fold(I(), |term, _| app(dup.clone(), term));
+
+        assert!(is_simply_typed(&term));
+        assert!(!is_simply_typed(&app(term, Y())));
+    }
+}
@@ -0,0 +1,187 @@
+use std::env;
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+
+const REVERSE: &[u8] = b"0001011001000110100000000001011100111110111100001011011110110000010";
+
+fn temp_file(name: &str, contents: &[u8]) -> PathBuf {
+    let path = env::temp_dir().join(format!("blc-cli-test-{}", name));
+    fs::write(&path, contents).unwrap();
+    path
+}
+
+fn blc(args: &[&str], stdin: &[u8]) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_blc"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(stdin).unwrap();
+
+    child.wait_with_output().unwrap()
+}
+
+#[test]
+fn run() {
+    let program = temp_file("reverse.blc", REVERSE);
+    let input = temp_file("reverse-input", b"herp derp");
+
+    let from_file = blc(&["run", program.to_str().unwrap(), input.to_str().unwrap()], b"");
+    let from_stdin = blc(&["run", program.to_str().unwrap()], b"abc");
+
+    assert_eq!(from_file.stdout, b"pred preh");
+    assert_eq!(from_stdin.stdout, b"cba");
+
+    // bytes above 0x7f are written as they are, not as UTF-8
+    let identity = temp_file("identity.blc", b"0010");
+    assert_eq!(blc(&["run", identity.to_str().unwrap()], b"\xffA").stdout, b"\xffA");
+}
+
+#[test]
+fn bit_mode() {
+    let program = temp_file("reverse-bits.blc", REVERSE);
+    let path = program.to_str().unwrap();
+
+    assert_eq!(blc(&["run", "--bits", path], b"0011").stdout, b"1100");
+    assert_eq!(blc(&["run", path], b"0011").stdout, b"1100");
+    assert_eq!(blc(&["run", "--bits", path], b"ab").stdout, b"01");
+}
+
+#[test]
+fn eta_reduction() {
+    // λ_.λλ2 1, returning the Church numeral 1, which is η-reduced to λ1
+    let program = temp_file("one.blc", b"0000000111010");
+    let path = program.to_str().unwrap();
+
+    assert_eq!(blc(&["run", "--eta", path], b"").stdout, "(λ1)".as_bytes());
+    assert_eq!(blc(&["run", path], b"").stdout, "(λλ21)".as_bytes());
+}
+
+#[test]
+fn escaping() {
+    let program = temp_file("reverse-escaping.blc", REVERSE);
+    let path = program.to_str().unwrap();
+
+    assert_eq!(blc(&["run", "--escape", "hex", path], b"\x07a").stdout, b"a\\x07");
+    assert_eq!(blc(&["run", "--escape", "strip", path], b"\x07a").stdout, b"a");
+    assert_eq!(blc(&["run", "--escape", "replace", path], b"\x07a").stdout, "a\u{fffd}".as_bytes());
+    assert!(!blc(&["run", "--escape", "nope", path], b"").status.success());
+}
+
+#[test]
+fn digests() {
+    let program = temp_file("reverse-digest.blc", REVERSE);
+    let output = blc(&["run", "--digest", program.to_str().unwrap()], b"cba");
+
+    assert_eq!(output.stdout, b"abc");
+    assert_eq!(
+        String::from_utf8_lossy(&output.stderr),
+        "sha256: ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad\n"
+    );
+
+    // the digest is the one of the bytes written, whether they are escaped or not
+    let path = program.to_str().unwrap();
+    let raw = blc(&["run", "--digest", path], b"\x80\xe9");
+    assert_eq!(raw.stdout, b"\xe9\x80");
+    assert_eq!(
+        String::from_utf8_lossy(&raw.stderr),
+        "sha256: d23e6687b40657c82f3a6bdcf0263a6086d4b85168964a7c30c7c2e0b259d07e\n"
+    );
+    let hex = blc(&["run", "--digest", "--escape", "hex", path], b"\xe9\x80a");
+    assert_eq!(hex.stdout, "a\\x80\u{e9}".as_bytes());
+    assert_eq!(
+        String::from_utf8_lossy(&hex.stderr),
+        "sha256: 2ac494702d28bbec34f7799df3f6545eb25c6c7624e71f21aada188796ac083a\n"
+    );
+}
+
+#[test]
+fn exec() {
+    let mut contents = b"#!/usr/bin/env -S blc exec\n".to_vec();
+    contents.extend_from_slice(REVERSE);
+    let script = temp_file("reverse-script.blc", &contents);
+    let (script, input) = (script.to_str().unwrap(), temp_file("exec-input", b"\x80\xff"));
+
+    assert_eq!(blc(&["exec", script], b"abc").stdout, b"cba");
+    assert_eq!(blc(&["exec", script, input.to_str().unwrap()], b"").stdout, b"\xff\x80");
+    assert!(!blc(&["exec", "/nonexistent/script.blc"], b"").status.success());
+}
+
+#[test]
+fn exec_on_demand() {
+    // λ1 (λλλλ1 4 (λλ1)) (λλ1): the first byte of the input
+    let script = temp_file("first-byte.blc", b"00 01 01 10 00000000 01 01 10 11110 000010 000010");
+    let mut child = Command::new(env!("CARGO_BIN_EXE_blc"))
+        .args(["exec", script.to_str().unwrap()])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    // the standard input is left open, so the script can only finish if it doesn't wait for it
+    let mut stdin = child.stdin.take().unwrap();
+    stdin.write_all(b"xy").unwrap();
+    let mut output = Vec::new();
+    child.stdout.take().unwrap().read_to_end(&mut output).unwrap();
+
+    assert_eq!(output, b"x");
+    assert!(child.wait().unwrap().success());
+    drop(stdin);
+}
+
+#[test]
+fn bundles() {
+    let reverse = temp_file("bundle-reverse.blc", REVERSE);
+    let id = temp_file("bundle-id.blc", b"0010");
+    let bundle = env::temp_dir().join("blc-cli-test-bundle");
+    let directory = env::temp_dir().join("blc-cli-test-unpacked");
+    fs::create_dir_all(&directory).unwrap();
+    let (bundle, directory) = (bundle.to_str().unwrap(), directory.to_str().unwrap());
+
+    let pack = ["bundle", "pack", bundle, reverse.to_str().unwrap(), id.to_str().unwrap()];
+    assert!(blc(&pack, b"").status.success());
+
+    let list = blc(&["bundle", "list", bundle], b"");
+    assert_eq!(
+        String::from_utf8_lossy(&list.stdout),
+        "blc-cli-test-bundle-reverse\t67 bits\nblc-cli-test-bundle-id\t4 bits\n"
+    );
+
+    let run = blc(&["bundle", "run", bundle, "blc-cli-test-bundle-reverse"], b"abc");
+    assert_eq!(run.stdout, b"cba");
+    let run = blc(&["bundle", "run", bundle, "blc-cli-test-bundle-id"], b"\x80a");
+    assert_eq!(run.stdout, b"\x80a");
+    assert!(!blc(&["bundle", "run", bundle, "nope"], b"").status.success());
+
+    assert!(blc(&["bundle", "unpack", bundle, directory], b"").status.success());
+    let unpacked = Path::new(directory).join("blc-cli-test-bundle-id.Blc");
+    assert_eq!(blc(&["run", unpacked.to_str().unwrap()], b"abc").stdout, b"abc");
+}
+
+#[test]
+fn lint() {
+    let valid = temp_file("lint-valid.blc", b"01 000010 0010"); // (λλ1)(λ1)
+    let invalid = temp_file("lint-invalid.blc", b"0011 10");
+
+    let output = blc(&["lint", valid.to_str().unwrap()], b"");
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "warning: the argument is discarded without being used\n\
+         note: the variable bound here is never used (at function)\n\
+         12 bits, 3 abstractions, 1 applications, 2 variables, depth 2, max index 1\n"
+    );
+    assert!(!blc(&["lint", invalid.to_str().unwrap()], b"").status.success());
+}
+
+#[test]
+fn usage() {
+    let output = blc(&[], b"");
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).starts_with("usage: blc"));
+}
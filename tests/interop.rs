@@ -0,0 +1,75 @@
+#[macro_use]
+extern crate blc;
+extern crate lambda_calculus;
+
+use blc::encoding::binary::{from_bits, to_bits};
+use blc::repr::Error;
+
+// a stand-in for `lambda_calculus` 3.x, whose applications hold a boxed tuple
+mod lambda_calculus_3 {
+    pub mod term {
+        #[derive(Debug, PartialEq)]
+        pub enum Term {
+            Var(usize),
+            Abs(Box<Term>),
+            App(Box<(Term, Term)>)
+        }
+    }
+}
+
+// a stand-in for a `lambda_calculus` with a variant blc has no counterpart for
+mod lambda_calculus_ext {
+    pub mod term {
+        #[derive(Debug, PartialEq)]
+        pub enum Term {
+            Var(usize),
+            Abs(Box<Term>),
+            App(Box<Term>, Box<Term>),
+            Const(u8)
+        }
+    }
+}
+
+lambda_calculus_adapter!(lc2, lambda_calculus::term, pair);
+lambda_calculus_adapter!(lc3, lambda_calculus_3::term, tuple);
+lambda_calculus_adapter!(ext, lambda_calculus_ext::term, pair);
+
+#[test]
+fn pair_applications() {
+    use lambda_calculus::{abs, app, Var};
+
+    let s = from_bits(b"00000001011110100111010").unwrap();
+    let exported = lc2::export(&s);
+
+    assert_eq!(
+        exported,
+        abs(abs(abs(app(app(Var(3), Var(1)), app(Var(2), Var(1))))))
+    );
+    assert_eq!(lc2::import::<lambda_calculus::Term>(&exported), Ok(s));
+}
+
+#[test]
+fn tuple_applications() {
+    use lambda_calculus_3::term::Term::*;
+
+    let s = from_bits(b"00000001011110100111010").unwrap();
+    let exported = lc3::export(&s);
+
+    assert_eq!(
+        exported,
+        Abs(Box::new(Abs(Box::new(Abs(Box::new(App(Box::new((
+            App(Box::new((Var(3), Var(1)))),
+            App(Box::new((Var(2), Var(1))))
+        )))))))))
+    );
+    assert_eq!(to_bits(&lc3::import::<lambda_calculus::Term>(&exported).unwrap()), to_bits(&s));
+}
+
+#[test]
+fn unsupported_terms() {
+    use lambda_calculus_ext::term::Term::*;
+
+    let foreign = Abs(Box::new(App(Box::new(Var(1)), Box::new(Const(0)))));
+
+    assert_eq!(ext::import::<lambda_calculus::Term>(&foreign), Err(Error::UnsupportedTerm));
+}
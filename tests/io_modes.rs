@@ -0,0 +1,35 @@
+extern crate blc;
+
+use blc::encoding::binary::to_bits;
+use blc::execution::{run_bits, run_to_term, run_with, run_with_cost, Input, IoMode, Options};
+
+const REVERSE: &[u8] = b"0001011001000110100000000001011100111110111100001011011110110000010";
+
+#[test]
+fn io_modes() {
+    let bits = Options { io_mode: IoMode::Bits, ..Options::default() };
+
+    assert_eq!(run_with(REVERSE, Input::Bytes(b"0011"), bits), Ok("1100".into()));
+    assert_eq!(run_with(REVERSE, Input::Bytes(b"0011"), Options::default()), Ok("1100".into()));
+    assert_eq!(run_bits(REVERSE, b"0011"), Ok(b"1100".to_vec()));
+    // BLC input is passed as it is in every mode
+    assert_eq!(run_with(b"0010", Input::Bits(b"0000110"), bits), Ok("(λλ2)".into()));
+    assert_eq!(run_bits(b"0000110", b""), Ok("(λλλ1)".as_bytes().to_vec()));
+
+    let term = run_to_term(b"0010", Input::Bytes(b"1"), bits).unwrap();
+    assert_eq!(to_bits(&term), b"00010110000010000010"); // λ1(λλ1)(λλ1)
+
+    let (output, cost) = run_with_cost(REVERSE, Input::Bytes(b"0011"), bits).unwrap();
+    assert_eq!(output, "1100");
+    assert_eq!(cost.input_bits, 4);
+}
+
+#[test]
+fn eta_reduction() {
+    let one = b"0000 01 110 10"; // λλ2 1, the Church numeral 1
+    let options = Options { eta: true, ..Options::default() };
+
+    assert_eq!(run_with(one, Input::Nothing, Options::default()), Ok("(λλ21)".into()));
+    assert_eq!(run_with(one, Input::Nothing, options), Ok("(λ1)".into()));
+    assert_eq!(run_with(REVERSE, Input::Bytes(b"ab"), options), Ok("ba".into()));
+}
@@ -0,0 +1,78 @@
+extern crate blc;
+
+use blc::execution::{run_cancellable, run_limited, run_traced, run_with, run_with_timeout};
+use blc::execution::{CancellationToken, Error, Input, Options};
+use std::time::{Duration, Instant};
+
+#[test]
+fn step_limits() {
+    let identity = b"0010";
+    let twice = b"01 0010 0010"; // (λ1)(λ1), applied to the input
+
+    assert_eq!(run_limited(identity, Input::Bytes(b"ab"), 1), Ok("ab".into()));
+    let exceeded = Err(Error::StepLimitExceeded { steps: 1 });
+    assert_eq!(run_limited(twice, Input::Bytes(b"ab"), 1), exceeded);
+    assert_eq!(run_limited(twice, Input::Bytes(b"ab"), 2), Ok("ab".into()));
+}
+
+#[test]
+fn timeouts() {
+    let omega = b"010001101000011010";
+    let start = Instant::now();
+    let timeout = Duration::from_millis(50);
+
+    let timed_out = Err(Error::Timeout { after: timeout });
+    assert_eq!(run_with_timeout(omega, Input::Nothing, timeout), timed_out);
+    assert!(start.elapsed() < Duration::from_secs(5));
+    assert_eq!(
+        run_with_timeout(b"0010", Input::Bytes(b"ok"), Duration::from_secs(5)),
+        Ok("ok".into())
+    );
+}
+
+#[test]
+fn size_limits() {
+    // (λ1 1 1)(λ1 1 1) grows by a copy of λ1 1 1 with every step
+    let growing = b"01 000101101010 000101101010";
+    let id = b"0010";
+    let options = |max_size| Options { max_size, max_output: 1, ..Options::default() };
+
+    assert_eq!(run_with(growing, Input::Nothing, options(1_000)), Err(Error::SizeLimitExceeded));
+    assert_eq!(run_with(id, Input::Bytes(b"a"), options(1_000)), Ok("a".into()));
+    assert_eq!(run_with(id, Input::Bytes(b"ab"), options(1_000)), Err(Error::OutputLimitExceeded));
+}
+
+#[test]
+fn observed_steps() {
+    let twice = b"01 0010 0010"; // (λ1)(λ1), applied to the input
+    let mut observed = Vec::new();
+
+    let output = run_traced(twice, Input::Bytes(b"ab"), 1, |step, _| { observed.push(step); true });
+    assert_eq!(output, Ok("ab".into()));
+    assert_eq!(observed, vec![1, 2]);
+
+    assert_eq!(run_traced(twice, Input::Bytes(b"ab"), 1, |step, _| step < 2), Err(Error::Aborted));
+    assert_eq!(run_traced(twice, Input::Bytes(b"ab"), 5, |_, _| false), Ok("ab".into()));
+}
+
+#[test]
+fn cancellation() {
+    let token = CancellationToken::new();
+    assert_eq!(run_cancellable(b"0010", Input::Bytes(b"ok"), &token), Ok("ok".into()));
+
+    token.clone().cancel();
+    assert_eq!(run_cancellable(b"0010", Input::Bytes(b"ok"), &token), Err(Error::Aborted));
+}
+
+#[test]
+fn divergence() {
+    let omega = b"010001101000011010";
+    let growing = b"01 000101101010 000101101010";
+    let id = b"0010";
+    let options = |max_size| Options { max_size, divergence_window: 4, ..Options::default() };
+
+    let divergent = Err(Error::LikelyDivergent { steps: 1, period: 1 });
+    assert_eq!(run_with(omega, Input::Nothing, options(0)), divergent);
+    assert_eq!(run_with(growing, Input::Nothing, options(1_000)), Err(Error::SizeLimitExceeded));
+    assert_eq!(run_with(id, Input::Bytes(b"a"), options(0)), Ok("a".into()));
+}
@@ -0,0 +1,47 @@
+extern crate blc;
+
+use blc::encoding::binary::decompress;
+use blc::execution::{run_with, Error, Input, Options, Strategy};
+
+#[test]
+fn strategies() {
+    // the sorting program from `sort.rs`
+    let code_compressed =
+        [0x15, 0x46, 0x84, 0x06, 0x05, 0x46, 0x81, 0x60, 0x15, 0xfb, 0xec, 0x2f, 0x80, 0x01,
+         0x5b, 0xf9, 0x7f, 0x0b, 0x7e, 0xf7, 0x2f, 0xec, 0x2d, 0xfb, 0x80, 0x56, 0x05, 0xfd,
+         0x85, 0xbb, 0x76, 0x11, 0x5d, 0x50, 0x5c, 0x00, 0xbe, 0x7f, 0xc1, 0x2b, 0xff, 0x0f,
+         0xfc, 0x2c, 0x1b, 0x72, 0xbf, 0xf0, 0xff, 0xc2, 0xc1, 0x6d, 0x34, 0x50, 0x40];
+    let code_blc = decompress(&code_compressed);
+    let run = |strategy, max_output| {
+        let options = Options { strategy, max_output, ..Options::default() };
+        run_with(&code_blc, Input::Bytes(b"3241"), options)
+    };
+
+    for &strategy in &[Strategy::Normal, Strategy::HybridNormal, Strategy::CallByNeed] {
+        assert_eq!(run(strategy, 0), Ok("1234".into()));
+        assert_eq!(run(strategy, 3), Err(Error::OutputLimitExceeded));
+    }
+    // call-by-name stops at the weak head normal form, which is not a string yet
+    assert_ne!(run(Strategy::CallByName, 0), Ok("1234".into()));
+}
+
+#[test]
+fn call_by_value_machine() {
+    let cek = Options { strategy: Strategy::Cek, ..Options::default() };
+    let applicative = Options { strategy: Strategy::Applicative, ..Options::default() };
+    let second = b"01 01 0000110 0010 000010"; // (λλ2)(λ1)(λλ1), applied to the input
+
+    assert_eq!(run_with(b"0010", Input::Bytes(b"herp"), cek), Ok("herp".into()));
+    assert_eq!(
+        run_with(second, Input::Bytes(b"ab"), cek),
+        run_with(second, Input::Bytes(b"ab"), applicative)
+    );
+}
+
+#[test]
+fn iterative_reduction() {
+    let iterative = Options { strategy: Strategy::Iterative, ..Options::default() };
+    let reverse = b"0001011001000110100000000001011100111110111100001011011110110000010";
+
+    assert_eq!(run_with(reverse, Input::Bytes(b"herp"), iterative), Ok("preh".into()));
+}
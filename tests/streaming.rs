@@ -0,0 +1,45 @@
+extern crate blc;
+
+use blc::execution::{run_bytes, run_streaming, Error, Input};
+use std::io::{self, Write};
+
+/// Accepts a limited number of bytes and fails afterwards.
+struct Limited {
+    written: Vec<u8>,
+    capacity: usize
+}
+
+impl Write for Limited {
+    fn write(&mut self, buffer: &[u8]) -> io::Result<usize> {
+        let accepted = buffer.len().min(self.capacity - self.written.len());
+        if accepted == 0 { return Err(io::ErrorKind::WriteZero.into()) }
+        self.written.extend_from_slice(&buffer[..accepted]);
+        Ok(accepted)
+    }
+
+    fn flush(&mut self) -> io::Result<()> { Ok(()) }
+}
+
+#[test]
+fn same_output_as_run_bytes() {
+    let reverse = b"0001011001000110100000000001011100111110111100001011011110110000010";
+    let k = b"0000110";
+
+    for program in [&reverse[..], &k[..], b"0010"] {
+        let mut output = Vec::new();
+        run_streaming(program, Input::Bytes(b"\x80 ab"), &mut output).unwrap();
+        assert_eq!(Ok(output), run_bytes(program, Input::Bytes(b"\x80 ab")));
+    }
+}
+
+#[test]
+fn endless_output() {
+    // λ (λx λf. f 0 (x x)) (λx λf. f 0 (x x)): an endless list of `0` bits
+    let zeros = b"00 01 0000010110000011001110110 0000010110000011001110110";
+    let mut sink = Limited { written: Vec::new(), capacity: 5 };
+
+    let result = run_streaming(zeros, Input::Bytes(b""), &mut sink);
+
+    assert_eq!(result, Err(Error::Io(io::ErrorKind::WriteZero)));
+    assert_eq!(sink.written, b"00000");
+}